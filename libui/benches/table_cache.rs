@@ -0,0 +1,135 @@
+//! Measures the cost of fetching table cells, as done on every paint of a `Table`.
+//!
+//! The mock backend formats log lines on demand, standing in for a database or log file.
+
+#[macro_use]
+extern crate criterion;
+extern crate libui;
+
+use criterion::{black_box, Criterion};
+use libui::controls::*;
+use std::ffi::CString;
+use std::thread;
+use std::time::Duration;
+
+const ROWS: i32 = 1_000_000;
+
+/// Formats a log line for a row, simulating the work of reading it from storage.
+fn log_row(row: i32) -> Vec<TableValue> {
+    vec![
+        TableValue::Int(row),
+        TableValue::String(format!("2024-01-01T00:{:02}:{:02}Z", row / 60 % 60, row % 60)),
+        TableValue::String(format!("worker-{} processed request {:08x}", row % 16, row)),
+    ]
+}
+
+/// The backend as a data source, formatting every cell it is asked for.
+struct DirectSource;
+
+impl TableDataSource for DirectSource {
+    fn num_columns(&mut self) -> i32 {
+        3
+    }
+
+    fn num_rows(&mut self) -> i32 {
+        ROWS
+    }
+
+    fn column_type(&mut self, column: i32) -> TableValueType {
+        match column {
+            0 => TableValueType::Int,
+            _ => TableValueType::String,
+        }
+    }
+
+    fn cell(&mut self, column: i32, row: i32) -> TableValue {
+        log_row(row).swap_remove(column as usize)
+    }
+
+    fn set_cell(&mut self, _column: i32, _row: i32, _value: TableValue) {}
+}
+
+fn cached_source(options: CacheOptions) -> CachedDataSource<DirectSource> {
+    CachedDataSource::new(DirectSource, options)
+}
+
+/// Requests the page containing `row` and blocks until it is cached.
+fn warm(source: &mut CachedDataSource<DirectSource>, row: i32) {
+    source.cell(0, row);
+    while !source.is_cached(row) {
+        source.poll();
+        thread::sleep(Duration::from_micros(50));
+    }
+}
+
+fn cell_fetch(c: &mut Criterion) {
+    // A screenful of rows, fetched cell by cell like a repaint does.
+    let visible = 0..40;
+
+    c.bench_function("direct/repaint", |b| {
+        let mut source = DirectSource;
+        b.iter(|| {
+            for row in visible.clone() {
+                for column in 0..3 {
+                    black_box(source.cell(column, black_box(row)));
+                }
+            }
+        })
+    });
+
+    c.bench_function("cached/repaint", |b| {
+        let mut source = cached_source(CacheOptions::default());
+        warm(&mut source, 0);
+        b.iter(|| {
+            for row in visible.clone() {
+                for column in 0..3 {
+                    black_box(source.cell(column, black_box(row)));
+                }
+            }
+        })
+    });
+
+    c.bench_function("direct/repaint_c_str", |b| {
+        // What a view does with each cell: convert its text for libui.
+        let mut source = DirectSource;
+        b.iter(|| {
+            for row in visible.clone() {
+                for column in 1..3 {
+                    if let TableValue::String(text) = source.cell(column, black_box(row)) {
+                        black_box(CString::new(text).unwrap());
+                    }
+                }
+            }
+        })
+    });
+
+    c.bench_function("cached/repaint_c_str", |b| {
+        let mut source = cached_source(CacheOptions::default());
+        warm(&mut source, 0);
+        b.iter(|| {
+            for row in visible.clone() {
+                for column in 1..3 {
+                    black_box(source.cell_c_str(column, black_box(row)));
+                }
+            }
+        })
+    });
+
+    c.bench_function("cached/scroll", |b| {
+        // Scrolling through uncached rows: placeholders are served while pages load.
+        let mut source = cached_source(CacheOptions::default());
+        let mut top = 0;
+        b.iter(|| {
+            source.poll();
+            for row in top..top + 40 {
+                for column in 0..3 {
+                    black_box(source.cell(column, row));
+                }
+            }
+            top = (top + 40) % (ROWS - 40);
+        })
+    });
+}
+
+criterion_group!(benches, cell_fetch);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 extern crate libui;
 
 use libui::controls::{Area, AreaDrawParams, AreaHandler, HorizontalBox, LayoutStrategy};
-use libui::draw::{Brush, FillMode, Path, SolidBrush};
+use libui::draw::{Brush, Color, FillMode, Path};
 use libui::prelude::*;
 use std::f64::consts::PI;
 
@@ -14,7 +14,7 @@ impl AreaHandler for HandleCanvas {
         path.add_rectangle(ctx, 0., 0., draw_params.area_width, draw_params.area_height);
         path.end(ctx);
 
-        let brush = Brush::Solid(SolidBrush {
+        let brush = Brush::Solid(Color {
             r: 0.2,
             g: 0.6,
             b: 0.8,
@@ -23,6 +23,17 @@ impl AreaHandler for HandleCanvas {
 
         draw_params.context.fill(&path, &brush);
 
+        // Keep the bars inside a margin around the area.
+        let viewport = Path::new(ctx, FillMode::Winding);
+        viewport.add_rectangle(
+            ctx,
+            10.,
+            10.,
+            draw_params.area_width - 20.,
+            draw_params.area_height - 20.,
+        );
+        viewport.end(ctx);
+
         let path = Path::new(ctx, FillMode::Winding);
         for i in 0..100 {
             let x = i as f64 / 100.;
@@ -37,14 +48,17 @@ impl AreaHandler for HandleCanvas {
         }
         path.end(ctx);
 
-        let brush = Brush::Solid(SolidBrush {
+        let brush = Brush::Solid(Color {
             r: 0.2,
             g: 0.,
             b: 0.3,
             a: 1.,
         });
 
-        draw_params.context.fill(&path, &brush);
+        ctx.saved(|ctx| {
+            ctx.clip(&viewport);
+            ctx.fill(&path, &brush);
+        });
     }
 }
 
@@ -24,8 +24,8 @@ pub fn make_data_page(_ui: UI, window: Rc<RefCell<Window>>) -> Control {
         let bt_font = bt_font.clone();
         move |_| -> () {
             let mut c = bt_color.color();
-            c.1 = 1.0;
-            bt_color.set_color(c.0, c.1, c.2, c.3);
+            c.g = 1.0;
+            bt_color.set_color(c);
 
             let mut time = bt_datetime.datetime();
             time.tm_hour = 0;
@@ -3,6 +3,7 @@ use std::rc::Rc;
 use std::vec;
 
 use libui::controls::*;
+use libui::draw::Color;
 use libui::prelude::*;
 
 struct MyDataSource {
@@ -47,12 +48,15 @@ impl TableDataSource for MyDataSource {
         match column {
             0 => TableValue::String(self.data[row as usize].0.to_string()),
             1 => TableValue::String(self.data[row as usize].1.clone()),
-            2 => TableValue::Color {
-                r: ((self.data[row as usize].2 & 0xFF0000) >> 16) as f64 / 255.0,
-                g: ((self.data[row as usize].2 & 0x00FF00) >> 8) as f64 / 255.0,
-                b: ((self.data[row as usize].2 & 0x0000FF) >> 0) as f64 / 255.0,
-                a: 1.0,
-            },
+            2 => {
+                let rgb = self.data[row as usize].2;
+                TableValue::Color(Color::from_rgba8(
+                    (rgb >> 16) as u8,
+                    (rgb >> 8) as u8,
+                    rgb as u8,
+                    255,
+                ))
+            }
             3 => TableValue::Int(self.data[row as usize].3.clone()),
             4 => TableValue::Int(self.data[row as usize].4.clone()),
             5 => TableValue::String(self.data[row as usize].5.clone()),
@@ -61,6 +65,10 @@ impl TableDataSource for MyDataSource {
     }
 
     fn set_cell(&mut self, _column: i32, _row: i32, _value: TableValue) {}
+
+    fn button_clicked(&mut self, _column: i32, row: i32) {
+        println!("Ordered {}", self.data[row as usize].1);
+    }
 }
 
 pub fn make_table_page(_ui: UI) -> Control {
@@ -83,5 +91,21 @@ pub fn make_table_page(_ui: UI) -> Control {
     table.append_button_column("", 5, Table::COLUMN_EDITABLE);
 
     vbox.append(table, LayoutStrategy::Stretchy);
+
+    let categories = TreeNode::new("")
+        .child(
+            TreeNode::new("Citrus")
+                .child(TreeNode::new("Oranges"))
+                .child(TreeNode::new("Limes")),
+        )
+        .child(
+            TreeNode::new("Berries")
+                .child(TreeNode::new("Blueberries"))
+                .child(TreeNode::new("Strawberries")),
+        )
+        .child(TreeNode::new("Bananas"));
+    let mut tree = TreeView::new(categories, "Category");
+    tree.expand(&[0]);
+    vbox.append(tree, LayoutStrategy::Stretchy);
     return vbox.into();
 }
@@ -0,0 +1,245 @@
+use animation::{Lerp, Timeline, Tween};
+use controls::Area;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::mem;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Identifies a timeline played by an [`Animator`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AnimationId(u64);
+
+struct Running {
+    id: AnimationId,
+    timeline: Timeline,
+    start: Option<Instant>,
+}
+
+#[derive(Default)]
+struct AnimatorState {
+    area: Option<Area>,
+    running: Vec<Running>,
+    next_id: u64,
+    frame_requested: bool,
+    /// Whether `advance()` has taken the running timelines out, so stops must be recorded.
+    advancing: bool,
+    /// Timelines stopped while advancing, applied when they are put back.
+    stopped: HashSet<AnimationId>,
+    stopped_all: bool,
+}
+
+/// Plays [`Timeline`]s, updating their properties on every animation frame.
+///
+/// An animator for an `Area` requests animation frames while timelines are playing and
+/// redraws the area after each frame; when all timelines have finished, no more frames
+/// are scheduled. Without an area, call [`Animator::advance()`] to drive it, e.g. from
+/// a timer.
+///
+/// ```no_run
+/// # use libui::animation::*;
+/// # use libui::controls::*;
+/// # use std::cell::Cell;
+/// # use std::rc::Rc;
+/// # use std::time::Duration;
+/// # struct Handler;
+/// # impl AreaHandler for Handler {}
+/// let area = Area::new(Box::new(Handler));
+/// let x = Rc::new(Cell::new(0.0));
+/// let animator = Animator::for_area(&area);
+/// let setter = x.clone();
+/// animator.animate(
+///     Tween::new(0.0, 200.0, Duration::from_millis(300)).with_easing(Easing::CubicOut),
+///     move |value| setter.set(value),
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct Animator {
+    state: Rc<RefCell<AnimatorState>>,
+}
+
+impl Animator {
+    /// Creates an animator driven by calls to [`Animator::advance()`].
+    pub fn new() -> Animator {
+        Animator::default()
+    }
+
+    /// Creates an animator driven by the animation frames of an area.
+    ///
+    /// No frames are requested once the area is destroyed.
+    pub fn for_area(area: &Area) -> Animator {
+        let animator = Animator::new();
+        animator.state.borrow_mut().area = Some(area.clone());
+        animator
+    }
+
+    /// Starts playing a timeline with the next frame.
+    pub fn play(&self, timeline: Timeline) -> AnimationId {
+        let id = {
+            let mut state = self.state.borrow_mut();
+            let id = AnimationId(state.next_id);
+            state.next_id += 1;
+            state.running.push(Running {
+                id,
+                timeline,
+                start: None,
+            });
+            id
+        };
+        self.request_frame();
+        id
+    }
+
+    /// Plays a single tween.
+    pub fn animate<T, F>(&self, tween: Tween<T>, setter: F) -> AnimationId
+    where
+        T: Lerp + 'static,
+        F: FnMut(T) + 'static,
+    {
+        self.play(Timeline::new().tween(tween, setter))
+    }
+
+    /// Stops a timeline, leaving its properties at their current values.
+    pub fn stop(&self, id: AnimationId) {
+        let mut state = self.state.borrow_mut();
+        state.running.retain(|running| running.id != id);
+        if state.advancing {
+            state.stopped.insert(id);
+        }
+    }
+
+    /// Stops all timelines.
+    pub fn stop_all(&self) {
+        let mut state = self.state.borrow_mut();
+        state.running.clear();
+        if state.advancing {
+            state.stopped_all = true;
+        }
+    }
+
+    pub fn is_playing(&self, id: AnimationId) -> bool {
+        self.state
+            .borrow()
+            .running
+            .iter()
+            .any(|running| running.id == id)
+    }
+
+    /// Returns whether no timeline is playing.
+    pub fn is_idle(&self) -> bool {
+        self.state.borrow().running.is_empty()
+    }
+
+    /// Updates all timelines to the given time, running the callbacks of those that
+    /// finished. Returns whether timelines are still playing.
+    ///
+    /// Timelines start at the time of the first call after they were played.
+    pub fn advance(&self, now: Instant) -> bool {
+        // Setters and callbacks may play or stop timelines, so none is borrowed while
+        // they run.
+        let mut running = {
+            let mut state = self.state.borrow_mut();
+            state.advancing = true;
+            mem::take(&mut state.running)
+        };
+        let mut finished = Vec::new();
+        running.retain_mut(|running| {
+            let start = *running.start.get_or_insert(now);
+            let done = running.timeline.seek(now.saturating_duration_since(start));
+            if done {
+                finished.extend(running.timeline.take_on_finished());
+            }
+            !done
+        });
+        {
+            let mut state = self.state.borrow_mut();
+            if mem::take(&mut state.stopped_all) {
+                running.clear();
+            }
+            let stopped = mem::take(&mut state.stopped);
+            running.retain(|running| !stopped.contains(&running.id));
+            state.advancing = false;
+            running.append(&mut state.running);
+            state.running = running;
+        }
+        for callback in finished {
+            callback();
+        }
+        !self.is_idle()
+    }
+
+    fn request_frame(&self) {
+        let area = {
+            let state = self.state.borrow();
+            if state.frame_requested || state.running.is_empty() {
+                return;
+            }
+            match state.area {
+                Some(ref area) => area.clone(),
+                None => return,
+            }
+        };
+        self.state.borrow_mut().frame_requested = true;
+        let animator = self.clone();
+        area.request_animation_frame(move |time| {
+            animator.state.borrow_mut().frame_requested = false;
+            animator.advance(time.time);
+            animator.request_frame();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    #[test]
+    fn advances_timelines() {
+        let animator = Animator::new();
+        let value = Rc::new(Cell::new(0.0));
+        let done = Rc::new(Cell::new(false));
+        let (setter, flag) = (value.clone(), done.clone());
+        let chained = animator.clone();
+        let first = animator.play(
+            Timeline::new()
+                .tween(Tween::new(0.0, 1.0, Duration::from_millis(100)), move |v| {
+                    setter.set(v)
+                })
+                .on_finished(move || {
+                    flag.set(true);
+                    chained.animate(Tween::new(0.0, 1.0, Duration::from_secs(1)), |_| {});
+                }),
+        );
+        let start = Instant::now();
+        assert!(animator.advance(start));
+        assert!(animator.advance(start + Duration::from_millis(50)));
+        assert_eq!(value.get(), 0.5);
+        assert!(animator.advance(start + Duration::from_millis(100)));
+        assert!(done.get());
+        assert!(!animator.is_playing(first));
+        assert!(!animator.is_idle());
+        animator.stop_all();
+        assert!(!animator.advance(start + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn stops_from_setters_take_effect() {
+        let animator = Animator::new();
+        let stopper = animator.clone();
+        let victim = animator.animate(Tween::new(0.0, 1.0, Duration::from_secs(1)), |_| {});
+        animator.animate(Tween::new(0.0, 1.0, Duration::from_secs(1)), move |_| {
+            stopper.stop(victim)
+        });
+        let start = Instant::now();
+        assert!(animator.advance(start));
+        assert!(!animator.is_playing(victim));
+
+        let stopper = animator.clone();
+        animator.animate(Tween::new(0.0, 1.0, Duration::from_secs(1)), move |_| {
+            stopper.stop_all()
+        });
+        assert!(!animator.advance(start));
+    }
+}
@@ -0,0 +1,145 @@
+use std::f64::consts::PI;
+
+/// Maps the linear progress of an animation to eased progress.
+///
+/// All easings map 0 to 0 and 1 to 1; some overshoot in between.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    /// Overshoots the end a little before settling.
+    BackOut,
+    /// Oscillates around the end before settling.
+    ElasticOut,
+    /// Bounces off the end like a dropped ball.
+    BounceOut,
+    /// A CSS-style cubic Bézier curve from (0, 0) to (1, 1) with the control points
+    /// `(x1, y1)` and `(x2, y2)`. The x coordinates are clamped to [0, 1].
+    CubicBezier(f64, f64, f64, f64),
+    /// Jumps in the given number of equal steps.
+    Steps(u32),
+}
+
+impl Easing {
+    /// The CSS `ease` timing function.
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+
+    /// Returns the eased progress for the linear progress `t`, which is clamped to [0, 1].
+    pub fn apply(self, t: f64) -> f64 {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2f64.powf(-10.0 * t) * ((10.0 * t - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Easing::BounceOut => bounce_out(t),
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2, t)
+            }
+            Easing::Steps(0) => t,
+            Easing::Steps(steps) => (t * steps as f64).floor() / steps as f64,
+        }
+    }
+}
+
+fn bounce_out(t: f64) -> f64 {
+    let (n1, d1) = (7.5625, 2.75);
+    if t < 1.0 / d1 {
+        n1 * t * t
+    } else if t < 2.0 / d1 {
+        let t = t - 1.5 / d1;
+        n1 * t * t + 0.75
+    } else if t < 2.5 / d1 {
+        let t = t - 2.25 / d1;
+        n1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d1;
+        n1 * t * t + 0.984375
+    }
+}
+
+/// Evaluates one coordinate of a cubic Bézier curve from 0 to 1.
+fn bezier(p1: f64, p2: f64, s: f64) -> f64 {
+    let u = 1.0 - s;
+    3.0 * u * u * s * p1 + 3.0 * u * s * s * p2 + s * s * s
+}
+
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, t: f64) -> f64 {
+    // With x1 and x2 in [0, 1], x grows monotonically with s, so bisection finds the
+    // curve parameter for t.
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..40 {
+        let middle = (low + high) / 2.0;
+        if bezier(x1, x2, middle) < t {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    bezier(y1, y2, (low + high) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_the_ends() {
+        let easings = [
+            Easing::Linear,
+            Easing::QuadInOut,
+            Easing::CubicIn,
+            Easing::CubicOut,
+            Easing::SineInOut,
+            Easing::BackOut,
+            Easing::ElasticOut,
+            Easing::BounceOut,
+            Easing::EASE,
+            Easing::Steps(4),
+        ];
+        for easing in &easings {
+            assert!(easing.apply(0.0).abs() < 1e-9, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{:?}", easing);
+        }
+        assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+        assert_eq!(Easing::Steps(4).apply(0.6), 0.5);
+        assert!((Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).apply(0.3) - 0.3).abs() < 1e-6);
+        assert!(Easing::BackOut.apply(0.8) > 1.0);
+        assert_eq!(Easing::Linear.apply(2.0), 1.0);
+    }
+}
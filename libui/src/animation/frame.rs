@@ -0,0 +1,124 @@
+use controls::Area;
+use libui_ffi;
+use std::cell::RefCell;
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+use std::rc::Weak;
+use std::time::{Duration, Instant};
+
+/// The interval of the frame timer, for about 60 frames per second.
+const FRAME_INTERVAL_MS: c_int = 16;
+
+/// The time of an animation frame, passed to
+/// [`Area::request_animation_frame()`](::controls::Area::request_animation_frame)
+/// callbacks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameTime {
+    /// When the frame started. All callbacks of a frame get the same time.
+    pub time: Instant,
+    /// The time since the previous frame, or the frame interval for the first frame
+    /// after an idle period.
+    pub delta: Duration,
+    /// Counts the frames since the first one.
+    pub frame: u64,
+}
+
+type FrameCallback = Box<dyn FnOnce(FrameTime)>;
+
+/// A requested frame, with the liveness token of its area.
+type Request = (Area, Weak<()>, FrameCallback);
+
+#[derive(Default)]
+struct Scheduler {
+    callbacks: Vec<Request>,
+    timer_running: bool,
+    last_frame: Option<Instant>,
+    frame: u64,
+}
+
+thread_local! {
+    static SCHEDULER: RefCell<Scheduler> = RefCell::new(Scheduler::default());
+}
+
+/// Queues a callback for the next frame, starting the frame timer if it is idle.
+///
+/// Callbacks for areas whose liveness can't be proven are dropped.
+pub(crate) fn request_frame(area: &Area, callback: FrameCallback) {
+    let alive = area.liveness();
+    if alive.upgrade().is_none() {
+        return;
+    }
+    let start_timer = SCHEDULER.with(|scheduler| {
+        let mut scheduler = scheduler.borrow_mut();
+        scheduler.callbacks.push((area.clone(), alive, callback));
+        !mem::replace(&mut scheduler.timer_running, true)
+    });
+    if start_timer {
+        unsafe { libui_ffi::uiTimer(FRAME_INTERVAL_MS, Some(on_timer), ptr::null_mut()) };
+    }
+}
+
+/// Runs the callbacks of a frame and redraws their areas, skipping destroyed areas.
+/// Returns whether the timer keeps running, which it does as long as callbacks request
+/// further frames.
+extern "C" fn on_timer(_data: *mut c_void) -> c_int {
+    let now = Instant::now();
+    let (callbacks, time) = SCHEDULER.with(|scheduler| {
+        let mut scheduler = scheduler.borrow_mut();
+        let delta = scheduler
+            .last_frame
+            .map_or(Duration::from_millis(FRAME_INTERVAL_MS as u64), |last| {
+                now.duration_since(last)
+            });
+        let time = FrameTime {
+            time: now,
+            delta,
+            frame: scheduler.frame,
+        };
+        scheduler.frame += 1;
+        scheduler.last_frame = Some(now);
+        (mem::take(&mut scheduler.callbacks), time)
+    });
+
+    let mut areas: Vec<(Area, Weak<()>)> = Vec::new();
+    for (area, alive, callback) in callbacks {
+        if alive.upgrade().is_none() {
+            continue;
+        }
+        callback(time);
+        if !areas.iter().any(|(a, _)| a.ptr() == area.ptr()) {
+            areas.push((area, alive));
+        }
+    }
+    // A callback may have destroyed its own or another area.
+    for (area, alive) in areas {
+        if alive.upgrade().is_some() {
+            area.queue_redraw_all();
+        }
+    }
+
+    SCHEDULER.with(|scheduler| {
+        let mut scheduler = scheduler.borrow_mut();
+        if scheduler.callbacks.is_empty() {
+            // Stop the timer while idle; the next request starts it again.
+            scheduler.timer_running = false;
+            scheduler.last_frame = None;
+            0
+        } else {
+            1
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_frames_of_unknown_areas() {
+        let area = unsafe { Area::from_ui_area(ptr::NonNull::dangling().as_ptr()) };
+        request_frame(&area, Box::new(|_| panic!("frame for an unknown area")));
+        SCHEDULER.with(|scheduler| assert!(scheduler.borrow().callbacks.is_empty()));
+    }
+}
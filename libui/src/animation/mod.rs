@@ -0,0 +1,18 @@
+//! Easing, tweens and timelines for animating `Area` content.
+//!
+//! [`Area::request_animation_frame()`](crate::controls::Area::request_animation_frame)
+//! runs a callback with the next frame of a UI thread timer, and an [`Animator`] plays
+//! [`Timeline`]s of [`Tween`]s on those frames until they finish.
+
+mod animator;
+mod easing;
+mod frame;
+mod timeline;
+mod tween;
+
+pub use self::animator::*;
+pub use self::easing::*;
+pub(crate) use self::frame::request_frame;
+pub use self::frame::FrameTime;
+pub use self::timeline::*;
+pub use self::tween::*;
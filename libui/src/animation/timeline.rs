@@ -0,0 +1,210 @@
+use animation::{Lerp, Tween};
+use std::fmt;
+use std::time::Duration;
+
+/// How often a [`Timeline`] plays.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Repeat {
+    #[default]
+    Once,
+    Times(u32),
+    Forever,
+}
+
+struct Track {
+    start: Duration,
+    duration: Duration,
+    /// Sets the property for a time since the start of the track.
+    apply: Box<dyn FnMut(Duration)>,
+}
+
+/// A set of property tweens played together, in parallel or in sequence.
+///
+/// Each tween comes with a setter, which is called with the current value on every frame
+/// once the tween started. A [`Animator`](super::Animator) plays timelines.
+///
+/// ```
+/// # use libui::animation::*;
+/// # use std::cell::Cell;
+/// # use std::rc::Rc;
+/// # use std::time::Duration;
+/// let x = Rc::new(Cell::new(0.0));
+/// let setter = x.clone();
+/// let ms = Duration::from_millis;
+/// let mut timeline = Timeline::new()
+///     .tween(Tween::new(0.0, 10.0, ms(100)), {
+///         let x = setter.clone();
+///         move |value| x.set(value)
+///     })
+///     .then(Tween::new(10.0, 0.0, ms(100)), move |value| setter.set(value));
+/// assert_eq!(timeline.duration(), ms(200));
+/// assert!(!timeline.seek(ms(150)));
+/// assert_eq!(x.get(), 5.0);
+/// assert!(timeline.seek(ms(250)));
+/// assert_eq!(x.get(), 0.0);
+/// ```
+#[derive(Default)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+    repeat: Repeat,
+    alternate: bool,
+    on_finished: Option<Box<dyn FnOnce()>>,
+}
+
+impl fmt::Debug for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeline")
+            .field("tracks", &self.tracks.len())
+            .field("duration", &self.duration())
+            .field("repeat", &self.repeat)
+            .field("alternate", &self.alternate)
+            .finish()
+    }
+}
+
+impl Timeline {
+    /// Creates an empty timeline.
+    pub fn new() -> Timeline {
+        Timeline::default()
+    }
+
+    /// Adds a tween starting with the timeline, after its delay.
+    pub fn tween<T, F>(self, tween: Tween<T>, setter: F) -> Self
+    where
+        T: Lerp + 'static,
+        F: FnMut(T) + 'static,
+    {
+        self.tween_at(Duration::from_secs(0), tween, setter)
+    }
+
+    /// Adds a tween starting when all previously added tweens have finished.
+    pub fn then<T, F>(self, tween: Tween<T>, setter: F) -> Self
+    where
+        T: Lerp + 'static,
+        F: FnMut(T) + 'static,
+    {
+        let end = self.duration();
+        self.tween_at(end, tween, setter)
+    }
+
+    /// Adds a tween starting at the given time of the timeline, plus its delay.
+    pub fn tween_at<T, F>(mut self, start: Duration, tween: Tween<T>, mut setter: F) -> Self
+    where
+        T: Lerp + 'static,
+        F: FnMut(T) + 'static,
+    {
+        let delay = tween.delay;
+        self.tracks.push(Track {
+            start: start + delay,
+            duration: tween.duration,
+            apply: Box::new(move |elapsed| setter(tween.value_at(delay + elapsed))),
+        });
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every other repetition backwards.
+    pub fn with_alternate(mut self, alternate: bool) -> Self {
+        self.alternate = alternate;
+        self
+    }
+
+    /// Sets a callback run once the timeline has finished playing. It is not run for
+    /// timelines stopped early or repeating forever.
+    pub fn on_finished<F: FnOnce() + 'static>(mut self, callback: F) -> Self {
+        self.on_finished = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the duration of one repetition.
+    pub fn duration(&self) -> Duration {
+        self.tracks
+            .iter()
+            .map(|track| track.start + track.duration)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the duration including repetitions, or `None` if it repeats forever.
+    pub fn total_duration(&self) -> Option<Duration> {
+        match self.repeat {
+            Repeat::Once => Some(self.duration()),
+            Repeat::Times(times) => Some(self.duration() * times),
+            Repeat::Forever => None,
+        }
+    }
+
+    /// Sets all properties to their values at the given time since the start. Returns
+    /// whether the timeline has finished.
+    pub fn seek(&mut self, elapsed: Duration) -> bool {
+        let duration = self.duration();
+        let finished = self.total_duration().is_some_and(|total| elapsed >= total);
+        let (cycle, local) = if finished || duration == Duration::from_secs(0) {
+            let cycles = self.total_duration().map_or(1, |total| {
+                (total.as_nanos() / duration.as_nanos().max(1)).max(1)
+            });
+            (cycles - 1, duration)
+        } else {
+            let nanos = duration.as_nanos();
+            let elapsed = elapsed.as_nanos();
+            (
+                elapsed / nanos,
+                Duration::from_nanos((elapsed % nanos) as u64),
+            )
+        };
+        let local = if self.alternate && cycle % 2 == 1 {
+            duration - local
+        } else {
+            local
+        };
+        for track in &mut self.tracks {
+            if local >= track.start {
+                (track.apply)(local - track.start);
+            }
+        }
+        finished
+    }
+
+    /// Takes the callback set by [`Timeline::on_finished()`].
+    pub(crate) fn take_on_finished(&mut self) -> Option<Box<dyn FnOnce()>> {
+        self.on_finished.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn repeats_and_alternates() {
+        let ms = Duration::from_millis;
+        let value = Rc::new(Cell::new(-1.0f64));
+        let setter = value.clone();
+        let mut timeline = Timeline::new()
+            .tween(
+                Tween::new(0.0, 100.0, ms(100)).with_delay(ms(100)),
+                move |v| setter.set(v),
+            )
+            .with_repeat(Repeat::Times(3))
+            .with_alternate(true);
+        assert_eq!(timeline.duration(), ms(200));
+        assert_eq!(timeline.total_duration(), Some(ms(600)));
+
+        timeline.seek(ms(50));
+        assert_eq!(value.get(), -1.0);
+        timeline.seek(ms(150));
+        assert_eq!(value.get(), 50.0);
+        // The second repetition runs backwards.
+        timeline.seek(ms(225));
+        assert!((value.get() - 75.0).abs() < 1e-9);
+        assert!(!timeline.seek(ms(599)));
+        assert!(timeline.seek(ms(700)));
+        assert_eq!(value.get(), 100.0);
+    }
+}
@@ -0,0 +1,101 @@
+use animation::Easing;
+use draw::Color;
+use std::time::Duration;
+
+/// Values which can be interpolated by a [`Tween`].
+pub trait Lerp: Clone {
+    /// Returns the value at `t` between `self` (0) and `other` (1). `t` may lie outside
+    /// of [0, 1] for overshooting easings.
+    fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &f64, t: f64) -> f64 {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &f32, t: f64) -> f32 {
+        (*self as f64).lerp(&(*other as f64), t) as f32
+    }
+}
+
+impl Lerp for (f64, f64) {
+    fn lerp(&self, other: &(f64, f64), t: f64) -> (f64, f64) {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, other: &Color, t: f64) -> Color {
+        Color::lerp(self, other, t)
+    }
+}
+
+/// A transition of a value from `from` to `to` over a duration, with an easing.
+///
+/// ```
+/// # use libui::animation::*;
+/// # use std::time::Duration;
+/// let tween = Tween::new(0.0, 100.0, Duration::from_millis(200))
+///     .with_easing(Easing::QuadIn)
+///     .with_delay(Duration::from_millis(100));
+/// assert_eq!(tween.value_at(Duration::from_millis(50)), 0.0);
+/// assert_eq!(tween.value_at(Duration::from_millis(200)), 25.0);
+/// assert_eq!(tween.value_at(Duration::from_secs(1)), 100.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Creates a linear tween without delay.
+    pub fn new(from: T, to: T, duration: Duration) -> Tween<T> {
+        Tween {
+            from,
+            to,
+            duration,
+            delay: Duration::from_secs(0),
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets the time before the value starts to change.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Returns the eased progress from 0 to 1 at the given time since the start,
+    /// including the delay.
+    pub fn progress_at(&self, elapsed: Duration) -> f64 {
+        let elapsed = elapsed.checked_sub(self.delay).unwrap_or_default();
+        let t = if self.duration == Duration::from_secs(0) {
+            1.0
+        } else {
+            elapsed.as_secs_f64() / self.duration.as_secs_f64()
+        };
+        self.easing.apply(t)
+    }
+
+    /// Returns the value at the given time since the start, including the delay.
+    pub fn value_at(&self, elapsed: Duration) -> T {
+        self.from.lerp(&self.to, self.progress_at(elapsed))
+    }
+
+    /// Returns the duration including the delay.
+    pub fn total_duration(&self) -> Duration {
+        self.delay + self.duration
+    }
+}
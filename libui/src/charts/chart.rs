@@ -0,0 +1,941 @@
+use charts::{Series, Ticks};
+use controls::FontDescription;
+use draw::{
+    Brush, Canvas, Color, PathBuilder, PathData, Rect, StrokeParams, TextAlign, TextLayoutParams,
+};
+use std::f64::consts::PI;
+
+/// The average width of a character relative to the font size, used to estimate the
+/// size of labels without laying them out.
+const CHAR_WIDTH: f64 = 0.6;
+
+/// How close the mouse has to be to a point for its tooltip, in points.
+const HIT_DISTANCE: f64 = 12.0;
+
+/// The kinds of charts a [`Chart`] can draw.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChartKind {
+    /// Lines connecting the points of each series.
+    Line,
+    /// Lines with the area down to zero filled.
+    Area,
+    /// Bars for the values of each series, grouped by category.
+    Bar,
+    /// A dot for every point.
+    Scatter,
+    /// Slices for the values of the first series, labeled by category.
+    Pie,
+}
+
+/// Colors and font of a chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartStyle {
+    /// The font of labels. Titles are drawn larger and bold.
+    pub font: FontDescription,
+    /// The colors of series without a color of their own, and of pie slices.
+    pub palette: Vec<Color>,
+    pub text_color: Color,
+    pub axis_color: Color,
+    pub grid_color: Color,
+    pub tooltip_background: Color,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        ChartStyle {
+            font: FontDescription {
+                size: 11.0,
+                ..FontDescription::default()
+            },
+            palette: [
+                0x4e79a7, 0xf28e2b, 0xe15759, 0x76b7b2, 0x59a14f, 0xedc948, 0xb07aa1, 0xff9da7,
+            ]
+            .iter()
+            .map(|&rgb| Color::from_rgba8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255))
+            .collect(),
+            text_color: Color::rgb(0.2, 0.2, 0.2),
+            axis_color: Color::rgb(0.4, 0.4, 0.4),
+            grid_color: Color::new(0.0, 0.0, 0.0, 0.12),
+            tooltip_background: Color::new(1.0, 1.0, 1.0, 0.95),
+        }
+    }
+}
+
+/// The data point under the mouse, as found by [`Chart::tooltip_at()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tooltip {
+    /// The index of the series.
+    pub series: usize,
+    /// The index of the point in the series.
+    pub index: usize,
+    /// The position the tooltip points at, in area coordinates.
+    pub x: f64,
+    pub y: f64,
+    pub text: String,
+}
+
+/// Maps data values linearly to area coordinates.
+#[derive(Copy, Clone, Debug)]
+struct Scale {
+    domain: (f64, f64),
+    range: (f64, f64),
+}
+
+impl Scale {
+    fn map(&self, value: f64) -> f64 {
+        let (d0, d1) = self.domain;
+        let (r0, r1) = self.range;
+        r0 + (value - d0) / (d1 - d0) * (r1 - r0)
+    }
+
+    /// Returns the data value at a position, the inverse of `map()`.
+    fn map_back(&self, position: f64) -> f64 {
+        self.domain.0 + (position - self.range.0) * self.per_point()
+    }
+
+    /// Returns the data distance covered by one point.
+    fn per_point(&self) -> f64 {
+        (self.domain.1 - self.domain.0) / (self.range.1 - self.range.0)
+    }
+}
+
+struct Layout {
+    plot: Rect,
+    legend: Option<Rect>,
+    x: Scale,
+    y: Scale,
+    x_ticks: Option<Ticks>,
+    y_ticks: Ticks,
+}
+
+/// A line, area, bar, scatter or pie chart, drawn onto any [`Canvas`].
+///
+/// Axes get ticks at nice values, and charts with more than one series, and pie charts,
+/// get a legend. Line, area, scatter and bar charts can be zoomed and panned along the
+/// x axis. Show a chart in a window with a [`ChartArea`](super::ChartArea).
+///
+/// ```
+/// # use libui::charts::*;
+/// # use libui::draw::Rasterizer;
+/// let chart = Chart::new(ChartKind::Bar)
+///     .with_title("Fruit sold")
+///     .with_categories(&["Apples", "Pears", "Plums"])
+///     .with_series(Series::from_values("Monday", &[12.0, 7.0, 3.0]))
+///     .with_series(Series::from_values("Tuesday", &[9.0, 11.0, 5.0]));
+/// let mut image = Rasterizer::new(400, 300);
+/// chart.render(&mut image, 400.0, 300.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chart {
+    kind: ChartKind,
+    title: Option<String>,
+    categories: Vec<String>,
+    series: Vec<Series>,
+    style: ChartStyle,
+    legend: bool,
+    x_view: Option<(f64, f64)>,
+    y_range: Option<(f64, f64)>,
+}
+
+impl Chart {
+    /// Creates an empty chart.
+    pub fn new(kind: ChartKind) -> Chart {
+        Chart {
+            kind,
+            title: None,
+            categories: Vec::new(),
+            series: Vec::new(),
+            style: ChartStyle::default(),
+            legend: true,
+            x_view: None,
+            y_range: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the labels of the points of bar and pie charts.
+    pub fn with_categories(mut self, categories: &[&str]) -> Self {
+        self.categories = categories.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    pub fn with_style(mut self, style: ChartStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Shows or hides the legend. It is shown by default.
+    pub fn with_legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Fixes the range of the y axis instead of fitting it to the data.
+    pub fn with_y_range(mut self, min: f64, max: f64) -> Self {
+        self.y_range = Some((min, max));
+        self
+    }
+
+    pub fn kind(&self) -> ChartKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: ChartKind) {
+        self.kind = kind;
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.title = title.map(str::to_string);
+    }
+
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    pub fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    /// Returns the series at the given index, e.g. to append streaming data.
+    pub fn series_mut(&mut self, index: usize) -> Option<&mut Series> {
+        self.series.get_mut(index)
+    }
+
+    /// Adds a series, returning its index.
+    pub fn add_series(&mut self, series: Series) -> usize {
+        self.series.push(series);
+        self.series.len() - 1
+    }
+
+    /// Appends a point to the series at the given index.
+    ///
+    /// # Panics
+    /// Panics if there is no such series.
+    pub fn push(&mut self, series: usize, x: f64, y: f64) {
+        self.series[series].push(x, y);
+    }
+
+    pub fn style(&self) -> &ChartStyle {
+        &self.style
+    }
+
+    pub fn style_mut(&mut self) -> &mut ChartStyle {
+        &mut self.style
+    }
+
+    /// Returns the visible range of the x axis, if it was zoomed or panned.
+    pub fn x_view(&self) -> Option<(f64, f64)> {
+        self.x_view
+    }
+
+    /// Sets the visible range of the x axis, or `None` to show all data.
+    pub fn set_x_view(&mut self, view: Option<(f64, f64)>) {
+        self.x_view = view.filter(|&(min, max)| max > min && min.is_finite() && max.is_finite());
+    }
+
+    /// Zooms the x axis by `factor` around the data value `center`. A factor above 1
+    /// zooms in.
+    pub fn zoom_x(&mut self, factor: f64, center: f64) {
+        if self.kind == ChartKind::Pie || factor <= 0.0 {
+            return;
+        }
+        let (min, max) = self.x_range();
+        self.set_x_view(Some((
+            center - (center - min) / factor,
+            center + (max - center) / factor,
+        )));
+    }
+
+    /// Moves the visible range of the x axis by `delta` in data units.
+    pub fn pan_x(&mut self, delta: f64) {
+        if self.kind == ChartKind::Pie {
+            return;
+        }
+        let (min, max) = self.x_range();
+        self.set_x_view(Some((min + delta, max + delta)));
+    }
+
+    /// Moves the visible range of the x axis so the data follows a drag by `dx` points,
+    /// for a chart drawn at the given size.
+    pub fn pan_x_by_points(&mut self, width: f64, height: f64, dx: f64) {
+        if self.kind == ChartKind::Pie {
+            return;
+        }
+        let per_point = self.layout(width, height).x.per_point();
+        self.pan_x(-dx * per_point);
+    }
+
+    /// Returns the data value on the x axis at a horizontal position, for a chart drawn at
+    /// the given size.
+    pub fn x_at(&self, width: f64, height: f64, x: f64) -> f64 {
+        self.layout(width, height).x.map_back(x)
+    }
+
+    /// Draws the chart into the rectangle from `(0, 0)` to `(width, height)`.
+    pub fn render<C: Canvas>(&self, canvas: &mut C, width: f64, height: f64) {
+        self.render_clipped(canvas, width, height, (0.0, 0.0, width, height));
+    }
+
+    /// Draws the chart like [`Chart::render()`], laying out only the x axis labels within
+    /// the clip rectangle `(x, y, width, height)`, e.g. the visible part of a zoomed chart.
+    pub fn render_clipped<C: Canvas>(&self, canvas: &mut C, width: f64, height: f64, clip: Rect) {
+        let size = self.font_size();
+        if let Some(ref title) = self.title {
+            let params = self.text(title, 1.3, 700, width, TextAlign::Center);
+            canvas.draw_text(&params, 0.0, size * 0.5);
+        }
+        let layout = self.layout(width, height);
+        if self.kind == ChartKind::Pie {
+            self.render_pie(canvas, &layout);
+        } else {
+            self.render_axes(canvas, &layout, clip);
+            canvas.saved(|canvas| {
+                let (x, y, w, h) = layout.plot;
+                canvas.clip(&PathData::rectangle(x, y, w, h));
+                for (index, series) in self.series.iter().enumerate() {
+                    self.render_series(canvas, &layout, index, series);
+                }
+            });
+        }
+        if let Some(legend) = layout.legend {
+            self.render_legend(canvas, legend);
+        }
+    }
+
+    /// Returns the data point at a position, for a chart drawn at the given size.
+    pub fn tooltip_at(&self, width: f64, height: f64, x: f64, y: f64) -> Option<Tooltip> {
+        let layout = self.layout(width, height);
+        match self.kind {
+            ChartKind::Pie => self.pie_tooltip(&layout, x, y),
+            ChartKind::Bar => self.bar_tooltip(&layout, x, y),
+            _ => self.point_tooltip(&layout, x, y),
+        }
+    }
+
+    /// Draws a tooltip box pointing at its data point.
+    pub fn render_tooltip<C: Canvas>(
+        &self,
+        canvas: &mut C,
+        width: f64,
+        height: f64,
+        tooltip: &Tooltip,
+    ) {
+        let size = self.font_size();
+        let line_count = tooltip.text.lines().count().max(1) as f64;
+        let w = self.text_width(&tooltip.text) + size;
+        let h = line_count * size * 1.3 + size * 0.6;
+        let x = (tooltip.x + size).min(width - w - 1.0).max(1.0);
+        let y = (tooltip.y - size - h).max(1.0).min(height - h - 1.0);
+
+        if self.kind != ChartKind::Pie {
+            let marker = PathBuilder::default()
+                .circle(tooltip.x, tooltip.y, 4.0)
+                .finish();
+            canvas.fill(&marker, &Brush::Solid(self.series_color(tooltip.series)));
+        }
+        let frame = PathBuilder::default()
+            .rounded_rect(x, y, w, h, 3.0)
+            .finish();
+        canvas.fill(&frame, &Brush::Solid(self.style.tooltip_background));
+        canvas.stroke(
+            &frame,
+            &Brush::Solid(self.style.axis_color),
+            &StrokeParams::new(1.0),
+        );
+        let params = self.text(&tooltip.text, 1.0, 400, -1.0, TextAlign::Left);
+        canvas.draw_text(&params, x + size / 2.0, y + size * 0.3);
+    }
+
+    fn font_size(&self) -> f64 {
+        if self.style.font.size > 0.0 {
+            self.style.font.size
+        } else {
+            11.0
+        }
+    }
+
+    fn text_width(&self, text: &str) -> f64 {
+        let longest = text.lines().map(|line| line.chars().count()).max();
+        longest.unwrap_or(0) as f64 * self.font_size() * CHAR_WIDTH
+    }
+
+    fn text(
+        &self,
+        text: &str,
+        scale: f64,
+        weight: u32,
+        width: f64,
+        align: TextAlign,
+    ) -> TextLayoutParams {
+        let mut font = self.style.font.clone();
+        font.size = self.font_size() * scale;
+        if weight > font.weight {
+            font.weight = weight;
+        }
+        TextLayoutParams::colored(text, self.style.text_color, &font).wrapped(width, align)
+    }
+
+    fn series_color(&self, index: usize) -> Color {
+        self.series
+            .get(index)
+            .and_then(|series| series.color)
+            .unwrap_or_else(|| self.palette_color(index))
+    }
+
+    fn palette_color(&self, index: usize) -> Color {
+        match self.style.palette.len() {
+            0 => self.style.axis_color,
+            len => self.style.palette[index % len],
+        }
+    }
+
+    fn category(&self, index: usize) -> String {
+        self.categories
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| (index + 1).to_string())
+    }
+
+    /// Returns the labels and colors of the legend, if it is shown.
+    fn legend_entries(&self) -> Vec<(String, Color)> {
+        if !self.legend {
+            return Vec::new();
+        }
+        match self.kind {
+            ChartKind::Pie => {
+                let count = self.series.first().map_or(0, Series::len);
+                (0..count)
+                    .map(|i| (self.category(i), self.palette_color(i)))
+                    .collect()
+            }
+            _ if self.series.len() > 1 => self
+                .series
+                .iter()
+                .enumerate()
+                .map(|(i, series)| (series.name.clone(), self.series_color(i)))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the visible range of the x axis: the zoomed one, or the range of the data.
+    pub fn x_range(&self) -> (f64, f64) {
+        if let Some(view) = self.x_view {
+            return view;
+        }
+        if self.kind == ChartKind::Bar {
+            let count = self.series.iter().map(Series::len).max().unwrap_or(0);
+            let count = count.max(self.categories.len()).max(1);
+            return (-0.5, count as f64 - 0.5);
+        }
+        let (min, max) = range(self.series.iter().flat_map(|s| s.points().map(|p| p.0)));
+        match (min, max) {
+            (Some(min), Some(max)) if max > min => {
+                if self.kind == ChartKind::Scatter {
+                    let ticks = Ticks::new(min, max, 5);
+                    (ticks.min, ticks.max)
+                } else {
+                    (min, max)
+                }
+            }
+            (Some(value), Some(_)) => (value - 0.5, value + 0.5),
+            _ => (0.0, 1.0),
+        }
+    }
+
+    fn y_ticks(&self, count: usize) -> Ticks {
+        if let Some((min, max)) = self.y_range {
+            let ticks = Ticks::within(min, max, count);
+            return Ticks { min, max, ..ticks };
+        }
+        let values = self.series.iter().flat_map(|s| s.points().map(|p| p.1));
+        let (min, max) = range(values);
+        let (mut min, mut max) = (min.unwrap_or(0.0), max.unwrap_or(1.0));
+        if self.kind == ChartKind::Bar || self.kind == ChartKind::Area {
+            min = min.min(0.0);
+            max = max.max(0.0);
+        }
+        Ticks::new(min, max, count)
+    }
+
+    fn layout(&self, width: f64, height: f64) -> Layout {
+        let size = self.font_size();
+        let top = if self.title.is_some() {
+            size * 2.8
+        } else {
+            size
+        };
+        let entries = self.legend_entries();
+        let legend_width = entries
+            .iter()
+            .map(|(label, _)| self.text_width(label))
+            .fold(0.0, f64::max)
+            + size * 2.5;
+        let legend = if entries.is_empty() {
+            None
+        } else {
+            Some((
+                width - legend_width - size / 2.0,
+                top,
+                legend_width,
+                entries.len() as f64 * size * 1.6,
+            ))
+        };
+        let right = legend.map_or(size, |_| legend_width + size);
+
+        let bottom = if self.kind == ChartKind::Pie {
+            size
+        } else {
+            size * 2.4
+        };
+        let tick_count = ((height - top - bottom) / (size * 3.5)).max(2.0) as usize;
+        let y_ticks = self.y_ticks(tick_count);
+        let left = if self.kind == ChartKind::Pie {
+            size
+        } else {
+            y_ticks
+                .values()
+                .iter()
+                .map(|&v| self.text_width(&y_ticks.format(v)))
+                .fold(0.0, f64::max)
+                + size * 1.2
+        };
+        let plot = (
+            left,
+            top,
+            (width - left - right).max(1.0),
+            (height - top - bottom).max(1.0),
+        );
+
+        let x = Scale {
+            domain: self.x_range(),
+            range: (plot.0, plot.0 + plot.2),
+        };
+        let y = Scale {
+            domain: (y_ticks.min, y_ticks.max),
+            range: (plot.1 + plot.3, plot.1),
+        };
+        let x_ticks = match self.kind {
+            ChartKind::Bar | ChartKind::Pie => None,
+            _ => {
+                let count = (plot.2 / (size * 7.0)).max(2.0) as usize;
+                Some(Ticks::within(x.domain.0, x.domain.1, count))
+            }
+        };
+        Layout {
+            plot,
+            legend,
+            x,
+            y,
+            x_ticks,
+            y_ticks,
+        }
+    }
+
+    fn render_axes<C: Canvas>(&self, canvas: &mut C, layout: &Layout, clip: Rect) {
+        let size = self.font_size();
+        let (px, py, pw, ph) = layout.plot;
+        let grid = Brush::Solid(self.style.grid_color);
+        let axis = Brush::Solid(self.style.axis_color);
+
+        let mut lines = PathBuilder::default();
+        for value in layout.y_ticks.values() {
+            let y = layout.y.map(value).round() + 0.5;
+            lines = lines.move_to(px, y).line_to(px + pw, y);
+            let label = self.text(
+                &layout.y_ticks.format(value),
+                1.0,
+                400,
+                px - size * 0.6,
+                TextAlign::Right,
+            );
+            canvas.draw_text(&label, 0.0, y - size * 0.65);
+        }
+        canvas.stroke(&lines.finish(), &grid, &StrokeParams::new(1.0));
+
+        let label_y = py + ph + size * 0.5;
+        match layout.x_ticks {
+            Some(ticks) => {
+                // Labels are centered on their tick, so include those half a label outside.
+                let margin = size * 4.0;
+                let from = layout.x.map_back(clip.0 - margin);
+                let to = layout.x.map_back(clip.0 + clip.2 + margin);
+                let visible = Ticks {
+                    min: ticks.min.max((from / ticks.step).ceil() * ticks.step),
+                    max: ticks.max.min((to / ticks.step).floor() * ticks.step),
+                    step: ticks.step,
+                };
+                for value in visible.values() {
+                    let x = layout.x.map(value);
+                    let label = ticks.format(value);
+                    let width = self.text_width(&label) + size;
+                    let params = self.text(&label, 1.0, 400, width, TextAlign::Center);
+                    canvas.draw_text(&params, x - width / 2.0, label_y);
+                }
+            }
+            None => {
+                let slot = layout.x.map(1.0) - layout.x.map(0.0);
+                let count = self.series.iter().map(Series::len).max().unwrap_or(0);
+                for i in 0..count.max(self.categories.len()) {
+                    let x = layout.x.map(i as f64);
+                    let params = self.text(&self.category(i), 1.0, 400, slot, TextAlign::Center);
+                    canvas.draw_text(&params, x - slot / 2.0, label_y);
+                }
+            }
+        }
+
+        let baseline = PathBuilder::default()
+            .move_to(px, py)
+            .line_to(px, py + ph)
+            .line_to(px + pw, py + ph)
+            .finish();
+        canvas.stroke(&baseline, &axis, &StrokeParams::new(1.0));
+    }
+
+    fn render_series<C: Canvas>(
+        &self,
+        canvas: &mut C,
+        layout: &Layout,
+        index: usize,
+        series: &Series,
+    ) {
+        let color = self.series_color(index);
+        let points: Vec<(f64, f64)> = series
+            .points()
+            .map(|(x, y)| (layout.x.map(x), layout.y.map(y)))
+            .collect();
+        match self.kind {
+            ChartKind::Line | ChartKind::Area => {
+                if let (Some(first), Some(last), ChartKind::Area) =
+                    (points.first(), points.last(), self.kind)
+                {
+                    let base = layout
+                        .y
+                        .map(0.0_f64.clamp(layout.y.domain.0, layout.y.domain.1));
+                    let area = PathBuilder::default()
+                        .polyline(&points)
+                        .line_to(last.0, base)
+                        .line_to(first.0, base)
+                        .close()
+                        .finish();
+                    canvas.fill(&area, &Brush::Solid(color.with_alpha(color.a * 0.3)));
+                }
+                let line = PathBuilder::default().polyline(&points).finish();
+                canvas.stroke(&line, &Brush::Solid(color), &StrokeParams::new(2.0));
+            }
+            ChartKind::Scatter => {
+                let dots = points.iter().fold(PathBuilder::default(), |dots, &(x, y)| {
+                    dots.circle(x, y, 3.5)
+                });
+                canvas.fill(&dots.finish(), &Brush::Solid(color));
+            }
+            ChartKind::Bar => {
+                let bars = (0..series.len())
+                    .filter_map(|i| self.bar_rect(layout, index, series, i))
+                    .fold(PathBuilder::default(), |bars, (x, y, w, h)| {
+                        bars.rect(x, y, w, h)
+                    });
+                canvas.fill(&bars.finish(), &Brush::Solid(color));
+            }
+            ChartKind::Pie => {}
+        }
+    }
+
+    /// Returns the rectangle of a bar in area coordinates.
+    fn bar_rect(
+        &self,
+        layout: &Layout,
+        series_index: usize,
+        series: &Series,
+        index: usize,
+    ) -> Option<Rect> {
+        let (_, value) = series.point(index)?;
+        let slot = layout.x.map(1.0) - layout.x.map(0.0);
+        let group = slot * 0.8;
+        let width = group / self.series.len().max(1) as f64;
+        let left = layout.x.map(index as f64) - group / 2.0 + series_index as f64 * width;
+        let base = layout
+            .y
+            .map(0.0_f64.clamp(layout.y.domain.0, layout.y.domain.1));
+        let top = layout.y.map(value);
+        let gap = if width > 4.0 { 1.0 } else { 0.0 };
+        Some((
+            left + gap,
+            top.min(base),
+            width - 2.0 * gap,
+            (top - base).abs(),
+        ))
+    }
+
+    /// Returns the center and radius of the pie.
+    fn pie_circle(&self, layout: &Layout) -> (f64, f64, f64) {
+        let (x, y, w, h) = layout.plot;
+        (x + w / 2.0, y + h / 2.0, (w.min(h) / 2.0).max(0.0))
+    }
+
+    /// Returns the start angle and sweep of each slice, and the total of the values.
+    fn pie_slices(&self) -> (Vec<(f64, f64)>, f64) {
+        let values: Vec<f64> = match self.series.first() {
+            Some(series) => series.points().map(|(_, y)| y.max(0.0)).collect(),
+            None => Vec::new(),
+        };
+        let total: f64 = values.iter().sum();
+        let mut start = -PI / 2.0;
+        let slices = values
+            .iter()
+            .map(|&value| {
+                let sweep = if total > 0.0 {
+                    value / total * 2.0 * PI
+                } else {
+                    0.0
+                };
+                let slice = (start, sweep);
+                start += sweep;
+                slice
+            })
+            .collect();
+        (slices, total)
+    }
+
+    fn render_pie<C: Canvas>(&self, canvas: &mut C, layout: &Layout) {
+        let (cx, cy, radius) = self.pie_circle(layout);
+        let separator = Brush::Solid(self.style.tooltip_background.with_alpha(1.0));
+        for (i, (start, sweep)) in self.pie_slices().0.into_iter().enumerate() {
+            if sweep <= 0.0 {
+                continue;
+            }
+            let slice = PathBuilder::default()
+                .move_to(cx, cy)
+                .arc(cx, cy, radius, start, sweep, false)
+                .close()
+                .finish();
+            canvas.fill(&slice, &Brush::Solid(self.palette_color(i)));
+            canvas.stroke(&slice, &separator, &StrokeParams::new(1.5));
+        }
+    }
+
+    fn render_legend<C: Canvas>(&self, canvas: &mut C, (x, y, _, _): Rect) {
+        let size = self.font_size();
+        for (i, (label, color)) in self.legend_entries().into_iter().enumerate() {
+            let top = y + i as f64 * size * 1.6;
+            let swatch = PathBuilder::default()
+                .rounded_rect(x, top, size, size, 2.0)
+                .finish();
+            canvas.fill(&swatch, &Brush::Solid(color));
+            let params = self.text(&label, 1.0, 400, -1.0, TextAlign::Left);
+            canvas.draw_text(&params, x + size * 1.5, top - size * 0.15);
+        }
+    }
+
+    fn point_tooltip(&self, layout: &Layout, x: f64, y: f64) -> Option<Tooltip> {
+        let (px, py, pw, ph) = layout.plot;
+        if x < px - HIT_DISTANCE
+            || x > px + pw + HIT_DISTANCE
+            || y < py - HIT_DISTANCE
+            || y > py + ph + HIT_DISTANCE
+        {
+            return None;
+        }
+        let mut best: Option<(f64, Tooltip)> = None;
+        for (series_index, series) in self.series.iter().enumerate() {
+            for (index, (vx, vy)) in series.points().enumerate() {
+                let (sx, sy) = (layout.x.map(vx), layout.y.map(vy));
+                let distance = ((sx - x).powi(2) + (sy - y).powi(2)).sqrt();
+                if distance > HIT_DISTANCE || best.as_ref().is_some_and(|b| b.0 <= distance) {
+                    continue;
+                }
+                let text = format!(
+                    "{}\n{}, {}",
+                    series.name,
+                    format_number(vx),
+                    format_number(vy)
+                );
+                best = Some((
+                    distance,
+                    Tooltip {
+                        series: series_index,
+                        index,
+                        x: sx,
+                        y: sy,
+                        text,
+                    },
+                ));
+            }
+        }
+        best.map(|(_, tooltip)| tooltip)
+    }
+
+    fn bar_tooltip(&self, layout: &Layout, x: f64, y: f64) -> Option<Tooltip> {
+        for (series_index, series) in self.series.iter().enumerate() {
+            for index in 0..series.len() {
+                let (bx, by, w, h) = match self.bar_rect(layout, series_index, series, index) {
+                    Some(rect) => rect,
+                    None => continue,
+                };
+                // Bars of small values can be hard to hit, so allow a few points around them.
+                if x >= bx && x < bx + w && y >= by - 3.0 && y < by + h + 3.0 {
+                    let value = format_number(series.point(index)?.1);
+                    let text = if self.series.len() > 1 {
+                        format!("{}\n{}: {}", series.name, self.category(index), value)
+                    } else {
+                        format!("{}: {}", self.category(index), value)
+                    };
+                    return Some(Tooltip {
+                        series: series_index,
+                        index,
+                        x: bx + w / 2.0,
+                        y: by,
+                        text,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn pie_tooltip(&self, layout: &Layout, x: f64, y: f64) -> Option<Tooltip> {
+        let (cx, cy, radius) = self.pie_circle(layout);
+        let (dx, dy) = (x - cx, y - cy);
+        if dx * dx + dy * dy > radius * radius {
+            return None;
+        }
+        let angle = dy.atan2(dx);
+        let (slices, total) = self.pie_slices();
+        let index = slices.iter().position(|&(start, sweep)| {
+            let offset = (angle - start).rem_euclid(2.0 * PI);
+            sweep > 0.0 && offset < sweep
+        })?;
+        let (start, sweep) = slices[index];
+        let value = self.series[0].point(index)?.1;
+        let middle = start + sweep / 2.0;
+        Some(Tooltip {
+            series: 0,
+            index,
+            x: cx + radius / 2.0 * middle.cos(),
+            y: cy + radius / 2.0 * middle.sin(),
+            text: format!(
+                "{}: {} ({:.1}%)",
+                self.category(index),
+                format_number(value),
+                value / total * 100.0
+            ),
+        })
+    }
+}
+
+/// Returns the smallest and largest finite value.
+fn range<I: Iterator<Item = f64>>(values: I) -> (Option<f64>, Option<f64>) {
+    values
+        .filter(|v| v.is_finite())
+        .fold((None, None), |(min, max), v| {
+            (
+                Some(min.map_or(v, |m: f64| m.min(v))),
+                Some(max.map_or(v, |m: f64| m.max(v))),
+            )
+        })
+}
+
+/// Formats a data value for tooltips, with at most three decimals.
+fn format_number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{DrawCommand, Recorder};
+
+    #[test]
+    fn finds_tooltips() {
+        let chart = Chart::new(ChartKind::Line)
+            .with_series(Series::from_values("a", &[0.0, 10.0, 5.0]))
+            .with_series(Series::from_values("b", &[2.0, 2.0, 2.0]));
+        let layout = chart.layout(300.0, 200.0);
+        let (x, y) = (layout.x.map(1.0), layout.y.map(10.0));
+        let tooltip = chart.tooltip_at(300.0, 200.0, x + 2.0, y - 2.0).unwrap();
+        assert_eq!((tooltip.series, tooltip.index), (0, 1));
+        assert_eq!(tooltip.text, "a\n1, 10");
+        assert_eq!(chart.tooltip_at(300.0, 200.0, x, y + 40.0), None);
+
+        let pie = Chart::new(ChartKind::Pie)
+            .with_categories(&["yes", "no"])
+            .with_series(Series::from_values("votes", &[3.0, 1.0]));
+        let (cx, cy, radius) = pie.pie_circle(&pie.layout(300.0, 200.0));
+        let tooltip = pie
+            .tooltip_at(300.0, 200.0, cx - radius / 2.0, cy - 1.0)
+            .unwrap();
+        assert_eq!(tooltip.text, "no: 1 (25.0%)");
+    }
+
+    #[test]
+    fn zooms_and_pans() {
+        let mut chart = Chart::new(ChartKind::Line).with_series(
+            Series::new("live")
+                .with_limit(3)
+                .with_points(vec![(0.0, 1.0)]),
+        );
+        for x in 1..10 {
+            chart.push(0, x as f64, 1.0);
+        }
+        assert_eq!(chart.x_range(), (7.0, 9.0));
+        chart.zoom_x(2.0, 8.0);
+        assert_eq!(chart.x_view(), Some((7.5, 8.5)));
+        chart.pan_x(-0.5);
+        assert_eq!(chart.x_view(), Some((7.0, 8.0)));
+        let x = chart.layout(200.0, 100.0).x.map(7.5);
+        assert!((chart.x_at(200.0, 100.0, x) - 7.5).abs() < 1e-9);
+        chart.set_x_view(None);
+        assert_eq!(chart.x_range(), (7.0, 9.0));
+    }
+
+    #[test]
+    fn renders_through_the_canvas() {
+        let chart = Chart::new(ChartKind::Bar)
+            .with_categories(&["a", "b"])
+            .with_series(Series::from_values("one", &[1.0, -2.0]))
+            .with_series(Series::from_values("two", &[3.0, 4.0]));
+        let mut recorder = Recorder::new();
+        chart.render(&mut recorder, 400.0, 300.0);
+        let list = recorder.finish();
+        let count =
+            |f: &dyn Fn(&DrawCommand) -> bool| list.commands().iter().filter(|c| f(c)).count();
+        assert_eq!(count(&|c| matches!(c, DrawCommand::Clip(_))), 1);
+        // Two series of bars and two legend swatches.
+        assert_eq!(count(&|c| matches!(c, DrawCommand::Fill { .. })), 4);
+        assert!(count(&|c| matches!(c, DrawCommand::Text { .. })) >= 2 + 2 + 2);
+    }
+
+    #[test]
+    fn lays_out_only_visible_x_labels() {
+        let chart = Chart::new(ChartKind::Line)
+            .with_series(Series::from_values("one", &[0.0, 1.0, 4.0, 9.0]));
+        let mut recorder = Recorder::new();
+        chart.render_clipped(&mut recorder, 1e9, 300.0, (0.0, 0.0, 400.0, 300.0));
+        let texts = recorder
+            .finish()
+            .commands()
+            .iter()
+            .filter(|c| matches!(c, DrawCommand::Text { .. }))
+            .count();
+        assert!(texts < 50, "{} labels", texts);
+    }
+}
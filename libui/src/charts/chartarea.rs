@@ -0,0 +1,266 @@
+use charts::{Chart, Tooltip};
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaMouseEvent, Control, MouseButton, MouseEvent,
+    ScrollingArea,
+};
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+
+/// The largest zoom factor of a scrolling chart.
+const MAX_ZOOM: f64 = 1000.0;
+
+struct ChartState {
+    chart: Chart,
+    tooltip: Option<Tooltip>,
+    /// The last mouse position of a left button drag panning the chart.
+    drag: Option<f64>,
+    /// The size at zoom 1 and the zoom factor of a scrolling area, whose draw and mouse
+    /// events do not carry the size.
+    scrolling: Option<(f64, f64, f64)>,
+}
+
+impl ChartState {
+    fn size(&self, width: f64, height: f64) -> (f64, f64) {
+        match self.scrolling {
+            Some((base_width, base_height, zoom)) => (base_width * zoom, base_height),
+            None => (width, height),
+        }
+    }
+}
+
+struct ChartHandler {
+    state: Rc<RefCell<ChartState>>,
+}
+
+impl AreaHandler for ChartHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let state = self.state.borrow();
+        let (width, height) = state.size(params.area_width, params.area_height);
+        let mut canvas = &params.context;
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        state.chart.render_clipped(&mut canvas, width, height, clip);
+        if let Some(ref tooltip) = state.tooltip {
+            state
+                .chart
+                .render_tooltip(&mut canvas, width, height, tooltip);
+        }
+    }
+
+    fn mouse_event(&mut self, area: &Area, event: &AreaMouseEvent) {
+        let mut state = self.state.borrow_mut();
+        let (width, height) = state.size(event.area_width, event.area_height);
+        let mut redraw = false;
+
+        if state.scrolling.is_none() {
+            match event.to_mouse_event() {
+                MouseEvent::Press {
+                    button: MouseButton::Left,
+                    count: 2,
+                    ..
+                } => {
+                    state.chart.set_x_view(None);
+                    redraw = true;
+                }
+                MouseEvent::Press {
+                    button: MouseButton::Left,
+                    ..
+                } => state.drag = Some(event.x),
+                MouseEvent::Release {
+                    button: MouseButton::Left,
+                    ..
+                } => state.drag = None,
+                MouseEvent::Move(_) => {
+                    if let Some(last) = state.drag {
+                        state.chart.pan_x_by_points(width, height, event.x - last);
+                        state.drag = Some(event.x);
+                        redraw = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let tooltip = if state.drag.is_some() {
+            None
+        } else {
+            state.chart.tooltip_at(width, height, event.x, event.y)
+        };
+        if tooltip != state.tooltip {
+            state.tooltip = tooltip;
+            redraw = true;
+        }
+        if redraw {
+            area.queue_redraw_all();
+        }
+    }
+
+    fn mouse_crossed(&mut self, area: &Area, left: bool) {
+        let mut state = self.state.borrow_mut();
+        if left && state.tooltip.take().is_some() {
+            area.queue_redraw_all();
+        }
+    }
+
+    fn drag_broken(&mut self, _area: &Area) {
+        self.state.borrow_mut().drag = None;
+    }
+}
+
+/// An `Area` showing a [`Chart`], with tooltips for the data point under the mouse.
+///
+/// In a non-scrolling area, dragging with the left button pans a zoomed chart and a
+/// double click shows all data again. A scrolling area zooms by growing its content
+/// instead, so the scroll bars pan the chart.
+///
+/// ```no_run
+/// # use libui::charts::*;
+/// let chart_area = ChartArea::new(
+///     Chart::new(ChartKind::Line).with_series(Series::new("Load").with_limit(100)),
+/// );
+/// // Called whenever a new measurement arrives:
+/// chart_area.push(0, 1.0, 0.42);
+/// ```
+#[derive(Clone)]
+pub struct ChartArea {
+    area: Area,
+    scrolling: Option<ScrollingArea>,
+    state: Rc<RefCell<ChartState>>,
+}
+
+impl ChartArea {
+    /// Creates a non-scrolling area showing the chart.
+    pub fn new(chart: Chart) -> ChartArea {
+        ChartArea::with_state(chart, None)
+    }
+
+    /// Creates a scrolling area showing the chart at the given size.
+    pub fn new_scrolling(chart: Chart, width: i64, height: i64) -> ChartArea {
+        ChartArea::with_state(chart, Some((width as f64, height as f64, 1.0)))
+    }
+
+    fn with_state(chart: Chart, scrolling: Option<(f64, f64, f64)>) -> ChartArea {
+        let state = Rc::new(RefCell::new(ChartState {
+            chart,
+            tooltip: None,
+            drag: None,
+            scrolling,
+        }));
+        let handler = Box::new(ChartHandler {
+            state: state.clone(),
+        });
+        let (area, scrolling) = match scrolling {
+            Some((width, height, _)) => {
+                let scrolling = ScrollingArea::new(handler, width as u64, height as u64);
+                (scrolling.area().clone(), Some(scrolling))
+            }
+            None => (Area::new(handler), None),
+        };
+        ChartArea {
+            area,
+            scrolling,
+            state,
+        }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    /// Returns the chart.
+    pub fn chart(&self) -> Ref<'_, Chart> {
+        Ref::map(self.state.borrow(), |state| &state.chart)
+    }
+
+    /// Modifies the chart and redraws it.
+    pub fn update<R, F: FnOnce(&mut Chart) -> R>(&self, f: F) -> R {
+        let result = {
+            let mut state = self.state.borrow_mut();
+            state.tooltip = None;
+            f(&mut state.chart)
+        };
+        self.area.queue_redraw_all();
+        result
+    }
+
+    /// Appends a point to the series at the given index and redraws the chart.
+    ///
+    /// # Panics
+    /// Panics if there is no such series.
+    pub fn push(&self, series: usize, x: f64, y: f64) {
+        self.update(|chart| chart.push(series, x, y));
+    }
+
+    /// Zooms the x axis by `factor`. A factor above 1 zooms in.
+    ///
+    /// A scrolling area grows or shrinks its content, keeping the center of the visible
+    /// part in place, up to a zoom of 1000; a non-scrolling one zooms around the center
+    /// of the visible range.
+    pub fn zoom(&self, factor: f64) {
+        if factor <= 0.0 {
+            return;
+        }
+        // Resizing and scrolling may draw right away, so the state must not be borrowed.
+        let resize = {
+            let mut state = self.state.borrow_mut();
+            state.tooltip = None;
+            match (state.scrolling, self.scrolling.as_ref()) {
+                (Some((width, height, zoom)), Some(scrolling)) => {
+                    let max_zoom = MAX_ZOOM.min(i32::MAX as f64 / width.max(1.0));
+                    let new_zoom = (zoom * factor).max(1.0).min(max_zoom);
+                    state.scrolling = Some((width, height, new_zoom));
+                    Some((scrolling, width, height, zoom, new_zoom))
+                }
+                _ => {
+                    let (min, max) = state.chart.x_range();
+                    state.chart.zoom_x(factor, (min + max) / 2.0);
+                    None
+                }
+            }
+        };
+        if let Some((scrolling, width, height, zoom, new_zoom)) = resize {
+            scrolling.set_size((width * new_zoom) as u64, height as u64);
+            if let Some((x, y, visible_width, visible_height)) = scrolling.visible_rect() {
+                let center = (x + visible_width / 2.0) * new_zoom / zoom;
+                scrolling.scroll_to(
+                    center - visible_width / 2.0,
+                    y,
+                    visible_width,
+                    visible_height,
+                );
+            }
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Shows all data again after zooming or panning.
+    pub fn reset_zoom(&self) {
+        let resize = {
+            let mut state = self.state.borrow_mut();
+            state.tooltip = None;
+            state.chart.set_x_view(None);
+            match (state.scrolling, self.scrolling.as_ref()) {
+                (Some((width, height, _)), Some(scrolling)) => {
+                    state.scrolling = Some((width, height, 1.0));
+                    Some((scrolling, width, height))
+                }
+                _ => None,
+            }
+        };
+        if let Some((scrolling, width, height)) = resize {
+            scrolling.set_size(width as u64, height as u64);
+        }
+        self.area.queue_redraw_all();
+    }
+}
+
+impl From<ChartArea> for Control {
+    fn from(chart_area: ChartArea) -> Control {
+        chart_area.area.into()
+    }
+}
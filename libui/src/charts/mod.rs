@@ -0,0 +1,15 @@
+//! Line, area, bar, scatter and pie charts.
+//!
+//! A [`Chart`] is plain data drawn through the [`draw`](crate::draw) API, so it renders
+//! onto any [`Canvas`](crate::draw::Canvas). A [`ChartArea`] shows it in a window, with
+//! tooltips on hover, zooming and panning, and redraws when data is appended.
+
+mod chart;
+mod chartarea;
+mod series;
+mod ticks;
+
+pub use self::chart::*;
+pub use self::chartarea::*;
+pub use self::series::*;
+pub use self::ticks::*;
@@ -0,0 +1,92 @@
+use draw::Color;
+use std::collections::VecDeque;
+
+/// A named sequence of `(x, y)` data points.
+///
+/// Bar and pie charts use the point index as category and ignore `x`. For streaming data,
+/// [`Series::with_limit()`] keeps only the most recent points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    pub name: String,
+    /// The color of the series, or `None` to use the palette of the chart.
+    pub color: Option<Color>,
+    points: VecDeque<(f64, f64)>,
+    limit: Option<usize>,
+}
+
+impl Series {
+    /// Creates an empty series.
+    pub fn new(name: &str) -> Series {
+        Series {
+            name: name.to_string(),
+            color: None,
+            points: VecDeque::new(),
+            limit: None,
+        }
+    }
+
+    /// Creates a series from values, using their index as `x`.
+    pub fn from_values(name: &str, values: &[f64]) -> Series {
+        Series::new(name).with_points(values.iter().enumerate().map(|(i, &y)| (i as f64, y)))
+    }
+
+    /// Appends points to the series.
+    pub fn with_points<I: IntoIterator<Item = (f64, f64)>>(mut self, points: I) -> Self {
+        for (x, y) in points {
+            self.push(x, y);
+        }
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Keeps only the last `limit` points, dropping the oldest ones on appends.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self.truncate();
+        self
+    }
+
+    /// Appends a point, dropping the oldest point if the series is at its limit.
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.points.push_back((x, y));
+        self.truncate();
+    }
+
+    /// Appends the next value, one after the `x` of the last point.
+    pub fn push_value(&mut self, y: f64) {
+        let x = self.points.back().map_or(0.0, |&(x, _)| x + 1.0);
+        self.push(x, y);
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, index: usize) -> Option<(f64, f64)> {
+        self.points.get(index).copied()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.points.iter().copied()
+    }
+
+    fn truncate(&mut self) {
+        if let Some(limit) = self.limit {
+            while self.points.len() > limit {
+                self.points.pop_front();
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+/// Evenly spaced axis ticks at "nice" values, that is, multiples of 1, 2 or 5 times a
+/// power of ten.
+///
+/// ```
+/// # use libui::charts::Ticks;
+/// let ticks = Ticks::new(0.3, 9.2, 5);
+/// assert_eq!((ticks.min, ticks.max, ticks.step), (0.0, 10.0, 2.0));
+/// assert_eq!(ticks.values(), [0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ticks {
+    /// The first tick, at or below the minimum of the range.
+    pub min: f64,
+    /// The last tick, at or above the maximum of the range.
+    pub max: f64,
+    pub step: f64,
+}
+
+impl Ticks {
+    /// Chooses ticks covering the range from `min` to `max`, with about `count` steps.
+    ///
+    /// An empty range is widened around its value.
+    pub fn new(min: f64, max: f64, count: usize) -> Ticks {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        let (min, max) = if max - min > f64::EPSILON * max.abs().max(1.0) {
+            (min, max)
+        } else {
+            let margin = if min == 0.0 { 1.0 } else { min.abs() / 10.0 };
+            (min - margin, max + margin)
+        };
+        let step = nice_step((max - min) / count.max(1) as f64);
+        Ticks {
+            min: (min / step).floor() * step,
+            max: (max / step).ceil() * step,
+            step,
+        }
+    }
+
+    /// Chooses ticks inside the range from `min` to `max`, for axes which must not be
+    /// extended, e.g. zoomed ones.
+    pub fn within(min: f64, max: f64, count: usize) -> Ticks {
+        let ticks = Ticks::new(min, max, count);
+        let step = ticks.step;
+        Ticks {
+            min: (min / step).ceil() * step,
+            max: (max / step).floor() * step,
+            step,
+        }
+    }
+
+    /// Returns the tick values from `min` to `max`.
+    pub fn values(&self) -> Vec<f64> {
+        let count = ((self.max - self.min) / self.step + 0.5).floor().max(-1.0) as i64;
+        let scale = 10f64.powi(self.decimals() as i32);
+        (0..=count)
+            .map(|i| {
+                let value = self.min + i as f64 * self.step;
+                // Avoid labels like 0.30000000000000004 and -0.
+                let rounded = (value * scale).round() / scale;
+                if rounded == 0.0 {
+                    0.0
+                } else {
+                    rounded
+                }
+            })
+            .collect()
+    }
+
+    /// Formats a value with as many decimals as the step needs.
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.decimals(), value)
+    }
+
+    fn decimals(&self) -> usize {
+        (-self.step.log10().floor()).max(0.0) as usize
+    }
+}
+
+/// Rounds a step up to 1, 2 or 5 times a power of ten.
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let fraction = raw / magnitude;
+    let nice = if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chooses_nice_steps() {
+        let ticks = Ticks::new(-0.013, 0.087, 4);
+        assert_eq!(ticks.step, 0.05);
+        assert_eq!(ticks.values(), [-0.05, 0.0, 0.05, 0.1]);
+        assert_eq!(ticks.format(0.05), "0.05");
+        assert_eq!(
+            Ticks::new(5.0, 5.0, 5).values(),
+            [4.4, 4.6, 4.8, 5.0, 5.2, 5.4, 5.6]
+        );
+        assert_eq!(Ticks::within(0.5, 7.5, 4).values(), [2.0, 4.0, 6.0]);
+        assert_eq!(Ticks::new(0.0, 1200.0, 5).format(400.0), "400");
+    }
+}
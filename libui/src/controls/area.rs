@@ -1,9 +1,13 @@
 //! Provides a way to allocate an area in the window for custom drawing.
 
-use controls::Control;
+use animation::{self, FrameTime};
+use controls::{Control, MouseEvent};
 use draw;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::mem;
 use std::os::raw::c_int;
+use std::rc::{Rc, Weak};
 pub use libui_ffi::uiExtKey as ExtKey;
 use libui_ffi::{
     self, uiArea, uiAreaDrawParams, uiAreaHandler, uiAreaKeyEvent, uiAreaMouseEvent, uiControl,
@@ -14,6 +18,10 @@ pub trait AreaHandler {
     fn mouse_event(&mut self, _area: &Area, _area_mouse_event: &AreaMouseEvent) {}
     fn mouse_crossed(&mut self, _area: &Area, _left: bool) {}
     fn drag_broken(&mut self, _area: &Area) {}
+    /// Receives all mouse events, crossings and broken drags as typed [`MouseEvent`]s,
+    /// after the raw handler methods above. Feed them to a
+    /// [`GestureRecognizer`](super::GestureRecognizer) to handle clicks and drags.
+    fn mouse(&mut self, _area: &Area, _event: &MouseEvent) {}
     fn key_event(&mut self, _area: &Area, _area_key_event: &AreaKeyEvent) -> bool {
         true
     }
@@ -64,9 +72,9 @@ impl RustAreaHandler {
                 let area = Area::from_ui_area(ui_area);
                 let area_mouse_event =
                     AreaMouseEvent::from_ui_area_mouse_event(&*ui_area_mouse_event);
-                (*(ui_area_handler as *mut RustAreaHandler))
-                    .trait_object
-                    .mouse_event(&area, &area_mouse_event);
+                let handler = &mut (*(ui_area_handler as *mut RustAreaHandler)).trait_object;
+                handler.mouse_event(&area, &area_mouse_event);
+                handler.mouse(&area, &area_mouse_event.to_mouse_event());
                 mem::forget(area_mouse_event);
                 mem::forget(area);
             }
@@ -79,9 +87,14 @@ impl RustAreaHandler {
         ) {
             unsafe {
                 let area = Area::from_ui_area(ui_area);
-                (*(ui_area_handler as *mut RustAreaHandler))
-                    .trait_object
-                    .mouse_crossed(&area, left != 0);
+                let handler = &mut (*(ui_area_handler as *mut RustAreaHandler)).trait_object;
+                handler.mouse_crossed(&area, left != 0);
+                let event = if left != 0 {
+                    MouseEvent::Leave
+                } else {
+                    MouseEvent::Enter
+                };
+                handler.mouse(&area, &event);
                 mem::forget(area);
             }
         }
@@ -89,9 +102,9 @@ impl RustAreaHandler {
         extern "C" fn drag_broken(ui_area_handler: *mut uiAreaHandler, ui_area: *mut uiArea) {
             unsafe {
                 let area = Area::from_ui_area(ui_area);
-                (*(ui_area_handler as *mut RustAreaHandler))
-                    .trait_object
-                    .drag_broken(&area);
+                let handler = &mut (*(ui_area_handler as *mut RustAreaHandler)).trait_object;
+                handler.drag_broken(&area);
+                handler.mouse(&area, &MouseEvent::DragBroken);
                 mem::forget(area);
             }
         }
@@ -115,6 +128,36 @@ impl RustAreaHandler {
     }
 }
 
+/// The `Destroy` function libui installed in an area's `uiControl`.
+type DestroyFn = unsafe extern "C" fn(*mut uiControl);
+
+thread_local! {
+    /// Liveness tokens of the areas created by this crate, dropped when libui destroys
+    /// the area, e.g. together with its window, along with the replaced `Destroy` function.
+    static LIVE_AREAS: RefCell<HashMap<usize, (Rc<()>, DestroyFn)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Hooks into the destruction of a new area, so frames requested for it can be dropped.
+unsafe fn watch_destroy(ui_area: *mut uiArea) {
+    let control = ui_area as *mut uiControl;
+    if let Some(destroy) = (*control).Destroy {
+        LIVE_AREAS.with(|areas| {
+            areas
+                .borrow_mut()
+                .insert(control as usize, (Rc::new(()), destroy))
+        });
+        (*control).Destroy = Some(destroy_area);
+    }
+}
+
+extern "C" fn destroy_area(control: *mut uiControl) {
+    let entry = LIVE_AREAS.with(|areas| areas.borrow_mut().remove(&(control as usize)));
+    if let Some((_, destroy)) = entry {
+        unsafe { destroy(control) }
+    }
+}
+
 define_control! {
     /// A space on which the application can draw custom content.
     /// Area is a Control that represents a blank canvas that a program can draw on as
@@ -154,11 +197,13 @@ impl Area {
                 &mut *rust_area_handler as *mut RustAreaHandler as *mut uiAreaHandler,
             ));
             mem::forget(rust_area_handler);
+            watch_destroy(area.uiArea);
             area
         }
     }
 
-    /// Creates a new scrolling area.
+    /// Creates a new scrolling area. A [`ScrollingArea`](super::ScrollingArea) offers safe
+    /// methods to resize and scroll it.
     pub fn new_scrolling(area_handler: Box<dyn AreaHandler>, width: i64, height: i64) -> Area {
         unsafe {
             let mut rust_area_handler = RustAreaHandler::new(area_handler);
@@ -168,6 +213,7 @@ impl Area {
                 height as i32,
             ));
             mem::forget(rust_area_handler);
+            watch_destroy(area.uiArea);
             area
         }
     }
@@ -180,8 +226,8 @@ impl Area {
     ///
     /// # Unsafety
     /// If called on a non-scrolling `Area`, this function's behavior is undefined.
+    #[deprecated(note = "use `ScrollingArea::set_size()` instead")]
     pub unsafe fn set_size(&self, width: u64, height: u64) {
-        // TODO: Check if the area is scrolling?
         libui_ffi::uiAreaSetSize(self.uiArea, width as i32, height as i32);
     }
 
@@ -191,14 +237,37 @@ impl Area {
         unsafe { libui_ffi::uiAreaQueueRedrawAll(self.uiArea) }
     }
 
+    /// Runs `callback` with the next animation frame and redraws the `Area` after it.
+    ///
+    /// Frames are driven by a UI thread timer at about 60 frames per second, which only
+    /// runs while callbacks are pending. Request another frame from the callback to
+    /// keep animating; an [`Animator`](::animation::Animator) does this for you.
+    ///
+    /// The callback is dropped without running if the area is destroyed first, and for
+    /// areas that were not created by [`Area::new()`] or [`Area::new_scrolling()`].
+    pub fn request_animation_frame<F: FnOnce(FrameTime) + 'static>(&self, callback: F) {
+        animation::request_frame(self, Box::new(callback));
+    }
+
+    /// Returns a token that stays alive until libui destroys the area. It is dead from
+    /// the start for areas that were not created by this crate.
+    pub(crate) fn liveness(&self) -> Weak<()> {
+        LIVE_AREAS.with(|areas| {
+            areas
+                .borrow()
+                .get(&(self.uiArea as usize))
+                .map_or_else(Weak::new, |(token, _)| Rc::downgrade(token))
+        })
+    }
+
     /// Scrolls the Area to show the given rectangle. This behavior is somewhat
     /// implementation defined, but you can assume that as much of the given rectangle
     /// as possible will be visible after this call.
     ///
     /// # Unsafety
     /// If called on a non-scrolling `Area`, this function's behavior is undefined.
+    #[deprecated(note = "use `ScrollingArea::scroll_to()` instead")]
     pub unsafe fn scroll_to(&self, x: f64, y: f64, width: f64, height: f64) {
-        // TODO: Make some way to check whether the given area is scrolling or not.
         libui_ffi::uiAreaScrollTo(self.uiArea, x, y, width, height);
     }
 }
@@ -0,0 +1,1246 @@
+//! A month calendar with single date and range selection, and the dates it works with.
+
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, FontDescription, Key, Modifiers,
+    MouseButton, MouseEvent,
+};
+use draw::{
+    Brush, Canvas, Color, PathBuilder, PathData, Rect, StrokeParams, TextAlign, TextLayoutParams,
+};
+use libc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type SelectionHandler = Box<dyn FnMut(&Calendar, Option<DateRange>)>;
+type MonthHandler = Box<dyn FnMut(&Calendar, i32, u8)>;
+type ActivateHandler = Box<dyn FnMut(&Calendar, Date)>;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A day of the week.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Returns the number of days since Monday, from 0 to 6.
+    pub fn days_from_monday(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the weekday a number of days after this one.
+    pub fn add_days(self, days: i64) -> Weekday {
+        Weekday::ALL[(self as i64 + days).rem_euclid(7) as usize]
+    }
+
+    /// Returns a two letter abbreviation, like "Mo".
+    pub fn short_name(self) -> &'static str {
+        ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"][self as usize]
+    }
+
+    pub fn is_weekend(self) -> bool {
+        self == Weekday::Saturday || self == Weekday::Sunday
+    }
+}
+
+/// A date of the proleptic Gregorian calendar, without a time or time zone.
+///
+/// Dates convert from and to the `libc::tm` of a [`DateTimePicker`](super::DateTimePicker),
+/// and compare and sort chronologically:
+///
+/// ```
+/// # use libui::controls::*;
+/// let date = Date::new(2024, 2, 28).unwrap();
+/// assert_eq!(date.add_days(2), Date::new(2024, 3, 1).unwrap());
+/// assert_eq!(date.add_months(12).day(), 28);
+/// assert_eq!(date.weekday(), Weekday::Wednesday);
+/// assert!(Date::new(2023, 2, 29).is_none());
+/// assert_eq!(date.to_string(), "2024-02-28");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "date_serde::DateData"))]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Returns the date, or `None` if the month or day does not exist.
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Date> {
+        if (1..=12).contains(&month) && day >= 1 && day <= Date::days_in_month(year, month) {
+            Some(Date { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    /// Returns the month, from 1 to 12.
+    pub fn month(self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, from 1.
+    pub fn day(self) -> u8 {
+        self.day
+    }
+
+    /// Returns the current date in the local time zone, or in UTC if it is unknown.
+    pub fn today() -> Date {
+        // `struct tm` has platform specific fields, which stay zeroed.
+        let mut tm = unsafe { std::mem::MaybeUninit::<libc::tm>::zeroed().assume_init() };
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        #[cfg(windows)]
+        let local = unsafe { libc::localtime_s(&mut tm, &now) == 0 };
+        #[cfg(not(windows))]
+        let local = unsafe { !libc::localtime_r(&now, &mut tm).is_null() };
+        if local {
+            Date::from_tm(&tm)
+        } else {
+            Date::today_utc()
+        }
+    }
+
+    /// Returns the current date in UTC.
+    pub fn today_utc() -> Date {
+        let seconds = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(error) => -(error.duration().as_secs() as i64),
+        };
+        Date::from_days(seconds.div_euclid(86_400))
+    }
+
+    pub fn is_leap_year(year: i32) -> bool {
+        year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    }
+
+    /// Returns the number of days of a month from 1 to 12, or 0 for other months.
+    pub fn days_in_month(year: i32, month: u8) -> u8 {
+        match month {
+            2 if Date::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of days since 1970-01-01, negative before.
+    pub fn to_days(self) -> i64 {
+        // The algorithm of Howard Hinnant's `days_from_civil`, with years starting in
+        // March so the leap day is last.
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Returns the date a number of days after 1970-01-01.
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        Date {
+            year: (year_of_era + era * 400 + (month <= 2) as i64) as i32,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    pub fn weekday(self) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        Weekday::Thursday.add_days(self.to_days())
+    }
+
+    pub fn add_days(self, days: i64) -> Date {
+        Date::from_days(self.to_days() + days)
+    }
+
+    /// Returns the date a number of months later, on the last day of the month if it is
+    /// shorter.
+    pub fn add_months(self, months: i32) -> Date {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        let (year, month) = (index.div_euclid(12), (index.rem_euclid(12) + 1) as u8);
+        let day = self.day.min(Date::days_in_month(year, month));
+        Date { year, month, day }
+    }
+
+    /// Returns the number of days from another date to this one.
+    pub fn days_since(self, other: Date) -> i64 {
+        self.to_days() - other.to_days()
+    }
+
+    pub fn first_of_month(self) -> Date {
+        Date { day: 1, ..self }
+    }
+
+    /// Returns the first day of the week containing this date.
+    pub fn start_of_week(self, first_weekday: Weekday) -> Date {
+        let offset = (self.weekday() as i64 - first_weekday as i64).rem_euclid(7);
+        self.add_days(-offset)
+    }
+
+    /// Reads the date of a `libc::tm`, as returned by
+    /// [`DateTimePicker::datetime()`](super::DateTimePicker::datetime). Out of range
+    /// months and days are normalized like `mktime` does.
+    pub fn from_tm(tm: &libc::tm) -> Date {
+        let first = Date {
+            year: tm.tm_year + 1900,
+            month: 1,
+            day: 1,
+        };
+        first.add_months(tm.tm_mon).add_days(tm.tm_mday as i64 - 1)
+    }
+
+    /// Returns a `libc::tm` at midnight of the date, for
+    /// [`DateTimePicker::set_datetime()`](super::DateTimePicker::set_datetime).
+    pub fn to_tm(self) -> libc::tm {
+        // `struct tm` has platform specific fields, which stay zeroed.
+        let mut tm = unsafe { std::mem::MaybeUninit::<libc::tm>::zeroed().assume_init() };
+        tm.tm_year = self.year - 1900;
+        tm.tm_mon = self.month as libc::c_int - 1;
+        tm.tm_mday = self.day as libc::c_int;
+        tm.tm_wday = (self.weekday() as libc::c_int + 1) % 7;
+        tm.tm_yday = self.days_since(Date {
+            month: 1,
+            day: 1,
+            ..self
+        }) as libc::c_int;
+        tm.tm_isdst = -1;
+        tm
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// Deserializing goes through `Date::new`, which rejects months and days that do not exist.
+#[cfg(feature = "serde")]
+mod date_serde {
+    use super::Date;
+    use std::convert::TryFrom;
+
+    #[derive(Deserialize)]
+    pub struct DateData {
+        year: i32,
+        month: u8,
+        day: u8,
+    }
+
+    impl TryFrom<DateData> for Date {
+        type Error = String;
+
+        fn try_from(data: DateData) -> Result<Self, Self::Error> {
+            Date::new(data.year, data.month, data.day)
+                .ok_or_else(|| format!("invalid date {}-{}-{}", data.year, data.month, data.day))
+        }
+    }
+}
+
+/// A range of dates, including both ends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DateRange {
+    pub start: Date,
+    pub end: Date,
+}
+
+impl DateRange {
+    /// Returns the range between two dates in either order.
+    pub fn new(a: Date, b: Date) -> DateRange {
+        DateRange {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    /// Returns the range of a single day.
+    pub fn day(date: Date) -> DateRange {
+        DateRange::new(date, date)
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// Returns the number of days in the range.
+    pub fn len(&self) -> usize {
+        self.end.days_since(self.start) as usize + 1
+    }
+
+    /// Always returns `false`, as a range includes at least one day.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+/// Whether a [`Calendar`] selects single dates or ranges of dates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CalendarSelection {
+    Single,
+    /// Ranges are selected by dragging, by clicking the start and Shift-clicking the end,
+    /// or with Shift and the arrow keys.
+    Range,
+}
+
+/// The font and colors of a [`Calendar`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CalendarStyle {
+    pub font: FontDescription,
+    pub background: Color,
+    pub text_color: Color,
+    /// The color of the weekday names, Saturdays and Sundays.
+    pub weekend_color: Color,
+    pub weekday_color: Color,
+    /// The color of days of the previous and next month.
+    pub other_month_color: Color,
+    /// The color of days before the minimum or after the maximum date.
+    pub disabled_color: Color,
+    /// The background of selected days, and of the ends of a range.
+    pub selection: Color,
+    pub selection_text: Color,
+    /// The background between the ends of a range.
+    pub range: Color,
+    /// The ring around today.
+    pub today: Color,
+    /// The frame of the day moved to with the keyboard.
+    pub focus: Color,
+    pub header_height: f64,
+    pub weekday_height: f64,
+}
+
+impl Default for CalendarStyle {
+    fn default() -> CalendarStyle {
+        CalendarStyle {
+            font: FontDescription {
+                family: "Sans".to_string(),
+                size: 11.0,
+                weight: 400,
+                ..FontDescription::default()
+            },
+            background: Color::WHITE,
+            text_color: Color::rgb(0.13, 0.13, 0.13),
+            weekend_color: Color::rgb(0.7, 0.25, 0.2),
+            weekday_color: Color::rgb(0.45, 0.45, 0.45),
+            other_month_color: Color::rgb(0.7, 0.7, 0.7),
+            disabled_color: Color::rgb(0.82, 0.82, 0.82),
+            selection: Color::rgb(0.2, 0.45, 0.85),
+            selection_text: Color::WHITE,
+            range: Color::rgb(0.85, 0.9, 1.0),
+            today: Color::rgb(0.2, 0.45, 0.85),
+            focus: Color::rgb(0.55, 0.55, 0.55),
+            header_height: 32.0,
+            weekday_height: 22.0,
+        }
+    }
+}
+
+/// What lies under a point of the calendar.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Hit {
+    Previous,
+    Next,
+    Day(Date),
+    Nothing,
+}
+
+struct CalendarState {
+    /// The first day of the month shown.
+    month: Date,
+    selection: Option<DateRange>,
+    /// The end of a range which stays when the selection is extended.
+    anchor: Option<Date>,
+    /// The day moved to with the keyboard, framed once the keyboard was used.
+    focus: Date,
+    show_focus: bool,
+    dragging: bool,
+    today: Option<Date>,
+    min: Option<Date>,
+    max: Option<Date>,
+    first_weekday: Weekday,
+    mode: CalendarSelection,
+    marks: BTreeMap<Date, Vec<Color>>,
+    style: CalendarStyle,
+    /// The size of the area, as of the last draw.
+    size: (f64, f64),
+    on_selection_changed: Option<SelectionHandler>,
+    on_month_changed: Option<MonthHandler>,
+    on_activated: Option<ActivateHandler>,
+}
+
+impl CalendarState {
+    fn new(today: Date) -> CalendarState {
+        CalendarState {
+            month: today.first_of_month(),
+            selection: None,
+            anchor: None,
+            focus: today,
+            show_focus: false,
+            dragging: false,
+            today: Some(today),
+            min: None,
+            max: None,
+            first_weekday: Weekday::Monday,
+            mode: CalendarSelection::Single,
+            marks: BTreeMap::new(),
+            style: CalendarStyle::default(),
+            size: (240.0, 220.0),
+            on_selection_changed: None,
+            on_month_changed: None,
+            on_activated: None,
+        }
+    }
+
+    fn is_enabled(&self, date: Date) -> bool {
+        self.min.is_none_or(|min| date >= min) && self.max.is_none_or(|max| date <= max)
+    }
+
+    fn clamp(&self, date: Date) -> Date {
+        let date = self.min.map_or(date, |min| date.max(min));
+        self.max.map_or(date, |max| date.min(max))
+    }
+
+    /// Selects a day within the bounds, or extends the range from the anchor to it,
+    /// moving the focus there. Returns whether the selection changed.
+    fn select(&mut self, date: Date, extend: bool, keyboard: bool) -> bool {
+        let date = self.clamp(date);
+        let selection = match (self.mode, self.anchor) {
+            (CalendarSelection::Range, Some(anchor)) if extend => DateRange::new(anchor, date),
+            _ => {
+                self.anchor = Some(date);
+                DateRange::day(date)
+            }
+        };
+        self.focus = date;
+        self.show_focus = keyboard;
+        let changed = self.selection != Some(selection);
+        self.selection = Some(selection);
+        changed
+    }
+
+    /// Returns the day a navigation key moves the focus to, within the bounds.
+    fn key_target(&self, key: Key, shift: bool) -> Option<Date> {
+        let focus = self.focus;
+        let target = match key {
+            Key::Left => focus.add_days(-1),
+            Key::Right => focus.add_days(1),
+            Key::Up => focus.add_days(-7),
+            Key::Down => focus.add_days(7),
+            Key::Home => focus.start_of_week(self.first_weekday),
+            Key::End => focus.start_of_week(self.first_weekday).add_days(6),
+            Key::PageUp => focus.add_months(if shift { -12 } else { -1 }),
+            Key::PageDown => focus.add_months(if shift { 12 } else { 1 }),
+            _ => return None,
+        };
+        Some(self.clamp(target))
+    }
+
+    /// Returns the first day of the grid, which starts with the week of the first of the
+    /// month.
+    fn grid_start(&self) -> Date {
+        self.month.start_of_week(self.first_weekday)
+    }
+
+    /// Returns the size of a day cell. The grid always has 6 weeks, so its height does
+    /// not change from month to month.
+    fn cell_size(&self) -> (f64, f64) {
+        let style = &self.style;
+        let height = self.size.1 - style.header_height - style.weekday_height;
+        (self.size.0 / 7.0, (height / 6.0).max(1.0))
+    }
+
+    fn day_rect(&self, date: Date) -> Rect {
+        let index = date.days_since(self.grid_start());
+        let (width, height) = self.cell_size();
+        let top = self.style.header_height + self.style.weekday_height;
+        (
+            (index % 7) as f64 * width,
+            top + (index / 7) as f64 * height,
+            width,
+            height,
+        )
+    }
+
+    fn arrow_rects(&self) -> (Rect, Rect) {
+        let size = self.style.header_height;
+        (
+            (0.0, 0.0, size, size),
+            (self.size.0 - size, 0.0, size, size),
+        )
+    }
+
+    fn hit(&self, x: f64, y: f64) -> Hit {
+        let inside = |(rx, ry, rw, rh): Rect| x >= rx && x < rx + rw && y >= ry && y < ry + rh;
+        let (previous, next) = self.arrow_rects();
+        if inside(previous) {
+            return Hit::Previous;
+        }
+        if inside(next) {
+            return Hit::Next;
+        }
+        let top = self.style.header_height + self.style.weekday_height;
+        let (width, height) = self.cell_size();
+        if y < top || x < 0.0 || x >= self.size.0 {
+            return Hit::Nothing;
+        }
+        let (col, row) = ((x / width) as i64, ((y - top) / height) as i64);
+        if col > 6 || row > 5 {
+            return Hit::Nothing;
+        }
+        Hit::Day(self.grid_start().add_days(row * 7 + col))
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let style = &self.style;
+        let size = style.font.size;
+        let fill =
+            |canvas: &mut C, path: &PathData, color: Color| canvas.fill(path, &Brush::Solid(color));
+        fill(
+            canvas,
+            &PathData::rectangle(clip.0, clip.1, clip.2, clip.3),
+            style.background,
+        );
+
+        // The header with the month and the arrows to the previous and next month.
+        let title = format!(
+            "{} {}",
+            MONTH_NAMES[self.month.month as usize - 1],
+            self.month.year
+        );
+        let title_y = style.header_height / 2.0 - size * 0.65;
+        canvas.draw_text(
+            &TextLayoutParams::colored(&title, style.text_color, &style.font)
+                .wrapped(self.size.0, TextAlign::Center)
+                .bold(true),
+            0.0,
+            title_y,
+        );
+        let (previous, next) = self.arrow_rects();
+        let can_go_back = self.min.is_none_or(|min| min < self.month);
+        let can_go_on = self.max.is_none_or(|max| max >= self.month.add_months(1));
+        for &(rect, direction, enabled) in &[(previous, -1.0, can_go_back), (next, 1.0, can_go_on)]
+        {
+            let (cx, cy) = (rect.0 + rect.2 / 2.0, rect.1 + rect.3 / 2.0);
+            let arm = size * 0.35;
+            let path = PathBuilder::default()
+                .move_to(cx - direction * arm / 2.0, cy - arm)
+                .line_to(cx + direction * arm / 2.0, cy)
+                .line_to(cx - direction * arm / 2.0, cy + arm)
+                .finish();
+            let color = if enabled {
+                style.text_color
+            } else {
+                style.disabled_color
+            };
+            canvas.stroke(&path, &Brush::Solid(color), &StrokeParams::new(1.5));
+        }
+
+        let (width, height) = self.cell_size();
+        let weekday_y = style.header_height + style.weekday_height / 2.0 - size * 0.65;
+        for col in 0..7 {
+            let weekday = self.first_weekday.add_days(col);
+            let color = if weekday.is_weekend() {
+                style.weekend_color
+            } else {
+                style.weekday_color
+            };
+            let label = TextLayoutParams::colored(weekday.short_name(), color, &style.font)
+                .wrapped(width, TextAlign::Center);
+            canvas.draw_text(&label, col as f64 * width, weekday_y);
+        }
+
+        let start = self.grid_start();
+        let radius = (width.min(height) * 0.4).max(1.0);
+        for index in 0..42 {
+            let date = start.add_days(index);
+            let (x, y, w, h) = self.day_rect(date);
+            if y > clip.1 + clip.3 || y + h < clip.1 {
+                continue;
+            }
+            let (cx, cy) = (x + w / 2.0, y + h / 2.0);
+            let enabled = self.is_enabled(date);
+            let selection = self.selection.filter(|range| range.contains(date));
+            let is_end = selection.is_some_and(|range| date == range.start || date == range.end);
+
+            if let Some(range) = selection.filter(|range| range.len() > 1) {
+                // The band between the ends of a range, from the centers of the ends.
+                let left = if date == range.start { cx } else { x };
+                let right = if date == range.end { cx } else { x + w };
+                fill(
+                    canvas,
+                    &PathData::rectangle(left, cy - radius, right - left, radius * 2.0),
+                    style.range,
+                );
+            }
+            if is_end {
+                let circle = PathBuilder::default().circle(cx, cy, radius).finish();
+                fill(canvas, &circle, style.selection);
+            }
+            if self.today == Some(date) && !is_end {
+                let ring = PathBuilder::default().circle(cx, cy, radius - 1.0).finish();
+                canvas.stroke(&ring, &Brush::Solid(style.today), &StrokeParams::new(1.5));
+            }
+            if self.show_focus && self.focus == date {
+                let frame = PathBuilder::default()
+                    .rounded_rect(x + 1.5, y + 1.5, w - 3.0, h - 3.0, 3.0)
+                    .finish();
+                canvas.stroke(&frame, &Brush::Solid(style.focus), &StrokeParams::new(1.0));
+            }
+
+            let color = if is_end {
+                style.selection_text
+            } else if !enabled {
+                style.disabled_color
+            } else if date.month != self.month.month {
+                style.other_month_color
+            } else {
+                style.text_color
+            };
+            let label = TextLayoutParams::colored(&date.day.to_string(), color, &style.font)
+                .wrapped(w, TextAlign::Center)
+                .bold(self.today == Some(date));
+            canvas.draw_text(&label, x, cy - size * 0.65);
+
+            if let Some(colors) = self.marks.get(&date) {
+                // Up to three dots below the day number.
+                let count = colors.len().min(3);
+                let dot = (radius * 0.09).clamp(1.5, 3.0);
+                let dot_y = (cy + size * 0.75).min(cy + radius - dot);
+                for (i, &color) in colors.iter().take(count).enumerate() {
+                    let dot_x = cx + (i as f64 - (count - 1) as f64 / 2.0) * dot * 3.0;
+                    let path = PathBuilder::default().circle(dot_x, dot_y, dot).finish();
+                    let color = if is_end { style.selection_text } else { color };
+                    fill(canvas, &path, color);
+                }
+            }
+        }
+    }
+}
+
+struct CalendarHandler {
+    state: Rc<RefCell<CalendarState>>,
+}
+
+impl CalendarHandler {
+    fn calendar(&self, area: &Area) -> Calendar {
+        Calendar {
+            area: area.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl AreaHandler for CalendarHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        let mut state = self.state.borrow_mut();
+        state.size = (params.area_width, params.area_height);
+        state.render(&mut &params.context, clip);
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        let calendar = self.calendar(area);
+        match *event {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                count,
+                ref state,
+            } => {
+                let hit = self.state.borrow().hit(state.x, state.y);
+                match hit {
+                    Hit::Previous => calendar.show_month_offset(-1),
+                    Hit::Next => calendar.show_month_offset(1),
+                    Hit::Day(date) if calendar.is_enabled(date) => {
+                        let extend = state.modifiers.contains(Modifiers::MODIFIER_SHIFT);
+                        self.state.borrow_mut().dragging = true;
+                        calendar.pick(date, extend, false);
+                        if count == 2 {
+                            calendar.activate(date);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            MouseEvent::Move(ref state) if self.state.borrow().dragging => {
+                let hit = self.state.borrow().hit(state.x, state.y);
+                if let Hit::Day(date) = hit {
+                    if calendar.is_enabled(date) {
+                        calendar.pick(date, true, false);
+                    }
+                }
+            }
+            MouseEvent::Release { .. } | MouseEvent::DragBroken => {
+                self.state.borrow_mut().dragging = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        if event.up {
+            return false;
+        }
+        self.calendar(area).handle_key(event)
+    }
+}
+
+/// A month calendar drawn on a plain `Area`, selecting a date or a range of dates.
+///
+/// The calendar shows six weeks starting on a configurable first weekday, with the days
+/// of the neighbouring months dimmed. Today is circled, and days can be marked with up
+/// to three colored dots, e.g. for appointments. Days before a minimum or after a
+/// maximum date cannot be selected.
+///
+/// Clicking a day selects it and a double click or Enter activates it. In
+/// [`CalendarSelection::Range`] mode, dragging over days or Shift-clicking selects a
+/// range. The arrow keys move by day and week, Home and End to the start and end of the
+/// week, PageUp and PageDown by month, and by year with Shift. In range mode the arrow
+/// keys move a focus frame, Shift with them extends the range and Space starts a new one.
+///
+/// Month and weekday names are English.
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # use libui::draw::Color;
+/// let calendar = Calendar::new();
+/// calendar.set_selection_mode(CalendarSelection::Range);
+/// calendar.set_first_weekday(Weekday::Sunday);
+/// calendar.set_bounds(Some(Date::today()), None);
+/// calendar.mark(Date::new(2030, 1, 15).unwrap(), Color::rgb(0.9, 0.3, 0.2));
+/// calendar.on_selection_changed(|_, range| {
+///     if let Some(range) = range {
+///         println!("{} to {}", range.start, range.end);
+///     }
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Calendar {
+    area: Area,
+    state: Rc<RefCell<CalendarState>>,
+}
+
+impl Calendar {
+    /// Creates a calendar showing the current month, with today marked.
+    pub fn new() -> Calendar {
+        let state = Rc::new(RefCell::new(CalendarState::new(Date::today())));
+        let area = Area::new(Box::new(CalendarHandler {
+            state: state.clone(),
+        }));
+        Calendar { area, state }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    pub fn selection(&self) -> Option<DateRange> {
+        self.state.borrow().selection
+    }
+
+    /// Returns the selected date, or the start of the selected range.
+    pub fn selected_date(&self) -> Option<Date> {
+        self.selection().map(|range| range.start)
+    }
+
+    /// Selects a range, or a single day, and shows its start. In single selection mode
+    /// only the start is selected. Does not call `on_selection_changed`.
+    pub fn set_selection(&self, selection: Option<DateRange>) {
+        {
+            let mut state = self.state.borrow_mut();
+            let selection = match state.mode {
+                CalendarSelection::Single => selection.map(|range| DateRange::day(range.start)),
+                CalendarSelection::Range => {
+                    selection.map(|range| DateRange::new(range.start, range.end))
+                }
+            };
+            state.selection = selection;
+            if let Some(range) = selection {
+                state.anchor = Some(range.start);
+                state.focus = range.end;
+                state.month = range.start.first_of_month();
+            }
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Returns the year and month shown.
+    pub fn month(&self) -> (i32, u8) {
+        let month = self.state.borrow().month;
+        (month.year, month.month)
+    }
+
+    /// Shows the month of a date. Does not call `on_month_changed`.
+    pub fn show_date(&self, date: Date) {
+        self.state.borrow_mut().month = date.first_of_month();
+        self.area.queue_redraw_all();
+    }
+
+    pub fn today(&self) -> Option<Date> {
+        self.state.borrow().today
+    }
+
+    /// Sets the date circled as today, by default the local date when the calendar was
+    /// created.
+    pub fn set_today(&self, today: Option<Date>) {
+        self.state.borrow_mut().today = today;
+        self.area.queue_redraw_all();
+    }
+
+    pub fn first_weekday(&self) -> Weekday {
+        self.state.borrow().first_weekday
+    }
+
+    /// Sets the weekday of the first column, Monday by default.
+    pub fn set_first_weekday(&self, weekday: Weekday) {
+        self.state.borrow_mut().first_weekday = weekday;
+        self.area.queue_redraw_all();
+    }
+
+    pub fn selection_mode(&self) -> CalendarSelection {
+        self.state.borrow().mode
+    }
+
+    pub fn set_selection_mode(&self, mode: CalendarSelection) {
+        let selection = {
+            let mut state = self.state.borrow_mut();
+            state.mode = mode;
+            state.selection
+        };
+        self.set_selection(selection);
+    }
+
+    /// Returns the earliest and the latest date which can be selected.
+    pub fn bounds(&self) -> (Option<Date>, Option<Date>) {
+        let state = self.state.borrow();
+        (state.min, state.max)
+    }
+
+    /// Sets the earliest and the latest date which can be selected. A selection
+    /// reaching beyond them is cut, or removed if it lies outside.
+    pub fn set_bounds(&self, min: Option<Date>, max: Option<Date>) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.min = min;
+            state.max = max;
+            let selection = state.selection.and_then(|range| {
+                let (start, end) = (state.clamp(range.start), state.clamp(range.end));
+                Some(DateRange::new(start, end))
+                    .filter(|_| state.is_enabled(range.start) || state.is_enabled(range.end))
+            });
+            state.selection = selection;
+            state.focus = state.clamp(state.focus);
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Returns whether a date lies within the bounds.
+    pub fn is_enabled(&self, date: Date) -> bool {
+        self.state.borrow().is_enabled(date)
+    }
+
+    /// Adds a colored dot to a day. Up to three dots are shown.
+    pub fn mark(&self, date: Date, color: Color) {
+        self.state
+            .borrow_mut()
+            .marks
+            .entry(date)
+            .or_default()
+            .push(color);
+        self.area.queue_redraw_all();
+    }
+
+    /// Removes the dots of a day.
+    pub fn unmark(&self, date: Date) {
+        self.state.borrow_mut().marks.remove(&date);
+        self.area.queue_redraw_all();
+    }
+
+    pub fn clear_marks(&self) {
+        self.state.borrow_mut().marks.clear();
+        self.area.queue_redraw_all();
+    }
+
+    /// Returns the colors of the dots of a day.
+    pub fn marks(&self, date: Date) -> Vec<Color> {
+        self.state
+            .borrow()
+            .marks
+            .get(&date)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn style(&self) -> CalendarStyle {
+        self.state.borrow().style.clone()
+    }
+
+    pub fn set_style(&self, style: CalendarStyle) {
+        self.state.borrow_mut().style = style;
+        self.area.queue_redraw_all();
+    }
+
+    /// Sets the callback run after the user changed the selection.
+    pub fn on_selection_changed<F: FnMut(&Calendar, Option<DateRange>) + 'static>(
+        &self,
+        callback: F,
+    ) {
+        self.state.borrow_mut().on_selection_changed = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run with the year and month after the user changed the month
+    /// shown, e.g. to mark its days.
+    pub fn on_month_changed<F: FnMut(&Calendar, i32, u8) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_month_changed = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run when a day is double clicked, or Enter is pressed.
+    pub fn on_activated<F: FnMut(&Calendar, Date) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_activated = Some(Box::new(callback));
+    }
+
+    /// Shows a month before or after the one shown.
+    fn show_month_offset(&self, months: i32) {
+        let month = self.state.borrow().month.add_months(months);
+        {
+            let state = self.state.borrow();
+            let before = state.min.is_some_and(|min| month.add_months(1) <= min);
+            let after = state.max.is_some_and(|max| month > max);
+            if before || after {
+                return;
+            }
+        }
+        self.set_month(month);
+    }
+
+    /// Shows the month of a date and notifies about it if it changed.
+    fn set_month(&self, date: Date) {
+        let month = date.first_of_month();
+        {
+            let mut state = self.state.borrow_mut();
+            if state.month == month {
+                return;
+            }
+            state.month = month;
+        }
+        self.area.queue_redraw_all();
+        let callback = self.state.borrow_mut().on_month_changed.take();
+        if let Some(mut callback) = callback {
+            callback(self, month.year, month.month);
+            let mut state = self.state.borrow_mut();
+            if state.on_month_changed.is_none() {
+                state.on_month_changed = Some(callback);
+            }
+        }
+    }
+
+    /// Selects a day, or extends the range from the anchor to it, shows its month and
+    /// notifies about a changed selection.
+    fn pick(&self, date: Date, extend: bool, keyboard: bool) {
+        let changed = self.state.borrow_mut().select(date, extend, keyboard);
+        let focus = self.state.borrow().focus;
+        self.set_month(focus);
+        self.area.queue_redraw_all();
+        if changed {
+            let callback = self.state.borrow_mut().on_selection_changed.take();
+            if let Some(mut callback) = callback {
+                callback(self, self.selection());
+                let mut state = self.state.borrow_mut();
+                if state.on_selection_changed.is_none() {
+                    state.on_selection_changed = Some(callback);
+                }
+            }
+        }
+    }
+
+    fn activate(&self, date: Date) {
+        let callback = self.state.borrow_mut().on_activated.take();
+        if let Some(mut callback) = callback {
+            callback(self, date);
+            let mut state = self.state.borrow_mut();
+            if state.on_activated.is_none() {
+                state.on_activated = Some(callback);
+            }
+        }
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        let key = match Key::from_event(event) {
+            Some(key) => key,
+            None => return false,
+        };
+        let shift = event.modifiers.contains(Modifiers::MODIFIER_SHIFT);
+        let (focus, mode, target) = {
+            let state = self.state.borrow();
+            (state.focus, state.mode, state.key_target(key, shift))
+        };
+        let target = match key {
+            Key::Space | Key::Enter | Key::KeypadEnter => {
+                self.pick(focus, false, true);
+                if key != Key::Space {
+                    self.activate(focus);
+                }
+                return true;
+            }
+            _ => match target {
+                Some(target) => target,
+                None => return false,
+            },
+        };
+        let paging = key == Key::PageUp || key == Key::PageDown;
+        if mode == CalendarSelection::Single || (shift && !paging) {
+            self.pick(target, shift, true);
+        } else {
+            {
+                let mut state = self.state.borrow_mut();
+                state.focus = target;
+                state.show_focus = true;
+            }
+            self.set_month(target);
+            self.area.queue_redraw_all();
+        }
+        true
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar::new()
+    }
+}
+
+impl From<Calendar> for Control {
+    fn from(calendar: Calendar) -> Control {
+        calendar.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{DrawCommand, Recorder, TextAttribute};
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn converts_dates() {
+        for &days in &[-719_468, -1, 0, 59, 11_016, 19_782, 2_932_896] {
+            assert_eq!(Date::from_days(days).to_days(), days);
+        }
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(date(2000, 3, 1).add_days(-1), date(2000, 2, 29));
+        assert_eq!(date(2024, 1, 31).add_months(1), date(2024, 2, 29));
+        assert_eq!(date(2024, 1, 15).add_months(-13), date(2022, 12, 15));
+        let tm = date(2024, 12, 31).to_tm();
+        assert_eq!((tm.tm_wday, tm.tm_yday), (2, 365));
+        assert_eq!(Date::from_tm(&tm), date(2024, 12, 31));
+        assert_eq!(Date::days_in_month(2024, 13), 0);
+        assert!(Date::new(2024, 0, 1).is_none());
+        assert!(Date::new(2024, 13, 1).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_dates_through_new() {
+        extern crate serde_json;
+
+        let json = serde_json::to_string(&date(2024, 2, 29)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Date>(&json).unwrap(),
+            date(2024, 2, 29)
+        );
+        let invalid = r#"{"year":2024,"month":13,"day":1}"#;
+        assert!(serde_json::from_str::<Date>(invalid).is_err());
+    }
+
+    #[test]
+    fn lays_out_and_draws_months() {
+        // September 2024 starts on a Sunday.
+        let mut state = CalendarState::new(date(2024, 9, 10));
+        state.size = (280.0, 32.0 + 22.0 + 6.0 * 30.0);
+        assert_eq!(state.grid_start(), date(2024, 8, 26));
+        state.first_weekday = Weekday::Sunday;
+        assert_eq!(state.grid_start(), date(2024, 9, 1));
+        assert_eq!(
+            state.hit(45.0, 32.0 + 22.0 + 35.0),
+            Hit::Day(date(2024, 9, 9))
+        );
+        assert_eq!(state.hit(5.0, 5.0), Hit::Previous);
+        state.min = Some(date(2024, 9, 5));
+        assert_eq!(state.clamp(date(2024, 9, 1)), date(2024, 9, 5));
+
+        state.selection = Some(DateRange::new(date(2024, 9, 12), date(2024, 9, 10)));
+        state.marks.insert(date(2024, 9, 20), vec![Color::BLACK]);
+        let mut recorder = Recorder::new();
+        state.render(&mut recorder, (0.0, 0.0, 280.0, 300.0));
+        let commands = recorder.finish();
+        let texts: Vec<_> = commands
+            .commands()
+            .iter()
+            .filter_map(|command| match *command {
+                DrawCommand::Text { ref layout, .. } => Some(layout.string.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts[0].text(), "September 2024");
+        assert_eq!(texts[1].text(), "Su");
+        assert_eq!(texts[8].text(), "1");
+        // Days before the minimum are disabled, the ends of the range selected.
+        let disabled = state.style.disabled_color;
+        assert!(texts[9]
+            .attributes()
+            .contains(&(0..1, TextAttribute::Color(disabled))));
+        let selected = state.style.selection_text;
+        assert!(texts[17]
+            .attributes()
+            .contains(&(0..2, TextAttribute::Color(selected))));
+    }
+
+    #[test]
+    fn moves_the_focus_with_the_keyboard() {
+        // A Tuesday.
+        let mut state = CalendarState::new(date(2024, 9, 10));
+        let target = |state: &CalendarState, key: Key, shift: bool| state.key_target(key, shift);
+        assert_eq!(target(&state, Key::Left, false), Some(date(2024, 9, 9)));
+        assert_eq!(target(&state, Key::Down, false), Some(date(2024, 9, 17)));
+        assert_eq!(target(&state, Key::Home, false), Some(date(2024, 9, 9)));
+        assert_eq!(target(&state, Key::End, false), Some(date(2024, 9, 15)));
+        assert_eq!(target(&state, Key::PageUp, false), Some(date(2024, 8, 10)));
+        assert_eq!(target(&state, Key::PageDown, true), Some(date(2025, 9, 10)));
+        assert_eq!(target(&state, Key::Char('x'), false), None);
+        state.first_weekday = Weekday::Sunday;
+        assert_eq!(target(&state, Key::Home, false), Some(date(2024, 9, 8)));
+
+        // In range mode, the keyboard extends the range from the anchor.
+        state.mode = CalendarSelection::Range;
+        assert!(state.select(date(2024, 9, 10), false, true));
+        let right = target(&state, Key::Right, true).unwrap();
+        assert!(state.select(right, true, true));
+        let down = target(&state, Key::Down, true).unwrap();
+        assert!(state.select(down, true, true));
+        assert_eq!(
+            state.selection,
+            Some(DateRange::new(date(2024, 9, 10), date(2024, 9, 18)))
+        );
+        assert!(state.show_focus);
+    }
+
+    #[test]
+    fn keeps_the_selection_within_bounds() {
+        let mut state = CalendarState::new(date(2024, 9, 10));
+        state.min = Some(date(2024, 9, 5));
+        state.max = Some(date(2024, 9, 20));
+        assert!(!state.is_enabled(date(2024, 9, 4)));
+        assert!(state.is_enabled(date(2024, 9, 20)));
+        assert_eq!(state.clamp(date(2024, 9, 1)), date(2024, 9, 5));
+        assert_eq!(state.clamp(date(2025, 1, 1)), date(2024, 9, 20));
+
+        state.focus = date(2024, 9, 6);
+        assert_eq!(state.key_target(Key::Up, false), Some(date(2024, 9, 5)));
+        assert_eq!(
+            state.key_target(Key::PageDown, false),
+            Some(date(2024, 9, 20))
+        );
+        assert!(state.select(date(2024, 10, 3), false, false));
+        assert_eq!(state.selection, Some(DateRange::day(date(2024, 9, 20))));
+        assert_eq!(state.focus, date(2024, 9, 20));
+        // Selecting the same day again changes nothing.
+        assert!(!state.select(date(2024, 12, 1), false, false));
+    }
+
+    #[test]
+    fn selects_ranges_by_dragging() {
+        let mut state = CalendarState::new(date(2024, 9, 10));
+        state.size = (280.0, 32.0 + 22.0 + 6.0 * 30.0);
+        state.mode = CalendarSelection::Range;
+        let day_at = |state: &CalendarState, col: f64, row: f64| match state
+            .hit(20.0 + col * 40.0, 32.0 + 22.0 + 15.0 + row * 30.0)
+        {
+            Hit::Day(date) => date,
+            hit => panic!("{:?}", hit),
+        };
+
+        // Pressing on a day, then moving over others extends the range, either way.
+        let start = day_at(&state, 2.0, 2.0);
+        assert_eq!(start, date(2024, 9, 11));
+        state.select(start, false, false);
+        let end = day_at(&state, 4.0, 3.0);
+        state.select(end, true, false);
+        assert_eq!(state.selection, Some(DateRange::new(start, end)));
+        assert_eq!(state.selection.unwrap().len(), 10);
+        let before = day_at(&state, 0.0, 1.0);
+        state.select(before, true, false);
+        assert_eq!(
+            state.selection,
+            Some(DateRange::new(date(2024, 9, 2), start))
+        );
+        assert!(!state.show_focus);
+
+        // Single mode ignores the anchor.
+        state.mode = CalendarSelection::Single;
+        state.select(end, true, false);
+        assert_eq!(state.selection, Some(DateRange::day(end)));
+    }
+}
@@ -1,5 +1,6 @@
 use super::Control;
 use callback_helpers::{from_void_ptr, to_heap_ptr};
+use draw::Color;
 use std::mem;
 use std::os::raw::c_void;
 use ui::UI;
@@ -17,19 +18,31 @@ impl ColorButton {
         unsafe { ColorButton::from_raw(libui_ffi::uiNewColorButton()) }
     }
 
-    /// Get the selected color as RGBA with values in range of [0, 1.0] per component.
-    pub fn color(&self) -> (f64, f64, f64, f64) {
+    /// Get the selected color.
+    pub fn color(&self) -> Color {
         unsafe {
-            let (mut r, mut g, mut b, mut a) = (0.0, 0.0, 0.0, 0.0);
-            libui_ffi::uiColorButtonColor(self.uiColorButton, &mut r, &mut g, &mut b, &mut a);
-            (r, g, b, a)
+            let mut c = Color::default();
+            libui_ffi::uiColorButtonColor(
+                self.uiColorButton,
+                &mut c.r,
+                &mut c.g,
+                &mut c.b,
+                &mut c.a,
+            );
+            c
         }
     }
 
-    /// Set the buttons selected color. Component are in the range of [0, 1.0].
-    pub fn set_color(&mut self, r: f64, g: f64, b: f64, a: f64) {
+    /// Set the buttons selected color.
+    pub fn set_color(&mut self, color: Color) {
         unsafe {
-            libui_ffi::uiColorButtonSetColor(self.uiColorButton, r, g, b, a);
+            libui_ffi::uiColorButtonSetColor(
+                self.uiColorButton,
+                color.r,
+                color.g,
+                color.b,
+                color.a,
+            );
         }
     }
 
@@ -8,7 +8,8 @@ use ui::UI;
 use libui_ffi::{self, uiControl, uiFontButton, uiFontDescriptor};
 
 /// An enum listing font styles.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SlantStyle {
     Normal,
     Oblique,
@@ -29,7 +30,8 @@ impl TryFrom<u32> for SlantStyle {
 }
 
 /// An enum listing font stretch levels.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum StretchStyle {
     UltraCondensed,
     ExtraCondensed,
@@ -62,7 +64,8 @@ impl TryFrom<u32> for StretchStyle {
 }
 
 /// A structure describing a font by it properties.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FontDescription {
     pub family: String,
     pub size: f64,
@@ -0,0 +1,329 @@
+//! Recognition of clicks, drags and press-and-hold from mouse events.
+
+use controls::{Modifiers, MouseButton, MouseEvent};
+use std::time::{Duration, Instant};
+
+/// A gesture recognized by a [`GestureRecognizer`]. Positions are in area coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gesture {
+    /// A button was pressed and released without moving.
+    Click {
+        button: MouseButton,
+        x: f64,
+        y: f64,
+        modifiers: Modifiers,
+    },
+    /// The second click of a double click. The first one is reported as a `Click`.
+    DoubleClick {
+        button: MouseButton,
+        x: f64,
+        y: f64,
+        modifiers: Modifiers,
+    },
+    /// The mouse moved past the drag threshold with a button held. `start_x` and
+    /// `start_y` are where the button was pressed.
+    DragStart {
+        button: MouseButton,
+        start_x: f64,
+        start_y: f64,
+        x: f64,
+        y: f64,
+    },
+    /// The mouse moved during a drag, by `dx` and `dy` since the last drag gesture.
+    DragMove {
+        button: MouseButton,
+        x: f64,
+        y: f64,
+        dx: f64,
+        dy: f64,
+    },
+    /// The button of a drag was released.
+    DragEnd { button: MouseButton, x: f64, y: f64 },
+    /// The system interrupted a drag.
+    DragCancel { button: MouseButton },
+    /// A button was held down without moving for the hold duration. No click follows.
+    Hold { button: MouseButton, x: f64, y: f64 },
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Press {
+    button: MouseButton,
+    count: u32,
+    modifiers: Modifiers,
+    start: (f64, f64),
+    last: (f64, f64),
+    time: Instant,
+    dragging: bool,
+    held: bool,
+}
+
+/// Turns the [`MouseEvent`]s of an `Area` into [`Gesture`]s.
+///
+/// Only one button is tracked at a time; presses of other buttons during a gesture are
+/// ignored. libui has no timers for areas, so a hold is recognized on the next mouse
+/// event after the hold duration, or when [`GestureRecognizer::poll()`] is called.
+///
+/// ```
+/// # use libui::controls::*;
+/// let mut gestures = GestureRecognizer::new();
+/// let state = |x| MouseState {
+///     x,
+///     y: 0.0,
+///     area_width: 100.0,
+///     area_height: 100.0,
+///     modifiers: Modifiers::empty(),
+///     held: MouseButtons::empty(),
+/// };
+/// let press = MouseEvent::Press { button: MouseButton::Left, count: 1, state: state(10.0) };
+/// assert_eq!(gestures.handle(&press), None);
+/// match gestures.handle(&MouseEvent::Move(state(20.0))) {
+///     Some(Gesture::DragStart { start_x, x, .. }) => assert_eq!((start_x, x), (10.0, 20.0)),
+///     gesture => panic!("{:?}", gesture),
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GestureRecognizer {
+    drag_threshold: f64,
+    hold_duration: Duration,
+    press: Option<Press>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer::new()
+    }
+}
+
+impl GestureRecognizer {
+    /// Creates a recognizer with a drag threshold of 4 points and a hold duration of
+    /// 500 milliseconds.
+    pub fn new() -> GestureRecognizer {
+        GestureRecognizer {
+            drag_threshold: 4.0,
+            hold_duration: Duration::from_millis(500),
+            press: None,
+        }
+    }
+
+    /// Sets how far the mouse has to move with a button held to start a drag.
+    pub fn with_drag_threshold(mut self, threshold: f64) -> Self {
+        self.drag_threshold = threshold;
+        self
+    }
+
+    /// Sets how long a button has to be held without moving for a hold.
+    pub fn with_hold_duration(mut self, duration: Duration) -> Self {
+        self.hold_duration = duration;
+        self
+    }
+
+    /// Returns whether a button is pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.press.is_some()
+    }
+
+    /// Returns whether a drag is in progress.
+    pub fn is_dragging(&self) -> bool {
+        self.press.is_some_and(|press| press.dragging)
+    }
+
+    /// Feeds a mouse event to the recognizer, returning the gesture it completes or
+    /// continues.
+    pub fn handle(&mut self, event: &MouseEvent) -> Option<Gesture> {
+        self.handle_at(event, Instant::now())
+    }
+
+    /// Like [`GestureRecognizer::handle()`], with the time of the event given.
+    pub fn handle_at(&mut self, event: &MouseEvent, now: Instant) -> Option<Gesture> {
+        match *event {
+            MouseEvent::Press {
+                button,
+                count,
+                state,
+            } => {
+                if self.press.is_none() {
+                    self.press = Some(Press {
+                        button,
+                        count,
+                        modifiers: state.modifiers,
+                        start: (state.x, state.y),
+                        last: (state.x, state.y),
+                        time: now,
+                        dragging: false,
+                        held: false,
+                    });
+                }
+                None
+            }
+            MouseEvent::Move(state) => {
+                let threshold = self.drag_threshold;
+                if let Some(hold) = self.poll(now) {
+                    return Some(hold);
+                }
+                let press = self.press.as_mut()?;
+                let (x, y) = (state.x, state.y);
+                if press.dragging {
+                    let (dx, dy) = (x - press.last.0, y - press.last.1);
+                    press.last = (x, y);
+                    Some(Gesture::DragMove {
+                        button: press.button,
+                        x,
+                        y,
+                        dx,
+                        dy,
+                    })
+                } else if (x - press.start.0).hypot(y - press.start.1) >= threshold {
+                    press.dragging = true;
+                    press.last = (x, y);
+                    Some(Gesture::DragStart {
+                        button: press.button,
+                        start_x: press.start.0,
+                        start_y: press.start.1,
+                        x,
+                        y,
+                    })
+                } else {
+                    None
+                }
+            }
+            MouseEvent::Release { button, state } => {
+                if self.press.map(|press| press.button) != Some(button) {
+                    return None;
+                }
+                let hold = self.poll(now);
+                let press = self.press.take()?;
+                let (x, y) = (state.x, state.y);
+                if press.dragging {
+                    Some(Gesture::DragEnd { button, x, y })
+                } else if press.held {
+                    hold
+                } else if press.count >= 2 {
+                    Some(Gesture::DoubleClick {
+                        button,
+                        x,
+                        y,
+                        modifiers: press.modifiers,
+                    })
+                } else {
+                    Some(Gesture::Click {
+                        button,
+                        x,
+                        y,
+                        modifiers: press.modifiers,
+                    })
+                }
+            }
+            MouseEvent::DragBroken => {
+                let press = self.press.take()?;
+                if press.dragging {
+                    Some(Gesture::DragCancel {
+                        button: press.button,
+                    })
+                } else {
+                    None
+                }
+            }
+            MouseEvent::Enter | MouseEvent::Leave => None,
+        }
+    }
+
+    /// Returns a [`Gesture::Hold`] once the pressed button has been held long enough,
+    /// e.g. when called from a timer.
+    pub fn poll(&mut self, now: Instant) -> Option<Gesture> {
+        let hold_duration = self.hold_duration;
+        let press = self.press.as_mut()?;
+        if press.dragging || press.held || now.duration_since(press.time) < hold_duration {
+            return None;
+        }
+        press.held = true;
+        Some(Gesture::Hold {
+            button: press.button,
+            x: press.start.0,
+            y: press.start.1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use controls::{MouseButtons, MouseState};
+
+    fn state(x: f64, y: f64) -> MouseState {
+        MouseState {
+            x,
+            y,
+            area_width: 100.0,
+            area_height: 100.0,
+            modifiers: Modifiers::empty(),
+            held: MouseButtons::empty(),
+        }
+    }
+
+    fn press(x: f64, count: u32) -> MouseEvent {
+        MouseEvent::Press {
+            button: MouseButton::Left,
+            count,
+            state: state(x, 0.0),
+        }
+    }
+
+    fn release(x: f64) -> MouseEvent {
+        MouseEvent::Release {
+            button: MouseButton::Left,
+            state: state(x, 0.0),
+        }
+    }
+
+    #[test]
+    fn recognizes_gestures() {
+        let mut gestures = GestureRecognizer::new();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        gestures.handle_at(&press(1.0, 1), at(0));
+        assert!(matches!(
+            gestures.handle_at(&release(2.0), at(50)),
+            Some(Gesture::Click { .. })
+        ));
+        gestures.handle_at(&press(1.0, 2), at(100));
+        assert!(matches!(
+            gestures.handle_at(&release(1.0), at(150)),
+            Some(Gesture::DoubleClick { .. })
+        ));
+
+        gestures.handle_at(&press(0.0, 1), at(200));
+        assert_eq!(
+            gestures.handle_at(&MouseEvent::Move(state(3.0, 0.0)), at(210)),
+            None
+        );
+        assert!(matches!(
+            gestures.handle_at(&MouseEvent::Move(state(5.0, 0.0)), at(220)),
+            Some(Gesture::DragStart { x, .. }) if x == 5.0
+        ));
+        assert_eq!(
+            gestures.handle_at(&MouseEvent::Move(state(7.0, 1.0)), at(1000)),
+            Some(Gesture::DragMove {
+                button: MouseButton::Left,
+                x: 7.0,
+                y: 1.0,
+                dx: 2.0,
+                dy: 1.0
+            })
+        );
+        assert!(matches!(
+            gestures.handle_at(&release(7.0), at(1010)),
+            Some(Gesture::DragEnd { .. })
+        ));
+
+        gestures.handle_at(&press(0.0, 1), at(2000));
+        assert_eq!(gestures.poll(at(2400)), None);
+        assert!(matches!(
+            gestures.poll(at(2500)),
+            Some(Gesture::Hold { .. })
+        ));
+        assert_eq!(gestures.poll(at(2600)), None);
+        assert_eq!(gestures.handle_at(&release(0.0), at(2700)), None);
+        assert!(!gestures.is_pressed());
+    }
+}
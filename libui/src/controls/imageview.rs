@@ -0,0 +1,727 @@
+//! An image viewer with zoom, panning and pixel inspection on a scrolling `Area`.
+
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, Key, Modifiers, MouseButton,
+    MouseEvent, ScrollingArea,
+};
+use draw::{
+    Brush, Canvas, Color, DisplayList, ImageBrush, PathBuilder, PathData, Rect, StrokeParams,
+    Transform,
+};
+use libui_ffi;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type HoverHandler = Box<dyn FnMut(&ImageView, Option<PixelInfo>)>;
+type ZoomHandler = Box<dyn FnMut(&ImageView, f64)>;
+
+/// The smallest and largest scale, in points per pixel.
+const MIN_SCALE: f64 = 1.0 / 64.0;
+const MAX_SCALE: f64 = 256.0;
+
+/// The factor by which one step of the wheel or keyboard zooms.
+const ZOOM_STEP: f64 = 1.25;
+
+/// The distance in points by which the arrow keys pan.
+const PAN_STEP: f64 = 40.0;
+
+/// The smallest block drawn for a sampled pixel when zoomed out, in points. Drawing
+/// several pixels of a large image into one point would only cost time.
+const MIN_BLOCK: f64 = 1.0;
+
+/// How an [`ImageView`] scales its image.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Zoom {
+    /// Scales the image to fit into the view.
+    Fit,
+    /// Scales the image to cover the view, scrolling along one axis.
+    Fill,
+    /// Scales the image by a factor in points per pixel, 1.0 for the actual size.
+    Scale(f64),
+}
+
+/// A pixel under the mouse, as reported by [`ImageView::on_hover()`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PixelInfo {
+    pub x: u32,
+    pub y: u32,
+    pub color: Color,
+}
+
+/// The colors of an [`ImageView`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImageViewStyle {
+    /// The color around the image and behind transparent pixels.
+    pub background: Color,
+    pub grid_color: Color,
+    /// The scale from which a grid separates the pixels.
+    pub grid_scale: f64,
+}
+
+impl Default for ImageViewStyle {
+    fn default() -> ImageViewStyle {
+        ImageViewStyle {
+            background: Color::rgb(0.25, 0.25, 0.25),
+            grid_color: Color::rgb(0.5, 0.5, 0.5).with_alpha(0.6),
+            grid_scale: 8.0,
+        }
+    }
+}
+
+struct ImageState {
+    image: Option<ImageBrush>,
+    zoom: Zoom,
+    /// The scale in effect, derived from `zoom` and the size of the view.
+    scale: f64,
+    /// The visible rectangle of the content, assumed until the next draw reports it.
+    visible: Rect,
+    overlay: DisplayList,
+    style: ImageViewStyle,
+    /// The last mouse position relative to the view while panning.
+    pan: Option<(f64, f64)>,
+    hover: Option<PixelInfo>,
+    scrolling: Option<ScrollingArea>,
+    on_hover: Option<HoverHandler>,
+    on_zoom: Option<ZoomHandler>,
+}
+
+impl ImageState {
+    fn new(image: Option<ImageBrush>) -> ImageState {
+        ImageState {
+            image,
+            zoom: Zoom::Fit,
+            scale: 1.0,
+            visible: (0.0, 0.0, 0.0, 0.0),
+            overlay: DisplayList::default(),
+            style: ImageViewStyle::default(),
+            pan: None,
+            hover: None,
+            scrolling: None,
+            on_hover: None,
+            on_zoom: None,
+        }
+    }
+
+    fn image_size(&self) -> (f64, f64) {
+        self.image.as_ref().map_or((0.0, 0.0), |image| {
+            (image.width() as f64, image.height() as f64)
+        })
+    }
+
+    /// Returns the scale of a zoom in the current view.
+    fn scale_of(&self, zoom: Zoom) -> f64 {
+        let (width, height) = self.image_size();
+        let (x, y) = (self.visible.2 / width, self.visible.3 / height);
+        let scale = match zoom {
+            Zoom::Fit => x.min(y),
+            Zoom::Fill => x.max(y),
+            Zoom::Scale(scale) => scale,
+        };
+        if scale.is_finite() && scale > 0.0 {
+            scale.clamp(MIN_SCALE, MAX_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    fn content_size(&self) -> (u64, u64) {
+        let (width, height) = self.image_size();
+        let size = |length: f64| ((length * self.scale).ceil() as u64).max(1);
+        (size(width), size(height))
+    }
+
+    /// Returns the position of the top left corner of the image, which is centered when
+    /// it is smaller than the view.
+    fn origin(&self) -> (f64, f64) {
+        let (width, height) = self.image_size();
+        (
+            ((self.visible.2 - width * self.scale) / 2.0)
+                .max(0.0)
+                .floor(),
+            ((self.visible.3 - height * self.scale) / 2.0)
+                .max(0.0)
+                .floor(),
+        )
+    }
+
+    /// Converts a point of the content to image coordinates.
+    fn image_point(&self, x: f64, y: f64) -> (f64, f64) {
+        let (origin_x, origin_y) = self.origin();
+        ((x - origin_x) / self.scale, (y - origin_y) / self.scale)
+    }
+
+    /// Returns the pixel at a point of the content, if any.
+    fn pixel_at(&self, x: f64, y: f64) -> Option<PixelInfo> {
+        let image = self.image.as_ref()?;
+        let (u, v) = self.image_point(x, y);
+        if u < 0.0 || v < 0.0 || u >= image.width() as f64 || v >= image.height() as f64 {
+            return None;
+        }
+        let (x, y) = (u as u32, v as u32);
+        Some(PixelInfo {
+            x,
+            y,
+            color: pixel(image, x, y),
+        })
+    }
+
+    /// Limits a scroll position to the scrollable range.
+    fn clamp_scroll(&self, x: f64, y: f64) -> (f64, f64) {
+        let (width, height) = self.content_size();
+        (
+            x.min(width as f64 - self.visible.2).max(0.0),
+            y.min(height as f64 - self.visible.3).max(0.0),
+        )
+    }
+
+    /// Returns the scroll position keeping an image point at a position in the view.
+    fn scroll_keeping(&self, image: (f64, f64), view: (f64, f64)) -> (f64, f64) {
+        let (origin_x, origin_y) = self.origin();
+        self.clamp_scroll(
+            origin_x + image.0 * self.scale - view.0,
+            origin_y + image.1 * self.scale - view.1,
+        )
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let fill = |canvas: &mut C, (x, y, w, h): Rect, color: Color| {
+            canvas.fill(&PathData::rectangle(x, y, w, h), &Brush::Solid(color))
+        };
+        fill(canvas, clip, self.style.background);
+        let image = match self.image {
+            Some(ref image) => image,
+            None => return,
+        };
+        let (origin_x, origin_y) = self.origin();
+        let scale = self.scale;
+
+        // The range of visible pixels, drawn in square blocks of `step` pixels.
+        let (left, top) = self.image_point(clip.0, clip.1);
+        let (right, bottom) = self.image_point(clip.0 + clip.2, clip.1 + clip.3);
+        let first = |v: f64| v.floor().max(0.0) as u32;
+        let last = |v: f64, size: u32| (v.ceil().max(0.0) as u32).min(size);
+        let (x0, x1) = (first(left), last(right, image.width()));
+        let (y0, y1) = (first(top), last(bottom, image.height()));
+        let step = (MIN_BLOCK / scale).ceil().max(1.0) as u32;
+        let (x0, y0) = (x0 - x0 % step, y0 - y0 % step);
+        if x0 >= x1 || y0 >= y1 {
+            return self.render_overlay(canvas);
+        }
+
+        for y in (y0..y1).step_by(step as usize) {
+            let top = origin_y + y as f64 * scale;
+            let height = (y + step).min(image.height()) - y;
+            let mut x = x0;
+            while x < x1 {
+                // Blocks of the same color are drawn as one rectangle.
+                let color = pixel(image, x, y);
+                let mut end = (x + step).min(image.width());
+                while end < x1 && pixel(image, end, y) == color {
+                    end = (end + step).min(image.width());
+                }
+                if color.a > 0.0 {
+                    let left = origin_x + x as f64 * scale;
+                    let rect = (left, top, (end - x) as f64 * scale, height as f64 * scale);
+                    fill(canvas, rect, color);
+                }
+                x = end;
+            }
+        }
+
+        if scale >= self.style.grid_scale {
+            let mut grid = PathBuilder::default();
+            let (x_top, x_bottom) = (origin_y + y0 as f64 * scale, origin_y + y1 as f64 * scale);
+            for x in x0..=x1 {
+                let at = origin_x + x as f64 * scale;
+                grid = grid.move_to(at, x_top).line_to(at, x_bottom);
+            }
+            let (y_left, y_right) = (origin_x + x0 as f64 * scale, origin_x + x1 as f64 * scale);
+            for y in y0..=y1 {
+                let at = origin_y + y as f64 * scale;
+                grid = grid.move_to(y_left, at).line_to(y_right, at);
+            }
+            let params = StrokeParams {
+                join: libui_ffi::uiDrawLineJoinMiter,
+                ..StrokeParams::new(1.0)
+            };
+            canvas.stroke(
+                &grid.finish(),
+                &Brush::Solid(self.style.grid_color),
+                &params,
+            );
+        }
+
+        self.render_overlay(canvas);
+    }
+
+    fn render_overlay<C: Canvas>(&self, canvas: &mut C) {
+        if self.overlay.is_empty() {
+            return;
+        }
+        let (origin_x, origin_y) = self.origin();
+        let scale = self.scale;
+        canvas.saved(|canvas| {
+            canvas.transform(&Transform::from_matrix([
+                scale, 0.0, 0.0, scale, origin_x, origin_y,
+            ]));
+            self.overlay.replay_to(canvas);
+        });
+    }
+}
+
+/// Returns the color of a pixel of an image.
+fn pixel(image: &ImageBrush, x: u32, y: u32) -> Color {
+    let i = (y as usize * image.width() as usize + x as usize) * 4;
+    match image.pixels().get(i..i + 4) {
+        Some(p) => Color::from_rgba8(p[0], p[1], p[2], p[3]),
+        None => Color::TRANSPARENT,
+    }
+}
+
+struct ImageHandler {
+    state: Rc<RefCell<ImageState>>,
+}
+
+impl ImageHandler {
+    fn view(&self, area: &Area) -> ImageView {
+        ImageView {
+            area: area.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl AreaHandler for ImageHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        self.state.borrow().render(&mut &params.context, clip);
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        let view = self.view(area);
+        match *event {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                ref state,
+                ..
+            }
+            | MouseEvent::Press {
+                button: MouseButton::Middle,
+                ref state,
+                ..
+            } => {
+                let mut image = self.state.borrow_mut();
+                image.pan = Some((state.x - image.visible.0, state.y - image.visible.1));
+            }
+            // Wheel steps, where the platform reports them as buttons 4 and 5.
+            MouseEvent::Press {
+                button: MouseButton::Other(button @ 4..=5),
+                ref state,
+                ..
+            } => {
+                let factor = if button == 4 {
+                    ZOOM_STEP
+                } else {
+                    1.0 / ZOOM_STEP
+                };
+                view.zoom_at(factor, state.x, state.y);
+            }
+            MouseEvent::Move(ref state) => {
+                let pan = self.state.borrow().pan;
+                if let Some((last_x, last_y)) = pan {
+                    let (x, y) = {
+                        let image = self.state.borrow();
+                        (state.x - image.visible.0, state.y - image.visible.1)
+                    };
+                    self.state.borrow_mut().pan = Some((x, y));
+                    view.pan_by(last_x - x, last_y - y);
+                } else {
+                    let pixel = self.state.borrow().pixel_at(state.x, state.y);
+                    view.hover(pixel);
+                }
+            }
+            MouseEvent::Release {
+                button: MouseButton::Left,
+                ..
+            }
+            | MouseEvent::Release {
+                button: MouseButton::Middle,
+                ..
+            }
+            | MouseEvent::DragBroken => self.state.borrow_mut().pan = None,
+            MouseEvent::Leave => view.hover(None),
+            _ => {}
+        }
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        if event.up {
+            return false;
+        }
+        self.view(area).handle_key(event)
+    }
+}
+
+/// An image viewer on a scrolling `Area`.
+///
+/// The image is fitted into the view, fills it or is shown at a scale, see [`Zoom`]. The
+/// keys `+` and `-` zoom in and out, `0` fits the image and `1` shows its actual size;
+/// the arrow keys and dragging with the left or middle button pan. libui does not report
+/// the mouse wheel on all platforms: where it arrives as buttons 4 and 5, it zooms
+/// around the mouse, otherwise applications can call [`ImageView::zoom_at()`].
+///
+/// From [`ImageViewStyle::grid_scale`] on, a grid separates the pixels. An overlay drawn
+/// in image coordinates annotates the image, and [`ImageView::on_hover()`] reports the
+/// pixel under the mouse.
+///
+/// libui has no native image drawing yet, so the visible pixels are drawn as rectangles,
+/// sampling blocks of pixels when zoomed out.
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # use libui::draw::*;
+/// let mut raster = Rasterizer::new(64, 64);
+/// raster.clear(1.0, 0.8, 0.2, 1.0);
+/// let image = ImageBrush::new(raster.width(), raster.height(), raster.to_rgba8()).unwrap();
+/// let view = ImageView::new(image);
+///
+/// let mut overlay = Recorder::new();
+/// let marker = PathBuilder::default().circle(32.0, 32.0, 8.0).finish();
+/// overlay.fill(&marker, &Brush::Solid(Color::rgb(1.0, 0.0, 0.0).with_alpha(0.5)));
+/// view.set_overlay(overlay.finish());
+///
+/// view.on_hover(|_, pixel| {
+///     if let Some(pixel) = pixel {
+///         println!("({}, {}): {:?}", pixel.x, pixel.y, pixel.color.to_rgba8());
+///     }
+/// });
+/// view.set_zoom(Zoom::Scale(4.0));
+/// ```
+#[derive(Clone)]
+pub struct ImageView {
+    area: Area,
+    state: Rc<RefCell<ImageState>>,
+}
+
+impl ImageView {
+    /// Creates a view fitting an image into it.
+    pub fn new(image: ImageBrush) -> ImageView {
+        ImageView::with_image(Some(image))
+    }
+
+    /// Creates a view without an image.
+    pub fn empty() -> ImageView {
+        ImageView::with_image(None)
+    }
+
+    fn with_image(image: Option<ImageBrush>) -> ImageView {
+        let state = Rc::new(RefCell::new(ImageState::new(image)));
+        let handler = Box::new(ImageHandler {
+            state: state.clone(),
+        });
+        let (width, height) = state.borrow().content_size();
+        let scrolling = ScrollingArea::new(handler, width, height);
+        let view = ImageView {
+            area: scrolling.area().clone(),
+            state: state.clone(),
+        };
+        // The area owns the callback, so it only holds on to the state weakly.
+        let (area, weak) = (view.area.clone(), Rc::downgrade(&state));
+        scrolling.on_scroll(move |_, visible| {
+            if let Some(state) = weak.upgrade() {
+                let area = area.clone();
+                ImageView { area, state }.track(visible);
+            }
+        });
+        state.borrow_mut().scrolling = Some(scrolling);
+        view
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    pub fn image(&self) -> Option<ImageBrush> {
+        self.state.borrow().image.clone()
+    }
+
+    /// Shows another image, keeping the zoom. The transform and repeat mode of the
+    /// brush are ignored.
+    pub fn set_image(&self, image: Option<ImageBrush>) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.image = image;
+            state.hover = None;
+        }
+        self.rescale(None);
+    }
+
+    pub fn zoom(&self) -> Zoom {
+        self.state.borrow().zoom
+    }
+
+    /// Sets the zoom, keeping the center of the view in place.
+    pub fn set_zoom(&self, zoom: Zoom) {
+        self.state.borrow_mut().zoom = zoom;
+        self.rescale(None);
+    }
+
+    /// Returns the scale in effect, in points per pixel.
+    pub fn scale(&self) -> f64 {
+        self.state.borrow().scale
+    }
+
+    /// Zooms by a factor, keeping the image point at a position of the content in place,
+    /// e.g. the mouse position given to a wheel handler.
+    pub fn zoom_at(&self, factor: f64, x: f64, y: f64) {
+        let scale = {
+            let mut state = self.state.borrow_mut();
+            let scale = state.scale * factor;
+            state.zoom = Zoom::Scale(scale.clamp(MIN_SCALE, MAX_SCALE));
+            scale
+        };
+        if scale.is_finite() {
+            self.rescale(Some((x, y)));
+        }
+    }
+
+    /// Scrolls by a distance in points.
+    pub fn pan_by(&self, dx: f64, dy: f64) {
+        let (x, y) = {
+            let state = self.state.borrow();
+            state.clamp_scroll(state.visible.0 + dx, state.visible.1 + dy)
+        };
+        self.scroll_to(x, y);
+    }
+
+    /// Returns the annotations drawn over the image.
+    pub fn overlay(&self) -> DisplayList {
+        self.state.borrow().overlay.clone()
+    }
+
+    /// Sets annotations drawn over the image, in image coordinates: pixel `(x, y)`
+    /// covers the unit square from `(x, y)` to `(x + 1, y + 1)`.
+    pub fn set_overlay(&self, overlay: DisplayList) {
+        self.state.borrow_mut().overlay = overlay;
+        self.area.queue_redraw_all();
+    }
+
+    pub fn style(&self) -> ImageViewStyle {
+        self.state.borrow().style
+    }
+
+    pub fn set_style(&self, style: ImageViewStyle) {
+        self.state.borrow_mut().style = style;
+        self.area.queue_redraw_all();
+    }
+
+    /// Returns the pixel under the mouse.
+    pub fn hovered_pixel(&self) -> Option<PixelInfo> {
+        self.state.borrow().hover
+    }
+
+    /// Sets the callback run when the mouse moved to another pixel or off the image.
+    pub fn on_hover<F: FnMut(&ImageView, Option<PixelInfo>) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_hover = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run when the scale changed, e.g. to show it in a status bar.
+    pub fn on_zoom<F: FnMut(&ImageView, f64) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_zoom = Some(Box::new(callback));
+    }
+
+    /// Updates the view after a draw reported the visible rectangle.
+    fn track(&self, visible: Rect) {
+        let resized = {
+            let mut state = self.state.borrow_mut();
+            let resized = (state.visible.2, state.visible.3) != (visible.2, visible.3);
+            state.visible = visible;
+            resized
+        };
+        if resized && self.zoom() != Zoom::Scale(self.scale()) {
+            self.rescale(None);
+        }
+    }
+
+    /// Applies the zoom, keeping the image point at a position of the content in place,
+    /// or the center of the view.
+    fn rescale(&self, anchor: Option<(f64, f64)>) {
+        let (changed, scrolling, size, scroll) = {
+            let mut state = self.state.borrow_mut();
+            let visible = state.visible;
+            let (x, y) =
+                anchor.unwrap_or((visible.0 + visible.2 / 2.0, visible.1 + visible.3 / 2.0));
+            let point = state.image_point(x, y);
+            let scale = state.scale_of(state.zoom);
+            let changed = scale != state.scale;
+            state.scale = scale;
+            let scroll = state.scroll_keeping(point, (x - visible.0, y - visible.1));
+            (
+                changed,
+                state.scrolling.clone(),
+                state.content_size(),
+                scroll,
+            )
+        };
+        if let Some(ref scrolling) = scrolling {
+            if scrolling.size() != size {
+                scrolling.set_size(size.0, size.1);
+            }
+        }
+        self.scroll_to(scroll.0, scroll.1);
+        if changed {
+            let callback = self.state.borrow_mut().on_zoom.take();
+            if let Some(mut callback) = callback {
+                callback(self, self.scale());
+                let mut state = self.state.borrow_mut();
+                if state.on_zoom.is_none() {
+                    state.on_zoom = Some(callback);
+                }
+            }
+        }
+    }
+
+    /// Scrolls the view to a position, assuming it until the next draw.
+    fn scroll_to(&self, x: f64, y: f64) {
+        let (scrolling, width, height) = {
+            let mut state = self.state.borrow_mut();
+            state.visible.0 = x;
+            state.visible.1 = y;
+            (state.scrolling.clone(), state.visible.2, state.visible.3)
+        };
+        if let Some(scrolling) = scrolling {
+            scrolling.scroll_to(x, y, width, height);
+        }
+        self.area.queue_redraw_all();
+    }
+
+    fn hover(&self, pixel: Option<PixelInfo>) {
+        if self.state.borrow().hover == pixel {
+            return;
+        }
+        self.state.borrow_mut().hover = pixel;
+        let callback = self.state.borrow_mut().on_hover.take();
+        if let Some(mut callback) = callback {
+            callback(self, pixel);
+            let mut state = self.state.borrow_mut();
+            if state.on_hover.is_none() {
+                state.on_hover = Some(callback);
+            }
+        }
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        if event
+            .modifiers
+            .intersects(Modifiers::MODIFIER_ALT | Modifiers::MODIFIER_SUPER)
+        {
+            return false;
+        }
+        let center = {
+            let visible = self.state.borrow().visible;
+            (visible.0 + visible.2 / 2.0, visible.1 + visible.3 / 2.0)
+        };
+        match Key::from_event(event) {
+            Some(Key::Char('+')) | Some(Key::Char('=')) | Some(Key::KeypadAdd) => {
+                self.zoom_at(ZOOM_STEP, center.0, center.1)
+            }
+            Some(Key::Char('-')) | Some(Key::KeypadSubtract) => {
+                self.zoom_at(1.0 / ZOOM_STEP, center.0, center.1)
+            }
+            Some(Key::Char('0')) | Some(Key::Keypad(0)) => self.set_zoom(Zoom::Fit),
+            Some(Key::Char('1')) | Some(Key::Keypad(1)) => self.set_zoom(Zoom::Scale(1.0)),
+            Some(Key::Left) => self.pan_by(-PAN_STEP, 0.0),
+            Some(Key::Right) => self.pan_by(PAN_STEP, 0.0),
+            Some(Key::Up) => self.pan_by(0.0, -PAN_STEP),
+            Some(Key::Down) => self.pan_by(0.0, PAN_STEP),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl From<ImageView> for Control {
+    fn from(view: ImageView) -> Control {
+        view.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{DrawCommand, Recorder};
+
+    #[test]
+    fn zooms_and_draws_visible_pixels() {
+        // A 4x2 image: a red run of three pixels and a blue one, then transparent.
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let mut pixels = [red, red, red, blue].concat();
+        pixels.extend_from_slice(&[0; 16]);
+        let image = ImageBrush::new(4, 2, pixels).unwrap();
+        let mut state = ImageState::new(Some(image));
+        state.visible = (0.0, 0.0, 100.0, 20.0);
+
+        assert_eq!(state.scale_of(Zoom::Fit), 10.0);
+        assert_eq!(state.scale_of(Zoom::Fill), 25.0);
+        state.scale = 10.0;
+        assert_eq!(state.content_size(), (40, 20));
+        assert_eq!(state.origin(), (30.0, 0.0));
+        let pixel = state.pixel_at(65.0, 5.0).unwrap();
+        assert_eq!(
+            (pixel.x, pixel.y, pixel.color),
+            (3, 0, Color::rgb(0.0, 0.0, 1.0))
+        );
+        assert_eq!(state.pixel_at(25.0, 5.0), None);
+
+        let mut recorder = Recorder::new();
+        state.render(&mut recorder, state.visible);
+        let fills: Vec<_> = recorder
+            .finish()
+            .commands()
+            .iter()
+            .filter_map(|command| match *command {
+                DrawCommand::Fill { ref path, .. } => path.bounds(),
+                _ => None,
+            })
+            .collect();
+        // The background, the red run and the blue pixel.
+        assert_eq!(
+            fills,
+            vec![
+                (0.0, 0.0, 100.0, 20.0),
+                (30.0, 0.0, 30.0, 10.0),
+                (60.0, 0.0, 10.0, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn samples_pixels_only_when_zoomed_out() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let image = ImageBrush::new(4, 1, [red, blue, blue, red].concat()).unwrap();
+        let mut state = ImageState::new(Some(image));
+        state.visible = (0.0, 0.0, 4.0, 1.0);
+        let blocks = |state: &ImageState| {
+            let mut recorder = Recorder::new();
+            state.render(&mut recorder, state.visible);
+            // Without the background.
+            recorder.finish().commands().len() - 1
+        };
+        state.scale = 1.0;
+        assert_eq!(blocks(&state), 3);
+        state.scale = 0.5;
+        assert_eq!(blocks(&state), 2);
+    }
+}
@@ -0,0 +1,495 @@
+//! Typed keys, key chords and keyboard shortcuts for `Area`s.
+
+use controls::{AreaKeyEvent, Modifiers};
+use libui_ffi;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A key of the keyboard, decoded from an [`AreaKeyEvent`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A key typing a character, as the character it types without Shift. Letters are
+    /// lower case.
+    Char(char),
+    Space,
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// A function key from F1 to F12.
+    F(u8),
+    /// A digit key of the keypad.
+    Keypad(u8),
+    KeypadDot,
+    KeypadEnter,
+    KeypadAdd,
+    KeypadSubtract,
+    KeypadMultiply,
+    KeypadDivide,
+}
+
+/// The names of keys, as used by `Display` and `FromStr`, besides `Char`, `F` and
+/// `Keypad`.
+const KEY_NAMES: &[(Key, &str)] = &[
+    (Key::Space, "Space"),
+    (Key::Enter, "Enter"),
+    (Key::Tab, "Tab"),
+    (Key::Backspace, "Backspace"),
+    (Key::Escape, "Esc"),
+    (Key::Insert, "Insert"),
+    (Key::Delete, "Delete"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"),
+    (Key::Up, "Up"),
+    (Key::Down, "Down"),
+    (Key::Left, "Left"),
+    (Key::Right, "Right"),
+    (Key::KeypadDot, "KeypadDot"),
+    (Key::KeypadEnter, "KeypadEnter"),
+    (Key::KeypadAdd, "KeypadAdd"),
+    (Key::KeypadSubtract, "KeypadSubtract"),
+    (Key::KeypadMultiply, "KeypadMultiply"),
+    (Key::KeypadDivide, "KeypadDivide"),
+];
+
+/// Alternative names accepted when parsing keys.
+const KEY_ALIASES: &[(Key, &str)] = &[
+    (Key::Enter, "Return"),
+    (Key::Escape, "Escape"),
+    (Key::Insert, "Ins"),
+    (Key::Delete, "Del"),
+    (Key::PageUp, "PgUp"),
+    (Key::PageDown, "PgDn"),
+    (Key::Char('+'), "Plus"),
+];
+
+impl Key {
+    /// Decodes the key of an event, or returns `None` if a modifier key was pressed or
+    /// released.
+    pub fn from_event(event: &AreaKeyEvent) -> Option<Key> {
+        if event.key != 0 {
+            return Some(match event.key {
+                b' ' => Key::Space,
+                b'\n' | b'\r' => Key::Enter,
+                b'\t' => Key::Tab,
+                0x08 | 0x7f => Key::Backspace,
+                key => Key::Char((key as char).to_ascii_lowercase()),
+            });
+        }
+        let key = match event.ext_key {
+            libui_ffi::uiExtKeyEscape => Key::Escape,
+            libui_ffi::uiExtKeyInsert => Key::Insert,
+            libui_ffi::uiExtKeyDelete => Key::Delete,
+            libui_ffi::uiExtKeyHome => Key::Home,
+            libui_ffi::uiExtKeyEnd => Key::End,
+            libui_ffi::uiExtKeyPageUp => Key::PageUp,
+            libui_ffi::uiExtKeyPageDown => Key::PageDown,
+            libui_ffi::uiExtKeyUp => Key::Up,
+            libui_ffi::uiExtKeyDown => Key::Down,
+            libui_ffi::uiExtKeyLeft => Key::Left,
+            libui_ffi::uiExtKeyRight => Key::Right,
+            libui_ffi::uiExtKeyNDot => Key::KeypadDot,
+            libui_ffi::uiExtKeyNEnter => Key::KeypadEnter,
+            libui_ffi::uiExtKeyNAdd => Key::KeypadAdd,
+            libui_ffi::uiExtKeyNSubtract => Key::KeypadSubtract,
+            libui_ffi::uiExtKeyNMultiply => Key::KeypadMultiply,
+            libui_ffi::uiExtKeyNDivide => Key::KeypadDivide,
+            key if (libui_ffi::uiExtKeyF1..=libui_ffi::uiExtKeyF12).contains(&key) => {
+                Key::F((key - libui_ffi::uiExtKeyF1 + 1) as u8)
+            }
+            key if (libui_ffi::uiExtKeyN0..=libui_ffi::uiExtKeyN9).contains(&key) => {
+                Key::Keypad((key - libui_ffi::uiExtKeyN0) as u8)
+            }
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Key::Char('+') => f.write_str("Plus"),
+            Key::Char(c) => write!(f, "{}", c.to_uppercase()),
+            Key::F(n) => write!(f, "F{}", n),
+            Key::Keypad(n) => write!(f, "Keypad{}", n),
+            key => {
+                let name = KEY_NAMES.iter().find(|&&(k, _)| k == key).map(|&(_, n)| n);
+                f.write_str(name.unwrap_or("?"))
+            }
+        }
+    }
+}
+
+/// Parses key names case-insensitively, like `"A"`, `"F5"`, `"PageUp"`, `"Keypad7"` or
+/// `"Plus"`. Single characters stand for themselves.
+impl FromStr for Key {
+    type Err = KeyChordParseError;
+
+    fn from_str(s: &str) -> Result<Key, KeyChordParseError> {
+        let error = || KeyChordParseError {
+            input: s.to_string(),
+        };
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return match c {
+                ' ' => Ok(Key::Space),
+                c if c.is_control() => Err(error()),
+                c => Ok(Key::Char(c.to_ascii_lowercase())),
+            };
+        }
+        let name = s.to_ascii_lowercase();
+        let mut named = KEY_NAMES.iter().chain(KEY_ALIASES);
+        if let Some(&(key, _)) = named.find(|&&(_, n)| n.eq_ignore_ascii_case(s)) {
+            return Ok(key);
+        }
+        let number = |prefix: &str, max: u8| {
+            name.strip_prefix(prefix)
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|&n| n <= max)
+        };
+        if let Some(n) = number("f", 12).filter(|&n| n >= 1) {
+            Ok(Key::F(n))
+        } else if let Some(n) = number("keypad", 9) {
+            Ok(Key::Keypad(n))
+        } else {
+            Err(error())
+        }
+    }
+}
+
+/// The error returned when a string is not a valid key or key chord.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyChordParseError {
+    pub input: String,
+}
+
+impl Display for KeyChordParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key chord: {:?}", self.input)
+    }
+}
+
+impl Error for KeyChordParseError {}
+
+/// A key pressed together with modifiers, like Ctrl+Shift+Z.
+///
+/// ```
+/// # use libui::controls::*;
+/// let redo: KeyChord = "ctrl+shift+z".parse().unwrap();
+/// assert_eq!(redo.key, Key::Char('z'));
+/// assert_eq!(redo.to_string(), "Ctrl+Shift+Z");
+/// assert_eq!("Ctrl++".parse::<KeyChord>().unwrap().key, Key::Char('+'));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+const MODIFIER_NAMES: &[(Modifiers, &str)] = &[
+    (Modifiers::MODIFIER_CTRL, "Ctrl"),
+    (Modifiers::MODIFIER_ALT, "Alt"),
+    (Modifiers::MODIFIER_SHIFT, "Shift"),
+    (Modifiers::MODIFIER_SUPER, "Super"),
+];
+
+const MODIFIER_ALIASES: &[(Modifiers, &str)] = &[
+    (Modifiers::MODIFIER_CTRL, "Control"),
+    (Modifiers::MODIFIER_ALT, "Option"),
+    (Modifiers::MODIFIER_SUPER, "Cmd"),
+    (Modifiers::MODIFIER_SUPER, "Command"),
+    (Modifiers::MODIFIER_SUPER, "Meta"),
+    (Modifiers::MODIFIER_SUPER, "Win"),
+];
+
+impl KeyChord {
+    pub fn new(modifiers: Modifiers, key: Key) -> KeyChord {
+        KeyChord { modifiers, key }
+    }
+
+    /// Returns the chord of a key press, or `None` for key releases and modifier keys.
+    pub fn from_event(event: &AreaKeyEvent) -> Option<KeyChord> {
+        if event.up {
+            return None;
+        }
+        Key::from_event(event).map(|key| KeyChord::new(event.modifiers, key))
+    }
+
+    /// Returns whether the event is a press of this chord, with exactly its modifiers.
+    pub fn matches(&self, event: &AreaKeyEvent) -> bool {
+        KeyChord::from_event(event) == Some(*self)
+    }
+}
+
+impl From<Key> for KeyChord {
+    fn from(key: Key) -> KeyChord {
+        KeyChord::new(Modifiers::empty(), key)
+    }
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(modifier, name) in MODIFIER_NAMES {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// Parses chords like `"Ctrl+Shift+Z"`, case-insensitively. Besides `Ctrl`, `Alt`,
+/// `Shift` and `Super`, the modifiers can be written as `Control`, `Option`, `Cmd`,
+/// `Command`, `Meta` and `Win`.
+impl FromStr for KeyChord {
+    type Err = KeyChordParseError;
+
+    fn from_str(s: &str) -> Result<KeyChord, KeyChordParseError> {
+        let error = || KeyChordParseError {
+            input: s.to_string(),
+        };
+        let input = s.trim();
+        // A trailing "+" is the key itself, as in "Ctrl++".
+        let (modifiers, key) = match input.strip_suffix("++") {
+            Some(modifiers) => (Some(modifiers), "+"),
+            None if input == "+" => (None, "+"),
+            None => match input.rfind('+') {
+                Some(i) => (Some(&input[..i]), &input[i + 1..]),
+                None => (None, input),
+            },
+        };
+        let mut chord = KeyChord::from(key.trim().parse::<Key>().map_err(|_| error())?);
+        for name in modifiers.into_iter().flat_map(|m| m.split('+')) {
+            let name = name.trim();
+            let modifier = MODIFIER_NAMES
+                .iter()
+                .chain(MODIFIER_ALIASES)
+                .find(|&&(_, n)| n.eq_ignore_ascii_case(name))
+                .ok_or_else(error)?;
+            chord.modifiers.insert(modifier.0);
+        }
+        Ok(chord)
+    }
+}
+
+/// A binding of a [`ShortcutMap`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shortcut {
+    pub chord: KeyChord,
+    pub action: String,
+    pub description: String,
+}
+
+/// Maps key chords to named actions, for the key events of an `Area`.
+///
+/// Bind chords to action names, register a handler per action and pass the key events
+/// of an [`AreaHandler`](super::AreaHandler) to [`ShortcutMap::handle()`]. An action can
+/// have several chords; binding a chord again replaces its previous action.
+///
+/// ```
+/// # use libui::controls::*;
+/// let mut shortcuts = ShortcutMap::new();
+/// shortcuts.bind_str("Ctrl+Z", "undo", "Undo the last change").unwrap();
+/// shortcuts.bind_str("Ctrl+Shift+Z", "redo", "Redo the last undone change").unwrap();
+/// shortcuts.bind_str("Ctrl+Y", "redo", "Redo the last undone change").unwrap();
+/// shortcuts.on_action("undo", || println!("undo"));
+/// assert_eq!(
+///     shortcuts.help_text(),
+///     "Ctrl+Z                Undo the last change\n\
+///      Ctrl+Shift+Z, Ctrl+Y  Redo the last undone change\n"
+/// );
+/// ```
+#[derive(Default)]
+pub struct ShortcutMap {
+    shortcuts: Vec<Shortcut>,
+    handlers: HashMap<String, Box<dyn FnMut()>>,
+}
+
+impl ShortcutMap {
+    /// Creates an empty map.
+    pub fn new() -> ShortcutMap {
+        ShortcutMap::default()
+    }
+
+    /// Binds a chord to an action, replacing a previous binding of the chord.
+    pub fn bind(&mut self, chord: KeyChord, action: &str, description: &str) {
+        self.shortcuts.retain(|shortcut| shortcut.chord != chord);
+        self.shortcuts.push(Shortcut {
+            chord,
+            action: action.to_string(),
+            description: description.to_string(),
+        });
+    }
+
+    /// Binds a chord given as a string like `"Ctrl+S"` to an action.
+    pub fn bind_str(
+        &mut self,
+        chord: &str,
+        action: &str,
+        description: &str,
+    ) -> Result<(), KeyChordParseError> {
+        self.bind(chord.parse()?, action, description);
+        Ok(())
+    }
+
+    /// Removes the binding of a chord.
+    pub fn unbind(&mut self, chord: KeyChord) {
+        self.shortcuts.retain(|shortcut| shortcut.chord != chord);
+    }
+
+    /// Sets the handler run when a chord of the action is pressed.
+    pub fn on_action<F: FnMut() + 'static>(&mut self, action: &str, handler: F) {
+        self.handlers.insert(action.to_string(), Box::new(handler));
+    }
+
+    /// Returns the bindings in the order they were made.
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.shortcuts
+    }
+
+    /// Returns the chords bound to an action.
+    pub fn chords(&self, action: &str) -> Vec<KeyChord> {
+        self.shortcuts
+            .iter()
+            .filter(|shortcut| shortcut.action == action)
+            .map(|shortcut| shortcut.chord)
+            .collect()
+    }
+
+    /// Returns the action bound to the chord of a key press.
+    pub fn action(&self, event: &AreaKeyEvent) -> Option<&str> {
+        let chord = KeyChord::from_event(event)?;
+        self.shortcuts
+            .iter()
+            .find(|shortcut| shortcut.chord == chord)
+            .map(|shortcut| shortcut.action.as_str())
+    }
+
+    /// Runs the handler of the action bound to a key press. Returns whether the key was
+    /// bound, for use as the result of [`AreaHandler::key_event()`](super::AreaHandler::key_event).
+    pub fn handle(&mut self, event: &AreaKeyEvent) -> bool {
+        let action = match self.action(event) {
+            Some(action) => action.to_string(),
+            None => return false,
+        };
+        if let Some(handler) = self.handlers.get_mut(&action) {
+            handler();
+        }
+        true
+    }
+
+    /// Returns one line per action with its chords and description, aligned in columns.
+    pub fn help_text(&self) -> String {
+        let mut lines: Vec<(String, &str)> = Vec::new();
+        for (i, shortcut) in self.shortcuts.iter().enumerate() {
+            let first = self.shortcuts[..i]
+                .iter()
+                .all(|other| other.action != shortcut.action);
+            if first {
+                let chords: Vec<String> = self
+                    .chords(&shortcut.action)
+                    .iter()
+                    .map(KeyChord::to_string)
+                    .collect();
+                lines.push((chords.join(", "), &shortcut.description));
+            }
+        }
+        let width = lines
+            .iter()
+            .map(|(chords, _)| chords.chars().count())
+            .max()
+            .unwrap_or(0);
+        lines
+            .iter()
+            .map(|(chords, description)| {
+                format!("{:width$}  {}\n", chords, description, width = width)
+            })
+            .collect()
+    }
+}
+
+impl AreaKeyEvent {
+    /// Decodes the key of the event, or returns `None` for modifier keys.
+    pub fn to_key(&self) -> Option<Key> {
+        Key::from_event(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use controls::ExtKey;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn event(key: u8, ext_key: ExtKey, modifiers: Modifiers) -> AreaKeyEvent {
+        AreaKeyEvent {
+            key,
+            ext_key,
+            modifier: Modifiers::empty(),
+            modifiers,
+            up: false,
+        }
+    }
+
+    #[test]
+    fn parses_and_formats_chords() {
+        for &text in &[
+            "Ctrl+Shift+Z",
+            "Alt+F4",
+            "Super+PageUp",
+            "Keypad7",
+            "Ctrl+Plus",
+            "Space",
+        ] {
+            assert_eq!(text.parse::<KeyChord>().unwrap().to_string(), text);
+        }
+        let chord: KeyChord = " cmd + option + del ".parse().unwrap();
+        assert_eq!(chord.to_string(), "Alt+Super+Delete");
+        assert!("Ctrl+".parse::<KeyChord>().is_err());
+        assert!("Hyper+A".parse::<KeyChord>().is_err());
+        assert!("F13".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn dispatches_shortcuts() {
+        let ctrl = Modifiers::MODIFIER_CTRL;
+        let f5 = event(0, libui_ffi::uiExtKeyF5, Modifiers::empty());
+        assert_eq!(f5.to_key(), Some(Key::F(5)));
+        assert_eq!(event(b'S', 0, ctrl).to_key(), Some(Key::Char('s')));
+
+        let saved = Rc::new(Cell::new(0));
+        let counter = saved.clone();
+        let mut shortcuts = ShortcutMap::new();
+        shortcuts.bind_str("Ctrl+S", "save", "Save").unwrap();
+        shortcuts.bind_str("F5", "reload", "Reload").unwrap();
+        shortcuts.on_action("save", move || counter.set(counter.get() + 1));
+        assert!(shortcuts.handle(&event(b's', 0, ctrl)));
+        assert!(!shortcuts.handle(&event(b's', 0, Modifiers::empty())));
+        assert_eq!(saved.get(), 1);
+        assert_eq!(shortcuts.action(&f5), Some("reload"));
+
+        shortcuts.bind_str("F5", "save", "Save").unwrap();
+        assert_eq!(shortcuts.chords("save").len(), 2);
+        assert!(shortcuts.handle(&f5));
+        assert_eq!(saved.get(), 2);
+        let mut release = f5;
+        release.up = true;
+        assert!(!shortcuts.handle(&release));
+    }
+}
@@ -13,6 +13,8 @@ mod area;
 pub use self::area::*;
 mod button;
 pub use self::button::*;
+mod calendar;
+pub use self::calendar::*;
 mod checkbox;
 pub use self::checkbox::*;
 mod colorbutton;
@@ -25,20 +27,36 @@ mod fontbutton;
 pub use self::fontbutton::*;
 mod form;
 pub use self::form::*;
+mod gesture;
+pub use self::gesture::*;
+mod imageview;
+pub use self::imageview::*;
+mod keys;
+pub use self::keys::*;
 mod label;
 pub use self::label::*;
 mod layout;
 pub use self::layout::*;
+mod mouse;
+pub use self::mouse::*;
+mod nodegraph;
+pub use self::nodegraph::*;
 mod numericentry;
 pub use self::numericentry::*;
 mod progressbar;
 pub use self::progressbar::*;
 mod radiobuttons;
 pub use self::radiobuttons::*;
+mod scene;
+pub use self::scene::*;
+mod scrollingarea;
+pub use self::scrollingarea::*;
 mod table;
 pub use self::table::*;
 mod textentry;
 pub use self::textentry::*;
+mod weekview;
+pub use self::weekview::*;
 mod window;
 pub use self::window::*;
 
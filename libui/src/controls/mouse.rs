@@ -0,0 +1,224 @@
+//! Typed mouse events for `Area`s.
+
+use controls::{AreaMouseEvent, Modifiers};
+use std::fmt;
+
+/// A mouse button, as numbered by libui.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    /// Any further button, numbered from 4 to 64.
+    Other(u8),
+}
+
+impl MouseButton {
+    /// Returns the button with the given libui number, from 1 to 64.
+    pub fn from_number(number: i32) -> Option<MouseButton> {
+        match number {
+            1 => Some(MouseButton::Left),
+            2 => Some(MouseButton::Middle),
+            3 => Some(MouseButton::Right),
+            4..=64 => Some(MouseButton::Other(number as u8)),
+            _ => None,
+        }
+    }
+
+    /// Returns the libui number of the button, from 1 to 64.
+    pub fn number(self) -> u8 {
+        match self {
+            MouseButton::Left => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Right => 3,
+            MouseButton::Other(number) => number,
+        }
+    }
+}
+
+/// A set of mouse buttons, e.g. those held down during a mouse event.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MouseButtons(u64);
+
+impl MouseButtons {
+    /// Returns an empty set.
+    pub fn empty() -> MouseButtons {
+        MouseButtons(0)
+    }
+
+    /// Creates a set from the `held_1_to_64` bitfield of an `AreaMouseEvent`, with bit 0
+    /// standing for button 1.
+    pub fn from_bits(bits: u64) -> MouseButtons {
+        MouseButtons(bits)
+    }
+
+    pub fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, button: MouseButton) -> bool {
+        self.0 & Self::bit(button) != 0
+    }
+
+    pub fn insert(&mut self, button: MouseButton) {
+        self.0 |= Self::bit(button);
+    }
+
+    pub fn remove(&mut self, button: MouseButton) {
+        self.0 &= !Self::bit(button);
+    }
+
+    /// Returns the buttons in the set, in the order of their numbers.
+    pub fn iter(self) -> impl Iterator<Item = MouseButton> {
+        (1..=64)
+            .filter_map(MouseButton::from_number)
+            .filter(move |&button| self.contains(button))
+    }
+
+    fn bit(button: MouseButton) -> u64 {
+        1 << (button.number() - 1)
+    }
+}
+
+impl fmt::Debug for MouseButtons {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// The position of the mouse and the state of the modifiers and buttons during a
+/// [`MouseEvent`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MouseState {
+    pub x: f64,
+    pub y: f64,
+    /// The size of the area, for non-scrolling areas.
+    pub area_width: f64,
+    pub area_height: f64,
+    pub modifiers: Modifiers,
+    /// The buttons held down, not including the one pressed or released by this event.
+    pub held: MouseButtons,
+}
+
+/// A mouse event in an `Area`, decoded from the raw [`AreaMouseEvent`] and crossing
+/// notifications. Received by [`AreaHandler::mouse()`](super::AreaHandler::mouse).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MouseEvent {
+    Move(MouseState),
+    /// A button was pressed. `count` is 2 for a double click, 3 for a triple click and
+    /// so on.
+    Press {
+        button: MouseButton,
+        count: u32,
+        state: MouseState,
+    },
+    Release {
+        button: MouseButton,
+        state: MouseState,
+    },
+    /// The mouse entered the area.
+    Enter,
+    /// The mouse left the area.
+    Leave,
+    /// The system interrupted a drag, e.g. to show a context menu. No release event
+    /// follows for the pressed buttons.
+    DragBroken,
+}
+
+impl MouseEvent {
+    /// Returns the position and modifier state, for events which have them.
+    pub fn state(&self) -> Option<&MouseState> {
+        match *self {
+            MouseEvent::Move(ref state)
+            | MouseEvent::Press { ref state, .. }
+            | MouseEvent::Release { ref state, .. } => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of the mouse, for events which have one.
+    pub fn position(&self) -> Option<(f64, f64)> {
+        self.state().map(|state| (state.x, state.y))
+    }
+}
+
+impl<'a> From<&'a AreaMouseEvent> for MouseEvent {
+    fn from(event: &'a AreaMouseEvent) -> MouseEvent {
+        let state = MouseState {
+            x: event.x,
+            y: event.y,
+            area_width: event.area_width,
+            area_height: event.area_height,
+            modifiers: event.modifiers,
+            held: MouseButtons::from_bits(event.held_1_to_64),
+        };
+        if let Some(button) = MouseButton::from_number(event.down) {
+            MouseEvent::Press {
+                button,
+                count: event.count.max(1) as u32,
+                state,
+            }
+        } else if let Some(button) = MouseButton::from_number(event.up) {
+            MouseEvent::Release { button, state }
+        } else {
+            MouseEvent::Move(state)
+        }
+    }
+}
+
+impl AreaMouseEvent {
+    /// Decodes the event into a [`MouseEvent`].
+    pub fn to_mouse_event(&self) -> MouseEvent {
+        MouseEvent::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_raw_events() {
+        let mut raw = AreaMouseEvent {
+            x: 3.0,
+            y: 4.0,
+            area_width: 100.0,
+            area_height: 50.0,
+            down: 3,
+            up: 0,
+            count: 2,
+            modifiers: Modifiers::MODIFIER_SHIFT,
+            held_1_to_64: 0b1001,
+        };
+        match raw.to_mouse_event() {
+            MouseEvent::Press {
+                button: MouseButton::Right,
+                count: 2,
+                state,
+            } => {
+                assert_eq!((state.x, state.y), (3.0, 4.0));
+                assert_eq!(
+                    state.held.iter().collect::<Vec<_>>(),
+                    [MouseButton::Left, MouseButton::Other(4)]
+                );
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+        raw.down = 0;
+        raw.up = 1;
+        assert!(matches!(
+            raw.to_mouse_event(),
+            MouseEvent::Release {
+                button: MouseButton::Left,
+                ..
+            }
+        ));
+        raw.up = 0;
+        assert_eq!(raw.to_mouse_event().position(), Some((3.0, 4.0)));
+        assert_eq!(MouseButton::from_number(65), None);
+    }
+}
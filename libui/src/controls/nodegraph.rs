@@ -0,0 +1,1159 @@
+//! A boxes-and-wires editor for graphs owned by the application.
+
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, FontDescription, Key, Modifiers,
+    MouseButton, MouseEvent,
+};
+use draw::{
+    Brush, Canvas, Color, PathBuilder, PathData, Rect, StrokeParams, TextAlign, TextLayoutParams,
+    Transform,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Validator = Box<dyn FnMut(&dyn GraphModel, &Connection) -> bool>;
+type SelectionHandler = Box<dyn FnMut(&NodeGraph, &[GraphNodeId])>;
+type ChangeHandler = Box<dyn FnMut(&NodeGraph)>;
+
+/// The smallest and largest zoom factor.
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 4.0;
+
+/// The factor by which one step of the wheel or keyboard zooms.
+const ZOOM_STEP: f64 = 1.2;
+
+/// The distance in points by which the arrow keys pan.
+const PAN_STEP: f64 = 40.0;
+
+/// The distance in points from the center of a port at which it is still hit.
+const PORT_HIT_RADIUS: f64 = 8.0;
+
+/// Identifies a node of a [`GraphModel`]. The model chooses the ids.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GraphNodeId(pub u64);
+
+/// An input or output of a node.
+///
+/// Ports have a kind, like the type of the data flowing through them, which by default
+/// only connect to ports of the same kind. Ports without a kind connect to any port.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Port {
+    pub name: String,
+    pub kind: String,
+    pub color: Color,
+}
+
+impl Port {
+    pub fn new(name: &str, kind: &str) -> Port {
+        Port {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            color: Color::rgb(0.55, 0.55, 0.55),
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// A wire from an output of one node to an input of another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Connection {
+    pub from: GraphNodeId,
+    /// The index of the output of `from`.
+    pub output: usize,
+    pub to: GraphNodeId,
+    /// The index of the input of `to`.
+    pub input: usize,
+}
+
+/// The graph shown by a [`NodeGraph`], owned by the application.
+///
+/// The node graph reads the model whenever it draws or handles an event and changes it
+/// only through the methods taking `&mut self`, so the application decides what an edit
+/// means, e.g. recording it for undo. [`GraphData`] is a ready-made model.
+pub trait GraphModel {
+    /// Returns the nodes, in drawing order from bottom to top.
+    fn nodes(&self) -> Vec<GraphNodeId>;
+
+    fn title(&self, node: GraphNodeId) -> String;
+
+    /// Returns the position of the top left corner of a node in graph coordinates.
+    fn position(&self, node: GraphNodeId) -> (f64, f64);
+
+    /// Moves a node, as it is dragged.
+    fn set_position(&mut self, node: GraphNodeId, x: f64, y: f64);
+
+    fn inputs(&self, node: GraphNodeId) -> Vec<Port>;
+
+    fn outputs(&self, node: GraphNodeId) -> Vec<Port>;
+
+    fn connections(&self) -> Vec<Connection>;
+
+    /// Adds a connection accepted by the validity check.
+    fn connect(&mut self, connection: Connection);
+
+    fn disconnect(&mut self, connection: Connection);
+
+    /// Removes a node and its connections when the user deletes it. Returns whether it
+    /// was removed; by default nodes cannot be deleted.
+    fn remove_node(&mut self, _node: GraphNodeId) -> bool {
+        false
+    }
+}
+
+/// A node of a [`GraphData`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GraphDataNode {
+    pub id: GraphNodeId,
+    pub title: String,
+    pub position: (f64, f64),
+    pub inputs: Vec<Port>,
+    pub outputs: Vec<Port>,
+}
+
+/// A graph model keeping nodes and connections in memory.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GraphData {
+    pub nodes: Vec<GraphDataNode>,
+    pub connections: Vec<Connection>,
+    next_id: u64,
+}
+
+impl GraphData {
+    pub fn new() -> GraphData {
+        GraphData::default()
+    }
+
+    /// Adds a node at a position, returning its id.
+    pub fn add_node(
+        &mut self,
+        title: &str,
+        position: (f64, f64),
+        inputs: Vec<Port>,
+        outputs: Vec<Port>,
+    ) -> GraphNodeId {
+        let id = GraphNodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.push(GraphDataNode {
+            id,
+            title: title.to_string(),
+            position,
+            inputs,
+            outputs,
+        });
+        id
+    }
+
+    pub fn node(&self, id: GraphNodeId) -> Option<&GraphDataNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn node_mut(&mut self, id: GraphNodeId) -> Option<&mut GraphDataNode> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+}
+
+impl GraphModel for GraphData {
+    fn nodes(&self) -> Vec<GraphNodeId> {
+        self.nodes.iter().map(|node| node.id).collect()
+    }
+
+    fn title(&self, node: GraphNodeId) -> String {
+        self.node(node)
+            .map_or_else(String::new, |node| node.title.clone())
+    }
+
+    fn position(&self, node: GraphNodeId) -> (f64, f64) {
+        self.node(node).map_or((0.0, 0.0), |node| node.position)
+    }
+
+    fn set_position(&mut self, node: GraphNodeId, x: f64, y: f64) {
+        if let Some(node) = self.node_mut(node) {
+            node.position = (x, y);
+        }
+    }
+
+    fn inputs(&self, node: GraphNodeId) -> Vec<Port> {
+        self.node(node)
+            .map_or_else(Vec::new, |node| node.inputs.clone())
+    }
+
+    fn outputs(&self, node: GraphNodeId) -> Vec<Port> {
+        self.node(node)
+            .map_or_else(Vec::new, |node| node.outputs.clone())
+    }
+
+    fn connections(&self) -> Vec<Connection> {
+        self.connections.clone()
+    }
+
+    fn connect(&mut self, connection: Connection) {
+        if !self.connections.contains(&connection) {
+            self.connections.push(connection);
+        }
+    }
+
+    fn disconnect(&mut self, connection: Connection) {
+        self.connections.retain(|&c| c != connection);
+    }
+
+    fn remove_node(&mut self, node: GraphNodeId) -> bool {
+        let count = self.nodes.len();
+        self.nodes.retain(|n| n.id != node);
+        self.connections.retain(|c| c.from != node && c.to != node);
+        self.nodes.len() != count
+    }
+}
+
+/// The font, colors and metrics of a [`NodeGraph`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeGraphStyle {
+    pub font: FontDescription,
+    pub background: Color,
+    pub grid_color: Color,
+    /// The distance between grid lines in graph coordinates, or 0 for no grid.
+    pub grid_spacing: f64,
+    pub node_background: Color,
+    pub title_background: Color,
+    pub text_color: Color,
+    pub border_color: Color,
+    pub selection_color: Color,
+    /// The color of ports a dragged wire can connect to.
+    pub target_color: Color,
+    pub node_width: f64,
+    pub title_height: f64,
+    pub port_height: f64,
+    pub port_radius: f64,
+    pub wire_thickness: f64,
+}
+
+impl Default for NodeGraphStyle {
+    fn default() -> NodeGraphStyle {
+        NodeGraphStyle {
+            font: FontDescription {
+                size: 11.0,
+                ..FontDescription::default()
+            },
+            background: Color::rgb(0.16, 0.16, 0.18),
+            grid_color: Color::new(1.0, 1.0, 1.0, 0.05),
+            grid_spacing: 20.0,
+            node_background: Color::rgb(0.26, 0.26, 0.29),
+            title_background: Color::rgb(0.33, 0.36, 0.45),
+            text_color: Color::rgb(0.92, 0.92, 0.92),
+            border_color: Color::rgb(0.1, 0.1, 0.1),
+            selection_color: Color::rgb(1.0, 0.75, 0.25),
+            target_color: Color::rgb(0.4, 0.9, 0.4),
+            node_width: 160.0,
+            title_height: 24.0,
+            port_height: 20.0,
+            port_radius: 5.0,
+            wire_thickness: 2.0,
+        }
+    }
+}
+
+/// Whether a port is an input or an output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Side {
+    Input,
+    Output,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct PortRef {
+    node: GraphNodeId,
+    side: Side,
+    index: usize,
+}
+
+/// What lies under a point of the graph.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Hit {
+    Port(PortRef),
+    Node(GraphNodeId),
+    Background,
+}
+
+/// A drag with a mouse button held down.
+enum Drag {
+    /// Moving the selected nodes, with the last mouse position in graph coordinates.
+    Nodes { last: (f64, f64), moved: bool },
+    /// Dragging a wire from a port to the mouse, in graph coordinates, with the port it
+    /// would connect to.
+    Wire {
+        from: PortRef,
+        to: (f64, f64),
+        target: Option<PortRef>,
+    },
+    /// Selecting the nodes within a rectangle, in view coordinates, in addition to the
+    /// nodes selected before.
+    Band {
+        start: (f64, f64),
+        end: (f64, f64),
+        base: Vec<GraphNodeId>,
+    },
+    /// Panning, with the last mouse position in view coordinates.
+    Pan { last: (f64, f64) },
+}
+
+struct GraphState {
+    model: Rc<RefCell<dyn GraphModel>>,
+    style: NodeGraphStyle,
+    zoom: f64,
+    /// The position of the graph origin in the view.
+    pan: (f64, f64),
+    /// The size of the area, as of the last draw.
+    size: (f64, f64),
+    selection: Vec<GraphNodeId>,
+    drag: Option<Drag>,
+    validator: Option<Validator>,
+    on_selection_changed: Option<SelectionHandler>,
+    on_changed: Option<ChangeHandler>,
+}
+
+impl GraphState {
+    fn new(model: Rc<RefCell<dyn GraphModel>>) -> GraphState {
+        GraphState {
+            model,
+            style: NodeGraphStyle::default(),
+            zoom: 1.0,
+            pan: (0.0, 0.0),
+            size: (0.0, 0.0),
+            selection: Vec::new(),
+            drag: None,
+            validator: None,
+            on_selection_changed: None,
+            on_changed: None,
+        }
+    }
+
+    fn to_graph(&self, x: f64, y: f64) -> (f64, f64) {
+        ((x - self.pan.0) / self.zoom, (y - self.pan.1) / self.zoom)
+    }
+
+    /// Returns the rectangle of a node in graph coordinates.
+    fn node_rect(&self, model: &dyn GraphModel, node: GraphNodeId) -> Rect {
+        let style = &self.style;
+        let (x, y) = model.position(node);
+        let rows = model.inputs(node).len().max(model.outputs(node).len());
+        let height = style.title_height + (rows as f64 + 0.5) * style.port_height;
+        (x, y, style.node_width, height)
+    }
+
+    /// Returns the center of a port in graph coordinates.
+    fn port_position(&self, model: &dyn GraphModel, port: PortRef) -> (f64, f64) {
+        let (x, y, width, _) = self.node_rect(model, port.node);
+        let row_y =
+            y + self.style.title_height + (port.index as f64 + 0.5) * self.style.port_height;
+        match port.side {
+            Side::Input => (x, row_y),
+            Side::Output => (x + width, row_y),
+        }
+    }
+
+    fn ports(model: &dyn GraphModel, node: GraphNodeId) -> Vec<(PortRef, Port)> {
+        let port = |side, index, port| (PortRef { node, side, index }, port);
+        let inputs = model.inputs(node).into_iter().enumerate();
+        let outputs = model.outputs(node).into_iter().enumerate();
+        inputs
+            .map(|(i, p)| port(Side::Input, i, p))
+            .chain(outputs.map(|(i, p)| port(Side::Output, i, p)))
+            .collect()
+    }
+
+    /// Returns what lies under a point in graph coordinates, the topmost node first.
+    fn hit(&self, model: &dyn GraphModel, x: f64, y: f64) -> Hit {
+        let radius = PORT_HIT_RADIUS / self.zoom.min(1.0);
+        for node in model.nodes().into_iter().rev() {
+            for (port, _) in GraphState::ports(model, node) {
+                let (px, py) = self.port_position(model, port);
+                if (px - x).hypot(py - y) <= radius {
+                    return Hit::Port(port);
+                }
+            }
+            let (nx, ny, width, height) = self.node_rect(model, node);
+            if x >= nx && x < nx + width && y >= ny && y < ny + height {
+                return Hit::Node(node);
+            }
+        }
+        Hit::Background
+    }
+
+    fn port(model: &dyn GraphModel, port: PortRef) -> Option<Port> {
+        let ports = match port.side {
+            Side::Input => model.inputs(port.node),
+            Side::Output => model.outputs(port.node),
+        };
+        ports.into_iter().nth(port.index)
+    }
+
+    /// Returns the connection a wire between two ports would make, if they are an output
+    /// and an input.
+    fn connection(a: PortRef, b: PortRef) -> Option<Connection> {
+        let (from, to) = match (a.side, b.side) {
+            (Side::Output, Side::Input) => (a, b),
+            (Side::Input, Side::Output) => (b, a),
+            _ => return None,
+        };
+        Some(Connection {
+            from: from.node,
+            output: from.index,
+            to: to.node,
+            input: to.index,
+        })
+    }
+
+    /// Decides whether a connection may be made, by the validator if there is one.
+    ///
+    /// Without a validator, connections between different nodes whose ports have the
+    /// same kind are valid.
+    fn is_valid(&mut self, model: &dyn GraphModel, connection: &Connection) -> bool {
+        if model.connections().contains(connection) {
+            return false;
+        }
+        if let Some(ref mut validator) = self.validator {
+            return validator(model, connection);
+        }
+        let output = GraphState::port(
+            model,
+            PortRef {
+                node: connection.from,
+                side: Side::Output,
+                index: connection.output,
+            },
+        );
+        let input = GraphState::port(
+            model,
+            PortRef {
+                node: connection.to,
+                side: Side::Input,
+                index: connection.input,
+            },
+        );
+        match (output, input) {
+            (Some(output), Some(input)) => {
+                connection.from != connection.to
+                    && (output.kind == input.kind
+                        || output.kind.is_empty()
+                        || input.kind.is_empty())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the nodes intersecting a rectangle in view coordinates.
+    fn nodes_in(&self, model: &dyn GraphModel, a: (f64, f64), b: (f64, f64)) -> Vec<GraphNodeId> {
+        let (x0, y0) = self.to_graph(a.0.min(b.0), a.1.min(b.1));
+        let (x1, y1) = self.to_graph(a.0.max(b.0), a.1.max(b.1));
+        model
+            .nodes()
+            .into_iter()
+            .filter(|&node| {
+                let (x, y, width, height) = self.node_rect(model, node);
+                x < x1 && x + width > x0 && y < y1 && y + height > y0
+            })
+            .collect()
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let style = &self.style;
+        let model = self.model.borrow();
+        let model = &*model;
+        canvas.fill(
+            &PathData::rectangle(clip.0, clip.1, clip.2, clip.3),
+            &Brush::Solid(style.background),
+        );
+
+        canvas.saved(|canvas| {
+            canvas.transform(&Transform::from_matrix([
+                self.zoom, 0.0, 0.0, self.zoom, self.pan.0, self.pan.1,
+            ]));
+            let (left, top) = self.to_graph(clip.0, clip.1);
+            let (right, bottom) = self.to_graph(clip.0 + clip.2, clip.1 + clip.3);
+            if style.grid_spacing > 0.0 && style.grid_spacing * self.zoom >= 4.0 {
+                let spacing = style.grid_spacing;
+                let mut grid = PathBuilder::default();
+                let mut x = (left / spacing).floor() * spacing;
+                while x <= right {
+                    grid = grid.move_to(x, top).line_to(x, bottom);
+                    x += spacing;
+                }
+                let mut y = (top / spacing).floor() * spacing;
+                while y <= bottom {
+                    grid = grid.move_to(left, y).line_to(right, y);
+                    y += spacing;
+                }
+                let thickness = 1.0 / self.zoom;
+                canvas.stroke(
+                    &grid.finish(),
+                    &Brush::Solid(style.grid_color),
+                    &StrokeParams::new(thickness),
+                );
+            }
+
+            for connection in model.connections() {
+                let from = PortRef {
+                    node: connection.from,
+                    side: Side::Output,
+                    index: connection.output,
+                };
+                let to = PortRef {
+                    node: connection.to,
+                    side: Side::Input,
+                    index: connection.input,
+                };
+                let color = GraphState::port(model, from).map_or(style.text_color, |p| p.color);
+                let (a, b) = (
+                    self.port_position(model, from),
+                    self.port_position(model, to),
+                );
+                self.render_wire(canvas, a, b, color);
+            }
+
+            for node in model.nodes() {
+                self.render_node(canvas, model, node);
+            }
+
+            if let Some(Drag::Wire { from, to, target }) = self.drag {
+                let start = self.port_position(model, from);
+                let end = target.map_or(to, |target| self.port_position(model, target));
+                let (a, b) = match from.side {
+                    Side::Output => (start, end),
+                    Side::Input => (end, start),
+                };
+                let color = GraphState::port(model, from).map_or(style.text_color, |p| p.color);
+                self.render_wire(canvas, a, b, color);
+            }
+        });
+
+        if let Some(Drag::Band { start, end, .. }) = self.drag {
+            let band = PathData::rectangle(
+                start.0.min(end.0),
+                start.1.min(end.1),
+                (end.0 - start.0).abs(),
+                (end.1 - start.1).abs(),
+            );
+            let color = style.selection_color;
+            canvas.fill(&band, &Brush::Solid(color.with_alpha(0.15)));
+            canvas.stroke(&band, &Brush::Solid(color), &StrokeParams::new(1.0));
+        }
+    }
+
+    /// Draws a wire from an output to an input as a horizontal S curve, a bezier built
+    /// with `Path::bezier_to()` in an `Area`.
+    fn render_wire<C: Canvas>(&self, canvas: &mut C, a: (f64, f64), b: (f64, f64), color: Color) {
+        let bend = ((b.0 - a.0).abs() / 2.0).max(40.0);
+        let wire = PathBuilder::default()
+            .move_to(a.0, a.1)
+            .cubic_to(a.0 + bend, a.1, b.0 - bend, b.1, b.0, b.1)
+            .finish();
+        canvas.stroke(
+            &wire,
+            &Brush::Solid(color),
+            &StrokeParams::new(self.style.wire_thickness),
+        );
+    }
+
+    fn render_node<C: Canvas>(&self, canvas: &mut C, model: &dyn GraphModel, node: GraphNodeId) {
+        let style = &self.style;
+        let (x, y, width, height) = self.node_rect(model, node);
+        let frame = PathBuilder::default()
+            .rounded_rect(x, y, width, height, 5.0)
+            .finish();
+        canvas.fill(&frame, &Brush::Solid(style.node_background));
+        canvas.saved(|canvas| {
+            canvas.clip(&frame);
+            canvas.fill(
+                &PathData::rectangle(x, y, width, style.title_height),
+                &Brush::Solid(style.title_background),
+            );
+            let title =
+                TextLayoutParams::colored(&model.title(node), self.style.text_color, &style.font)
+                    .wrapped(width - 16.0, TextAlign::Left)
+                    .bold(true);
+            let text_y = y + style.title_height / 2.0 - style.font.size * 0.65;
+            canvas.draw_text(&title, x + 8.0, text_y);
+        });
+        let (border, thickness) = if self.selection.contains(&node) {
+            (style.selection_color, 2.0)
+        } else {
+            (style.border_color, 1.0)
+        };
+        canvas.stroke(&frame, &Brush::Solid(border), &StrokeParams::new(thickness));
+
+        let target = match self.drag {
+            Some(Drag::Wire { target, .. }) => target,
+            _ => None,
+        };
+        let label_width = width / 2.0 - style.port_radius - 6.0;
+        for (port, info) in GraphState::ports(model, node) {
+            let (px, py) = self.port_position(model, port);
+            let (fill, radius) = if Some(port) == target {
+                (style.target_color, style.port_radius * 1.4)
+            } else {
+                (info.color, style.port_radius)
+            };
+            let circle = PathBuilder::default().circle(px, py, radius).finish();
+            canvas.fill(&circle, &Brush::Solid(fill));
+            canvas.stroke(
+                &circle,
+                &Brush::Solid(style.border_color),
+                &StrokeParams::new(1.0),
+            );
+            let text_y = py - style.font.size * 0.65;
+            match port.side {
+                Side::Input => {
+                    let label =
+                        TextLayoutParams::colored(&info.name, self.style.text_color, &style.font)
+                            .wrapped(label_width, TextAlign::Left);
+                    canvas.draw_text(&label, px + style.port_radius + 6.0, text_y);
+                }
+                Side::Output => {
+                    let label =
+                        TextLayoutParams::colored(&info.name, self.style.text_color, &style.font)
+                            .wrapped(label_width, TextAlign::Right);
+                    canvas.draw_text(&label, px - style.port_radius - 6.0 - label_width, text_y);
+                }
+            }
+        }
+    }
+}
+
+struct GraphHandler {
+    state: Rc<RefCell<GraphState>>,
+}
+
+impl GraphHandler {
+    fn graph(&self, area: &Area) -> NodeGraph {
+        NodeGraph {
+            area: area.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl AreaHandler for GraphHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        let mut state = self.state.borrow_mut();
+        state.size = (params.area_width, params.area_height);
+        state.render(&mut &params.context, clip);
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        let graph = self.graph(area);
+        match *event {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                ref state,
+                ..
+            } => {
+                let extend = state.modifiers.contains(Modifiers::MODIFIER_SHIFT);
+                graph.press(state.x, state.y, extend);
+            }
+            MouseEvent::Press {
+                button: MouseButton::Middle,
+                ref state,
+                ..
+            }
+            | MouseEvent::Press {
+                button: MouseButton::Right,
+                ref state,
+                ..
+            } => {
+                self.state.borrow_mut().drag = Some(Drag::Pan {
+                    last: (state.x, state.y),
+                });
+            }
+            // Wheel steps, where the platform reports them as buttons 4 and 5.
+            MouseEvent::Press {
+                button: MouseButton::Other(button @ 4..=5),
+                ref state,
+                ..
+            } => {
+                let factor = if button == 4 {
+                    ZOOM_STEP
+                } else {
+                    1.0 / ZOOM_STEP
+                };
+                graph.zoom_at(factor, state.x, state.y);
+            }
+            MouseEvent::Move(ref state) => graph.drag_to(state.x, state.y),
+            MouseEvent::Release { .. } | MouseEvent::DragBroken => graph.release(),
+            _ => {}
+        }
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        if event.up {
+            return false;
+        }
+        self.graph(area).handle_key(event)
+    }
+}
+
+/// A boxes-and-wires editor for a [`GraphModel`] owned by the application, on a plain
+/// `Area`.
+///
+/// Nodes are dragged by their body and selected by clicking them, with Shift to add to
+/// the selection, or with a rubber band dragged over the background. Wires are dragged
+/// from one port to another and drawn as bezier curves; dragging the input end of a wire
+/// detaches it. The middle or right button pans, the keys `+`, `-` and `0` zoom, as does
+/// the wheel where libui reports it as buttons 4 and 5. Delete removes the selected nodes
+/// if the model allows it.
+///
+/// Whether a wire may connect two ports is decided by the callback set with
+/// [`NodeGraph::on_validate_connection()`], by default ports of the same kind on
+/// different nodes connect. An input takes a single wire, which replaces any other.
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # use libui::draw::Color;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// let mut data = GraphData::new();
+/// let image = Port::new("image", "image").with_color(Color::rgb(0.3, 0.6, 1.0));
+/// let source = data.add_node("Load", (20.0, 20.0), vec![], vec![image.clone()]);
+/// let blur = data.add_node(
+///     "Blur",
+///     (240.0, 60.0),
+///     vec![image.clone(), Port::new("radius", "number")],
+///     vec![image],
+/// );
+/// data.connect(Connection { from: source, output: 0, to: blur, input: 0 });
+///
+/// let model = Rc::new(RefCell::new(data));
+/// let graph = NodeGraph::new(model.clone());
+/// graph.on_changed(move |_| println!("{} wires", model.borrow().connections.len()));
+/// ```
+#[derive(Clone)]
+pub struct NodeGraph {
+    area: Area,
+    state: Rc<RefCell<GraphState>>,
+}
+
+impl NodeGraph {
+    /// Creates an editor for a model, which the application keeps a reference to.
+    pub fn new<M: GraphModel + 'static>(model: Rc<RefCell<M>>) -> NodeGraph {
+        let state = Rc::new(RefCell::new(GraphState::new(model)));
+        let area = Area::new(Box::new(GraphHandler {
+            state: state.clone(),
+        }));
+        NodeGraph { area, state }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    pub fn model(&self) -> Rc<RefCell<dyn GraphModel>> {
+        self.state.borrow().model.clone()
+    }
+
+    /// Redraws the graph after the application changed the model.
+    pub fn refresh(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            let nodes = state.model.borrow().nodes();
+            state.selection.retain(|node| nodes.contains(node));
+        }
+        self.area.queue_redraw_all();
+    }
+
+    pub fn selection(&self) -> Vec<GraphNodeId> {
+        self.state.borrow().selection.clone()
+    }
+
+    pub fn set_selection(&self, selection: Vec<GraphNodeId>) {
+        self.select(selection);
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.state.borrow().zoom
+    }
+
+    pub fn set_zoom(&self, zoom: f64) {
+        self.state.borrow_mut().zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.area.queue_redraw_all();
+    }
+
+    /// Returns the position of the graph origin in the view.
+    pub fn pan(&self) -> (f64, f64) {
+        self.state.borrow().pan
+    }
+
+    pub fn set_pan(&self, x: f64, y: f64) {
+        self.state.borrow_mut().pan = (x, y);
+        self.area.queue_redraw_all();
+    }
+
+    /// Zooms by a factor, keeping the graph point at a position of the view in place.
+    pub fn zoom_at(&self, factor: f64, x: f64, y: f64) {
+        {
+            let mut state = self.state.borrow_mut();
+            let point = state.to_graph(x, y);
+            state.zoom = (state.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+            state.pan = (x - point.0 * state.zoom, y - point.1 * state.zoom);
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Converts a point of the view to graph coordinates.
+    pub fn to_graph(&self, x: f64, y: f64) -> (f64, f64) {
+        self.state.borrow().to_graph(x, y)
+    }
+
+    pub fn style(&self) -> NodeGraphStyle {
+        self.state.borrow().style.clone()
+    }
+
+    pub fn set_style(&self, style: NodeGraphStyle) {
+        self.state.borrow_mut().style = style;
+        self.area.queue_redraw_all();
+    }
+
+    /// Sets the callback deciding whether a wire may make a connection. It gets the
+    /// model, which it must not borrow again.
+    pub fn on_validate_connection<F>(&self, callback: F)
+    where
+        F: FnMut(&dyn GraphModel, &Connection) -> bool + 'static,
+    {
+        self.state.borrow_mut().validator = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run when the selection changed.
+    pub fn on_selection_changed<F: FnMut(&NodeGraph, &[GraphNodeId]) + 'static>(
+        &self,
+        callback: F,
+    ) {
+        self.state.borrow_mut().on_selection_changed = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run after the user moved, connected or deleted something.
+    pub fn on_changed<F: FnMut(&NodeGraph) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_changed = Some(Box::new(callback));
+    }
+
+    fn select(&self, selection: Vec<GraphNodeId>) {
+        let changed = {
+            let mut state = self.state.borrow_mut();
+            let changed = state.selection != selection;
+            state.selection = selection;
+            changed
+        };
+        self.area.queue_redraw_all();
+        if !changed {
+            return;
+        }
+        let callback = self.state.borrow_mut().on_selection_changed.take();
+        if let Some(mut callback) = callback {
+            callback(self, &self.selection());
+            let mut state = self.state.borrow_mut();
+            if state.on_selection_changed.is_none() {
+                state.on_selection_changed = Some(callback);
+            }
+        }
+    }
+
+    fn changed(&self) {
+        self.area.queue_redraw_all();
+        let callback = self.state.borrow_mut().on_changed.take();
+        if let Some(mut callback) = callback {
+            callback(self);
+            let mut state = self.state.borrow_mut();
+            if state.on_changed.is_none() {
+                state.on_changed = Some(callback);
+            }
+        }
+    }
+
+    /// Starts a drag with the left button at a position of the view.
+    fn press(&self, x: f64, y: f64, extend: bool) {
+        let (hit, point, model) = {
+            let state = self.state.borrow();
+            let point = state.to_graph(x, y);
+            let hit = state.hit(&*state.model.borrow(), point.0, point.1);
+            (hit, point, state.model.clone())
+        };
+        let mut selection = self.selection();
+        match hit {
+            Hit::Port(port) => {
+                // Dragging the input end of a wire detaches it.
+                let attached = match port.side {
+                    Side::Input => model
+                        .borrow()
+                        .connections()
+                        .into_iter()
+                        .find(|c| c.to == port.node && c.input == port.index),
+                    Side::Output => None,
+                };
+                let from = match attached {
+                    Some(connection) => {
+                        model.borrow_mut().disconnect(connection);
+                        self.changed();
+                        PortRef {
+                            node: connection.from,
+                            side: Side::Output,
+                            index: connection.output,
+                        }
+                    }
+                    None => port,
+                };
+                self.state.borrow_mut().drag = Some(Drag::Wire {
+                    from,
+                    to: point,
+                    target: None,
+                });
+            }
+            Hit::Node(node) => {
+                if extend {
+                    match selection.iter().position(|&n| n == node) {
+                        Some(i) => {
+                            selection.remove(i);
+                        }
+                        None => selection.push(node),
+                    }
+                } else if !selection.contains(&node) {
+                    selection = vec![node];
+                }
+                self.select(selection);
+                self.state.borrow_mut().drag = Some(Drag::Nodes {
+                    last: point,
+                    moved: false,
+                });
+            }
+            Hit::Background => {
+                let base = if extend { selection } else { Vec::new() };
+                self.select(base.clone());
+                self.state.borrow_mut().drag = Some(Drag::Band {
+                    start: (x, y),
+                    end: (x, y),
+                    base,
+                });
+            }
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Continues a drag at a position of the view.
+    fn drag_to(&self, x: f64, y: f64) {
+        let mut selection = None;
+        {
+            let mut state = self.state.borrow_mut();
+            let state = &mut *state;
+            let point = state.to_graph(x, y);
+            let model = state.model.clone();
+            match state.drag.take() {
+                Some(Drag::Nodes { last, .. }) => {
+                    let mut model = model.borrow_mut();
+                    for &node in &state.selection {
+                        let (nx, ny) = model.position(node);
+                        model.set_position(node, nx + point.0 - last.0, ny + point.1 - last.1);
+                    }
+                    state.drag = Some(Drag::Nodes {
+                        last: point,
+                        moved: true,
+                    });
+                }
+                Some(Drag::Wire { from, .. }) => {
+                    let model = model.borrow();
+                    let target = match state.hit(&*model, point.0, point.1) {
+                        Hit::Port(port) => GraphState::connection(from, port)
+                            .filter(|connection| state.is_valid(&*model, connection))
+                            .map(|_| port),
+                        _ => None,
+                    };
+                    state.drag = Some(Drag::Wire {
+                        from,
+                        to: point,
+                        target,
+                    });
+                }
+                Some(Drag::Band { start, base, .. }) => {
+                    let mut nodes = base.clone();
+                    for node in state.nodes_in(&*model.borrow(), start, (x, y)) {
+                        if !nodes.contains(&node) {
+                            nodes.push(node);
+                        }
+                    }
+                    selection = Some(nodes);
+                    state.drag = Some(Drag::Band {
+                        start,
+                        end: (x, y),
+                        base,
+                    });
+                }
+                Some(Drag::Pan { last }) => {
+                    state.pan = (state.pan.0 + x - last.0, state.pan.1 + y - last.1);
+                    state.drag = Some(Drag::Pan { last: (x, y) });
+                }
+                None => return,
+            }
+        }
+        if let Some(selection) = selection {
+            self.select(selection);
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Ends a drag, connecting a dragged wire to the port under the mouse.
+    fn release(&self) {
+        let (drag, model) = {
+            let mut state = self.state.borrow_mut();
+            (state.drag.take(), state.model.clone())
+        };
+        match drag {
+            Some(Drag::Wire {
+                from,
+                target: Some(target),
+                ..
+            }) => {
+                if let Some(connection) = GraphState::connection(from, target) {
+                    let mut model = model.borrow_mut();
+                    let replaced: Vec<_> = model
+                        .connections()
+                        .into_iter()
+                        .filter(|c| c.to == connection.to && c.input == connection.input)
+                        .collect();
+                    for old in replaced {
+                        model.disconnect(old);
+                    }
+                    model.connect(connection);
+                }
+                self.changed();
+            }
+            Some(Drag::Nodes { moved: true, .. }) => self.changed(),
+            _ => self.area.queue_redraw_all(),
+        }
+    }
+
+    /// Removes the selected nodes the model allows to remove.
+    fn delete_selection(&self) {
+        let model = self.model();
+        let removed: Vec<_> = self
+            .selection()
+            .into_iter()
+            .filter(|&node| model.borrow_mut().remove_node(node))
+            .collect();
+        if !removed.is_empty() {
+            let mut selection = self.selection();
+            selection.retain(|node| !removed.contains(node));
+            self.select(selection);
+            self.changed();
+        }
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        let command = event
+            .modifiers
+            .intersects(Modifiers::MODIFIER_CTRL | Modifiers::MODIFIER_SUPER);
+        let (width, height) = self.state.borrow().size;
+        let (x, y) = (width / 2.0, height / 2.0);
+        let pan = self.pan();
+        match Key::from_event(event) {
+            Some(Key::Delete) | Some(Key::Backspace) => self.delete_selection(),
+            Some(Key::Escape) => {
+                self.state.borrow_mut().drag = None;
+                self.select(Vec::new());
+            }
+            Some(Key::Char('a')) if command => {
+                let nodes = self.model().borrow().nodes();
+                self.select(nodes);
+            }
+            Some(Key::Char('+')) | Some(Key::Char('=')) | Some(Key::KeypadAdd) => {
+                self.zoom_at(ZOOM_STEP, x, y)
+            }
+            Some(Key::Char('-')) | Some(Key::KeypadSubtract) => self.zoom_at(1.0 / ZOOM_STEP, x, y),
+            Some(Key::Char('0')) | Some(Key::Keypad(0)) => {
+                let zoom = self.zoom();
+                self.zoom_at(1.0 / zoom, x, y)
+            }
+            Some(Key::Left) => self.set_pan(pan.0 + PAN_STEP, pan.1),
+            Some(Key::Right) => self.set_pan(pan.0 - PAN_STEP, pan.1),
+            Some(Key::Up) => self.set_pan(pan.0, pan.1 + PAN_STEP),
+            Some(Key::Down) => self.set_pan(pan.0, pan.1 - PAN_STEP),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl From<NodeGraph> for Control {
+    fn from(graph: NodeGraph) -> Control {
+        graph.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_tests_and_validates_connections() {
+        let mut data = GraphData::new();
+        let number = Port::new("value", "number");
+        let a = data.add_node("A", (0.0, 0.0), vec![], vec![number.clone()]);
+        let b = data.add_node(
+            "B",
+            (300.0, 0.0),
+            vec![number.clone(), Port::new("text", "string")],
+            vec![],
+        );
+        let model = Rc::new(RefCell::new(data));
+        let mut state = GraphState::new(model.clone());
+        state.zoom = 2.0;
+        state.pan = (10.0, 10.0);
+        let data = model.borrow();
+
+        // Node rows are 20 points high below a 24 point title, so the first port of a
+        // node lies 34 points below its top.
+        let (x, y) = (10.0 + 2.0 * 160.0, 10.0 + 2.0 * 34.0);
+        let point = state.to_graph(x + 3.0, y - 3.0);
+        let output = PortRef {
+            node: a,
+            side: Side::Output,
+            index: 0,
+        };
+        assert_eq!(state.hit(&*data, point.0, point.1), Hit::Port(output));
+        let (px, py) = state.to_graph(100.0, 100.0);
+        assert_eq!(state.hit(&*data, px, py), Hit::Node(a));
+        let (px, py) = state.to_graph(500.0, 500.0);
+        assert_eq!(state.hit(&*data, px, py), Hit::Background);
+        assert_eq!(
+            state.nodes_in(&*data, (0.0, 0.0), (700.0, 20.0)),
+            vec![a, b]
+        );
+
+        let input = |index| PortRef {
+            node: b,
+            side: Side::Input,
+            index,
+        };
+        let valid = GraphState::connection(input(0), output).unwrap();
+        assert_eq!(valid.from, a);
+        assert!(state.is_valid(&*data, &valid));
+        let wrong_kind = GraphState::connection(output, input(1)).unwrap();
+        assert!(!state.is_valid(&*data, &wrong_kind));
+        assert_eq!(GraphState::connection(output, output), None);
+
+        state.validator = Some(Box::new(|_, connection| connection.input == 1));
+        assert!(state.is_valid(&*data, &wrong_kind));
+        assert!(!state.is_valid(&*data, &valid));
+    }
+}
@@ -0,0 +1,1050 @@
+//! A retained-mode scene graph drawn in an `Area`.
+
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaMouseEvent, Control, MouseButton, MouseEvent,
+};
+use draw::{Brush, Canvas, PathData, Rect, StrokeParams, TextLayout, TextLayoutParams, Transform};
+use libui_ffi;
+use std::cell::{Ref, RefCell};
+use std::f64::consts::SQRT_2;
+use std::mem;
+use std::rc::Rc;
+
+/// The distance by which antialiasing may paint outside of the exact bounds of a shape.
+const ANTIALIAS_MARGIN: f64 = 1.0;
+
+/// How far, in points, the mouse must move with the button held down to start a drag.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// Identifies a node of a [`Scene`]. Ids of removed nodes are not reused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// The content of a node in a [`Scene`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneNode {
+    /// A node without content of its own, grouping its children.
+    Group,
+    /// A path, filled and/or stroked.
+    Shape {
+        path: PathData,
+        fill: Option<Brush>,
+        stroke: Option<(Brush, StrokeParams)>,
+    },
+    /// A block of text with its top left corner at `(x, y)`.
+    Text {
+        params: TextLayoutParams,
+        x: f64,
+        y: f64,
+    },
+}
+
+impl SceneNode {
+    /// Creates a shape filled with the given brush.
+    pub fn filled(path: PathData, brush: Brush) -> SceneNode {
+        SceneNode::Shape {
+            path,
+            fill: Some(brush),
+            stroke: None,
+        }
+    }
+
+    /// Creates a shape stroked with the given brush.
+    pub fn stroked(path: PathData, brush: Brush, params: StrokeParams) -> SceneNode {
+        SceneNode::Shape {
+            path,
+            fill: None,
+            stroke: Some((brush, params)),
+        }
+    }
+}
+
+/// The phase of a drag reported to [`Scene::on_drag()`] handlers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DragPhase {
+    /// The mouse moved a few points away from where the left button was pressed.
+    Start,
+    Move,
+    /// The button was released, or the drag was broken by the system.
+    End,
+}
+
+/// A step of dragging a node with the left mouse button.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DragEvent {
+    pub phase: DragPhase,
+    /// The mouse position in area coordinates.
+    pub x: f64,
+    pub y: f64,
+    /// The movement since the last drag event, or since the button was pressed.
+    pub dx: f64,
+    pub dy: f64,
+}
+
+type ClickHandler = Box<dyn FnMut(&mut Scene, NodeId, &AreaMouseEvent)>;
+type HoverHandler = Box<dyn FnMut(&mut Scene, NodeId, bool)>;
+type DragHandler = Box<dyn FnMut(&mut Scene, NodeId, &DragEvent)>;
+
+#[derive(Default)]
+struct Handlers {
+    click: Option<ClickHandler>,
+    hover: Option<HoverHandler>,
+    drag: Option<DragHandler>,
+}
+
+struct Node {
+    content: SceneNode,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    transform: Transform,
+    z_index: i32,
+    visible: bool,
+    handlers: Handlers,
+}
+
+/// The state of the left mouse button while it is held down.
+struct Press {
+    click_target: Option<NodeId>,
+    drag_target: Option<NodeId>,
+    last: (f64, f64),
+    dragging: bool,
+}
+
+/// A tree of shapes, groups and text, with z-order, visibility and event handlers.
+///
+/// The scene is plain data and can be rendered onto any [`Canvas`]; a [`SceneArea`]
+/// shows it in a window and feeds it mouse events. Every node has a transform, applied
+/// within the transform of its parent. Children are drawn above their parent, in the
+/// order of their z-index and then in the order they were added.
+///
+/// Mouse events go to the topmost visible node under the mouse, or to its nearest
+/// ancestor with a handler for the event. Handlers receive the scene, so they can modify
+/// it while it is borrowed:
+///
+/// ```
+/// # use libui::controls::*;
+/// # use libui::draw::*;
+/// let mut scene = Scene::new();
+/// let root = scene.root();
+/// let ball = scene.add(
+///     root,
+///     SceneNode::filled(
+///         PathBuilder::default().circle(20.0, 20.0, 10.0).finish(),
+///         Brush::Solid(Color::rgb(1.0, 0.0, 0.0)),
+///     ),
+/// );
+/// scene.on_drag(ball, |scene, ball, drag| scene.translate(ball, drag.dx, drag.dy));
+/// assert_eq!(scene.hit_test(20.0, 20.0), Some(ball));
+/// assert_eq!(scene.hit_test(35.0, 20.0), None);
+/// ```
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    dirty: Option<Rect>,
+    viewport: Option<Rect>,
+    measure_text: fn(&TextLayoutParams) -> (f64, f64),
+    hovered: Option<NodeId>,
+    press: Option<Press>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene::new()
+    }
+}
+
+impl Scene {
+    /// Creates a scene with an empty root group.
+    pub fn new() -> Scene {
+        Scene {
+            nodes: vec![Some(Node {
+                content: SceneNode::Group,
+                parent: None,
+                children: Vec::new(),
+                transform: Transform::identity(),
+                z_index: 0,
+                visible: true,
+                handlers: Handlers::default(),
+            })],
+            dirty: None,
+            viewport: None,
+            measure_text: estimate_text_extents,
+            hovered: None,
+            press: None,
+        }
+    }
+
+    /// Returns the root group, which cannot be removed.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Returns true if the node exists, that is, it was not removed.
+    pub fn contains(&self, id: NodeId) -> bool {
+        matches!(self.nodes.get(id.0), Some(Some(_)))
+    }
+
+    /// Adds a node as the last child of `parent`.
+    pub fn add(&mut self, parent: NodeId, content: SceneNode) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.node_mut(parent).children.push(id);
+        self.nodes.push(Some(Node {
+            content,
+            parent: Some(parent),
+            children: Vec::new(),
+            transform: Transform::identity(),
+            z_index: 0,
+            visible: true,
+            handlers: Handlers::default(),
+        }));
+        self.mark_dirty(id);
+        id
+    }
+
+    /// Removes a node and all of its children.
+    ///
+    /// # Panics
+    /// Panics if `id` is the root.
+    pub fn remove(&mut self, id: NodeId) {
+        assert!(id != self.root(), "the root of a scene cannot be removed");
+        self.mark_dirty(id);
+        if let Some(parent) = self.get(id).parent {
+            self.node_mut(parent).children.retain(|&child| child != id);
+        }
+        let mut removed = vec![id];
+        while let Some(id) = removed.pop() {
+            if let Some(node) = self.nodes[id.0].take() {
+                removed.extend(node.children);
+            }
+        }
+        if self.hovered.is_some_and(|id| !self.contains(id)) {
+            self.hovered = None;
+        }
+        let nodes = &self.nodes;
+        if let Some(ref mut press) = self.press {
+            for target in &mut [&mut press.click_target, &mut press.drag_target] {
+                if target.is_some_and(|id| nodes[id.0].is_none()) {
+                    **target = None;
+                }
+            }
+        }
+    }
+
+    /// Returns the content of a node.
+    pub fn node(&self, id: NodeId) -> &SceneNode {
+        &self.get(id).content
+    }
+
+    /// Replaces the content of a node.
+    pub fn set_node(&mut self, id: NodeId, content: SceneNode) {
+        self.edit(id, |node| *node = content);
+    }
+
+    /// Modifies the content of a node in place.
+    pub fn edit<F: FnOnce(&mut SceneNode)>(&mut self, id: NodeId, f: F) {
+        self.mark_dirty(id);
+        f(&mut self.node_mut(id).content);
+        self.mark_dirty(id);
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.get(id).parent
+    }
+
+    /// Returns the children of a node in the order they were added.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.get(id).children
+    }
+
+    pub fn transform(&self, id: NodeId) -> &Transform {
+        &self.get(id).transform
+    }
+
+    /// Sets the transform of a node, applied within the transform of its parent.
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        self.mark_dirty(id);
+        self.node_mut(id).transform = transform;
+        self.mark_dirty(id);
+    }
+
+    /// Moves a node by the given distance, in the coordinates of its parent.
+    pub fn translate(&mut self, id: NodeId, dx: f64, dy: f64) {
+        let mut transform = *self.transform(id);
+        transform.compose(&Transform::from_matrix([1.0, 0.0, 0.0, 1.0, dx, dy]));
+        self.set_transform(id, transform);
+    }
+
+    /// Returns the transform from the coordinates of a node to area coordinates.
+    pub fn world_transform(&self, id: NodeId) -> Transform {
+        let mut transform = Transform::identity();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = self.get(id);
+            transform = transform * node.transform;
+            current = node.parent;
+        }
+        transform
+    }
+
+    pub fn z_index(&self, id: NodeId) -> i32 {
+        self.get(id).z_index
+    }
+
+    /// Sets the z-index of a node. Nodes with a higher z-index are drawn above their
+    /// siblings with a lower one.
+    pub fn set_z_index(&mut self, id: NodeId, z_index: i32) {
+        self.node_mut(id).z_index = z_index;
+        self.mark_dirty(id);
+    }
+
+    pub fn visible(&self, id: NodeId) -> bool {
+        self.get(id).visible
+    }
+
+    /// Shows or hides a node with all of its children. Hidden nodes get no events.
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        self.mark_dirty(id);
+        self.node_mut(id).visible = visible;
+        self.mark_dirty(id);
+    }
+
+    /// Sets the function measuring text nodes, which a [`SceneArea`] sets to use libui.
+    ///
+    /// Without it, the size of text is estimated from the font size.
+    pub fn set_text_measure(&mut self, measure: fn(&TextLayoutParams) -> (f64, f64)) {
+        self.measure_text = measure;
+    }
+
+    /// Runs `f` when the node, or a child without a click handler, is clicked with the
+    /// left mouse button.
+    pub fn on_click<F>(&mut self, id: NodeId, f: F)
+    where
+        F: FnMut(&mut Scene, NodeId, &AreaMouseEvent) + 'static,
+    {
+        self.node_mut(id).handlers.click = Some(Box::new(f));
+    }
+
+    /// Runs `f` with `true` when the mouse enters the node, or a child without a hover
+    /// handler, and with `false` when it leaves.
+    pub fn on_hover<F>(&mut self, id: NodeId, f: F)
+    where
+        F: FnMut(&mut Scene, NodeId, bool) + 'static,
+    {
+        self.node_mut(id).handlers.hover = Some(Box::new(f));
+    }
+
+    /// Runs `f` when the node, or a child without a drag handler, is dragged with the
+    /// left mouse button. A drag does not count as a click.
+    pub fn on_drag<F>(&mut self, id: NodeId, f: F)
+    where
+        F: FnMut(&mut Scene, NodeId, &DragEvent) + 'static,
+    {
+        self.node_mut(id).handlers.drag = Some(Box::new(f));
+    }
+
+    /// Returns the bounds of a visible node and its children in area coordinates, as
+    /// `(x, y, width, height)`.
+    pub fn bounds(&self, id: NodeId) -> Option<Rect> {
+        let parent = self
+            .parent(id)
+            .map_or_else(Transform::identity, |parent| self.world_transform(parent));
+        self.subtree_bounds(id, &parent)
+    }
+
+    /// Returns the topmost visible node at a point in area coordinates.
+    pub fn hit_test(&self, x: f64, y: f64) -> Option<NodeId> {
+        self.hit_test_node(self.root(), &Transform::identity(), (x, y))
+    }
+
+    /// Returns the region changed since the scene was last drawn, in area coordinates.
+    pub fn dirty_region(&self) -> Option<Rect> {
+        self.dirty
+    }
+
+    /// Returns true if the changed region is visible, or the visible region is unknown.
+    pub fn needs_redraw(&self) -> bool {
+        match (self.dirty, self.viewport) {
+            (Some(dirty), Some(viewport)) => intersects(&dirty, &viewport),
+            (dirty, None) => dirty.is_some(),
+            (None, _) => false,
+        }
+    }
+
+    /// Sets the visible region of the scene in area coordinates, to skip redraws for
+    /// changes outside of it.
+    pub fn set_viewport(&mut self, viewport: Option<Rect>) {
+        self.viewport = viewport;
+    }
+
+    /// Draws the visible nodes intersecting `clip`, or all of them, and clears the
+    /// dirty region.
+    pub fn render<C: Canvas>(&mut self, canvas: &mut C, clip: Option<Rect>) {
+        self.render_node(canvas, self.root(), &Transform::identity(), clip.as_ref());
+        self.dirty = None;
+    }
+
+    /// Dispatches a mouse event in area coordinates to the node handlers.
+    pub fn mouse_event(&mut self, event: &AreaMouseEvent) {
+        let position = (event.x, event.y);
+        let hit = self.hit_test(event.x, event.y);
+        let hover_target = hit.and_then(|id| self.handler_target(id, |h| h.hover.is_some()));
+        if hover_target != self.hovered {
+            self.set_hovered(hover_target);
+        }
+
+        match event.to_mouse_event() {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.press = Some(Press {
+                    click_target: hit.and_then(|id| self.handler_target(id, |h| h.click.is_some())),
+                    drag_target: hit.and_then(|id| self.handler_target(id, |h| h.drag.is_some())),
+                    last: position,
+                    dragging: false,
+                });
+            }
+            MouseEvent::Release {
+                button: MouseButton::Left,
+                ..
+            } => {
+                let press = match self.press.take() {
+                    Some(press) => press,
+                    None => return,
+                };
+                if press.dragging {
+                    if let Some(target) = press.drag_target {
+                        self.emit_drag(target, DragPhase::End, press.last, position);
+                    }
+                } else if let Some(target) = press.click_target {
+                    if hit.and_then(|id| self.handler_target(id, |h| h.click.is_some()))
+                        == Some(target)
+                    {
+                        self.with_handler(
+                            target,
+                            |h| &mut h.click,
+                            |f, scene| f(scene, target, event),
+                        );
+                    }
+                }
+            }
+            MouseEvent::Move(_) => {
+                let (target, phase, last) = match self.press {
+                    Some(Press {
+                        drag_target: Some(target),
+                        ref mut last,
+                        ref mut dragging,
+                        ..
+                    }) => {
+                        let phase = if *dragging {
+                            DragPhase::Move
+                        } else {
+                            let (dx, dy) = (position.0 - last.0, position.1 - last.1);
+                            if dx.hypot(dy) < DRAG_THRESHOLD {
+                                return;
+                            }
+                            DragPhase::Start
+                        };
+                        *dragging = true;
+                        (target, phase, mem::replace(last, position))
+                    }
+                    _ => return,
+                };
+                self.emit_drag(target, phase, last, position);
+            }
+            _ => {}
+        }
+    }
+
+    /// Ends hovering, as the mouse left the area.
+    pub fn mouse_left(&mut self) {
+        self.set_hovered(None);
+    }
+
+    /// Ends a drag without a button release, e.g. because a window was opened.
+    pub fn drag_broken(&mut self) {
+        if let Some(press) = self.press.take() {
+            if let (true, Some(target)) = (press.dragging, press.drag_target) {
+                self.emit_drag(target, DragPhase::End, press.last, press.last);
+            }
+        }
+    }
+
+    fn get(&self, id: NodeId) -> &Node {
+        self.nodes
+            .get(id.0)
+            .and_then(Option::as_ref)
+            .unwrap_or_else(|| panic!("{:?} is not in the scene", id))
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        self.nodes
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .unwrap_or_else(|| panic!("{:?} is not in the scene", id))
+    }
+
+    /// Adds the current bounds of a node to the dirty region, if it is shown.
+    fn mark_dirty(&mut self, id: NodeId) {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            if !self.get(id).visible {
+                return;
+            }
+            current = self.get(id).parent;
+        }
+        if let Some(bounds) = self.bounds(id) {
+            self.dirty = Some(match self.dirty {
+                Some(dirty) => union(&dirty, &bounds),
+                None => bounds,
+            });
+        }
+    }
+
+    /// Returns the children in the order they are drawn.
+    fn paint_order(&self, node: &Node) -> Vec<NodeId> {
+        let mut children = node.children.clone();
+        children.sort_by_key(|&id| self.get(id).z_index);
+        children
+    }
+
+    fn subtree_bounds(&self, id: NodeId, parent: &Transform) -> Option<Rect> {
+        let node = self.get(id);
+        if !node.visible {
+            return None;
+        }
+        let world = node.transform * *parent;
+        let mut bounds = self
+            .content_bounds(&node.content)
+            .map(|rect| inflate(&transform_rect(&world, &rect), ANTIALIAS_MARGIN));
+        for &child in &node.children {
+            if let Some(child) = self.subtree_bounds(child, &world) {
+                bounds = Some(match bounds {
+                    Some(bounds) => union(&bounds, &child),
+                    None => child,
+                });
+            }
+        }
+        bounds
+    }
+
+    /// Returns the bounds of the content of a node in its own coordinates.
+    fn content_bounds(&self, content: &SceneNode) -> Option<Rect> {
+        match *content {
+            SceneNode::Group => None,
+            SceneNode::Shape {
+                ref path,
+                ref stroke,
+                ..
+            } => {
+                let bounds = path.bounds()?;
+                Some(match *stroke {
+                    Some((_, ref params)) => inflate(&bounds, stroke_extent(params)),
+                    None => bounds,
+                })
+            }
+            SceneNode::Text { ref params, x, y } => {
+                let (width, height) = (self.measure_text)(params);
+                Some((x, y, width, height))
+            }
+        }
+    }
+
+    fn render_node<C: Canvas>(
+        &self,
+        canvas: &mut C,
+        id: NodeId,
+        parent: &Transform,
+        clip: Option<&Rect>,
+    ) {
+        let node = self.get(id);
+        if !node.visible {
+            return;
+        }
+        let world = node.transform * *parent;
+        let identity = node.transform == Transform::identity();
+        if !identity {
+            canvas.save();
+            canvas.transform(&node.transform);
+        }
+        let culled = match (clip, self.content_bounds(&node.content)) {
+            (Some(clip), Some(bounds)) => !intersects(
+                clip,
+                &inflate(&transform_rect(&world, &bounds), ANTIALIAS_MARGIN),
+            ),
+            _ => false,
+        };
+        if !culled {
+            match node.content {
+                SceneNode::Group => {}
+                SceneNode::Shape {
+                    ref path,
+                    ref fill,
+                    ref stroke,
+                } => {
+                    if let Some(ref brush) = *fill {
+                        canvas.fill(path, brush);
+                    }
+                    if let Some((ref brush, ref params)) = *stroke {
+                        canvas.stroke(path, brush, params);
+                    }
+                }
+                SceneNode::Text { ref params, x, y } => canvas.draw_text(params, x, y),
+            }
+        }
+        for child in self.paint_order(node) {
+            self.render_node(canvas, child, &world, clip);
+        }
+        if !identity {
+            canvas.restore();
+        }
+    }
+
+    fn hit_test_node(&self, id: NodeId, parent: &Transform, point: (f64, f64)) -> Option<NodeId> {
+        let node = self.get(id);
+        if !node.visible {
+            return None;
+        }
+        let world = node.transform * *parent;
+        for child in self.paint_order(node).into_iter().rev() {
+            if let Some(hit) = self.hit_test_node(child, &world, point) {
+                return Some(hit);
+            }
+        }
+        let (x, y) = world.inverse()?.transform_point(point);
+        let hit = match node.content {
+            SceneNode::Group => false,
+            SceneNode::Shape {
+                ref path,
+                ref fill,
+                ref stroke,
+            } => {
+                (fill.is_some() && path.contains(x, y))
+                    || stroke.as_ref().is_some_and(|(_, params)| {
+                        near_outline(path, (x, y), params.thickness / 2.0)
+                    })
+            }
+            SceneNode::Text { .. } => {
+                let (bx, by, width, height) = self.content_bounds(&node.content)?;
+                x >= bx && x < bx + width && y >= by && y < by + height
+            }
+        };
+        if hit {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the node itself or its nearest ancestor having a handler.
+    fn handler_target(&self, id: NodeId, has: fn(&Handlers) -> bool) -> Option<NodeId> {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = self.get(id);
+            if has(&node.handlers) {
+                return Some(id);
+            }
+            current = node.parent;
+        }
+        None
+    }
+
+    /// Takes a handler out of its node while it runs, so it can access the scene.
+    fn with_handler<H, S, F>(&mut self, id: NodeId, slot: S, call: F)
+    where
+        S: Fn(&mut Handlers) -> &mut Option<H>,
+        F: FnOnce(&mut H, &mut Scene),
+    {
+        let mut handler = match slot(&mut self.node_mut(id).handlers).take() {
+            Some(handler) => handler,
+            None => return,
+        };
+        call(&mut handler, self);
+        // The handler may have removed its node, or registered a replacement.
+        if let Some(Some(node)) = self.nodes.get_mut(id.0) {
+            let slot = slot(&mut node.handlers);
+            if slot.is_none() {
+                *slot = Some(handler);
+            }
+        }
+    }
+
+    fn set_hovered(&mut self, hovered: Option<NodeId>) {
+        if let Some(previous) = self.hovered.take() {
+            if self.contains(previous) {
+                self.with_handler(
+                    previous,
+                    |h| &mut h.hover,
+                    |f, scene| f(scene, previous, false),
+                );
+            }
+        }
+        self.hovered = hovered;
+        if let Some(current) = hovered {
+            self.with_handler(
+                current,
+                |h| &mut h.hover,
+                |f, scene| f(scene, current, true),
+            );
+        }
+    }
+
+    fn emit_drag(&mut self, id: NodeId, phase: DragPhase, last: (f64, f64), position: (f64, f64)) {
+        let event = DragEvent {
+            phase,
+            x: position.0,
+            y: position.1,
+            dx: position.0 - last.0,
+            dy: position.1 - last.1,
+        };
+        self.with_handler(id, |h| &mut h.drag, |f, scene| f(scene, id, &event));
+    }
+}
+
+/// Estimates the size of text without libui, from the font size and character count.
+fn estimate_text_extents(params: &TextLayoutParams) -> (f64, f64) {
+    let size = if params.default_font.size > 0.0 {
+        params.default_font.size
+    } else {
+        12.0
+    };
+    let lines = params.string.text().lines();
+    let longest = lines.clone().map(|line| line.chars().count()).max();
+    let width = if params.width >= 0.0 {
+        params.width
+    } else {
+        longest.unwrap_or(0) as f64 * size * 0.6
+    };
+    (width, lines.count().max(1) as f64 * size * 1.2)
+}
+
+/// Returns how far a stroke can paint outside of its path.
+fn stroke_extent(params: &StrokeParams) -> f64 {
+    let factor = if params.join == libui_ffi::uiDrawLineJoinMiter {
+        params.miter_limit.max(SQRT_2)
+    } else {
+        SQRT_2
+    };
+    params.thickness / 2.0 * factor
+}
+
+/// Returns true if the point is within `distance` of the outline of the path.
+fn near_outline(path: &PathData, (x, y): (f64, f64), distance: f64) -> bool {
+    path.flatten(0.25).iter().any(|polyline| {
+        let points = &polyline.points;
+        let closing = if polyline.closed {
+            points.first()
+        } else {
+            None
+        };
+        points
+            .iter()
+            .zip(points.iter().skip(1).chain(closing))
+            .any(|(&(x0, y0), &(x1, y1))| {
+                let (dx, dy) = (x1 - x0, y1 - y0);
+                let length = dx * dx + dy * dy;
+                let t = if length > 0.0 {
+                    (((x - x0) * dx + (y - y0) * dy) / length).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let (px, py) = (x0 + t * dx - x, y0 + t * dy - y);
+                px * px + py * py <= distance * distance
+            })
+    })
+}
+
+fn transform_rect(transform: &Transform, &(x, y, width, height): &Rect) -> Rect {
+    let corners = [
+        transform.transform_point((x, y)),
+        transform.transform_point((x + width, y)),
+        transform.transform_point((x, y + height)),
+        transform.transform_point((x + width, y + height)),
+    ];
+    let (mut min_x, mut min_y) = corners[0];
+    let (mut max_x, mut max_y) = corners[0];
+    for &(x, y) in &corners[1..] {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    (min_x, min_y, max_x - min_x, max_y - min_y)
+}
+
+fn inflate(&(x, y, width, height): &Rect, distance: f64) -> Rect {
+    (
+        x - distance,
+        y - distance,
+        width + 2.0 * distance,
+        height + 2.0 * distance,
+    )
+}
+
+fn union(a: &Rect, b: &Rect) -> Rect {
+    let (x, y) = (a.0.min(b.0), a.1.min(b.1));
+    let right = (a.0 + a.2).max(b.0 + b.2);
+    let bottom = (a.1 + a.3).max(b.1 + b.3);
+    (x, y, right - x, bottom - y)
+}
+
+fn intersects(a: &Rect, b: &Rect) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+struct SceneHandler {
+    scene: Rc<RefCell<Scene>>,
+}
+
+impl AreaHandler for SceneHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let mut scene = self.scene.borrow_mut();
+        scene.set_viewport(Some((0.0, 0.0, params.area_width, params.area_height)));
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        scene.render(&mut &params.context, Some(clip));
+    }
+
+    fn mouse_event(&mut self, area: &Area, event: &AreaMouseEvent) {
+        let mut scene = self.scene.borrow_mut();
+        scene.set_viewport(Some((0.0, 0.0, event.area_width, event.area_height)));
+        scene.mouse_event(event);
+        if scene.needs_redraw() {
+            area.queue_redraw_all();
+        }
+    }
+
+    fn mouse_crossed(&mut self, area: &Area, left: bool) {
+        if left {
+            let mut scene = self.scene.borrow_mut();
+            scene.mouse_left();
+            if scene.needs_redraw() {
+                area.queue_redraw_all();
+            }
+        }
+    }
+
+    fn drag_broken(&mut self, area: &Area) {
+        let mut scene = self.scene.borrow_mut();
+        scene.drag_broken();
+        if scene.needs_redraw() {
+            area.queue_redraw_all();
+        }
+    }
+}
+
+/// An `Area` showing a [`Scene`], replacing the usual drawing and hit testing code of an
+/// [`AreaHandler`].
+///
+/// Change the scene through [`SceneArea::update()`], which queues a redraw if a visible
+/// part of it changed. libui can only redraw the whole area, so only nodes intersecting
+/// the clip rectangle of the draw are drawn, and changes outside of the visible area do
+/// not cause redraws at all.
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # use libui::draw::*;
+/// let scene_area = SceneArea::new();
+/// scene_area.update(|scene| {
+///     let root = scene.root();
+///     let button = scene.add(
+///         root,
+///         SceneNode::filled(
+///             PathBuilder::default().rounded_rect(10.0, 10.0, 80.0, 30.0, 5.0).finish(),
+///             Brush::Solid(Color::rgb(0.2, 0.4, 0.8)),
+///         ),
+///     );
+///     scene.on_click(button, |scene, button, _| scene.set_visible(button, false));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct SceneArea {
+    area: Area,
+    scene: Rc<RefCell<Scene>>,
+}
+
+impl Default for SceneArea {
+    fn default() -> Self {
+        SceneArea::new()
+    }
+}
+
+impl SceneArea {
+    /// Creates a non-scrolling area with an empty scene.
+    pub fn new() -> SceneArea {
+        let mut scene = Scene::new();
+        scene.set_text_measure(|params| TextLayout::new(params).extents());
+        let scene = Rc::new(RefCell::new(scene));
+        let area = Area::new(Box::new(SceneHandler {
+            scene: scene.clone(),
+        }));
+        SceneArea { area, scene }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    /// Returns the scene.
+    ///
+    /// # Panics
+    /// Panics if called from a scene event handler, which gets the scene passed instead.
+    pub fn scene(&self) -> Ref<'_, Scene> {
+        self.scene.borrow()
+    }
+
+    /// Modifies the scene, queueing a redraw if a visible part of it changed.
+    ///
+    /// # Panics
+    /// Panics if called from a scene event handler, which gets the scene passed instead.
+    pub fn update<R, F: FnOnce(&mut Scene) -> R>(&self, f: F) -> R {
+        let mut scene = self.scene.borrow_mut();
+        let result = f(&mut scene);
+        if scene.needs_redraw() {
+            self.area.queue_redraw_all();
+        }
+        result
+    }
+}
+
+impl From<SceneArea> for Control {
+    fn from(scene_area: SceneArea) -> Control {
+        scene_area.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use controls::Modifiers;
+    use draw::{Color, DrawCommand, Recorder};
+    use std::cell::Cell;
+
+    fn square(x: f64, y: f64) -> SceneNode {
+        SceneNode::filled(
+            PathData::rectangle(x, y, 10.0, 10.0),
+            Brush::Solid(Color::BLACK),
+        )
+    }
+
+    fn mouse(x: f64, y: f64, down: i32, up: i32) -> AreaMouseEvent {
+        AreaMouseEvent {
+            x,
+            y,
+            area_width: 100.0,
+            area_height: 100.0,
+            down,
+            up,
+            count: 1,
+            modifiers: Modifiers::empty(),
+            held_1_to_64: 0,
+        }
+    }
+
+    fn fills(scene: &mut Scene, clip: Option<Rect>) -> usize {
+        let mut recorder = Recorder::new();
+        scene.render(&mut recorder, clip);
+        recorder
+            .finish()
+            .commands()
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::Fill { .. }))
+            .count()
+    }
+
+    #[test]
+    fn hit_tests_by_z_order_and_visibility() {
+        let mut scene = Scene::new();
+        let root = scene.root();
+        let group = scene.add(root, SceneNode::Group);
+        let below = scene.add(group, square(0.0, 0.0));
+        let above = scene.add(group, square(5.0, 5.0));
+        assert_eq!(scene.hit_test(7.0, 7.0), Some(above));
+        scene.set_z_index(below, 1);
+        assert_eq!(scene.hit_test(7.0, 7.0), Some(below));
+        scene.set_visible(below, false);
+        assert_eq!(scene.hit_test(7.0, 7.0), Some(above));
+
+        let mut transform = Transform::identity();
+        transform.scale(0.0, 0.0, 2.0, 2.0);
+        scene.set_transform(group, transform);
+        assert_eq!(scene.hit_test(25.0, 25.0), Some(above));
+        assert_eq!(scene.bounds(group), Some((9.0, 9.0, 22.0, 22.0)));
+        scene.remove(group);
+        assert_eq!(scene.hit_test(25.0, 25.0), None);
+        assert!(!scene.contains(above));
+    }
+
+    #[test]
+    fn tracks_dirty_regions_and_culls() {
+        let mut scene = Scene::new();
+        let root = scene.root();
+        let left = scene.add(root, square(0.0, 0.0));
+        scene.add(root, square(50.0, 0.0));
+        assert_eq!(fills(&mut scene, None), 2);
+        assert_eq!(scene.dirty_region(), None);
+        assert_eq!(fills(&mut scene, Some((0.0, 0.0, 20.0, 20.0))), 1);
+
+        scene.set_viewport(Some((0.0, 0.0, 100.0, 100.0)));
+        scene.translate(left, 200.0, 0.0);
+        assert_eq!(scene.dirty_region(), Some((-1.0, -1.0, 212.0, 12.0)));
+        assert!(scene.needs_redraw());
+        fills(&mut scene, None);
+        scene.translate(left, 100.0, 0.0);
+        assert!(!scene.needs_redraw());
+    }
+
+    #[test]
+    fn dispatches_clicks_and_drags() {
+        let mut scene = Scene::new();
+        let root = scene.root();
+        let group = scene.add(root, SceneNode::Group);
+        let node = scene.add(group, square(0.0, 0.0));
+        let clicks = Rc::new(Cell::new(0));
+        let counter = clicks.clone();
+        scene.on_click(group, move |_, id, _| {
+            assert_eq!(id, group);
+            counter.set(counter.get() + 1);
+        });
+        scene.on_drag(node, |scene, id, drag| {
+            scene.translate(id, drag.dx, drag.dy)
+        });
+
+        scene.mouse_event(&mouse(5.0, 5.0, 1, 0));
+        scene.mouse_event(&mouse(5.0, 5.0, 0, 1));
+        assert_eq!(clicks.get(), 1);
+
+        scene.mouse_event(&mouse(5.0, 5.0, 1, 0));
+        scene.mouse_event(&mouse(7.0, 6.0, 0, 0));
+        scene.mouse_event(&mouse(7.0, 6.0, 0, 1));
+        assert_eq!(clicks.get(), 2);
+        assert_eq!(scene.hit_test(1.0, 1.0), Some(node));
+
+        scene.mouse_event(&mouse(5.0, 5.0, 1, 0));
+        scene.mouse_event(&mouse(25.0, 5.0, 0, 0));
+        scene.mouse_event(&mouse(25.0, 5.0, 0, 1));
+        assert_eq!(clicks.get(), 2);
+        assert_eq!(scene.hit_test(25.0, 5.0), Some(node));
+    }
+
+    #[test]
+    fn reports_hover_changes() {
+        let mut scene = Scene::new();
+        let root = scene.root();
+        let node = scene.add(root, square(0.0, 0.0));
+        let hovered = Rc::new(Cell::new(false));
+        let state = hovered.clone();
+        scene.on_hover(node, move |_, _, entered| state.set(entered));
+        scene.mouse_event(&mouse(5.0, 5.0, 0, 0));
+        assert!(hovered.get());
+        scene.mouse_event(&mouse(50.0, 5.0, 0, 0));
+        assert!(!hovered.get());
+        scene.mouse_event(&mouse(5.0, 5.0, 0, 0));
+        scene.mouse_left();
+        assert!(!hovered.get());
+    }
+}
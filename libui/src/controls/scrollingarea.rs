@@ -0,0 +1,243 @@
+//! A scrolling `Area` with safe size and scroll methods.
+
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, AreaMouseEvent, Control, MouseEvent,
+};
+use draw::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type ScrollHandler = Box<dyn FnMut(&ScrollingArea, Rect)>;
+
+struct ScrollState {
+    width: u64,
+    height: u64,
+    visible: Option<Rect>,
+    on_scroll: Option<ScrollHandler>,
+}
+
+impl ScrollState {
+    /// Updates the visible rectangle from the clip rectangle of a draw, returning whether
+    /// it changed.
+    ///
+    /// A draw after scrolling may only cover the newly exposed strip, so the last visible
+    /// rectangle is moved just far enough to contain the clip rectangle along each axis,
+    /// unless the clip rectangle is larger.
+    fn track(&mut self, clip: Rect) -> bool {
+        let visible = match self.visible {
+            None => clip,
+            Some(visible) => {
+                let (x, width) =
+                    follow((visible.0, visible.2), (clip.0, clip.2), self.width as f64);
+                let (y, height) =
+                    follow((visible.1, visible.3), (clip.1, clip.3), self.height as f64);
+                (x, y, width, height)
+            }
+        };
+        let changed = self.visible != Some(visible);
+        self.visible = Some(visible);
+        changed
+    }
+}
+
+/// Moves the span `(start, length)` of `visible` to contain `clip` along one axis,
+/// within the content size.
+fn follow(visible: (f64, f64), clip: (f64, f64), size: f64) -> (f64, f64) {
+    if clip.1 >= visible.1 {
+        return clip;
+    }
+    let start = if clip.0 < visible.0 {
+        clip.0
+    } else if clip.0 + clip.1 > visible.0 + visible.1 {
+        clip.0 + clip.1 - visible.1
+    } else {
+        visible.0
+    };
+    (start.min(size - visible.1).max(0.0), visible.1)
+}
+
+struct ScrollingHandler {
+    handler: Box<dyn AreaHandler>,
+    state: Rc<RefCell<ScrollState>>,
+}
+
+impl AreaHandler for ScrollingHandler {
+    fn draw(&mut self, area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        let changed = self.state.borrow_mut().track(clip);
+        if changed {
+            let scrolling_area = ScrollingArea {
+                area: area.clone(),
+                state: self.state.clone(),
+            };
+            // The callback is taken out while it runs, so it can use the area.
+            let callback = self.state.borrow_mut().on_scroll.take();
+            if let Some(mut callback) = callback {
+                let visible = self.state.borrow().visible.unwrap_or(clip);
+                callback(&scrolling_area, visible);
+                let mut state = self.state.borrow_mut();
+                if state.on_scroll.is_none() {
+                    state.on_scroll = Some(callback);
+                }
+            }
+        }
+        self.handler.draw(area, params);
+    }
+
+    fn mouse_event(&mut self, area: &Area, event: &AreaMouseEvent) {
+        self.handler.mouse_event(area, event)
+    }
+
+    fn mouse_crossed(&mut self, area: &Area, left: bool) {
+        self.handler.mouse_crossed(area, left)
+    }
+
+    fn drag_broken(&mut self, area: &Area) {
+        self.handler.drag_broken(area)
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        self.handler.mouse(area, event)
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        self.handler.key_event(area, event)
+    }
+}
+
+/// An `Area` with scroll bars, whose content has a size set by the program.
+///
+/// Unlike the unsafe methods of a plain `Area`, the size and scroll methods of a
+/// `ScrollingArea` are always valid. Drawing and mouse events use content coordinates.
+///
+/// libui cannot report the scroll position, so [`ScrollingArea::visible_rect()`] is
+/// derived from the clip rectangles of draws: it is accurate after scrolling and full
+/// redraws, but may stay larger than the window after it shrinks, until the next full
+/// redraw. [`ScrollingArea::on_scroll()`] is called before a draw when the visible
+/// rectangle changed, so tiles of large canvases can be loaded lazily:
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # struct Canvas;
+/// # impl AreaHandler for Canvas {}
+/// let area = ScrollingArea::new(Box::new(Canvas), 100_000, 100_000);
+/// area.on_scroll(|_, (x, y, width, height)| {
+///     println!("load tiles for {}x{} at {}, {}", width, height, x, y);
+/// });
+/// area.scroll_to(50_000.0, 50_000.0, 800.0, 600.0);
+/// ```
+#[derive(Clone)]
+pub struct ScrollingArea {
+    area: Area,
+    state: Rc<RefCell<ScrollState>>,
+}
+
+impl ScrollingArea {
+    /// The largest content size along either axis, as libui takes sizes as `i32`.
+    pub const MAX_SIZE: u64 = i32::MAX as u64;
+
+    /// Creates a scrolling area with content of the given size in points, clamped to
+    /// [`ScrollingArea::MAX_SIZE`].
+    pub fn new(handler: Box<dyn AreaHandler>, width: u64, height: u64) -> ScrollingArea {
+        let (width, height) = (width.min(Self::MAX_SIZE), height.min(Self::MAX_SIZE));
+        let state = Rc::new(RefCell::new(ScrollState {
+            width,
+            height,
+            visible: None,
+            on_scroll: None,
+        }));
+        let handler = Box::new(ScrollingHandler {
+            handler,
+            state: state.clone(),
+        });
+        let area = Area::new_scrolling(handler, width as i64, height as i64);
+        ScrollingArea { area, state }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    /// Returns the size of the content.
+    pub fn size(&self) -> (u64, u64) {
+        let state = self.state.borrow();
+        (state.width, state.height)
+    }
+
+    /// Sets the size of the content in points, clamped to [`ScrollingArea::MAX_SIZE`].
+    pub fn set_size(&self, width: u64, height: u64) {
+        let (width, height) = (width.min(Self::MAX_SIZE), height.min(Self::MAX_SIZE));
+        {
+            let mut state = self.state.borrow_mut();
+            state.width = width;
+            state.height = height;
+        }
+        // Safe: the area was created as a scrolling area.
+        #[allow(deprecated)]
+        unsafe {
+            self.area.set_size(width, height);
+        }
+    }
+
+    /// Scrolls to show as much of the given rectangle of the content as possible.
+    pub fn scroll_to(&self, x: f64, y: f64, width: f64, height: f64) {
+        // Safe: the area was created as a scrolling area.
+        #[allow(deprecated)]
+        unsafe {
+            self.area.scroll_to(x, y, width, height);
+        }
+    }
+
+    /// Queues the entire area to be redrawn.
+    pub fn queue_redraw_all(&self) {
+        self.area.queue_redraw_all()
+    }
+
+    /// Returns the visible part of the content, once it has been drawn.
+    pub fn visible_rect(&self) -> Option<Rect> {
+        self.state.borrow().visible
+    }
+
+    /// Sets the callback run when the visible part of the content changed, before it is
+    /// drawn.
+    pub fn on_scroll<F: FnMut(&ScrollingArea, Rect) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_scroll = Some(Box::new(callback));
+    }
+}
+
+impl From<ScrollingArea> for Control {
+    fn from(scrolling_area: ScrollingArea) -> Control {
+        scrolling_area.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_the_visible_rect() {
+        let mut state = ScrollState {
+            width: 1000,
+            height: 1000,
+            visible: None,
+            on_scroll: None,
+        };
+        assert!(state.track((0.0, 0.0, 200.0, 100.0)));
+        assert!(!state.track((10.0, 10.0, 50.0, 50.0)));
+        // Scrolling down by 30 points only exposes a strip at the bottom.
+        assert!(state.track((0.0, 100.0, 200.0, 30.0)));
+        assert_eq!(state.visible, Some((0.0, 30.0, 200.0, 100.0)));
+        assert!(state.track((0.0, 0.0, 200.0, 10.0)));
+        assert_eq!(state.visible, Some((0.0, 0.0, 200.0, 100.0)));
+        // A larger window shows more.
+        assert!(state.track((0.0, 0.0, 300.0, 100.0)));
+        assert_eq!(state.visible, Some((0.0, 0.0, 300.0, 100.0)));
+    }
+}
@@ -0,0 +1,612 @@
+//! Caching the cells of huge or slow data sources, loading them in the background.
+
+use super::{CellValue, TableDataSource, TableEdit, TableModel, TableValue, TableValueType};
+use draw::Color;
+use libui_ffi;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_void;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+/// Tuning parameters of a [`CachedDataSource`].
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    /// The number of rows loaded at once.
+    pub page_size: i32,
+    /// The number of pages kept in memory. The least recently used pages are evicted first.
+    pub capacity: usize,
+    /// The number of pages after a requested page that are loaded in advance.
+    pub prefetch: i32,
+    /// The text shown in string cells while their page loads.
+    pub placeholder: String,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        CacheOptions {
+            page_size: 256,
+            capacity: 64,
+            prefetch: 1,
+            placeholder: "\u{2026}".to_string(),
+        }
+    }
+}
+
+/// Counters describing how well a [`CachedDataSource`] performs.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    /// Cells served from the cache.
+    pub hits: u64,
+    /// Cells served as placeholders.
+    pub misses: u64,
+    pub pages_loaded: u64,
+    pub pages_evicted: u64,
+}
+
+/// A cell as kept in the cache, with text already converted for libui.
+#[derive(Clone)]
+enum CachedCell {
+    Value(TableValue),
+    Text(Arc<CStr>),
+}
+
+impl CachedCell {
+    fn new(value: TableValue) -> CachedCell {
+        match value {
+            TableValue::String(text) => CachedCell::Text(c_text(text)),
+            value => CachedCell::Value(value),
+        }
+    }
+
+    fn into_value(self) -> TableValue {
+        match self {
+            CachedCell::Value(value) => value,
+            CachedCell::Text(text) => TableValue::String(text.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+/// Converts text for libui, dropping NUL characters it cannot show.
+fn c_text(text: String) -> Arc<CStr> {
+    let text = CString::new(text).unwrap_or_else(|error| {
+        let mut bytes = error.into_vec();
+        bytes.retain(|&b| b != 0);
+        CString::new(bytes).unwrap()
+    });
+    Arc::from(text)
+}
+
+fn load_row<S: TableDataSource + ?Sized>(
+    source: &mut S,
+    columns: i32,
+    row: i32,
+) -> Vec<CachedCell> {
+    (0..columns)
+        .map(|column| CachedCell::new(source.cell(column, row)))
+        .collect()
+}
+
+struct LoadRequest {
+    generation: u64,
+    page: i32,
+    first_row: i32,
+    count: i32,
+}
+
+struct LoadedPage {
+    generation: u64,
+    page: i32,
+    rows: Vec<Vec<CachedCell>>,
+}
+
+struct CachedPage {
+    rows: Vec<Vec<CachedCell>>,
+    last_used: u64,
+}
+
+type Waker = Arc<Mutex<Option<Box<dyn Fn() + Send>>>>;
+
+/// A [`TableDataSource`] wrapping a slow one, e.g. backed by a database or log file, and
+/// serving its cells from an LRU cache of pages that are loaded on a background thread.
+///
+/// Cells of pages that are not cached yet are shown as placeholders: the configured
+/// placeholder text, `0` or a transparent color. Their page is requested from the loading
+/// thread. Tables showing a model created by [`CachedDataSource::into_model()`] are refreshed
+/// as soon as the page arrives. Text is converted for libui once, when its page is loaded,
+/// rather than on every paint.
+///
+/// The number of columns, their types and the number of rows are read from the wrapped
+/// source once; call [`CachedDataSource::invalidate()`] after changing its data. Edits are
+/// passed on to the wrapped source right away, after which the edited row is read again.
+/// Edits and the typed cells of columns with a [`ColumnCodec`](super::ColumnCodec) are not
+/// cached: they wait for a page being loaded from the wrapped source, blocking the thread
+/// of the UI meanwhile.
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// struct Squares;
+///
+/// impl TableDataSource for Squares {
+///     fn num_columns(&mut self) -> i32 { 2 }
+///     fn num_rows(&mut self) -> i32 { 1_000_000 }
+///     fn column_type(&mut self, column: i32) -> TableValueType {
+///         [TableValueType::Int, TableValueType::String][column as usize]
+///     }
+///     fn cell(&mut self, column: i32, row: i32) -> TableValue {
+///         match column {
+///             0 => TableValue::Int(row),
+///             _ => TableValue::String((row as i64 * row as i64).to_string()),
+///         }
+///     }
+///     fn set_cell(&mut self, _column: i32, _row: i32, _value: TableValue) {}
+/// }
+///
+/// let source = CachedDataSource::new(Squares, CacheOptions::default());
+/// let (_source, model) = source.into_model();
+/// let mut table = Table::new(TableParameters::new(model));
+/// table.append_text_column("n", 0, Table::COLUMN_READONLY);
+/// table.append_text_column("n²", 1, Table::COLUMN_READONLY);
+/// ```
+pub struct CachedDataSource<S> {
+    source: Arc<Mutex<S>>,
+    column_types: Vec<TableValueType>,
+    num_rows: i32,
+    options: CacheOptions,
+    placeholder: Arc<CStr>,
+    pages: HashMap<i32, CachedPage>,
+    pending: HashSet<i32>,
+    clock: u64,
+    generation: u64,
+    stats: CacheStats,
+    requests: Sender<LoadRequest>,
+    loaded: Receiver<LoadedPage>,
+    waker: Waker,
+}
+
+impl<S: TableDataSource + Send + 'static> CachedDataSource<S> {
+    /// Wraps a data source, starting a background thread that loads its pages.
+    ///
+    /// # Panics
+    /// Panics if the page size or capacity is zero.
+    pub fn new(mut source: S, options: CacheOptions) -> CachedDataSource<S> {
+        assert!(options.page_size > 0, "Page size must be positive");
+        assert!(options.capacity > 0, "Cache capacity must be positive");
+
+        let column_types = (0..source.num_columns())
+            .map(|column| source.column_type(column))
+            .collect::<Vec<_>>();
+        let num_rows = source.num_rows();
+        let source = Arc::new(Mutex::new(source));
+
+        let (requests, request_rx) = mpsc::channel::<LoadRequest>();
+        let (loaded_tx, loaded) = mpsc::channel();
+        let waker: Waker = Arc::new(Mutex::new(None));
+        let worker_waker = waker.clone();
+        let worker_source = source.clone();
+        let columns = column_types.len() as i32;
+
+        // The thread ends once the data source and with it the request sender is dropped.
+        thread::spawn(move || {
+            for request in request_rx {
+                let rows = {
+                    let mut source = worker_source.lock().unwrap();
+                    (request.first_row..request.first_row + request.count)
+                        .map(|row| load_row(&mut *source, columns, row))
+                        .collect()
+                };
+                let page = LoadedPage {
+                    generation: request.generation,
+                    page: request.page,
+                    rows,
+                };
+                if loaded_tx.send(page).is_err() {
+                    break;
+                }
+                if let Some(ref wake) = *worker_waker.lock().unwrap() {
+                    wake();
+                }
+            }
+        });
+
+        CachedDataSource {
+            source,
+            column_types,
+            num_rows,
+            placeholder: c_text(options.placeholder.clone()),
+            options,
+            pages: HashMap::new(),
+            pending: HashSet::new(),
+            clock: 0,
+            generation: 0,
+            stats: CacheStats::default(),
+            requests,
+            loaded,
+            waker,
+        }
+    }
+
+    /// Creates a [`TableModel`] for this data source that updates its views whenever
+    /// pages arrive from the loading thread.
+    pub fn into_model(self) -> (Rc<RefCell<CachedDataSource<S>>>, Rc<RefCell<TableModel>>) {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        let waker = self.waker.clone();
+        let source = Rc::new(RefCell::new(self));
+        let model = Rc::new(RefCell::new(TableModel::new(source.clone())));
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let pages: Rc<RefCell<dyn PollPages>> = source.clone();
+        DELIVERIES.with(|d| {
+            d.borrow_mut()
+                .insert(id, (Rc::downgrade(&pages), Rc::downgrade(&model)))
+        });
+        *waker.lock().unwrap() = Some(Box::new(move || unsafe {
+            // uiQueueMain() is the one libui function that may be called from any thread.
+            libui_ffi::uiQueueMain(Some(c_deliver_pages), id as *mut c_void);
+        }));
+
+        (source, model)
+    }
+
+    /// Gives access to the wrapped data source, waiting for a page being loaded from it.
+    ///
+    /// Call [`CachedDataSource::invalidate()`] after changing its data.
+    pub fn source(&self) -> MutexGuard<'_, S> {
+        self.source.lock().unwrap()
+    }
+
+    /// Moves pages that arrived from the loading thread into the cache.
+    ///
+    /// Returns the `(first_row, count)` ranges of rows that became available.
+    /// This is done automatically for data sources turned into a model by
+    /// [`CachedDataSource::into_model()`].
+    pub fn poll(&mut self) -> Vec<(i32, i32)> {
+        let mut ranges = Vec::new();
+        while let Ok(loaded) = self.loaded.try_recv() {
+            if loaded.generation != self.generation {
+                continue;
+            }
+            self.pending.remove(&loaded.page);
+            self.clock += 1;
+            let first_row = loaded.page * self.options.page_size;
+            ranges.push((first_row, loaded.rows.len() as i32));
+            self.pages.insert(
+                loaded.page,
+                CachedPage {
+                    rows: loaded.rows,
+                    last_used: self.clock,
+                },
+            );
+            self.stats.pages_loaded += 1;
+            self.evict();
+        }
+        ranges
+    }
+
+    /// Drops all cached pages, ignores pages still being loaded and reads the number
+    /// of rows again, e.g. after the wrapped data changed or a log file grew.
+    ///
+    /// Cells are loaded again as views ask for them; use the `TableModel::notify_XXX()`
+    /// methods to have them ask and to inform them about new rows.
+    pub fn invalidate(&mut self) {
+        self.pages.clear();
+        self.pending.clear();
+        self.generation += 1;
+        let num_rows = self.source().num_rows();
+        self.num_rows = num_rows;
+    }
+
+    /// Returns whether the row is cached, rather than shown as placeholders.
+    pub fn is_cached(&self, row: i32) -> bool {
+        self.pages.contains_key(&(row / self.options.page_size))
+    }
+
+    /// Returns the cache performance counters.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn placeholder(&self, column: i32) -> TableValue {
+        match self.column_types[column as usize] {
+            TableValueType::String => TableValue::String(self.options.placeholder.clone()),
+            TableValueType::Int => TableValue::Int(0),
+            TableValueType::Color => TableValue::Color(Color::TRANSPARENT),
+            TableValueType::Image => TableValue::String(String::new()),
+        }
+    }
+
+    /// Returns a cached cell, or `None` after requesting its page.
+    fn lookup(&mut self, column: i32, row: i32) -> Option<CachedCell> {
+        let page = row / self.options.page_size;
+        let offset = (row % self.options.page_size) as usize;
+        self.clock += 1;
+
+        let clock = self.clock;
+        let cached = self.pages.get_mut(&page).map(|p| {
+            p.last_used = clock;
+            p.rows
+                .get(offset)
+                .and_then(|r| r.get(column as usize))
+                .cloned()
+        });
+        match cached {
+            Some(cell) => {
+                self.stats.hits += 1;
+                cell
+            }
+            None => {
+                self.stats.misses += 1;
+                for next in page..=page + self.options.prefetch {
+                    self.request(next);
+                }
+                None
+            }
+        }
+    }
+
+    fn request(&mut self, page: i32) {
+        let first_row = page * self.options.page_size;
+        if first_row >= self.num_rows
+            || self.pages.contains_key(&page)
+            || !self.pending.insert(page)
+        {
+            return;
+        }
+        let count = self.options.page_size.min(self.num_rows - first_row);
+        // The loading thread only ends when we are dropped, so sending cannot fail.
+        let _ = self.requests.send(LoadRequest {
+            generation: self.generation,
+            page,
+            first_row,
+            count,
+        });
+    }
+
+    /// Passes an edit on to the wrapped source and reads the edited row again.
+    ///
+    /// This blocks while a page is being loaded from the wrapped source.
+    fn edit<F: FnOnce(&mut S)>(&mut self, row: i32, f: F) {
+        let columns = self.column_types.len() as i32;
+        let cells = {
+            let mut source = self.source();
+            f(&mut source);
+            load_row(&mut *source, columns, row)
+        };
+        let page = row / self.options.page_size;
+        let offset = (row % self.options.page_size) as usize;
+        if let Some(cached) = self
+            .pages
+            .get_mut(&page)
+            .and_then(|p| p.rows.get_mut(offset))
+        {
+            *cached = cells;
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.pages.len() > self.options.capacity {
+            let oldest = self
+                .pages
+                .iter()
+                .min_by_key(|&(_, p)| p.last_used)
+                .map(|(&page, _)| page)
+                .unwrap();
+            self.pages.remove(&oldest);
+            self.stats.pages_evicted += 1;
+        }
+    }
+}
+
+impl<S: TableDataSource + Send + 'static> TableDataSource for CachedDataSource<S> {
+    fn num_columns(&mut self) -> i32 {
+        self.column_types.len() as i32
+    }
+
+    fn num_rows(&mut self) -> i32 {
+        self.num_rows
+    }
+
+    fn column_type(&mut self, column: i32) -> TableValueType {
+        self.column_types[column as usize]
+    }
+
+    fn cell(&mut self, column: i32, row: i32) -> TableValue {
+        match self.lookup(column, row) {
+            Some(cell) => cell.into_value(),
+            None => self.placeholder(column),
+        }
+    }
+
+    fn cell_c_str(&mut self, column: i32, row: i32) -> Option<Arc<CStr>> {
+        if self.column_types[column as usize] != TableValueType::String {
+            return None;
+        }
+        match self.lookup(column, row) {
+            Some(CachedCell::Text(text)) => Some(text),
+            Some(CachedCell::Value(_)) => None,
+            None => Some(self.placeholder.clone()),
+        }
+    }
+
+    fn set_cell(&mut self, column: i32, row: i32, value: TableValue) {
+        self.edit(row, |source| source.set_cell(column, row, value));
+    }
+
+    fn cell_edited(&mut self, column: i32, row: i32, edit: TableEdit) {
+        self.edit(row, |source| source.cell_edited(column, row, edit));
+    }
+
+    fn typed_cell(&mut self, column: i32, row: i32) -> CellValue {
+        self.source().typed_cell(column, row)
+    }
+
+    fn set_typed_cell(&mut self, column: i32, row: i32, value: CellValue) {
+        self.edit(row, |source| source.set_typed_cell(column, row, value));
+    }
+}
+
+/// What the main thread does with a [`CachedDataSource`] when pages arrive.
+trait PollPages {
+    fn poll(&mut self) -> Vec<(i32, i32)>;
+}
+
+impl<S: TableDataSource + Send + 'static> PollPages for CachedDataSource<S> {
+    fn poll(&mut self) -> Vec<(i32, i32)> {
+        CachedDataSource::poll(self)
+    }
+}
+
+type Delivery = (Weak<RefCell<dyn PollPages>>, Weak<RefCell<TableModel>>);
+
+thread_local! {
+    static DELIVERIES: RefCell<HashMap<usize, Delivery>> = RefCell::new(HashMap::new())
+}
+
+extern "C" fn c_deliver_pages(data: *mut c_void) {
+    let id = data as usize;
+    let delivery = DELIVERIES.with(|d| {
+        let mut deliveries = d.borrow_mut();
+        let upgraded = deliveries
+            .get(&id)
+            .and_then(|(s, m)| Some((s.upgrade()?, m.upgrade()?)));
+        if upgraded.is_none() {
+            deliveries.remove(&id);
+        }
+        upgraded
+    });
+
+    if let Some((source, model)) = delivery {
+        // Release the data source before the views ask it for the new cells.
+        let ranges = source.borrow_mut().poll();
+        let model = model.borrow();
+        for (first_row, count) in ranges {
+            for row in first_row..first_row + count {
+                model.notify_row_changed(row);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Numbers and their names, remembering edits.
+    struct Numbers {
+        names: HashMap<i32, String>,
+    }
+
+    impl TableDataSource for Numbers {
+        fn num_columns(&mut self) -> i32 {
+            2
+        }
+
+        fn num_rows(&mut self) -> i32 {
+            95
+        }
+
+        fn column_type(&mut self, column: i32) -> TableValueType {
+            [TableValueType::Int, TableValueType::String][column as usize]
+        }
+
+        fn cell(&mut self, column: i32, row: i32) -> TableValue {
+            match column {
+                0 => TableValue::Int(row),
+                _ => TableValue::String(
+                    self.names
+                        .get(&row)
+                        .cloned()
+                        .unwrap_or_else(|| format!("#{}", row)),
+                ),
+            }
+        }
+
+        fn set_cell(&mut self, _column: i32, row: i32, value: TableValue) {
+            if let TableValue::String(name) = value {
+                self.names.insert(row, name);
+            }
+        }
+    }
+
+    fn source(page_size: i32, capacity: usize) -> CachedDataSource<Numbers> {
+        let options = CacheOptions {
+            page_size,
+            capacity,
+            prefetch: 0,
+            placeholder: "...".into(),
+        };
+        let numbers = Numbers {
+            names: HashMap::new(),
+        };
+        CachedDataSource::new(numbers, options)
+    }
+
+    fn wait_for_page(source: &mut CachedDataSource<Numbers>) -> Vec<(i32, i32)> {
+        for _ in 0..1000 {
+            let ranges = source.poll();
+            if !ranges.is_empty() {
+                return ranges;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Page did not arrive");
+    }
+
+    #[test]
+    fn serves_placeholders_until_page_arrives() {
+        let mut source = source(10, 4);
+        assert_eq!(source.cell(1, 93), TableValue::String("...".into()));
+        assert_eq!(source.cell(0, 94), TableValue::Int(0));
+        assert_eq!(wait_for_page(&mut source), [(90, 5)]);
+        assert_eq!(source.cell(1, 93), TableValue::String("#93".into()));
+        assert_eq!(
+            &*source.cell_c_str(1, 92).unwrap(),
+            &*CString::new("#92").unwrap()
+        );
+        assert_eq!(source.cell_c_str(0, 92), None);
+        let stats = source.stats();
+        assert_eq!((stats.hits, stats.misses, stats.pages_loaded), (2, 2, 1));
+    }
+
+    #[test]
+    fn evicts_least_recently_used_page() {
+        let mut source = source(10, 2);
+        for row in &[0, 10] {
+            source.cell(0, *row);
+            wait_for_page(&mut source);
+        }
+        source.cell(0, 5);
+        source.cell(0, 20);
+        wait_for_page(&mut source);
+        assert!(source.is_cached(0));
+        assert!(!source.is_cached(10));
+        assert!(source.is_cached(20));
+        assert_eq!(source.stats().pages_evicted, 1);
+    }
+
+    #[test]
+    fn edits_reach_the_wrapped_source() {
+        let mut source = source(10, 2);
+        source.cell(0, 0);
+        wait_for_page(&mut source);
+        source.cell_edited(1, 3, TableEdit::TextEdited("three".into()));
+        assert_eq!(source.cell(1, 3), TableValue::String("three".into()));
+        assert_eq!(source.source().names[&3], "three");
+        source.invalidate();
+        assert!(!source.is_cached(3));
+        assert_eq!(source.cell(1, 3), TableValue::String("...".into()));
+        wait_for_page(&mut source);
+        assert_eq!(source.cell(1, 3), TableValue::String("three".into()));
+
+        source.set_typed_cell(1, 4, CellValue::Text("four".into()));
+        assert_eq!(source.source().names[&4], "four");
+        assert_eq!(source.typed_cell(1, 4), CellValue::Text("four".into()));
+        assert_eq!(source.cell(1, 4), TableValue::String("four".into()));
+    }
+}
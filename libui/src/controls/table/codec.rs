@@ -0,0 +1,479 @@
+//! Typed editing of `Table` text cells.
+//!
+//! Editable text cells hand back whatever the user typed as a [`TableValue::String`].
+//! A [`ColumnCodec`] attached to a model column via [`TableModel::set_column_codec()`]
+//! parses that text into a [`CellValue`], runs validation hooks on it and formats
+//! stored values for display. Edits that fail to parse or validate never reach the
+//! [`TableDataSource`]; the cell is reverted instead and the rejection is reported to
+//! the callback registered with [`TableModel::on_cell_edit_rejected()`].
+
+use super::TableValue;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// A typed cell value, as produced by a [`CellCodec`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    /// Index into the choices of a [`ChoiceCodec`].
+    Choice(usize),
+    Text(String),
+}
+
+impl CellValue {
+    /// Returns the value as an integer, if it is numeric or the text of a whole number.
+    ///
+    /// Integers beyond the range of `i32` are stored in the model as text, see
+    /// [`CellValue::into_table_value()`].
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            CellValue::Int(v) => Some(v),
+            CellValue::Float(v) => Some(v as i64),
+            CellValue::Bool(v) => Some(v as i64),
+            CellValue::Choice(v) => Some(v as i64),
+            CellValue::Text(ref s) => s.trim().parse().ok(),
+        }
+    }
+
+    /// Returns the value as a float, if it is numeric or the text of a number.
+    ///
+    /// Floats are stored in the model as text, see [`CellValue::into_table_value()`].
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            CellValue::Float(v) => Some(v),
+            CellValue::Text(ref s) => s.trim().parse().ok(),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    /// Returns the value as a boolean, treating non-zero numbers as `true`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            CellValue::Bool(v) => Some(v),
+            _ => self.as_f64().map(|v| v != 0.0),
+        }
+    }
+
+    /// Converts a raw model value into a typed value.
+    ///
+    /// Colors have no typed representation and are mapped to their `#rrggbb[aa]` form.
+    pub fn from_table_value(value: TableValue) -> CellValue {
+        match value {
+            TableValue::Int(v) => CellValue::Int(v as i64),
+            TableValue::String(s) => CellValue::Text(s),
+            TableValue::Color(color) => CellValue::Text(color.to_string()),
+        }
+    }
+
+    /// Converts the typed value into a raw model value.
+    ///
+    /// Booleans and choices are stored as [`TableValue::Int`], which is also what
+    /// checkbox columns expect. Floats, and integers and choices beyond the range of
+    /// `i32`, are stored as their shortest textual form.
+    pub fn into_table_value(self) -> TableValue {
+        match self {
+            CellValue::Int(v) => match i32::try_from(v) {
+                Ok(v) => TableValue::Int(v),
+                Err(_) => TableValue::String(v.to_string()),
+            },
+            CellValue::Float(v) => TableValue::String(v.to_string()),
+            CellValue::Bool(v) => TableValue::Int(v as i32),
+            CellValue::Choice(v) => match i32::try_from(v) {
+                Ok(v) => TableValue::Int(v),
+                Err(_) => TableValue::String(v.to_string()),
+            },
+            CellValue::Text(s) => TableValue::String(s),
+        }
+    }
+}
+
+/// The reason a cell edit was rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellEditError {
+    /// The input could not be parsed by the column's codec.
+    Parse(String),
+    /// The input parsed, but a validation hook refused the value.
+    Invalid(String),
+}
+
+impl Display for CellEditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellEditError::Parse(msg) => write!(f, "invalid input: {}", msg),
+            CellEditError::Invalid(msg) => write!(f, "invalid value: {}", msg),
+        }
+    }
+}
+
+impl Error for CellEditError {}
+
+/// Describes an edit that was rejected by a [`ColumnCodec`].
+#[derive(Clone, Debug)]
+pub struct CellEditRejection {
+    pub column: i32,
+    pub row: i32,
+    /// The text the user entered.
+    pub input: String,
+    pub error: CellEditError,
+}
+
+/// Converts between the text shown in a cell and a typed [`CellValue`].
+pub trait CellCodec {
+    /// Parses user input into a typed value.
+    fn parse(&self, text: &str) -> Result<CellValue, CellEditError>;
+
+    /// Formats a typed value for display.
+    fn format(&self, value: &CellValue) -> String;
+}
+
+/// A validation hook run on every successfully parsed value of a column.
+pub type CellValidator = Box<dyn Fn(&CellValue) -> Result<(), String>>;
+
+/// A [`CellCodec`] together with the validation hooks of a model column.
+pub struct ColumnCodec {
+    codec: Box<dyn CellCodec>,
+    validators: Vec<CellValidator>,
+}
+
+impl ColumnCodec {
+    pub fn new<C: CellCodec + 'static>(codec: C) -> ColumnCodec {
+        ColumnCodec {
+            codec: Box::new(codec),
+            validators: Vec::new(),
+        }
+    }
+
+    /// Adds a validation hook. Hooks run in the order they were added and the
+    /// first error message returned rejects the edit.
+    pub fn with_validator<F>(mut self, validator: F) -> ColumnCodec
+    where
+        F: Fn(&CellValue) -> Result<(), String> + 'static,
+    {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Parses and validates user input.
+    pub fn decode(&self, text: &str) -> Result<CellValue, CellEditError> {
+        let value = self.codec.parse(text)?;
+        for validator in &self.validators {
+            validator(&value).map_err(CellEditError::Invalid)?;
+        }
+        Ok(value)
+    }
+
+    /// Formats a typed value for display.
+    pub fn format(&self, value: &CellValue) -> String {
+        self.codec.format(value)
+    }
+}
+
+/// Codec for integer cells, optionally limited to an inclusive range.
+///
+/// Values are stored as [`TableValue::Int`], so input beyond the range of `i32` is
+/// rejected.
+#[derive(Clone, Debug, Default)]
+pub struct IntCodec {
+    min: Option<i64>,
+    max: Option<i64>,
+}
+
+impl IntCodec {
+    pub fn new() -> IntCodec {
+        IntCodec::default()
+    }
+
+    /// Limits accepted values to `min..=max`.
+    pub fn range(mut self, min: i64, max: i64) -> IntCodec {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+}
+
+impl CellCodec for IntCodec {
+    fn parse(&self, text: &str) -> Result<CellValue, CellEditError> {
+        let v = text.trim().parse::<i64>().map_err(|_| {
+            CellEditError::Parse(format!("'{}' is not a whole number", text.trim()))
+        })?;
+        if i32::try_from(v).is_err() {
+            return Err(CellEditError::Parse(format!("{} is too large", v)));
+        }
+        check_range(v, self.min, self.max)?;
+        Ok(CellValue::Int(v))
+    }
+
+    fn format(&self, value: &CellValue) -> String {
+        match value.as_i64() {
+            Some(v) => v.to_string(),
+            None => String::new(),
+        }
+    }
+}
+
+/// Codec for decimal cells with an optional unit suffix, e.g. `"12.50 kg"`.
+///
+/// The unit is optional on input and matched case-insensitively.
+#[derive(Clone, Debug)]
+pub struct FloatCodec {
+    unit: String,
+    decimals: usize,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl FloatCodec {
+    /// Creates a codec displaying `decimals` fractional digits followed by `unit`.
+    /// Pass an empty `unit` for plain numbers.
+    pub fn new(unit: &str, decimals: usize) -> FloatCodec {
+        FloatCodec {
+            unit: unit.to_string(),
+            decimals,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// Limits accepted values to `min..=max`.
+    pub fn range(mut self, min: f64, max: f64) -> FloatCodec {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+}
+
+impl CellCodec for FloatCodec {
+    fn parse(&self, text: &str) -> Result<CellValue, CellEditError> {
+        let mut number = text.trim();
+        let unit_len = self.unit.len();
+        if unit_len > 0
+            && number.len() >= unit_len
+            && number.is_char_boundary(number.len() - unit_len)
+            && number[number.len() - unit_len..].eq_ignore_ascii_case(&self.unit)
+        {
+            number = number[..number.len() - unit_len].trim_end();
+        }
+
+        let v = match number.parse::<f64>() {
+            Ok(v) if v.is_finite() => v,
+            _ => {
+                return Err(CellEditError::Parse(format!(
+                    "'{}' is not a number",
+                    text.trim()
+                )))
+            }
+        };
+        check_range(v, self.min, self.max)?;
+        Ok(CellValue::Float(v))
+    }
+
+    fn format(&self, value: &CellValue) -> String {
+        match value.as_f64() {
+            Some(v) if self.unit.is_empty() => format!("{:.*}", self.decimals, v),
+            Some(v) => format!("{:.*} {}", self.decimals, v, self.unit),
+            None => String::new(),
+        }
+    }
+}
+
+/// Codec for boolean cells.
+///
+/// Besides the configured labels, `true`/`false`, `yes`/`no`, `on`/`off` and `1`/`0`
+/// are accepted in any letter case.
+#[derive(Clone, Debug)]
+pub struct BoolCodec {
+    true_text: String,
+    false_text: String,
+}
+
+impl BoolCodec {
+    /// Creates a codec displaying `true_text` and `false_text`.
+    pub fn new(true_text: &str, false_text: &str) -> BoolCodec {
+        BoolCodec {
+            true_text: true_text.to_string(),
+            false_text: false_text.to_string(),
+        }
+    }
+}
+
+impl Default for BoolCodec {
+    fn default() -> Self {
+        BoolCodec::new("true", "false")
+    }
+}
+
+impl CellCodec for BoolCodec {
+    fn parse(&self, text: &str) -> Result<CellValue, CellEditError> {
+        let text = text.trim();
+        let is = |s: &str| text.eq_ignore_ascii_case(s);
+        if is(&self.true_text) || is("true") || is("yes") || is("on") || is("1") {
+            Ok(CellValue::Bool(true))
+        } else if is(&self.false_text) || is("false") || is("no") || is("off") || is("0") {
+            Ok(CellValue::Bool(false))
+        } else {
+            Err(CellEditError::Parse(format!(
+                "expected '{}' or '{}'",
+                self.true_text, self.false_text
+            )))
+        }
+    }
+
+    fn format(&self, value: &CellValue) -> String {
+        match value.as_bool() {
+            Some(true) => self.true_text.clone(),
+            Some(false) => self.false_text.clone(),
+            None => String::new(),
+        }
+    }
+}
+
+/// Codec for cells holding one of a fixed set of choices.
+///
+/// Values are stored as the index of the choice. Input is matched case-insensitively.
+#[derive(Clone, Debug)]
+pub struct ChoiceCodec {
+    choices: Vec<String>,
+}
+
+impl ChoiceCodec {
+    pub fn new<S: AsRef<str>>(choices: &[S]) -> ChoiceCodec {
+        ChoiceCodec {
+            choices: choices.iter().map(|c| c.as_ref().to_string()).collect(),
+        }
+    }
+
+    /// Returns the available choices.
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+}
+
+impl CellCodec for ChoiceCodec {
+    fn parse(&self, text: &str) -> Result<CellValue, CellEditError> {
+        let text = text.trim();
+        self.choices
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(text))
+            .map(CellValue::Choice)
+            .ok_or_else(|| {
+                CellEditError::Parse(format!("expected one of: {}", self.choices.join(", ")))
+            })
+    }
+
+    fn format(&self, value: &CellValue) -> String {
+        match *value {
+            CellValue::Text(ref s) => s.clone(),
+            _ => value
+                .as_i64()
+                .and_then(|i| self.choices.get(i as usize))
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn check_range<T: PartialOrd + Display>(
+    v: T,
+    min: Option<T>,
+    max: Option<T>,
+) -> Result<(), CellEditError> {
+    match (min, max) {
+        (Some(min), Some(max)) if v < min || v > max => Err(CellEditError::Invalid(format!(
+            "{} is not between {} and {}",
+            v, min, max
+        ))),
+        (Some(min), _) if v < min => Err(CellEditError::Invalid(format!(
+            "{} is less than {}",
+            v, min
+        ))),
+        (_, Some(max)) if v > max => Err(CellEditError::Invalid(format!(
+            "{} is greater than {}",
+            v, max
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_codec_parses_and_checks_range() {
+        let codec = IntCodec::new().range(0, 10);
+        assert_eq!(codec.parse(" 7 "), Ok(CellValue::Int(7)));
+        assert!(matches!(codec.parse("11"), Err(CellEditError::Invalid(_))));
+        assert!(matches!(codec.parse("seven"), Err(CellEditError::Parse(_))));
+        assert!(matches!(
+            IntCodec::new().parse("4294967297"),
+            Err(CellEditError::Parse(_))
+        ));
+        assert_eq!(codec.format(&CellValue::Int(3)), "3");
+        assert_eq!(
+            CellValue::Int(4_294_967_297).into_table_value(),
+            TableValue::String("4294967297".into())
+        );
+        assert_eq!(
+            check_range(-1, Some(0), None),
+            Err(CellEditError::Invalid("-1 is less than 0".into()))
+        );
+        assert!(check_range(11, None, Some(10)).is_err());
+        let stored = CellValue::from_table_value(CellValue::Int(-4_294_967_297).into_table_value());
+        assert_eq!(codec.format(&stored), "-4294967297");
+    }
+
+    #[test]
+    fn float_codec_handles_units() {
+        let codec = FloatCodec::new("kg", 2);
+        assert_eq!(codec.parse("1.5 kg"), Ok(CellValue::Float(1.5)));
+        assert_eq!(codec.parse("1.5KG"), Ok(CellValue::Float(1.5)));
+        assert_eq!(codec.parse("2"), Ok(CellValue::Float(2.0)));
+        assert!(codec.parse("inf").is_err());
+        assert!(codec.parse("kg").is_err());
+        assert_eq!(codec.format(&CellValue::Float(1.5)), "1.50 kg");
+        assert_eq!(FloatCodec::new("", 1).format(&CellValue::Int(4)), "4.0");
+
+        let stored = CellValue::from_table_value(CellValue::Float(1.25).into_table_value());
+        assert_eq!(codec.format(&stored), "1.25 kg");
+        assert_eq!(CellValue::Text("many".into()).as_f64(), None);
+    }
+
+    #[test]
+    fn bool_codec_accepts_common_spellings() {
+        let codec = BoolCodec::new("Active", "Inactive");
+        assert_eq!(codec.parse("active"), Ok(CellValue::Bool(true)));
+        assert_eq!(codec.parse("No"), Ok(CellValue::Bool(false)));
+        assert!(codec.parse("maybe").is_err());
+        assert_eq!(codec.format(&CellValue::Int(1)), "Active");
+    }
+
+    #[test]
+    fn choice_codec_maps_to_indices() {
+        let codec = ChoiceCodec::new(&["Low", "Medium", "High"]);
+        assert_eq!(codec.parse("high"), Ok(CellValue::Choice(2)));
+        assert!(codec.parse("Extreme").is_err());
+        assert_eq!(codec.format(&CellValue::Int(1)), "Medium");
+        assert_eq!(codec.format(&CellValue::Choice(7)), "");
+    }
+
+    #[test]
+    fn column_codec_runs_validators_in_order() {
+        let column = ColumnCodec::new(IntCodec::new())
+            .with_validator(|v| match v.as_i64() {
+                Some(v) if v % 2 == 0 => Ok(()),
+                _ => Err("must be even".into()),
+            })
+            .with_validator(|_| Err("unreachable for odd numbers".into()));
+        assert_eq!(
+            column.decode("3"),
+            Err(CellEditError::Invalid("must be even".into()))
+        );
+        assert_eq!(
+            column.decode("4"),
+            Err(CellEditError::Invalid("unreachable for odd numbers".into()))
+        );
+    }
+}
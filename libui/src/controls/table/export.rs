@@ -0,0 +1,366 @@
+//! Writing table data as CSV, TSV or Markdown, and reading CSV back.
+
+use super::{TableDataSource, TableModel, TableValue};
+use std::io::{self, Write};
+
+/// The text formats a [`TableExport`] can write.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// Comma separated values as described by [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180).
+    Csv,
+    /// Tab separated values, which is what spreadsheets expect on the clipboard.
+    /// Tabs and line breaks inside cells are replaced by spaces.
+    Tsv,
+    /// A GitHub flavored Markdown table.
+    Markdown,
+}
+
+/// A model column to export, together with its header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportColumn {
+    pub header: String,
+    pub model_column: i32,
+}
+
+impl ExportColumn {
+    pub fn new(header: &str, model_column: i32) -> ExportColumn {
+        ExportColumn {
+            header: header.to_string(),
+            model_column,
+        }
+    }
+}
+
+/// Writes the rows of a [`TableDataSource`] or [`TableModel`] as text.
+///
+/// Without explicitly selected columns, all model columns are exported with the
+/// headers `Column 1`, `Column 2`, and so on. Use [`Table::export_columns()`](super::Table::export_columns)
+/// to export the columns of a table under their titles.
+///
+/// ```
+/// # use libui::controls::*;
+/// let mut source = VecTableDataSource::new(
+///     vec!["Fruit".into(), "Stock".into()],
+///     vec![TableValueType::String, TableValueType::Int],
+/// );
+/// source.push_row(vec![TableValue::String("Apples".into()), TableValue::Int(73)]);
+///
+/// let csv = TableExport::new(ExportFormat::Csv)
+///     .columns(source.export_columns())
+///     .to_string(&mut source);
+/// assert_eq!(csv, "Fruit,Stock\r\nApples,73\r\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TableExport {
+    format: ExportFormat,
+    columns: Vec<ExportColumn>,
+    header: bool,
+}
+
+impl TableExport {
+    pub fn new(format: ExportFormat) -> TableExport {
+        TableExport {
+            format,
+            columns: Vec::new(),
+            header: true,
+        }
+    }
+
+    /// Adds a column to export.
+    pub fn column(mut self, header: &str, model_column: i32) -> TableExport {
+        self.columns.push(ExportColumn::new(header, model_column));
+        self
+    }
+
+    /// Adds several columns to export.
+    pub fn columns<I: IntoIterator<Item = ExportColumn>>(mut self, columns: I) -> TableExport {
+        self.columns.extend(columns);
+        self
+    }
+
+    /// Sets whether a header row is written. Defaults to `true`.
+    ///
+    /// Markdown tables always have a header row, which is left empty if disabled.
+    pub fn header(mut self, header: bool) -> TableExport {
+        self.header = header;
+        self
+    }
+
+    /// Writes all rows of the data source, using the raw cell values.
+    pub fn write_source<W: Write>(
+        &self,
+        source: &mut dyn TableDataSource,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let columns = self.resolve_columns(source.num_columns());
+        let num_rows = source.num_rows();
+        self.write_rows(&columns, num_rows, |c, r| source.cell(c, r), writer)
+    }
+
+    /// Writes all rows of the model, formatted the way views display them.
+    pub fn write_model<W: Write>(&self, model: &TableModel, writer: &mut W) -> io::Result<()> {
+        let source = model.data_source();
+        let (num_columns, num_rows) = {
+            let mut source = source.borrow_mut();
+            (source.num_columns(), source.num_rows())
+        };
+        let columns = self.resolve_columns(num_columns);
+        self.write_rows(&columns, num_rows, |c, r| model.display_value(c, r), writer)
+    }
+
+    /// Returns all rows of the data source as a string, e.g. to put on the clipboard.
+    pub fn to_string(&self, source: &mut dyn TableDataSource) -> String {
+        let mut buffer = Vec::new();
+        self.write_source(source, &mut buffer)
+            .expect("Writing to a Vec cannot fail");
+        String::from_utf8(buffer).expect("Export produced invalid UTF-8")
+    }
+
+    fn resolve_columns(&self, num_columns: i32) -> Vec<ExportColumn> {
+        if self.columns.is_empty() {
+            (0..num_columns)
+                .map(|c| ExportColumn::new(&format!("Column {}", c + 1), c))
+                .collect()
+        } else {
+            self.columns.clone()
+        }
+    }
+
+    fn write_rows<W, F>(
+        &self,
+        columns: &[ExportColumn],
+        num_rows: i32,
+        mut cell: F,
+        writer: &mut W,
+    ) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(i32, i32) -> TableValue,
+    {
+        let headers: Vec<String> = columns
+            .iter()
+            .map(|c| {
+                if self.header {
+                    c.header.clone()
+                } else {
+                    String::new()
+                }
+            })
+            .collect();
+        if self.header || self.format == ExportFormat::Markdown {
+            self.write_record(&headers, writer)?;
+        }
+        if self.format == ExportFormat::Markdown {
+            let rule = vec!["---".to_string(); columns.len()];
+            writeln!(writer, "| {} |", rule.join(" | "))?;
+        }
+
+        for row in 0..num_rows {
+            let fields: Vec<String> = columns
+                .iter()
+                .map(|c| format_value(&cell(c.model_column, row)))
+                .collect();
+            self.write_record(&fields, writer)?;
+        }
+        Ok(())
+    }
+
+    fn write_record<W: Write>(&self, fields: &[String], writer: &mut W) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => {
+                let fields: Vec<String> = fields.iter().map(|f| escape_csv(f)).collect();
+                write!(writer, "{}\r\n", fields.join(","))
+            }
+            ExportFormat::Tsv => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|f| f.replace(['\t', '\r', '\n'], " "))
+                    .collect();
+                writeln!(writer, "{}", fields.join("\t"))
+            }
+            ExportFormat::Markdown => {
+                let fields: Vec<String> = fields.iter().map(|f| escape_markdown(f)).collect();
+                writeln!(writer, "| {} |", fields.join(" | "))
+            }
+        }
+    }
+}
+
+/// Formats a cell value as text. Colors are written as `#rrggbb`, or `#rrggbbaa`
+/// if they are not fully opaque.
+pub fn format_value(value: &TableValue) -> String {
+    match *value {
+        TableValue::Int(v) => v.to_string(),
+        TableValue::String(ref s) => s.clone(),
+        TableValue::Color(color) => color.to_string(),
+    }
+}
+
+/// Parses a color written by [`format_value()`].
+pub(crate) fn parse_hex_color(s: &str) -> Option<TableValue> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+    s.parse().ok().map(TableValue::Color)
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn escape_markdown(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Splits CSV text into records of fields, following RFC 4180.
+///
+/// Both `\r\n` and `\n` line endings are accepted. Returns an error for
+/// unterminated quotes or stray characters after a closing quote.
+pub fn parse_csv(text: &str) -> io::Result<Vec<Vec<String>>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    quoted = false;
+                    match chars.peek() {
+                        None | Some(&',') | Some(&'\r') | Some(&'\n') => {}
+                        Some(_) => return Err(invalid("unexpected character after closing quote")),
+                    }
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(invalid("unterminated quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use controls::{TableValueType, VecTableDataSource};
+    use draw::Color;
+
+    fn source() -> VecTableDataSource {
+        let mut source = VecTableDataSource::new(
+            vec!["Name".into(), "Count".into(), "Color".into()],
+            vec![
+                TableValueType::String,
+                TableValueType::Int,
+                TableValueType::Color,
+            ],
+        );
+        source.push_row(vec![
+            TableValue::String("a, \"quoted\"\nline".into()),
+            TableValue::Int(-3),
+            TableValue::Color(Color::rgb(1.0, 0.5, 0.0)),
+        ]);
+        source.push_row(vec![
+            TableValue::String("x|y".into()),
+            TableValue::Int(7),
+            TableValue::Color(Color::TRANSPARENT),
+        ]);
+        source
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        let mut source = source();
+        let csv = TableExport::new(ExportFormat::Csv)
+            .columns(source.export_columns())
+            .to_string(&mut source);
+        assert_eq!(
+            csv,
+            "Name,Count,Color\r\n\"a, \"\"quoted\"\"\nline\",-3,#ff8000\r\nx|y,7,#00000000\r\n"
+        );
+    }
+
+    #[test]
+    fn tsv_selects_columns_without_header() {
+        let mut source = source();
+        let tsv = TableExport::new(ExportFormat::Tsv)
+            .column("Count", 1)
+            .column("Name", 0)
+            .header(false)
+            .to_string(&mut source);
+        assert_eq!(tsv, "-3\ta, \"quoted\" line\n7\tx|y\n");
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_and_newlines() {
+        let mut source = source();
+        let md = TableExport::new(ExportFormat::Markdown)
+            .column("Name", 0)
+            .to_string(&mut source);
+        assert_eq!(
+            md,
+            "| Name |\n| --- |\n| a, \"quoted\"<br>line |\n| x\\|y |\n"
+        );
+    }
+
+    #[test]
+    fn default_columns_cover_the_model() {
+        let mut source = source();
+        let csv = TableExport::new(ExportFormat::Csv).to_string(&mut source);
+        assert!(csv.starts_with("Column 1,Column 2,Column 3\r\n"));
+    }
+
+    #[test]
+    fn csv_parser_roundtrips_export() {
+        let mut source = source();
+        let csv = TableExport::new(ExportFormat::Csv)
+            .columns(source.export_columns())
+            .to_string(&mut source);
+        let records = parse_csv(&csv).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1][0], "a, \"quoted\"\nline");
+        assert_eq!(records[2], vec!["x|y", "7", "#00000000"]);
+    }
+
+    #[test]
+    fn csv_parser_rejects_malformed_input() {
+        assert!(parse_csv("\"open").is_err());
+        assert!(parse_csv("\"a\"b,c").is_err());
+        assert_eq!(parse_csv("a,,b\n").unwrap(), vec![vec!["a", "", "b"]]);
+    }
+}
@@ -1,17 +1,32 @@
 use super::Control;
 use callback_helpers::{from_void_ptr, to_heap_ptr};
+use draw::Color;
 use libui_ffi::{
     self, uiControl, uiSortIndicator, uiTable, uiTableModel, uiTableModelHandler, uiTableParams,
     uiTableSelectionMode, uiTableValue, uiTableValueType,
 };
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::io::{self, Write};
 use std::mem;
 use std::os::raw::{c_int, c_uint, c_void};
 use std::rc::Rc;
+use std::sync::Arc;
+
+mod cached;
+pub use self::cached::*;
+mod codec;
+pub use self::codec::*;
+mod export;
+pub use self::export::*;
+mod tree;
+pub use self::tree::*;
+mod vecdatasource;
+pub use self::vecdatasource::*;
 
 /// An enum of possible `Table` cell/column types.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TableValueType {
     String,
     Image,
@@ -53,10 +68,22 @@ impl TableValueType {
 }
 
 /// An enum representing the value of a `Table` cell.
+#[derive(Clone, Debug, PartialEq)]
 pub enum TableValue {
     Int(i32),
     String(String),
-    Color { r: f64, g: f64, b: f64, a: f64 },
+    Color(Color),
+}
+
+/// An edit made by the user through a `Table` view.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TableEdit {
+    /// A button of a button column was clicked.
+    ButtonClicked,
+    /// A checkbox was toggled to the given state.
+    CheckboxToggled(bool),
+    /// The text of an editable text cell was changed.
+    TextEdited(String),
 }
 
 pub trait TableDataSource {
@@ -66,6 +93,57 @@ pub trait TableDataSource {
 
     fn cell(&mut self, column: i32, row: i32) -> TableValue;
     fn set_cell(&mut self, column: i32, row: i32, value: TableValue);
+
+    /// Returns the text of a string cell already converted for libui, or `None` to
+    /// have views call `cell()` and convert its result.
+    ///
+    /// Data sources keeping their text converted, like [`CachedDataSource`], override
+    /// this to spare views a conversion on every paint. The default returns `None`.
+    fn cell_c_str(&mut self, _column: i32, _row: i32) -> Option<Arc<CStr>> {
+        None
+    }
+
+    /// Called for every edit the user makes in a view, except for text edits in
+    /// columns with a [`ColumnCodec`], which go to `set_typed_cell()`.
+    ///
+    /// The default implementation calls `button_clicked()` for button clicks, and
+    /// `set_cell()` with a [`TableValue::Int`] of `0` or `1` for checkbox toggles
+    /// or a [`TableValue::String`] for text edits.
+    fn cell_edited(&mut self, column: i32, row: i32, edit: TableEdit) {
+        match edit {
+            TableEdit::ButtonClicked => self.button_clicked(column, row),
+            TableEdit::CheckboxToggled(checked) => {
+                self.set_cell(column, row, TableValue::Int(checked as i32))
+            }
+            TableEdit::TextEdited(text) => self.set_cell(column, row, TableValue::String(text)),
+        }
+    }
+
+    /// Called when a button of a button column is clicked. `column` is the model
+    /// column holding the button text.
+    ///
+    /// The default implementation calls `set_cell()` with a `TableValue::Int(0)`,
+    /// which is how clicks were reported before this method existed.
+    fn button_clicked(&mut self, column: i32, row: i32) {
+        self.set_cell(column, row, TableValue::Int(0))
+    }
+
+    /// Returns the typed value of a cell in a column with a [`ColumnCodec`].
+    ///
+    /// The default implementation converts the result of `cell()` with
+    /// [`CellValue::from_table_value()`]. Override it for values without an
+    /// exact `TableValue` representation, such as floats.
+    fn typed_cell(&mut self, column: i32, row: i32) -> CellValue {
+        CellValue::from_table_value(self.cell(column, row))
+    }
+
+    /// Stores a parsed and validated edit of a cell in a column with a [`ColumnCodec`].
+    ///
+    /// The default implementation forwards to `set_cell()` using
+    /// [`CellValue::into_table_value()`].
+    fn set_typed_cell(&mut self, column: i32, row: i32, value: CellValue) {
+        self.set_cell(column, row, value.into_table_value())
+    }
 }
 
 extern "C" fn c_num_columns(
@@ -101,11 +179,12 @@ extern "C" fn c_column_type(
     _ui_model: *mut uiTableModel,
     column: c_int,
 ) -> uiTableValueType {
-    let t = unsafe {
-        (*(ui_handler as *mut RustTableModelHandler))
-            .trait_object
-            .borrow_mut()
-            .column_type(column)
+    let handler = unsafe { &*(ui_handler as *mut RustTableModelHandler) };
+    // Coded columns are always presented as text.
+    let t = if handler.codecs.borrow().contains_key(&column) {
+        TableValueType::String
+    } else {
+        handler.trait_object.borrow_mut().column_type(column)
     };
 
     t.into_ui()
@@ -117,67 +196,82 @@ extern "C" fn c_cell_value(
     row: c_int,
     column: c_int,
 ) -> *mut uiTableValue {
-    let value = unsafe {
-        (*(ui_handler as *mut RustTableModelHandler))
-            .trait_object
-            .borrow_mut()
-            .cell(column, row)
-    };
+    let handler = unsafe { &*(ui_handler as *mut RustTableModelHandler) };
+    if let Some(text) = handler.display_c_str(column, row) {
+        return unsafe { libui_ffi::uiNewTableValueString(text.as_ptr()) };
+    }
+    let value = handler.display_value(column, row);
 
     match value {
         TableValue::Int(v) => unsafe { libui_ffi::uiNewTableValueInt(v) },
         TableValue::String(s) => unsafe {
-            let c_string = CString::new(s.as_bytes().to_vec()).unwrap();
+            let c_string = CString::new(s).unwrap();
             libui_ffi::uiNewTableValueString(c_string.as_ptr())
         },
-        TableValue::Color { r, g, b, a } => unsafe { libui_ffi::uiNewTableValueColor(r, g, b, a) },
+        TableValue::Color(c) => unsafe { libui_ffi::uiNewTableValueColor(c.r, c.g, c.b, c.a) },
     }
 }
 
 extern "C" fn c_set_cell_value(
     ui_handler: *mut uiTableModelHandler,
-    _ui_model: *mut uiTableModel,
+    ui_model: *mut uiTableModel,
     row: c_int,
     column: c_int,
     value: *const uiTableValue,
 ) {
     unsafe {
-        // Button columns call SetCellValue() with a value of `NULL` in case of
-        // a click. We don't want to have a special enum or Option<TableValue> just for
-        // this one instance, so we provide an integer instead. The function is only ever
-        // called for clicks anyway, making a check on the users side unnecessary.
-        if value == std::ptr::null() {
-            (*(ui_handler as *mut RustTableModelHandler))
-                .trait_object
-                .borrow_mut()
-                .set_cell(column, row, TableValue::Int(0));
-            return;
-        }
-
-        let vt = libui_ffi::uiTableValueGetType(value);
-        let rt = TableValueType::from_ui(vt);
-
-        let rust_value = match rt {
-            TableValueType::Int => {
-                let i = libui_ffi::uiTableValueInt(value);
-                TableValue::Int(i)
-            }
-            TableValueType::String => {
-                let s = libui_ffi::uiTableValueString(value);
-                TableValue::String(CStr::from_ptr(s).to_string_lossy().into_owned())
+        // Button columns call SetCellValue() with a value of `NULL` in case of a click.
+        // Checkboxes are the only columns editing integers, text columns edit strings.
+        let edit = if value.is_null() {
+            TableEdit::ButtonClicked
+        } else {
+            let vt = libui_ffi::uiTableValueGetType(value);
+            match TableValueType::from_ui(vt) {
+                TableValueType::Int => {
+                    TableEdit::CheckboxToggled(libui_ffi::uiTableValueInt(value) != 0)
+                }
+                TableValueType::String => {
+                    let s = libui_ffi::uiTableValueString(value);
+                    TableEdit::TextEdited(CStr::from_ptr(s).to_string_lossy().into_owned())
+                }
+                _ => panic!("Unsupported table value type"),
             }
-            TableValueType::Color => {
-                let (mut r, mut g, mut b, mut a) = (0.0, 0.0, 0.0, 0.0);
-                libui_ffi::uiTableValueColor(value, &mut r, &mut g, &mut b, &mut a);
-                TableValue::Color { r, g, b, a }
-            }
-            _ => panic!("Unsupported table value type"),
         };
 
-        (*(ui_handler as *mut RustTableModelHandler))
+        let handler = &*(ui_handler as *mut RustTableModelHandler);
+        if let TableEdit::TextEdited(ref input) = edit {
+            let decoded = handler
+                .codecs
+                .borrow()
+                .get(&column)
+                .map(|c| c.decode(input));
+            match decoded {
+                Some(Ok(typed)) => {
+                    handler
+                        .trait_object
+                        .borrow_mut()
+                        .set_typed_cell(column, row, typed);
+                    return;
+                }
+                Some(Err(error)) => {
+                    // Have the views fetch the unchanged value again to revert the edit.
+                    libui_ffi::uiTableModelRowChanged(ui_model, row);
+                    handler.edit_rejected(CellEditRejection {
+                        column,
+                        row,
+                        input: input.clone(),
+                        error,
+                    });
+                    return;
+                }
+                None => {}
+            }
+        }
+
+        handler
             .trait_object
             .borrow_mut()
-            .set_cell(column, row, rust_value);
+            .cell_edited(column, row, edit);
     }
 }
 
@@ -185,8 +279,12 @@ extern "C" fn c_set_cell_value(
 struct RustTableModelHandler {
     ui_table_model_handler: uiTableModelHandler,
     trait_object: Rc<RefCell<dyn TableDataSource>>,
+    codecs: RefCell<HashMap<i32, ColumnCodec>>,
+    on_edit_rejected: RefCell<Option<EditRejectedCallback>>,
 }
 
+type EditRejectedCallback = Box<dyn FnMut(&CellEditRejection)>;
+
 impl RustTableModelHandler {
     fn new(trait_object: Rc<RefCell<dyn TableDataSource>>) -> Self {
         RustTableModelHandler {
@@ -198,6 +296,37 @@ impl RustTableModelHandler {
                 SetCellValue: Some(c_set_cell_value),
             },
             trait_object,
+            codecs: RefCell::new(HashMap::new()),
+            on_edit_rejected: RefCell::new(None),
+        }
+    }
+
+    fn display_c_str(&self, column: i32, row: i32) -> Option<Arc<CStr>> {
+        if self.codecs.borrow().contains_key(&column) {
+            return None;
+        }
+        self.trait_object.borrow_mut().cell_c_str(column, row)
+    }
+
+    fn display_value(&self, column: i32, row: i32) -> TableValue {
+        match self.codecs.borrow().get(&column) {
+            Some(codec) => {
+                let typed = self.trait_object.borrow_mut().typed_cell(column, row);
+                TableValue::String(codec.format(&typed))
+            }
+            None => self.trait_object.borrow_mut().cell(column, row),
+        }
+    }
+
+    fn edit_rejected(&self, rejection: CellEditRejection) {
+        // Take the callback out while it runs so it may replace itself.
+        let callback = self.on_edit_rejected.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(&rejection);
+            let mut slot = self.on_edit_rejected.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
         }
     }
 }
@@ -205,7 +334,7 @@ impl RustTableModelHandler {
 /// The view model for a `Table` control.
 pub struct TableModel {
     ui_table_model: *mut libui_ffi::uiTableModel,
-    _model_handler: Box<RustTableModelHandler>,
+    model_handler: Box<RustTableModelHandler>,
 }
 
 impl TableModel {
@@ -217,7 +346,7 @@ impl TableModel {
                 ui_table_model: libui_ffi::uiNewTableModel(
                     ptr as *mut libui_ffi::uiTableModelHandler,
                 ),
-                _model_handler: handler, // We store the object to bind its lifetime to ours.
+                model_handler: handler, // We store the object to bind its lifetime to ours.
             }
         }
     }
@@ -251,6 +380,43 @@ impl TableModel {
             libui_ffi::uiTableModelRowDeleted(self.ui_table_model, old_row);
         }
     }
+
+    /// Returns the data source this model delegates to.
+    pub fn data_source(&self) -> Rc<RefCell<dyn TableDataSource>> {
+        self.model_handler.trait_object.clone()
+    }
+
+    /// Returns the value of a cell as presented to views, that is formatted
+    /// by the column's [`ColumnCodec`] if one is attached.
+    pub fn display_value(&self, column: i32, row: i32) -> TableValue {
+        self.model_handler.display_value(column, row)
+    }
+
+    /// Attaches a codec to a model column, turning its text cells into typed, validated values.
+    ///
+    /// The column is reported to views as [`TableValueType::String`]. Cell contents are read with
+    /// [`TableDataSource::typed_cell()`] and formatted by the codec. Edits are parsed and validated
+    /// before being passed to [`TableDataSource::set_typed_cell()`]; rejected edits are reverted
+    /// and reported to the [`TableModel::on_cell_edit_rejected()`] callback.
+    /// Replaces any codec previously attached to the column.
+    pub fn set_column_codec(&mut self, column: i32, codec: ColumnCodec) {
+        self.model_handler.codecs.borrow_mut().insert(column, codec);
+    }
+
+    /// Detaches the codec from a model column, restoring untyped editing.
+    pub fn remove_column_codec(&mut self, column: i32) {
+        self.model_handler.codecs.borrow_mut().remove(&column);
+    }
+
+    /// Registers a callback for when an edit was rejected by a column codec.
+    ///
+    /// Note: Only one callback can be registered at a time.
+    pub fn on_cell_edit_rejected<F>(&mut self, callback: F)
+    where
+        F: FnMut(&CellEditRejection) + 'static,
+    {
+        *self.model_handler.on_edit_rejected.borrow_mut() = Some(Box::new(callback));
+    }
 }
 
 impl Drop for TableModel {
@@ -358,6 +524,30 @@ impl Default for TextColumnParameters {
     }
 }
 
+/// Describes a column appended to a `Table`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableColumn {
+    /// The column header.
+    pub title: String,
+    /// The model columns whose data the column displays, in display order.
+    /// Checkbox-and-text columns list the checkbox column first.
+    pub model_columns: Vec<i32>,
+}
+
+/// What libui does not remember about a `Table` for us.
+///
+/// Tables wrapped with `from_raw()` get an entry without a model when a column is
+/// first appended.
+#[derive(Default)]
+struct TableInfo {
+    model: Option<Rc<RefCell<TableModel>>>,
+    columns: Vec<TableColumn>,
+}
+
+thread_local! {
+    static TABLES: RefCell<HashMap<usize, TableInfo>> = RefCell::new(HashMap::new())
+}
+
 define_control! {
     /// A tabular control that can be used to display and edit data.
     /// The table itself does not store any data but is a "View" on
@@ -390,18 +580,87 @@ impl Table {
                 Model: params.model.borrow().ui_table_model,
                 RowBackgroundColorModelColumn: params.row_background_color_column,
             };
-            // TODO: keep the model alive via the shared ptr. This requires storing it tho.
-            // Because i am lazy, i leak the model for now.
-            mem::forget(params.model);
-            Table {
+            let table = Table {
                 // The parameter struct is not stored. we can safely provide
                 // a raw pointer and let the struct go out of scope. Only the
                 // uiTableModel inside must be kept alive.
                 uiTable: libui_ffi::uiNewTable(&mut ui_params as *mut uiTableParams),
-            }
+            };
+            // The registry keeps the model alive, as tables are never freed.
+            TABLES.with(|tables| {
+                tables.borrow_mut().insert(
+                    table.uiTable as usize,
+                    TableInfo {
+                        model: Some(params.model),
+                        columns: Vec::new(),
+                    },
+                )
+            });
+            table
         }
     }
 
+    fn with_info<R, F: FnOnce(&mut TableInfo) -> R>(&self, f: F) -> R {
+        TABLES.with(|tables| {
+            let mut tables = tables.borrow_mut();
+            f(tables.entry(self.uiTable as usize).or_default())
+        })
+    }
+
+    fn push_column(&mut self, title: &str, model_columns: Vec<i32>) {
+        let column = TableColumn {
+            title: title.to_string(),
+            model_columns,
+        };
+        self.with_info(|info| info.columns.push(column));
+    }
+
+    /// Returns the model displayed by this table, or `None` if the table was not
+    /// created by [`Table::new()`].
+    pub fn model(&self) -> Option<Rc<RefCell<TableModel>>> {
+        self.with_info(|info| info.model.clone())
+    }
+
+    /// Returns the columns appended to this table, in display order.
+    pub fn columns(&self) -> Vec<TableColumn> {
+        self.with_info(|info| info.columns.clone())
+    }
+
+    /// Returns one [`ExportColumn`] per model column shown by the appended columns,
+    /// in display order and titled like the column showing it.
+    ///
+    /// Filter the result to export a selection of columns with [`TableExport`].
+    pub fn export_columns(&self) -> Vec<ExportColumn> {
+        self.columns()
+            .into_iter()
+            .flat_map(|c| {
+                let title = c.title;
+                c.model_columns
+                    .into_iter()
+                    .map(move |m| ExportColumn::new(&title, m))
+            })
+            .collect()
+    }
+
+    /// Writes the rows of the table in the given format.
+    ///
+    /// All appended columns are exported, with their titles as headers and cells formatted by
+    /// [`ColumnCodec`]s. Rows are written in model order: libui neither sorts nor filters rows
+    /// itself, so clicking a header only reorders the rows if your data source does. Fails with
+    /// [`io::ErrorKind::InvalidInput`] if the table was not created by [`Table::new()`].
+    pub fn export<W: Write>(&self, format: ExportFormat, writer: &mut W) -> io::Result<()> {
+        let model = self.model().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the table was not created by Table::new()",
+            )
+        })?;
+        let model = model.borrow();
+        TableExport::new(format)
+            .columns(self.export_columns())
+            .write_model(&model, writer)
+    }
+
     /// Appends a text column to the table.
     ///
     /// * `title`               - The columns header.
@@ -426,6 +685,7 @@ impl Table {
                 std::ptr::null_mut(), // TODO: support text params
             );
         }
+        self.push_column(title, vec![text_model_column]);
     }
 
     /// Appends a text column to the table, allowing for colored text using the [`TextColumnParameters`] argument.
@@ -457,6 +717,7 @@ impl Table {
                 &mut c_params as *mut libui_ffi::uiTableTextColumnOptionalParams,
             );
         }
+        self.push_column(title, vec![text_model_column]);
     }
 
     // TODO: uiTableAppendImageColumn
@@ -486,6 +747,7 @@ impl Table {
                 state_model_column,
             );
         }
+        self.push_column(title, vec![check_model_column]);
     }
 
     /// Appends a column to the table containing a checkbox and text.
@@ -519,6 +781,7 @@ impl Table {
                 std::ptr::null_mut(),
             );
         }
+        self.push_column(title, vec![check_model_column, text_model_column]);
     }
 
     /// Appends a column to the table containing a progress bar.
@@ -531,14 +794,14 @@ impl Table {
             let c_title = CString::new(title.as_bytes().to_vec()).unwrap();
             libui_ffi::uiTableAppendProgressBarColumn(self.uiTable, c_title.as_ptr(), model_column);
         }
+        self.push_column(title, vec![model_column]);
     }
 
     /// Appends a column to the table containing a button.
     ///
     /// * `title`               - The columns header.
     /// * `btn_model_column`    - Index to the model column with the button text ([`TableValue::String`]).
-    ///                           Clicks are signaled to the [`TableDataSource`] by calling [`TableDataSource::set_cell()`]
-    ///                           with a `TableValue::Int()`. Because no other reason for the call exists, checking the type is not strictly necessary.
+    ///                           Clicks are signaled to the [`TableDataSource`] by calling [`TableDataSource::button_clicked()`].
     /// * `state_model_column`  - Index to the model column with the state data ([`TableValue::Int`]). An entry with value != `0`
     ///                           means the button shall be clickable. Alternatively use [`Table::COLUMN_EDITABLE`] or [`Table::COLUMN_READONLY`]
     ///                           for this parameter to make all rows either state.
@@ -557,6 +820,7 @@ impl Table {
                 state_model_column,
             );
         }
+        self.push_column(title, vec![btn_model_column]);
     }
 
     /// Returns whether or not the table header is visible.
@@ -0,0 +1,711 @@
+//! Showing hierarchical data in a `Table`.
+
+use super::{Table, TableDataSource, TableModel, TableParameters, TableValue, TableValueType};
+use controls::Control;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+/// Identifies a node by the child indices leading to it from the (hidden) root.
+///
+/// The empty path is the root, `[2, 0]` the first child of the third top level node.
+pub type TreePath = Vec<usize>;
+
+/// Provides the nodes of a tree shown by a [`TreeView`].
+///
+/// Nodes are addressed by their [`TreePath`]. The root node itself is not displayed.
+///
+/// Besides the node text, a tree can provide additional columns. Tree column `n` is
+/// model column `n + 1` of the view's [`TableModel`]; model column [`TreeView::TEXT_COLUMN`]
+/// holds the indented node text.
+pub trait TreeModel {
+    /// Returns the number of children of the node at `path`.
+    fn num_children(&mut self, path: &[usize]) -> usize;
+    /// Returns the text of the node at `path`.
+    fn text(&mut self, path: &[usize]) -> String;
+
+    /// Returns the number of additional columns.
+    fn num_columns(&mut self) -> i32 {
+        0
+    }
+
+    /// Returns the type of an additional column.
+    fn column_type(&mut self, _column: i32) -> TableValueType {
+        TableValueType::String
+    }
+
+    /// Returns the value of an additional column for the node at `path`.
+    fn cell(&mut self, _path: &[usize], _column: i32) -> TableValue {
+        TableValue::String(String::new())
+    }
+
+    /// Stores an edit of an additional column for the node at `path`.
+    fn set_cell(&mut self, _path: &[usize], _column: i32, _value: TableValue) {}
+}
+
+/// A simple tree of text nodes, e.g. for static menus or parsed documents.
+///
+/// The node a `TreeView` is created with is the hidden root; its children are shown.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TreeNode {
+    pub text: String,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    pub fn new(text: &str) -> TreeNode {
+        TreeNode {
+            text: text.to_string(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Appends a child node.
+    pub fn child(mut self, child: TreeNode) -> TreeNode {
+        self.children.push(child);
+        self
+    }
+
+    /// Returns the node at `path`, if there is one.
+    pub fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        path.iter()
+            .try_fold(self, |node, &index| node.children.get(index))
+    }
+
+    /// Returns the node at `path` for modification, if there is one.
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        path.iter()
+            .try_fold(self, |node, &index| node.children.get_mut(index))
+    }
+}
+
+impl TreeModel for TreeNode {
+    fn num_children(&mut self, path: &[usize]) -> usize {
+        self.node(path).map_or(0, |n| n.children.len())
+    }
+
+    fn text(&mut self, path: &[usize]) -> String {
+        self.node(path).map(|n| n.text.clone()).unwrap_or_default()
+    }
+}
+
+/// The strings used to draw the tree structure in front of the node text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeGlyphs {
+    /// Prefixed once per nesting level.
+    pub indent: String,
+    /// Marks a node whose children are shown.
+    pub expanded: String,
+    /// Marks a node whose children are hidden.
+    pub collapsed: String,
+    /// Marks a node without children.
+    pub leaf: String,
+}
+
+impl Default for TreeGlyphs {
+    fn default() -> Self {
+        TreeGlyphs {
+            indent: "    ".to_string(),
+            expanded: "\u{25be}".to_string(),
+            collapsed: "\u{25b8}".to_string(),
+            leaf: "  ".to_string(),
+        }
+    }
+}
+
+const TEXT_COLUMN: i32 = 0;
+
+/// A node currently shown as a table row.
+#[derive(Clone, Debug)]
+struct VisibleNode {
+    path: TreePath,
+    has_children: bool,
+}
+
+/// How the visible rows changed, for notifying the `TableModel`.
+#[derive(Clone, Debug, PartialEq)]
+enum RowChange {
+    Changed(i32),
+    Inserted(i32, i32),
+    Deleted(i32, i32),
+}
+
+impl RowChange {
+    fn notify(&self, model: &TableModel) {
+        match *self {
+            RowChange::Changed(row) => model.notify_row_changed(row),
+            RowChange::Inserted(start, count) => {
+                for row in start..start + count {
+                    model.notify_row_inserted(row);
+                }
+            }
+            RowChange::Deleted(start, count) => {
+                for row in (start..start + count).rev() {
+                    model.notify_row_deleted(row);
+                }
+            }
+        }
+    }
+}
+
+/// Adapts a [`TreeModel`] to a flat [`TableDataSource`] of its visible nodes.
+///
+/// Rows are the nodes whose ancestors are all expanded, in depth first order.
+pub struct TreeDataSource<M: TreeModel> {
+    tree: M,
+    glyphs: TreeGlyphs,
+    expanded: BTreeSet<TreePath>,
+    rows: Vec<VisibleNode>,
+}
+
+impl<M: TreeModel> TreeDataSource<M> {
+    /// Creates a data source showing the top level nodes of `tree`, all collapsed.
+    pub fn new(tree: M) -> TreeDataSource<M> {
+        let mut source = TreeDataSource {
+            tree,
+            glyphs: TreeGlyphs::default(),
+            expanded: BTreeSet::new(),
+            rows: Vec::new(),
+        };
+        source.rows = source.visible_descendants(&[]);
+        source
+    }
+
+    /// Returns the tree.
+    pub fn tree(&self) -> &M {
+        &self.tree
+    }
+
+    /// Returns the tree for modification.
+    ///
+    /// Call [`TreeView::refresh()`] when done changing the tree structure.
+    pub fn tree_mut(&mut self) -> &mut M {
+        &mut self.tree
+    }
+
+    /// Returns the path of the node shown in `row`.
+    pub fn path(&self, row: i32) -> Option<&[usize]> {
+        if row < 0 {
+            return None;
+        }
+        self.rows.get(row as usize).map(|n| n.path.as_slice())
+    }
+
+    /// Returns the row showing the node at `path`, or `None` if the node is not visible.
+    pub fn row(&self, path: &[usize]) -> Option<i32> {
+        // Depth first order is the lexicographic order of paths.
+        self.rows
+            .binary_search_by(|n| n.path.as_slice().cmp(path))
+            .ok()
+            .map(|row| row as i32)
+    }
+
+    /// Returns whether the node at `path` is expanded.
+    ///
+    /// Expanded nodes only show their children if all their ancestors are expanded, too.
+    pub fn is_expanded(&self, path: &[usize]) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// Returns the paths of all expanded nodes, in depth first order.
+    pub fn expanded_paths(&self) -> Vec<TreePath> {
+        self.expanded.iter().cloned().collect()
+    }
+
+    /// Returns the node text as displayed, prefixed by indentation and a glyph.
+    pub fn display_text(&mut self, row: i32) -> String {
+        let node = &self.rows[row as usize];
+        let glyph = if !node.has_children {
+            &self.glyphs.leaf
+        } else if self.expanded.contains(&node.path) {
+            &self.glyphs.expanded
+        } else {
+            &self.glyphs.collapsed
+        };
+        format!(
+            "{}{} {}",
+            self.glyphs.indent.repeat(node.path.len() - 1),
+            glyph,
+            self.tree.text(&node.path)
+        )
+    }
+
+    fn visible_descendants(&mut self, parent: &[usize]) -> Vec<VisibleNode> {
+        let mut nodes = Vec::new();
+        collect_visible(&mut self.tree, &self.expanded, parent, &mut nodes);
+        nodes
+    }
+
+    fn expand(&mut self, path: &[usize]) -> Vec<RowChange> {
+        if !self.expanded.insert(path.to_vec()) {
+            return Vec::new();
+        }
+        let row = match self.row(path) {
+            Some(row) => row,
+            None => return Vec::new(),
+        };
+        let children = self.visible_descendants(path);
+        let count = children.len() as i32;
+        let at = row as usize + 1;
+        self.rows.splice(at..at, children);
+        vec![RowChange::Changed(row), RowChange::Inserted(row + 1, count)]
+    }
+
+    fn collapse(&mut self, path: &[usize]) -> Vec<RowChange> {
+        if !self.expanded.remove(path) {
+            return Vec::new();
+        }
+        let row = match self.row(path) {
+            Some(row) => row,
+            None => return Vec::new(),
+        };
+        let start = row as usize + 1;
+        let count = self.rows[start..]
+            .iter()
+            .take_while(|n| n.path.starts_with(path))
+            .count();
+        self.rows.drain(start..start + count);
+        vec![
+            RowChange::Deleted(row + 1, count as i32),
+            RowChange::Changed(row),
+        ]
+    }
+
+    /// Rebuilds the visible rows, forgetting the expansion state of removed nodes.
+    fn refresh(&mut self) -> Vec<RowChange> {
+        let tree = &mut self.tree;
+        self.expanded.retain(|path| path_exists(tree, path));
+
+        let old_count = self.rows.len() as i32;
+        self.rows = self.visible_descendants(&[]);
+        let new_count = self.rows.len() as i32;
+
+        let mut changes: Vec<RowChange> = (0..old_count.min(new_count))
+            .map(RowChange::Changed)
+            .collect();
+        if new_count > old_count {
+            changes.push(RowChange::Inserted(old_count, new_count - old_count));
+        } else if new_count < old_count {
+            changes.push(RowChange::Deleted(new_count, old_count - new_count));
+        }
+        changes
+    }
+}
+
+fn collect_visible<M: TreeModel>(
+    tree: &mut M,
+    expanded: &BTreeSet<TreePath>,
+    parent: &[usize],
+    nodes: &mut Vec<VisibleNode>,
+) {
+    for index in 0..tree.num_children(parent) {
+        let mut path = parent.to_vec();
+        path.push(index);
+        let has_children = tree.num_children(&path) > 0;
+        let show_children = has_children && expanded.contains(&path);
+        nodes.push(VisibleNode {
+            path: path.clone(),
+            has_children,
+        });
+        if show_children {
+            collect_visible(tree, expanded, &path, nodes);
+        }
+    }
+}
+
+fn path_exists<M: TreeModel>(tree: &mut M, path: &[usize]) -> bool {
+    (0..path.len()).all(|depth| path[depth] < tree.num_children(&path[..depth]))
+}
+
+impl<M: TreeModel> TableDataSource for TreeDataSource<M> {
+    fn num_columns(&mut self) -> i32 {
+        1 + self.tree.num_columns()
+    }
+
+    fn num_rows(&mut self) -> i32 {
+        self.rows.len() as i32
+    }
+
+    fn column_type(&mut self, column: i32) -> TableValueType {
+        if column == TEXT_COLUMN {
+            TableValueType::String
+        } else {
+            self.tree.column_type(column - 1)
+        }
+    }
+
+    fn cell(&mut self, column: i32, row: i32) -> TableValue {
+        if column == TEXT_COLUMN {
+            TableValue::String(self.display_text(row))
+        } else {
+            let path = self.rows[row as usize].path.clone();
+            self.tree.cell(&path, column - 1)
+        }
+    }
+
+    fn set_cell(&mut self, column: i32, row: i32, value: TableValue) {
+        if column != TEXT_COLUMN {
+            let path = self.rows[row as usize].path.clone();
+            self.tree.set_cell(&path, column - 1, value);
+        }
+    }
+}
+
+/// The row event that expands or collapses a node in a [`TreeView`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExpandTrigger {
+    Click,
+    DoubleClick,
+    /// Nodes are only expanded and collapsed programmatically.
+    Manual,
+}
+
+type NodeCallback<M> = Box<dyn FnMut(&mut TreeView<M>, &[usize])>;
+
+struct TreeShared<M: TreeModel + 'static> {
+    source: Rc<RefCell<TreeDataSource<M>>>,
+    model: Rc<RefCell<TableModel>>,
+    trigger: Cell<ExpandTrigger>,
+    on_clicked: RefCell<Option<NodeCallback<M>>>,
+    on_double_clicked: RefCell<Option<NodeCallback<M>>>,
+}
+
+impl<M: TreeModel + 'static> TreeShared<M> {
+    fn apply(&self, changes: Vec<RowChange>) {
+        let model = self.model.borrow();
+        for change in changes {
+            change.notify(&model);
+        }
+    }
+}
+
+/// A hierarchical view of a [`TreeModel`], built on a [`Table`].
+///
+/// The first table column shows the node texts, indented by depth and prefixed by
+/// expand/collapse glyphs. Nodes are toggled by double clicking their row, see
+/// [`TreeView::set_expand_trigger()`]. More columns can be appended through
+/// [`TreeView::table_mut()`].
+///
+/// Rows change as nodes are expanded and collapsed, so use tree paths instead of row
+/// numbers, e.g. [`TreeView::selected_paths()`]. After changing the tree structure through
+/// [`TreeDataSource::tree_mut()`], call [`TreeView::refresh()`].
+///
+/// The view registers the table's `on_row_clicked()` and `on_row_double_clicked()`
+/// callbacks; use [`TreeView::on_node_clicked()`] and [`TreeView::on_node_double_clicked()`]
+/// instead.
+pub struct TreeView<M: TreeModel + 'static> {
+    table: Table,
+    shared: Rc<TreeShared<M>>,
+}
+
+impl<M: TreeModel + 'static> Clone for TreeView<M> {
+    fn clone(&self) -> Self {
+        TreeView {
+            table: self.table.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<M: TreeModel + 'static> TreeView<M> {
+    /// The model column holding the displayed node text.
+    pub const TEXT_COLUMN: i32 = TEXT_COLUMN;
+
+    /// Creates a view on `tree`, showing its top level nodes under the column header `title`.
+    pub fn new(tree: M, title: &str) -> TreeView<M> {
+        let source = Rc::new(RefCell::new(TreeDataSource::new(tree)));
+        let model = Rc::new(RefCell::new(TableModel::new(source.clone())));
+        let mut table = Table::new(TableParameters::new(model.clone()));
+        table.append_text_column(title, Self::TEXT_COLUMN, Table::COLUMN_READONLY);
+
+        let shared = Rc::new(TreeShared {
+            source,
+            model,
+            trigger: Cell::new(ExpandTrigger::DoubleClick),
+            on_clicked: RefCell::new(None),
+            on_double_clicked: RefCell::new(None),
+        });
+
+        let clicked = shared.clone();
+        table.on_row_clicked(move |table, row| {
+            Self::row_event(table, &clicked, row, ExpandTrigger::Click, |s| {
+                &s.on_clicked
+            })
+        });
+        let double_clicked = shared.clone();
+        table.on_row_double_clicked(move |table, row| {
+            Self::row_event(
+                table,
+                &double_clicked,
+                row,
+                ExpandTrigger::DoubleClick,
+                |s| &s.on_double_clicked,
+            )
+        });
+
+        TreeView { table, shared }
+    }
+
+    fn row_event<F>(
+        table: &Table,
+        shared: &Rc<TreeShared<M>>,
+        row: i32,
+        event: ExpandTrigger,
+        slot: F,
+    ) where
+        F: Fn(&TreeShared<M>) -> &RefCell<Option<NodeCallback<M>>>,
+    {
+        let path = match shared.source.borrow().path(row) {
+            Some(path) => path.to_vec(),
+            None => return,
+        };
+        let mut view = TreeView {
+            table: table.clone(),
+            shared: shared.clone(),
+        };
+        if shared.trigger.get() == event {
+            view.toggle(&path);
+        }
+
+        // Take the callback out while it runs, so it may replace itself.
+        let callback = slot(shared).borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(&mut view, &path);
+            let mut slot = slot(shared).borrow_mut();
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+
+    /// Returns the underlying table, e.g. to query column widths.
+    pub fn table(&self) -> &Table {
+        &self.table
+    }
+
+    /// Returns the underlying table, e.g. to append columns for [`TreeModel::cell()`] data.
+    pub fn table_mut(&mut self) -> &mut Table {
+        &mut self.table
+    }
+
+    /// Returns the table model delegating to the tree.
+    pub fn model(&self) -> Rc<RefCell<TableModel>> {
+        self.shared.model.clone()
+    }
+
+    /// Returns the data source flattening the tree into rows. Use it to access the tree.
+    pub fn source(&self) -> Rc<RefCell<TreeDataSource<M>>> {
+        self.shared.source.clone()
+    }
+
+    /// Sets which row event toggles nodes. Defaults to [`ExpandTrigger::DoubleClick`].
+    pub fn set_expand_trigger(&mut self, trigger: ExpandTrigger) {
+        self.shared.trigger.set(trigger);
+    }
+
+    /// Sets the strings used to draw the tree structure.
+    pub fn set_glyphs(&mut self, glyphs: TreeGlyphs) {
+        let changes = {
+            let mut source = self.shared.source.borrow_mut();
+            source.glyphs = glyphs;
+            (0..source.rows.len() as i32)
+                .map(RowChange::Changed)
+                .collect()
+        };
+        self.shared.apply(changes);
+    }
+
+    /// Shows the children of the node at `path`.
+    pub fn expand(&mut self, path: &[usize]) {
+        let changes = self.shared.source.borrow_mut().expand(path);
+        self.shared.apply(changes);
+    }
+
+    /// Hides the children of the node at `path`.
+    pub fn collapse(&mut self, path: &[usize]) {
+        let changes = self.shared.source.borrow_mut().collapse(path);
+        self.shared.apply(changes);
+    }
+
+    /// Expands the node at `path` if it is collapsed, and collapses it otherwise.
+    pub fn toggle(&mut self, path: &[usize]) {
+        if self.is_expanded(path) {
+            self.collapse(path);
+        } else {
+            self.expand(path);
+        }
+    }
+
+    /// Returns whether the node at `path` is expanded.
+    pub fn is_expanded(&self, path: &[usize]) -> bool {
+        self.shared.source.borrow().is_expanded(path)
+    }
+
+    /// Expands all ancestors of the node at `path`, making it visible.
+    pub fn reveal(&mut self, path: &[usize]) {
+        for depth in 1..path.len() {
+            self.expand(&path[..depth]);
+        }
+    }
+
+    /// Returns the paths of all expanded nodes, e.g. to restore them with
+    /// [`TreeView::set_expanded_paths()`].
+    pub fn expanded_paths(&self) -> Vec<TreePath> {
+        self.shared.source.borrow().expanded_paths()
+    }
+
+    /// Expands exactly the nodes at the given paths, collapsing all others.
+    pub fn set_expanded_paths(&mut self, paths: &[TreePath]) {
+        let changes = {
+            let mut source = self.shared.source.borrow_mut();
+            source.expanded = paths.iter().cloned().collect();
+            source.refresh()
+        };
+        self.shared.apply(changes);
+    }
+
+    /// Returns the paths of the selected nodes.
+    pub fn selected_paths(&self) -> Vec<TreePath> {
+        let source = self.shared.source.borrow();
+        self.table
+            .selection()
+            .into_iter()
+            .filter_map(|row| source.path(row).map(|p| p.to_vec()))
+            .collect()
+    }
+
+    /// Selects the nodes at the given paths, revealing them if necessary.
+    ///
+    /// Paths of nodes that do not exist are ignored.
+    pub fn set_selected_paths(&mut self, paths: &[TreePath]) {
+        for path in paths {
+            self.reveal(path);
+        }
+        let rows: Vec<i32> = {
+            let source = self.shared.source.borrow();
+            paths.iter().filter_map(|p| source.row(p)).collect()
+        };
+        self.table.set_selection(&rows);
+    }
+
+    /// Informs the view that the text or cells of the node at `path` changed.
+    pub fn notify_node_changed(&self, path: &[usize]) {
+        let row = self.shared.source.borrow().row(path);
+        if let Some(row) = row {
+            self.shared.model.borrow().notify_row_changed(row);
+        }
+    }
+
+    /// Informs the view that nodes were added or removed, rebuilding all rows.
+    ///
+    /// Nodes keep their expansion state by path; the state of paths that no
+    /// longer exist is dropped.
+    pub fn refresh(&mut self) {
+        let changes = self.shared.source.borrow_mut().refresh();
+        self.shared.apply(changes);
+    }
+
+    /// Registers a callback for when the user single clicks a node.
+    ///
+    /// Note: Only one callback can be registered at a time.
+    pub fn on_node_clicked<F>(&mut self, callback: F)
+    where
+        F: FnMut(&mut TreeView<M>, &[usize]) + 'static,
+    {
+        *self.shared.on_clicked.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Registers a callback for when the user double clicks a node.
+    ///
+    /// Note: Only one callback can be registered at a time.
+    /// Bug: Like [`Table::on_row_double_clicked()`], this is preceded by a single click event.
+    pub fn on_node_double_clicked<F>(&mut self, callback: F)
+    where
+        F: FnMut(&mut TreeView<M>, &[usize]) + 'static,
+    {
+        *self.shared.on_double_clicked.borrow_mut() = Some(Box::new(callback));
+    }
+}
+
+impl<M: TreeModel + 'static> From<TreeView<M>> for Control {
+    fn from(view: TreeView<M>) -> Control {
+        view.table.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> TreeNode {
+        TreeNode::new("root")
+            .child(
+                TreeNode::new("src")
+                    .child(TreeNode::new("lib.rs"))
+                    .child(TreeNode::new("ui").child(TreeNode::new("mod.rs"))),
+            )
+            .child(TreeNode::new("README.md"))
+    }
+
+    fn texts(source: &mut TreeDataSource<TreeNode>) -> Vec<String> {
+        (0..source.num_rows())
+            .map(|r| source.display_text(r))
+            .collect()
+    }
+
+    #[test]
+    fn shows_top_level_collapsed() {
+        let mut source = TreeDataSource::new(tree());
+        assert_eq!(texts(&mut source), ["\u{25b8} src", "   README.md"]);
+        assert_eq!(source.path(1), Some(&[1][..]));
+        assert_eq!(source.path(2), None);
+    }
+
+    #[test]
+    fn expand_and_collapse_report_row_changes() {
+        let mut source = TreeDataSource::new(tree());
+        assert_eq!(
+            source.expand(&[0]),
+            [RowChange::Changed(0), RowChange::Inserted(1, 2)]
+        );
+        assert!(source.expand(&[0]).is_empty());
+        assert_eq!(source.row(&[1]), Some(3));
+        assert_eq!(
+            source.expand(&[0, 1]),
+            [RowChange::Changed(2), RowChange::Inserted(3, 1)]
+        );
+        assert_eq!(
+            texts(&mut source)[2..4],
+            ["    \u{25be} ui", "           mod.rs"]
+        );
+
+        assert_eq!(
+            source.collapse(&[0]),
+            [RowChange::Deleted(1, 3), RowChange::Changed(0)]
+        );
+        assert_eq!(source.num_rows(), 2);
+        // Nested expansion state is kept while hidden.
+        assert!(source.is_expanded(&[0, 1]));
+        source.expand(&[0]);
+        assert_eq!(source.num_rows(), 5);
+    }
+
+    #[test]
+    fn refresh_drops_state_of_removed_nodes() {
+        let mut source = TreeDataSource::new(tree());
+        source.expand(&[0]);
+        source.expand(&[0, 1]);
+        source.tree_mut().node_mut(&[0]).unwrap().children.pop();
+        assert_eq!(
+            source.refresh(),
+            [
+                RowChange::Changed(0),
+                RowChange::Changed(1),
+                RowChange::Changed(2),
+                RowChange::Deleted(3, 2)
+            ]
+        );
+        assert_eq!(source.expanded_paths(), vec![vec![0]]);
+        assert_eq!(source.row(&[1]), Some(2));
+    }
+}
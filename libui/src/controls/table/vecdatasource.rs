@@ -0,0 +1,213 @@
+use super::{
+    parse_csv, parse_hex_color, ExportColumn, TableDataSource, TableValue, TableValueType,
+};
+use std::io::{self, Read};
+
+/// A [`TableDataSource`] keeping all cells in memory, one `Vec` per row.
+///
+/// Edits made through a `Table` are stored as is, unless the value does not match the type
+/// of its column. Button clicks leave the cells unchanged. Call the `TableModel::notify_XXX()`
+/// methods after changing rows through `push_row()`, `insert_row()` or `remove_row()`.
+#[derive(Clone, Debug, Default)]
+pub struct VecTableDataSource {
+    headers: Vec<String>,
+    column_types: Vec<TableValueType>,
+    rows: Vec<Vec<TableValue>>,
+}
+
+impl VecTableDataSource {
+    /// Creates an empty data source with the given column headers and types.
+    ///
+    /// # Panics
+    /// Panics if the number of headers and column types differ.
+    pub fn new(headers: Vec<String>, column_types: Vec<TableValueType>) -> VecTableDataSource {
+        assert_eq!(
+            headers.len(),
+            column_types.len(),
+            "Every column needs a header and a type"
+        );
+        VecTableDataSource {
+            headers,
+            column_types,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Reads a data source from CSV data as written by [`TableExport`](super::TableExport).
+    ///
+    /// If `has_header` is set, the first record provides the column headers. Otherwise the
+    /// columns are named `Column 1`, `Column 2`, and so on. Short records are padded with
+    /// empty fields. A column becomes [`TableValueType::Int`] if all its fields are integers,
+    /// [`TableValueType::Color`] if all are `#rrggbb[aa]` colors and [`TableValueType::String`]
+    /// otherwise.
+    pub fn from_csv<R: Read>(mut reader: R, has_header: bool) -> io::Result<VecTableDataSource> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let mut records = parse_csv(&text)?;
+
+        let width = records.iter().map(|r| r.len()).max().unwrap_or(0);
+        for record in &mut records {
+            record.resize(width, String::new());
+        }
+
+        let headers = if has_header && !records.is_empty() {
+            records.remove(0)
+        } else {
+            (1..=width).map(|c| format!("Column {}", c)).collect()
+        };
+
+        let column_types: Vec<TableValueType> = (0..width)
+            .map(|c| {
+                let mut fields = records.iter().map(|r| r[c].as_str());
+                if records.is_empty() {
+                    TableValueType::String
+                } else if fields.clone().all(|f| f.parse::<i32>().is_ok()) {
+                    TableValueType::Int
+                } else if fields.all(|f| parse_hex_color(f).is_some()) {
+                    TableValueType::Color
+                } else {
+                    TableValueType::String
+                }
+            })
+            .collect();
+
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                record
+                    .into_iter()
+                    .zip(&column_types)
+                    .map(|(field, t)| match *t {
+                        TableValueType::Int => TableValue::Int(field.parse().unwrap()),
+                        TableValueType::Color => parse_hex_color(&field).unwrap(),
+                        _ => TableValue::String(field),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(VecTableDataSource {
+            headers,
+            column_types,
+            rows,
+        })
+    }
+
+    /// Returns the column headers.
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Returns the rows.
+    pub fn rows(&self) -> &[Vec<TableValue>] {
+        &self.rows
+    }
+
+    /// Returns one [`ExportColumn`] per column, named by its header.
+    pub fn export_columns(&self) -> Vec<ExportColumn> {
+        self.headers
+            .iter()
+            .enumerate()
+            .map(|(i, h)| ExportColumn::new(h, i as i32))
+            .collect()
+    }
+
+    /// Appends a row.
+    ///
+    /// # Panics
+    /// Panics if the row does not have a value for every column.
+    pub fn push_row(&mut self, row: Vec<TableValue>) {
+        let index = self.rows.len();
+        self.insert_row(index, row);
+    }
+
+    /// Inserts a row before the row at `index`.
+    ///
+    /// # Panics
+    /// Panics if the row does not have a value for every column, or if `index` is out of bounds.
+    pub fn insert_row(&mut self, index: usize, row: Vec<TableValue>) {
+        assert_eq!(
+            row.len(),
+            self.headers.len(),
+            "Row must have a value for every column"
+        );
+        self.rows.insert(index, row);
+    }
+
+    /// Removes and returns the row at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove_row(&mut self, index: usize) -> Vec<TableValue> {
+        self.rows.remove(index)
+    }
+}
+
+impl TableDataSource for VecTableDataSource {
+    fn num_columns(&mut self) -> i32 {
+        self.headers.len() as i32
+    }
+
+    fn num_rows(&mut self) -> i32 {
+        self.rows.len() as i32
+    }
+
+    fn column_type(&mut self, column: i32) -> TableValueType {
+        self.column_types[column as usize]
+    }
+
+    fn cell(&mut self, column: i32, row: i32) -> TableValue {
+        self.rows[row as usize][column as usize].clone()
+    }
+
+    fn set_cell(&mut self, column: i32, row: i32, value: TableValue) {
+        if matches!(
+            (&value, self.column_types[column as usize]),
+            (TableValue::Int(_), TableValueType::Int)
+                | (TableValue::String(_), TableValueType::String)
+                | (TableValue::Color(_), TableValueType::Color)
+        ) {
+            self.rows[row as usize][column as usize] = value;
+        }
+    }
+
+    fn button_clicked(&mut self, _column: i32, _row: i32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TableEdit;
+    use super::*;
+
+    #[test]
+    fn from_csv_infers_column_types() {
+        let csv = "Name,Stock,Color\r\nApples,73,#cc0000\r\n\"Limes, green\",12,#32cd3280\r\n";
+        let mut source = VecTableDataSource::from_csv(csv.as_bytes(), true).unwrap();
+        assert_eq!(source.headers(), ["Name", "Stock", "Color"]);
+        assert_eq!(source.num_rows(), 2);
+        assert_eq!(source.column_type(0), TableValueType::String);
+        assert_eq!(source.column_type(1), TableValueType::Int);
+        assert_eq!(source.column_type(2), TableValueType::Color);
+        assert_eq!(source.cell(0, 1), TableValue::String("Limes, green".into()));
+        assert_eq!(source.cell(1, 0), TableValue::Int(73));
+    }
+
+    #[test]
+    fn from_csv_pads_short_records() {
+        let mut source = VecTableDataSource::from_csv("1,2\n3\n".as_bytes(), false).unwrap();
+        assert_eq!(source.headers(), ["Column 1", "Column 2"]);
+        assert_eq!(source.column_type(1), TableValueType::String);
+        assert_eq!(source.cell(1, 1), TableValue::String(String::new()));
+    }
+
+    #[test]
+    fn keeps_cells_of_the_column_type() {
+        let mut source = VecTableDataSource::from_csv("Apples,73\n".as_bytes(), false).unwrap();
+        source.cell_edited(0, 0, TableEdit::ButtonClicked);
+        assert_eq!(source.cell(0, 0), TableValue::String("Apples".into()));
+        source.set_cell(1, 0, TableValue::String("many".into()));
+        assert_eq!(source.cell(1, 0), TableValue::Int(73));
+        source.cell_edited(1, 0, TableEdit::CheckboxToggled(true));
+        assert_eq!(source.cell(1, 0), TableValue::Int(1));
+    }
+}
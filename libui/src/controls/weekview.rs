@@ -0,0 +1,1044 @@
+//! A week timeline for scheduling events.
+
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, Date, FontDescription, Key,
+    Modifiers, MouseButton, MouseEvent, Weekday,
+};
+use draw::{
+    Brush, Canvas, Color, PathBuilder, PathData, Rect, StrokeParams, TextAlign, TextLayoutParams,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type SelectionHandler = Box<dyn FnMut(&WeekView, Option<u64>)>;
+type EventHandler = Box<dyn FnMut(&WeekView, &TimelineEvent)>;
+type SlotHandler = Box<dyn FnMut(&WeekView, Date, u32)>;
+type WeekChangedHandler = Box<dyn FnMut(&WeekView, Date)>;
+
+/// The minutes of a day.
+const DAY: u32 = 24 * 60;
+
+/// The distance in points from the bottom edge of an event at which dragging resizes it.
+const RESIZE_HANDLE: f64 = 5.0;
+
+/// An appointment shown in a [`WeekView`], within a single day.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimelineEvent {
+    /// Identifies the event, chosen by the application.
+    pub id: u64,
+    pub title: String,
+    pub date: Date,
+    /// The start in minutes after midnight.
+    pub start: u32,
+    /// The end in minutes after midnight, after the start and up to 1440.
+    pub end: u32,
+    pub color: Color,
+}
+
+impl TimelineEvent {
+    /// Creates an event, keeping it within the day and at least a minute long.
+    pub fn new(id: u64, title: &str, date: Date, start: u32, end: u32) -> TimelineEvent {
+        TimelineEvent {
+            id,
+            title: title.to_string(),
+            date,
+            start,
+            end,
+            color: Color::rgb(0.3, 0.55, 0.9),
+        }
+        .clamped()
+    }
+
+    /// Moves the start and end into the day, so that the start comes first.
+    fn clamped(mut self) -> Self {
+        self.start = self.start.min(DAY - 1);
+        self.end = self.end.clamp(self.start + 1, DAY);
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Returns the length in minutes.
+    pub fn duration(&self) -> u32 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// The font and colors of a [`WeekView`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WeekViewStyle {
+    pub font: FontDescription,
+    pub background: Color,
+    pub text_color: Color,
+    /// The color of the hour labels.
+    pub time_color: Color,
+    pub grid_color: Color,
+    pub weekend_background: Color,
+    pub today_background: Color,
+    /// The color of the date of today in the header.
+    pub today_color: Color,
+    pub event_text: Color,
+    /// The frame of the selected event.
+    pub selection_color: Color,
+    pub header_height: f64,
+    /// The width of the column with the hour labels.
+    pub time_width: f64,
+}
+
+impl Default for WeekViewStyle {
+    fn default() -> WeekViewStyle {
+        WeekViewStyle {
+            font: FontDescription {
+                size: 11.0,
+                ..FontDescription::default()
+            },
+            background: Color::WHITE,
+            text_color: Color::rgb(0.13, 0.13, 0.13),
+            time_color: Color::rgb(0.5, 0.5, 0.5),
+            grid_color: Color::rgb(0.88, 0.88, 0.88),
+            weekend_background: Color::rgb(0.97, 0.97, 0.97),
+            today_background: Color::rgb(0.95, 0.97, 1.0),
+            today_color: Color::rgb(0.2, 0.45, 0.85),
+            event_text: Color::WHITE,
+            selection_color: Color::rgb(0.1, 0.1, 0.1),
+            header_height: 36.0,
+            time_width: 48.0,
+        }
+    }
+}
+
+/// What lies under a point of the view.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Hit {
+    /// An event, by index, and whether the point is on its resize handle.
+    Event(usize, bool),
+    /// An empty time of a day, in minutes after midnight.
+    Slot(Date, u32),
+    Nothing,
+}
+
+/// A drag of an event with the left button held down.
+struct Drag {
+    id: u64,
+    /// The event before the drag, restored if the drag is broken.
+    original: TimelineEvent,
+    /// Whether the end of the event is dragged, rather than the whole event.
+    resize: bool,
+    /// The minutes from the start of the event to the point it was grabbed at.
+    grab: i64,
+    moved: bool,
+}
+
+fn format_time(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+struct WeekState {
+    /// The first day shown.
+    week: Date,
+    first_weekday: Weekday,
+    /// The first and last hour shown.
+    hours: (u32, u32),
+    /// The step in minutes to which dragged and moved events snap.
+    snap: u32,
+    today: Option<Date>,
+    events: Vec<TimelineEvent>,
+    selected: Option<u64>,
+    drag: Option<Drag>,
+    style: WeekViewStyle,
+    /// The size of the area, as of the last draw.
+    size: (f64, f64),
+    on_selection_changed: Option<SelectionHandler>,
+    on_event_changed: Option<EventHandler>,
+    on_event_activated: Option<EventHandler>,
+    on_slot_activated: Option<SlotHandler>,
+    on_week_changed: Option<WeekChangedHandler>,
+}
+
+impl WeekState {
+    fn new(today: Date) -> WeekState {
+        WeekState {
+            week: today.start_of_week(Weekday::Monday),
+            first_weekday: Weekday::Monday,
+            hours: (6, 22),
+            snap: 15,
+            today: Some(today),
+            events: Vec::new(),
+            selected: None,
+            drag: None,
+            style: WeekViewStyle::default(),
+            size: (700.0, 500.0),
+            on_selection_changed: None,
+            on_event_changed: None,
+            on_event_activated: None,
+            on_slot_activated: None,
+            on_week_changed: None,
+        }
+    }
+
+    /// Returns the rectangle of the hours of the days, below the header.
+    fn grid(&self) -> Rect {
+        let (time_width, header) = (self.style.time_width, self.style.header_height);
+        (
+            time_width,
+            header,
+            self.size.0 - time_width,
+            self.size.1 - header,
+        )
+    }
+
+    fn day_width(&self) -> f64 {
+        ((self.size.0 - self.style.time_width) / 7.0).max(1.0)
+    }
+
+    /// Returns the height of a minute.
+    fn scale(&self) -> f64 {
+        let minutes = (self.hours.1 - self.hours.0) * 60;
+        ((self.size.1 - self.style.header_height) / minutes as f64).max(0.01)
+    }
+
+    fn y_of(&self, minutes: u32) -> f64 {
+        let first = self.hours.0 * 60;
+        self.style.header_height + (minutes as f64 - first as f64) * self.scale()
+    }
+
+    /// Returns the minutes after midnight at a height, not snapped.
+    fn minutes_at(&self, y: f64) -> i64 {
+        let first = self.hours.0 as f64 * 60.0;
+        (first + (y - self.style.header_height) / self.scale()).round() as i64
+    }
+
+    /// Returns the day of the week at a horizontal position, clamped to the week.
+    fn day_at(&self, x: f64) -> Date {
+        let column = ((x - self.style.time_width) / self.day_width()).floor();
+        self.week.add_days(column.clamp(0.0, 6.0) as i64)
+    }
+
+    fn snapped(&self, minutes: i64) -> i64 {
+        let snap = self.snap.max(1) as i64;
+        (minutes as f64 / snap as f64).round() as i64 * snap
+    }
+
+    /// Returns an event moved by a number of days and minutes within its day, or with its
+    /// end moved and at least one snap step long.
+    fn nudged(
+        &self,
+        event: &TimelineEvent,
+        days: i64,
+        minutes: i64,
+        resize: bool,
+    ) -> TimelineEvent {
+        let snap = self.snap.max(1) as i64;
+        let mut moved = event.clone();
+        moved.date = event.date.add_days(days);
+        if resize {
+            let end = event.end as i64 + minutes;
+            let shortest = (event.start as i64 + snap).min(DAY as i64);
+            moved.end = end.clamp(shortest, DAY as i64) as u32;
+        } else {
+            let latest = DAY.saturating_sub(event.duration()) as i64;
+            let start = (event.start as i64 + minutes).clamp(0, latest);
+            moved.start = start as u32;
+            moved.end = moved.start + event.duration();
+        }
+        moved
+    }
+
+    /// Returns the rectangles of the events of the week, by index. Overlapping events of
+    /// a day share its width in columns.
+    fn event_rects(&self) -> Vec<(usize, Rect)> {
+        let day_width = self.day_width();
+        let mut rects = Vec::new();
+        for day in 0..7 {
+            let date = self.week.add_days(day);
+            let mut events: Vec<usize> = (0..self.events.len())
+                .filter(|&i| self.events[i].date == date)
+                .collect();
+            events.sort_by_key(|&i| (self.events[i].start, self.events[i].end));
+            let x = self.style.time_width + day as f64 * day_width;
+
+            // Events are placed in the first free column of a cluster of events which
+            // overlap each other, directly or through others.
+            let mut cluster: Vec<(usize, usize)> = Vec::new();
+            let mut columns: Vec<u32> = Vec::new();
+            let mut cluster_end = 0;
+            let flush = |cluster: &mut Vec<(usize, usize)>,
+                         columns: &mut Vec<u32>,
+                         rects: &mut Vec<(usize, Rect)>| {
+                let width = day_width / columns.len().max(1) as f64;
+                for (i, column) in cluster.drain(..) {
+                    let event = &self.events[i];
+                    let top = self.y_of(event.start);
+                    let bottom = self.y_of(event.end.max(event.start + 1));
+                    let rect = (
+                        x + column as f64 * width + 1.0,
+                        top,
+                        width - 3.0,
+                        bottom - top - 1.0,
+                    );
+                    rects.push((i, rect));
+                }
+                columns.clear();
+            };
+            for i in events {
+                let event = &self.events[i];
+                if !cluster.is_empty() && event.start >= cluster_end {
+                    flush(&mut cluster, &mut columns, &mut rects);
+                }
+                let column = match columns.iter().position(|&end| end <= event.start) {
+                    Some(column) => column,
+                    None => {
+                        columns.push(0);
+                        columns.len() - 1
+                    }
+                };
+                columns[column] = event.end.max(event.start + 1);
+                cluster_end = cluster_end.max(columns[column]);
+                cluster.push((i, column));
+            }
+            flush(&mut cluster, &mut columns, &mut rects);
+        }
+        rects
+    }
+
+    fn hit(&self, x: f64, y: f64) -> Hit {
+        // Events are clipped to the grid, like they are drawn.
+        let (gx, gy, gw, gh) = self.grid();
+        if x < gx || y < gy || x >= gx + gw || y >= gy + gh {
+            return Hit::Nothing;
+        }
+        for (i, (rx, ry, rw, rh)) in self.event_rects().into_iter().rev() {
+            if x >= rx && x < rx + rw && y >= ry && y < ry + rh {
+                return Hit::Event(i, y >= ry + rh - RESIZE_HANDLE);
+            }
+        }
+        let minutes = (self.minutes_at(y) / self.snap.max(1) as i64) * self.snap.max(1) as i64;
+        Hit::Slot(self.day_at(x), minutes.clamp(0, DAY as i64 - 1) as u32)
+    }
+
+    fn index_of(&self, id: u64) -> Option<usize> {
+        self.events.iter().position(|event| event.id == id)
+    }
+
+    /// Moves the dragged event to a position of the view, returning whether it changed.
+    fn drag_to(&mut self, x: f64, y: f64) -> bool {
+        let (id, resize, grab, duration) = match self.drag {
+            Some(ref drag) => (drag.id, drag.resize, drag.grab, drag.original.duration()),
+            None => return false,
+        };
+        let index = match self.index_of(id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let snap = self.snap.max(1) as i64;
+        let mut event = self.events[index].clone();
+        if resize {
+            let end = self.snapped(self.minutes_at(y));
+            let shortest = (event.start as i64 + snap).min(DAY as i64);
+            event.end = end.clamp(shortest, DAY as i64) as u32;
+        } else {
+            let start = self.snapped(self.minutes_at(y) - grab);
+            event.start = start.clamp(0, DAY.saturating_sub(duration) as i64) as u32;
+            event.end = event.start + duration;
+            event.date = self.day_at(x);
+        }
+        if event == self.events[index] {
+            return false;
+        }
+        self.events[index] = event;
+        if let Some(ref mut drag) = self.drag {
+            drag.moved = true;
+        }
+        true
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let style = &self.style;
+        let size = style.font.size;
+        let fill = |canvas: &mut C, (x, y, w, h): Rect, color: Color| {
+            canvas.fill(&PathData::rectangle(x, y, w, h), &Brush::Solid(color))
+        };
+        fill(canvas, clip, style.background);
+
+        let day_width = self.day_width();
+        let header = style.header_height;
+        let grid_height = self.size.1 - header;
+        for day in 0..7 {
+            let date = self.week.add_days(day);
+            let x = style.time_width + day as f64 * day_width;
+            let is_today = self.today == Some(date);
+            if is_today {
+                fill(
+                    canvas,
+                    (x, 0.0, day_width, self.size.1),
+                    style.today_background,
+                );
+            } else if date.weekday().is_weekend() {
+                fill(
+                    canvas,
+                    (x, header, day_width, grid_height),
+                    style.weekend_background,
+                );
+            }
+            let label = format!("{} {}", date.weekday().short_name(), date.day());
+            let color = if is_today {
+                style.today_color
+            } else {
+                style.text_color
+            };
+            let label = TextLayoutParams::colored(&label, color, &style.font)
+                .wrapped(day_width, TextAlign::Center)
+                .bold(is_today);
+            canvas.draw_text(&label, x, header / 2.0 - size * 0.65);
+            fill(canvas, (x, 0.0, 1.0, self.size.1), style.grid_color);
+        }
+
+        fill(
+            canvas,
+            (0.0, header - 1.0, self.size.0, 1.0),
+            style.grid_color,
+        );
+        for hour in self.hours.0..self.hours.1 {
+            let y = self.y_of(hour * 60);
+            if hour > self.hours.0 {
+                fill(
+                    canvas,
+                    (style.time_width, y, self.size.0 - style.time_width, 1.0),
+                    style.grid_color,
+                );
+            }
+            let label =
+                TextLayoutParams::colored(&format_time(hour * 60), style.time_color, &style.font)
+                    .wrapped(style.time_width - 6.0, TextAlign::Right);
+            canvas.draw_text(&label, 0.0, y + 2.0);
+        }
+
+        let line_height = size * 1.3;
+        // Events outside the hours shown are cut off at the header and the bottom.
+        canvas.saved(|canvas| {
+            let (gx, gy, gw, gh) = self.grid();
+            canvas.clip(&PathData::rectangle(gx, gy, gw, gh));
+            for (i, (x, y, width, height)) in self.event_rects() {
+                let event = &self.events[i];
+                let frame = PathBuilder::default()
+                    .rounded_rect(x, y, width, height, 3.0)
+                    .finish();
+                canvas.fill(&frame, &Brush::Solid(event.color.with_alpha(0.9)));
+                canvas.saved(|canvas| {
+                    canvas.clip(&frame);
+                    let title =
+                        TextLayoutParams::colored(&event.title, style.event_text, &style.font)
+                            .wrapped(width - 8.0, TextAlign::Left)
+                            .bold(true);
+                    canvas.draw_text(&title, x + 4.0, y + 2.0);
+                    if height > line_height * 2.0 + 4.0 {
+                        let time =
+                            format!("{}–{}", format_time(event.start), format_time(event.end));
+                        let time = TextLayoutParams::colored(&time, style.event_text, &style.font)
+                            .wrapped(width - 8.0, TextAlign::Left);
+                        canvas.draw_text(&time, x + 4.0, y + 2.0 + line_height);
+                    }
+                });
+                if self.selected == Some(event.id) {
+                    canvas.stroke(
+                        &frame,
+                        &Brush::Solid(style.selection_color),
+                        &StrokeParams::new(2.0),
+                    );
+                }
+            }
+        });
+    }
+}
+
+struct WeekHandler {
+    state: Rc<RefCell<WeekState>>,
+}
+
+impl WeekHandler {
+    fn view(&self, area: &Area) -> WeekView {
+        WeekView {
+            area: area.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl AreaHandler for WeekHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        let mut state = self.state.borrow_mut();
+        state.size = (params.area_width, params.area_height);
+        state.render(&mut &params.context, clip);
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        let view = self.view(area);
+        match *event {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                count,
+                ref state,
+            } => view.press(state.x, state.y, count),
+            MouseEvent::Move(ref state) if self.state.borrow_mut().drag_to(state.x, state.y) => {
+                area.queue_redraw_all()
+            }
+            MouseEvent::Release { .. } => view.release(false),
+            MouseEvent::DragBroken => view.release(true),
+            _ => {}
+        }
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        if event.up {
+            return false;
+        }
+        self.view(area).handle_key(event)
+    }
+}
+
+/// A week of days side by side, with events placed on a vertical time axis, drawn on
+/// a plain `Area`.
+///
+/// Events are shown as colored blocks with their title and time, and overlapping events
+/// share the width of their day. Clicking an event selects it; dragging moves it to
+/// another time or day, and dragging its bottom edge changes its end, both snapped to a
+/// step of 15 minutes by default. Double clicking an event or an empty time calls
+/// [`WeekView::on_event_activated()`] or [`WeekView::on_slot_activated()`], e.g. to edit
+/// or create an event.
+///
+/// The arrow keys move the selected event by a step or a day, with Shift Up and Down
+/// change its end, and Escape deselects it. PageUp and PageDown, or the arrow keys
+/// without a selection, show the previous and next week, and Home the week of today.
+///
+/// The events belong to the view; changes made by the user are reported with
+/// [`WeekView::on_event_changed()`].
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # use libui::draw::Color;
+/// let view = WeekView::new();
+/// view.set_hours(8, 18);
+/// let monday = view.week_start();
+/// view.add_event(TimelineEvent::new(1, "Standup", monday, 9 * 60, 9 * 60 + 15));
+/// view.add_event(
+///     TimelineEvent::new(2, "Review", monday.add_days(2), 14 * 60, 16 * 60)
+///         .with_color(Color::rgb(0.85, 0.4, 0.3)),
+/// );
+/// view.on_event_changed(|_, event| {
+///     println!("{} moved to {} at {}", event.title, event.date, event.start);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct WeekView {
+    area: Area,
+    state: Rc<RefCell<WeekState>>,
+}
+
+impl WeekView {
+    /// Creates a view of the current week, with today highlighted.
+    pub fn new() -> WeekView {
+        let state = Rc::new(RefCell::new(WeekState::new(Date::today())));
+        let area = Area::new(Box::new(WeekHandler {
+            state: state.clone(),
+        }));
+        WeekView { area, state }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    /// Returns the first day shown.
+    pub fn week_start(&self) -> Date {
+        self.state.borrow().week
+    }
+
+    /// Shows the week containing a date. Does not call `on_week_changed`.
+    pub fn show_date(&self, date: Date) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.week = date.start_of_week(state.first_weekday);
+        }
+        self.area.queue_redraw_all();
+    }
+
+    pub fn first_weekday(&self) -> Weekday {
+        self.state.borrow().first_weekday
+    }
+
+    /// Sets the weekday of the first day, Monday by default.
+    pub fn set_first_weekday(&self, weekday: Weekday) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.first_weekday = weekday;
+            state.week = state.week.start_of_week(weekday);
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Returns the first and the last hour shown.
+    pub fn hours(&self) -> (u32, u32) {
+        self.state.borrow().hours
+    }
+
+    /// Sets the hours shown, from 6 to 22 by default. The time axis fills the height of
+    /// the area.
+    ///
+    /// # Panics
+    /// Panics unless `first` is less than `last`, and `last` is at most 24.
+    pub fn set_hours(&self, first: u32, last: u32) {
+        assert!(
+            first < last && last <= 24,
+            "invalid hours {}..{}",
+            first,
+            last
+        );
+        self.state.borrow_mut().hours = (first, last);
+        self.area.queue_redraw_all();
+    }
+
+    pub fn snap(&self) -> u32 {
+        self.state.borrow().snap
+    }
+
+    /// Sets the step in minutes to which dragged events snap, 15 by default.
+    pub fn set_snap(&self, minutes: u32) {
+        self.state.borrow_mut().snap = minutes.clamp(1, 60);
+    }
+
+    pub fn today(&self) -> Option<Date> {
+        self.state.borrow().today
+    }
+
+    /// Sets the day highlighted as today, by default the local date when the view was
+    /// created.
+    pub fn set_today(&self, today: Option<Date>) {
+        self.state.borrow_mut().today = today;
+        self.area.queue_redraw_all();
+    }
+
+    pub fn events(&self) -> Vec<TimelineEvent> {
+        self.state.borrow().events.clone()
+    }
+
+    pub fn set_events(&self, events: Vec<TimelineEvent>) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.events = events.into_iter().map(TimelineEvent::clamped).collect();
+            state.drag = None;
+            if let Some(id) = state.selected {
+                if state.index_of(id).is_none() {
+                    state.selected = None;
+                }
+            }
+        }
+        self.area.queue_redraw_all();
+    }
+
+    pub fn event(&self, id: u64) -> Option<TimelineEvent> {
+        let state = self.state.borrow();
+        state.index_of(id).map(|index| state.events[index].clone())
+    }
+
+    /// Adds an event, or replaces the event with the same id. Events are kept within
+    /// their day.
+    pub fn add_event(&self, event: TimelineEvent) {
+        let event = event.clamped();
+        {
+            let mut state = self.state.borrow_mut();
+            match state.index_of(event.id) {
+                Some(index) => state.events[index] = event,
+                None => state.events.push(event),
+            }
+        }
+        self.area.queue_redraw_all();
+    }
+
+    pub fn remove_event(&self, id: u64) -> Option<TimelineEvent> {
+        let removed = {
+            let mut state = self.state.borrow_mut();
+            if state.selected == Some(id) {
+                state.selected = None;
+            }
+            state.index_of(id).map(|index| state.events.remove(index))
+        };
+        self.area.queue_redraw_all();
+        removed
+    }
+
+    /// Returns the id of the selected event.
+    pub fn selected(&self) -> Option<u64> {
+        self.state.borrow().selected
+    }
+
+    /// Selects an event by id. Does not call `on_selection_changed`.
+    pub fn set_selected(&self, id: Option<u64>) {
+        self.state.borrow_mut().selected = id;
+        self.area.queue_redraw_all();
+    }
+
+    pub fn style(&self) -> WeekViewStyle {
+        self.state.borrow().style.clone()
+    }
+
+    pub fn set_style(&self, style: WeekViewStyle) {
+        self.state.borrow_mut().style = style;
+        self.area.queue_redraw_all();
+    }
+
+    /// Sets the callback run with the id of the selected event after the user changed the
+    /// selection.
+    pub fn on_selection_changed<F: FnMut(&WeekView, Option<u64>) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_selection_changed = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run after the user moved an event or changed its end.
+    pub fn on_event_changed<F: FnMut(&WeekView, &TimelineEvent) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_event_changed = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run when an event is double clicked, or Enter is pressed with an
+    /// event selected.
+    pub fn on_event_activated<F: FnMut(&WeekView, &TimelineEvent) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_event_activated = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run with the day and the time in minutes after midnight, snapped
+    /// down, when an empty time is double clicked.
+    pub fn on_slot_activated<F: FnMut(&WeekView, Date, u32) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_slot_activated = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run with the first day after the user changed the week shown.
+    pub fn on_week_changed<F: FnMut(&WeekView, Date) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_week_changed = Some(Box::new(callback));
+    }
+
+    fn press(&self, x: f64, y: f64, count: u32) {
+        let hit = self.state.borrow().hit(x, y);
+        match hit {
+            Hit::Event(index, resize) => {
+                let event = {
+                    let mut state = self.state.borrow_mut();
+                    let event = state.events[index].clone();
+                    let grab = state.minutes_at(y) - event.start as i64;
+                    state.drag = Some(Drag {
+                        id: event.id,
+                        original: event.clone(),
+                        resize,
+                        grab,
+                        moved: false,
+                    });
+                    event
+                };
+                self.select(Some(event.id));
+                if count == 2 {
+                    self.notify(
+                        |state| &mut state.on_event_activated,
+                        |callback| callback(self, &event),
+                    );
+                }
+            }
+            Hit::Slot(date, minutes) => {
+                self.select(None);
+                if count == 2 {
+                    self.notify(
+                        |state| &mut state.on_slot_activated,
+                        |callback| callback(self, date, minutes),
+                    );
+                }
+            }
+            Hit::Nothing => {}
+        }
+    }
+
+    /// Ends a drag, restoring the event if the drag was broken.
+    fn release(&self, broken: bool) {
+        let drag = self.state.borrow_mut().drag.take();
+        let drag = match drag {
+            Some(drag) if drag.moved => drag,
+            _ => return,
+        };
+        if broken {
+            self.add_event(drag.original);
+            return;
+        }
+        if let Some(event) = self.event(drag.id) {
+            self.notify(
+                |state| &mut state.on_event_changed,
+                |callback| callback(self, &event),
+            );
+        }
+    }
+
+    fn select(&self, id: Option<u64>) {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.selected == id {
+                return;
+            }
+            state.selected = id;
+        }
+        self.area.queue_redraw_all();
+        self.notify(
+            |state| &mut state.on_selection_changed,
+            |callback| callback(self, id),
+        );
+    }
+
+    /// Runs a callback, taken out while it runs so it can use the view.
+    fn notify<T, S, F>(&self, slot: S, f: F)
+    where
+        S: Fn(&mut WeekState) -> &mut Option<T>,
+        F: FnOnce(&mut T),
+    {
+        let callback = slot(&mut self.state.borrow_mut()).take();
+        if let Some(mut callback) = callback {
+            f(&mut callback);
+            let mut state = self.state.borrow_mut();
+            let slot = slot(&mut state);
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+
+    /// Shows a week before or after the one shown.
+    fn show_week_offset(&self, weeks: i64) {
+        let week = self.state.borrow().week.add_days(weeks * 7);
+        self.set_week(week);
+    }
+
+    fn set_week(&self, week: Date) {
+        {
+            let mut state = self.state.borrow_mut();
+            let week = week.start_of_week(state.first_weekday);
+            if state.week == week {
+                return;
+            }
+            state.week = week;
+        }
+        self.area.queue_redraw_all();
+        let week = self.week_start();
+        self.notify(
+            |state| &mut state.on_week_changed,
+            |callback| callback(self, week),
+        );
+    }
+
+    /// Moves the selected event by a number of days and minutes, or changes its end.
+    fn nudge(&self, id: u64, days: i64, minutes: i64, resize: bool) {
+        let event = match self.event(id) {
+            Some(event) => event,
+            None => return,
+        };
+        let moved = self.state.borrow().nudged(&event, days, minutes, resize);
+        if moved == event {
+            return;
+        }
+        self.add_event(moved.clone());
+        let week = self.week_start();
+        if moved.date < week || moved.date >= week.add_days(7) {
+            self.set_week(moved.date);
+        }
+        self.notify(
+            |state| &mut state.on_event_changed,
+            |callback| callback(self, &moved),
+        );
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        let key = match Key::from_event(event) {
+            Some(key) => key,
+            None => return false,
+        };
+        let shift = event.modifiers.contains(Modifiers::MODIFIER_SHIFT);
+        let snap = self.snap() as i64;
+        match (key, self.selected()) {
+            (Key::PageUp, _) | (Key::Left, None) => self.show_week_offset(-1),
+            (Key::PageDown, _) | (Key::Right, None) => self.show_week_offset(1),
+            (Key::Home, _) => match self.today() {
+                Some(today) => self.set_week(today),
+                None => return false,
+            },
+            (Key::Up, Some(id)) => self.nudge(id, 0, -snap, shift),
+            (Key::Down, Some(id)) => self.nudge(id, 0, snap, shift),
+            (Key::Left, Some(id)) => self.nudge(id, -1, 0, false),
+            (Key::Right, Some(id)) => self.nudge(id, 1, 0, false),
+            (Key::Enter, Some(id)) | (Key::KeypadEnter, Some(id)) => {
+                if let Some(event) = self.event(id) {
+                    self.notify(
+                        |state| &mut state.on_event_activated,
+                        |callback| callback(self, &event),
+                    );
+                }
+            }
+            (Key::Escape, Some(_)) => self.select(None),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Default for WeekView {
+    fn default() -> Self {
+        WeekView::new()
+    }
+}
+
+impl From<WeekView> for Control {
+    fn from(view: WeekView) -> Control {
+        view.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{DrawCommand, Recorder};
+
+    fn monday() -> Date {
+        Date::new(2024, 9, 9).unwrap()
+    }
+
+    /// Returns a state with 7 days of 100 points, and 10 hours of 60 points from 8:00.
+    fn week() -> WeekState {
+        let mut state = WeekState::new(monday().add_days(2));
+        state.size = (48.0 + 700.0, 36.0 + 600.0);
+        state.hours = (8, 18);
+        state
+    }
+
+    fn drag(state: &mut WeekState, index: usize, resize: bool, grab: i64) {
+        state.drag = Some(Drag {
+            id: state.events[index].id,
+            original: state.events[index].clone(),
+            resize,
+            grab,
+            moved: false,
+        });
+    }
+
+    #[test]
+    fn lays_out_overlapping_events() {
+        let monday = monday();
+        let mut state = week();
+        assert_eq!(state.week, monday);
+        state.events = vec![
+            TimelineEvent::new(1, "a", monday, 9 * 60, 11 * 60),
+            TimelineEvent::new(2, "b", monday, 10 * 60, 12 * 60),
+            TimelineEvent::new(3, "c", monday, 11 * 60, 13 * 60),
+            TimelineEvent::new(4, "d", monday.add_days(1), 14 * 60, 15 * 60),
+        ];
+        let rects = state.event_rects();
+        let rect = |i: usize| rects.iter().find(|r| r.0 == i).unwrap().1;
+        // a and c share the first of two columns, as they only touch.
+        assert_eq!(rect(0).0, 49.0);
+        assert_eq!(rect(2).0, 49.0);
+        assert_eq!(rect(1).0, 99.0);
+        assert_eq!(rect(0).1, 36.0 + 60.0);
+        assert_eq!(rect(3), (149.0, 36.0 + 360.0, 97.0, 59.0));
+
+        assert_eq!(state.hit(200.0, 36.0 + 390.0), Hit::Event(3, false));
+        assert_eq!(state.hit(200.0, 36.0 + 417.0), Hit::Event(3, true));
+        assert_eq!(
+            state.hit(400.0, 36.0 + 130.0),
+            Hit::Slot(monday.add_days(3), 10 * 60)
+        );
+    }
+
+    #[test]
+    fn clips_events_to_the_grid() {
+        let monday = monday();
+        let mut state = week();
+        state.events = vec![TimelineEvent::new(1, "early", monday, 6 * 60, 9 * 60)];
+        assert_eq!(state.event_rects()[0].1 .1, 36.0 - 120.0);
+        assert_eq!(state.hit(60.0, 20.0), Hit::Nothing);
+        assert_eq!(state.hit(60.0, 36.0 + 30.0), Hit::Event(0, false));
+
+        let mut recorder = Recorder::new();
+        state.render(&mut recorder, (0.0, 0.0, 748.0, 636.0));
+        let grid = PathData::rectangle(48.0, 36.0, 700.0, 600.0);
+        assert!(recorder
+            .finish()
+            .commands()
+            .contains(&DrawCommand::Clip(grid)));
+    }
+
+    #[test]
+    fn drags_and_resizes_events_in_snap_steps() {
+        let monday = monday();
+        let mut state = week();
+        state.events = vec![TimelineEvent::new(
+            4,
+            "d",
+            monday.add_days(1),
+            14 * 60,
+            15 * 60,
+        )];
+        // Grabbed at 14:30 and dropped on Thursday at 16:40, snapping to 16:15.
+        drag(&mut state, 0, false, 30);
+        assert!(state.drag_to(400.0, 36.0 + 520.0));
+        let event = &state.events[0];
+        assert_eq!(
+            (event.date, event.start, event.end),
+            (monday.add_days(3), 975, 1035)
+        );
+        assert!(!state.drag_to(400.0, 36.0 + 521.0));
+        assert!(state.drag.as_ref().unwrap().moved);
+        state.drag.as_mut().unwrap().resize = true;
+        assert!(state.drag_to(400.0, 36.0 + 580.0));
+        assert_eq!(state.events[0].end, 17 * 60 + 45);
+        // An event stays at least one snap step long.
+        assert!(state.drag_to(400.0, 36.0));
+        assert_eq!(state.events[0].end, 975 + 15);
+    }
+
+    #[test]
+    fn keeps_events_within_the_day() {
+        let monday = monday();
+        let event = TimelineEvent::new(1, "late", monday, 1435, 1500);
+        assert_eq!((event.start, event.end), (1435, 1440));
+        let event = TimelineEvent::new(2, "empty", monday, 2000, 0);
+        assert_eq!((event.start, event.end), (1439, 1440));
+
+        let mut state = week();
+        state.hours = (0, 24);
+        state.events = vec![TimelineEvent::new(1, "late", monday, 1435, 1440)];
+        // Resizing an event ending at midnight keeps it within the day.
+        drag(&mut state, 0, true, 0);
+        state.drag_to(100.0, 36.0 + 600.0);
+        assert_eq!((state.events[0].start, state.events[0].end), (1435, 1440));
+        let late = state.events[0].clone();
+        let resized = state.nudged(&late, 0, -15, true);
+        assert_eq!(resized.end, 1440);
+        let moved = state.nudged(&late, 1, 30, false);
+        assert_eq!(
+            (moved.date, moved.start, moved.end),
+            (monday.add_days(1), 1435, 1440)
+        );
+        let moved = state.nudged(&late, 0, -15, false);
+        assert_eq!((moved.start, moved.end), (1420, 1425));
+
+        // Events set with public fields out of range are clamped.
+        let mut wide = late.clone();
+        wide.start = 0;
+        wide.end = 5000;
+        let wide = wide.clamped();
+        assert_eq!(wide.duration(), DAY);
+        assert_eq!(state.nudged(&wide, 0, 15, false).end, DAY);
+    }
+}
@@ -1,4 +1,6 @@
-use draw::DrawContext;
+use draw::{Color, DrawContext, Transform};
+use std::error::Error;
+use std::fmt::{self, Display};
 use std::marker::PhantomData;
 use std::ptr;
 use libui_ffi::{self, uiDrawBrush};
@@ -6,14 +8,50 @@ use libui_ffi::{self, uiDrawBrush};
 pub use libui_ffi::uiDrawBrushGradientStop as BrushGradientStop;
 
 /// Used to determine how a given stroke or fill is drawn.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Brush {
-    Solid(SolidBrush),
+    Solid(Color),
     LinearGradient(LinearGradientBrush),
     RadialGradient(RadialGradientBrush),
-    Image,
+    /// Fills with an image or pattern.
+    ///
+    /// libui itself cannot draw images with a brush, so a `DrawContext` fills with the
+    /// average color of the image instead. The `Rasterizer` and `SvgWriter` draw the image.
+    Image(ImageBrush),
+}
+
+/// The error returned when creating a brush from invalid values.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BrushError {
+    /// A gradient needs at least one stop.
+    NoStops,
+    /// A stop position is not a finite number.
+    InvalidStopPosition(f64),
+    /// The outer radius of a radial gradient is negative or not finite.
+    InvalidRadius(f64),
+    /// The pixel buffer of an image brush does not hold `width * height` RGBA pixels,
+    /// or the image is empty.
+    InvalidImageSize { width: u32, height: u32, len: usize },
+}
+
+impl Display for BrushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BrushError::NoStops => write!(f, "a gradient needs at least one stop"),
+            BrushError::InvalidStopPosition(pos) => write!(f, "invalid stop position {}", pos),
+            BrushError::InvalidRadius(radius) => write!(f, "invalid gradient radius {}", radius),
+            BrushError::InvalidImageSize { width, height, len } => write!(
+                f,
+                "{} bytes are no RGBA image of {}x{} pixels",
+                len, width, height
+            ),
+        }
+    }
 }
 
+impl Error for BrushError {}
+
 /// A reference to a DrawBrush
 #[derive(Clone, Debug)]
 pub struct BrushRef<'a> {
@@ -24,14 +62,14 @@ pub struct BrushRef<'a> {
 impl Brush {
     pub fn as_ui_draw_brush_ref(&self, _ctx: &DrawContext) -> BrushRef {
         match *self {
-            Brush::Solid(ref solid_brush) => BrushRef {
+            Brush::Solid(ref color) => BrushRef {
                 ui_draw_brush: uiDrawBrush {
                     Type: libui_ffi::uiDrawBrushTypeSolid as u32,
 
-                    R: solid_brush.r,
-                    G: solid_brush.g,
-                    B: solid_brush.b,
-                    A: solid_brush.a,
+                    R: color.r,
+                    G: color.g,
+                    B: color.b,
+                    A: color.a,
 
                     X0: 0.0,
                     Y0: 0.0,
@@ -81,16 +119,17 @@ impl Brush {
                 },
                 phantom: PhantomData,
             },
-            Brush::Image => {
-                // These don't work yet in `libui`, but just for completeness' sake…
+            Brush::Image(ref image) => {
+                // Image brushes don't work yet in `libui`, so fill with the average color.
+                let color = image.average_color();
                 BrushRef {
                     ui_draw_brush: uiDrawBrush {
-                        Type: libui_ffi::uiDrawBrushTypeImage as u32,
+                        Type: libui_ffi::uiDrawBrushTypeSolid as u32,
 
-                        R: 0.0,
-                        G: 0.0,
-                        B: 0.0,
-                        A: 0.0,
+                        R: color.r,
+                        G: color.g,
+                        B: color.b,
+                        A: color.a,
 
                         X0: 0.0,
                         Y0: 0.0,
@@ -115,35 +154,519 @@ impl<'a> BrushRef<'a> {
 }
 
 /// A brush that paints all pixels with the same color, respecting alpha.
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub struct SolidBrush {
-    /// Red component of the color
-    pub r: f64,
-    /// Green component of the color
-    pub g: f64,
-    /// Blue component of the color
-    pub b: f64,
-    /// Alpha (α) component of the color (that is, opacity).
-    pub a: f64,
-}
+#[deprecated(note = "use `Color` instead")]
+pub type SolidBrush = Color;
 
 /// A brush that paints a linear gradient.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LinearGradientBrush {
     pub start_x: f64,
     pub start_y: f64,
     pub end_x: f64,
     pub end_y: f64,
+    #[cfg_attr(feature = "serde", serde(with = "stops_serde"))]
     pub stops: Vec<BrushGradientStop>,
 }
 
+impl LinearGradientBrush {
+    /// Creates a gradient from `(position, color)` stops.
+    ///
+    /// The stops are sorted by position, and positions are clamped to the range of [0, 1.0].
+    ///
+    /// ```
+    /// # use libui::draw::*;
+    /// let brush = LinearGradientBrush::new(
+    ///     0.0, 0.0, 100.0, 0.0,
+    ///     &[(1.0, Color::WHITE), (1.5, Color::BLACK), (0.2, Color::BLACK)],
+    /// ).unwrap();
+    /// let positions: Vec<f64> = brush.stops.iter().map(|stop| stop.Pos).collect();
+    /// assert_eq!(positions, [0.2, 1.0, 1.0]);
+    /// ```
+    pub fn new(
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+        stops: &[(f64, Color)],
+    ) -> Result<LinearGradientBrush, BrushError> {
+        Ok(LinearGradientBrush {
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            stops: checked_stops(stops)?,
+        })
+    }
+
+    /// Creates a gradient from one color at the start to another at the end.
+    pub fn between(
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+        from: Color,
+        to: Color,
+    ) -> LinearGradientBrush {
+        LinearGradientBrush {
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            stops: vec![from.gradient_stop(0.0), to.gradient_stop(1.0)],
+        }
+    }
+
+    /// Creates a gradient through the given colors, spaced evenly from start to end.
+    pub fn evenly_spaced(
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+        colors: &[Color],
+    ) -> Result<LinearGradientBrush, BrushError> {
+        LinearGradientBrush::new(start_x, start_y, end_x, end_y, &even_stops(colors))
+    }
+}
+
+impl PartialEq for LinearGradientBrush {
+    fn eq(&self, other: &Self) -> bool {
+        self.start_x == other.start_x
+            && self.start_y == other.start_y
+            && self.end_x == other.end_x
+            && self.end_y == other.end_y
+            && stops_eq(&self.stops, &other.stops)
+    }
+}
+
 /// A brush that paints a radial gradient.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RadialGradientBrush {
     pub start_x: f64,
     pub start_y: f64,
     pub outer_circle_center_x: f64,
     pub outer_circle_center_y: f64,
     pub outer_radius: f64,
+    #[cfg_attr(feature = "serde", serde(with = "stops_serde"))]
     pub stops: Vec<BrushGradientStop>,
 }
+
+impl RadialGradientBrush {
+    /// Creates a gradient from `(position, color)` stops, from the start point at
+    /// position 0 to the outer circle at 1.
+    ///
+    /// The stops are sorted by position, and positions are clamped to the range of [0, 1.0].
+    pub fn new(
+        start_x: f64,
+        start_y: f64,
+        outer_circle_center_x: f64,
+        outer_circle_center_y: f64,
+        outer_radius: f64,
+        stops: &[(f64, Color)],
+    ) -> Result<RadialGradientBrush, BrushError> {
+        if !(outer_radius >= 0.0 && outer_radius.is_finite()) {
+            return Err(BrushError::InvalidRadius(outer_radius));
+        }
+        Ok(RadialGradientBrush {
+            start_x,
+            start_y,
+            outer_circle_center_x,
+            outer_circle_center_y,
+            outer_radius,
+            stops: checked_stops(stops)?,
+        })
+    }
+
+    /// Creates a gradient from one color at the center to another at the given radius.
+    pub fn between(
+        center_x: f64,
+        center_y: f64,
+        radius: f64,
+        from: Color,
+        to: Color,
+    ) -> Result<RadialGradientBrush, BrushError> {
+        RadialGradientBrush::new(
+            center_x,
+            center_y,
+            center_x,
+            center_y,
+            radius,
+            &[(0.0, from), (1.0, to)],
+        )
+    }
+
+    /// Creates a gradient through the given colors, spaced evenly from the center to
+    /// the given radius.
+    pub fn evenly_spaced(
+        center_x: f64,
+        center_y: f64,
+        radius: f64,
+        colors: &[Color],
+    ) -> Result<RadialGradientBrush, BrushError> {
+        RadialGradientBrush::new(
+            center_x,
+            center_y,
+            center_x,
+            center_y,
+            radius,
+            &even_stops(colors),
+        )
+    }
+}
+
+impl PartialEq for RadialGradientBrush {
+    fn eq(&self, other: &Self) -> bool {
+        self.start_x == other.start_x
+            && self.start_y == other.start_y
+            && self.outer_circle_center_x == other.outer_circle_center_x
+            && self.outer_circle_center_y == other.outer_circle_center_y
+            && self.outer_radius == other.outer_radius
+            && stops_eq(&self.stops, &other.stops)
+    }
+}
+
+/// Sorts the stops by position and clamps the positions.
+fn checked_stops(stops: &[(f64, Color)]) -> Result<Vec<BrushGradientStop>, BrushError> {
+    if stops.is_empty() {
+        return Err(BrushError::NoStops);
+    }
+    if let Some(&(pos, _)) = stops.iter().find(|(pos, _)| !pos.is_finite()) {
+        return Err(BrushError::InvalidStopPosition(pos));
+    }
+    let mut stops: Vec<BrushGradientStop> = stops
+        .iter()
+        .map(|(pos, color)| color.gradient_stop(pos.clamp(0.0, 1.0)))
+        .collect();
+    stops.sort_by(|a, b| a.Pos.partial_cmp(&b.Pos).unwrap());
+    Ok(stops)
+}
+
+fn even_stops(colors: &[Color]) -> Vec<(f64, Color)> {
+    let last = colors.len().saturating_sub(1).max(1) as f64;
+    colors
+        .iter()
+        .enumerate()
+        .map(|(i, &color)| (i as f64 / last, color))
+        .collect()
+}
+
+/// How an image brush fills the area outside of its image.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImageRepeat {
+    /// The area outside the image is left transparent.
+    None,
+    /// The image is tiled.
+    #[default]
+    Repeat,
+    /// The image is tiled, mirroring every other tile.
+    Reflect,
+}
+
+/// A brush that paints an image, or a pattern tiled from it.
+///
+/// Pixel `(x, y)` of the image covers the unit square from `(x, y)` to `(x + 1, y + 1)`,
+/// which the transform of the brush maps to user space. Pixels are sampled without
+/// smoothing.
+///
+/// ```
+/// # use libui::draw::*;
+/// // A checkerboard with squares of 10x10.
+/// let mut transform = Transform::identity();
+/// transform.scale(0.0, 0.0, 10.0, 10.0);
+/// let pixels = [0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 255];
+/// let checkerboard = ImageBrush::new(2, 2, pixels.to_vec())
+///     .unwrap()
+///     .with_transform(transform);
+/// assert_eq!(checkerboard.color_at(5.0, 5.0), Color::BLACK);
+/// assert_eq!(checkerboard.color_at(15.0, 5.0), Color::WHITE);
+/// assert_eq!(checkerboard.color_at(25.0, 5.0), Color::BLACK);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "image_serde::ImageBrushData"))]
+pub struct ImageBrush {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    transform: Transform,
+    repeat: ImageRepeat,
+}
+
+impl ImageBrush {
+    /// Creates a brush from a buffer of RGBA pixels, row by row, with straight alpha.
+    ///
+    /// The brush repeats the image without a transform.
+    /// The pixels of a `Rasterizer` make an image brush with
+    /// `ImageBrush::new(raster.width(), raster.height(), raster.to_rgba8())`.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageBrush, BrushError> {
+        if width == 0 || height == 0 || width as usize * height as usize * 4 != pixels.len() {
+            return Err(BrushError::InvalidImageSize {
+                width,
+                height,
+                len: pixels.len(),
+            });
+        }
+        Ok(ImageBrush {
+            width,
+            height,
+            pixels,
+            transform: Transform::identity(),
+            repeat: ImageRepeat::default(),
+        })
+    }
+
+    /// Sets the transform from image space to user space.
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Sets how the area outside the image is filled.
+    pub fn with_repeat(mut self, repeat: ImageRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGBA pixels, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn repeat(&self) -> ImageRepeat {
+        self.repeat
+    }
+
+    /// Returns the color painted at a point in user space.
+    pub fn color_at(&self, x: f64, y: f64) -> Color {
+        let (u, v) = match self.transform.inverse() {
+            Some(inverse) => inverse.transform_point((x, y)),
+            None => return Color::TRANSPARENT,
+        };
+        match (self.wrap(u, self.width), self.wrap(v, self.height)) {
+            (Some(column), Some(row)) => self.pixel(column, row),
+            _ => Color::TRANSPARENT,
+        }
+    }
+
+    /// Returns the average color of the image, weighting colors by their opacity.
+    pub fn average_color(&self) -> Color {
+        let mut sum = [0.0; 4];
+        for pixel in self.pixels.chunks(4) {
+            let a = pixel[3] as f64;
+            for i in 0..3 {
+                sum[i] += pixel[i] as f64 * a;
+            }
+            sum[3] += a;
+        }
+        if sum[3] == 0.0 {
+            return Color::TRANSPARENT;
+        }
+        let count = (self.pixels.len() / 4) as f64;
+        Color::new(
+            sum[0] / sum[3] / 255.0,
+            sum[1] / sum[3] / 255.0,
+            sum[2] / sum[3] / 255.0,
+            sum[3] / count / 255.0,
+        )
+    }
+
+    /// Maps an image coordinate to a pixel index according to the repeat mode.
+    fn wrap(&self, coordinate: f64, size: u32) -> Option<usize> {
+        if !coordinate.is_finite() {
+            return None;
+        }
+        let size = size as f64;
+        let coordinate = match self.repeat {
+            ImageRepeat::None if coordinate < 0.0 || coordinate >= size => return None,
+            ImageRepeat::None => coordinate,
+            ImageRepeat::Repeat => coordinate.rem_euclid(size),
+            ImageRepeat::Reflect => {
+                let coordinate = coordinate.rem_euclid(2.0 * size);
+                if coordinate >= size {
+                    2.0 * size - coordinate
+                } else {
+                    coordinate
+                }
+            }
+        };
+        Some((coordinate.floor() as usize).min(size as usize - 1))
+    }
+
+    fn pixel(&self, column: usize, row: usize) -> Color {
+        let i = (row * self.width as usize + column) * 4;
+        match self.pixels.get(i..i + 4) {
+            Some(p) => Color::from_rgba8(p[0], p[1], p[2], p[3]),
+            None => Color::TRANSPARENT,
+        }
+    }
+}
+
+// The stops are a bindgen generated type, which neither compares nor serializes.
+fn stops_eq(a: &[BrushGradientStop], b: &[BrushGradientStop]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.Pos == b.Pos && a.R == b.R && a.G == b.G && a.B == b.B && a.A == b.A
+        })
+}
+
+// Deserializing goes through `ImageBrush::new`, which checks the size of the pixels.
+#[cfg(feature = "serde")]
+mod image_serde {
+    use super::{BrushError, ImageBrush, ImageRepeat};
+    use draw::Transform;
+    use std::convert::TryFrom;
+
+    #[derive(Deserialize)]
+    pub struct ImageBrushData {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+        transform: Transform,
+        repeat: ImageRepeat,
+    }
+
+    impl TryFrom<ImageBrushData> for ImageBrush {
+        type Error = BrushError;
+
+        fn try_from(data: ImageBrushData) -> Result<Self, Self::Error> {
+            Ok(ImageBrush::new(data.width, data.height, data.pixels)?
+                .with_transform(data.transform)
+                .with_repeat(data.repeat))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod stops_serde {
+    use super::BrushGradientStop;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Stop {
+        pos: f64,
+        r: f64,
+        g: f64,
+        b: f64,
+        a: f64,
+    }
+
+    pub fn serialize<S: Serializer>(
+        stops: &[BrushGradientStop],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(stops.iter().map(|s| Stop {
+            pos: s.Pos,
+            r: s.R,
+            g: s.G,
+            b: s.B,
+            a: s.A,
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<BrushGradientStop>, D::Error> {
+        let stops = Vec::<Stop>::deserialize(deserializer)?;
+        Ok(stops
+            .into_iter()
+            .map(|s| BrushGradientStop {
+                Pos: s.pos,
+                R: s.r,
+                G: s.g,
+                B: s.b,
+                A: s.a,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_gradient_stops() {
+        let brush = LinearGradientBrush::evenly_spaced(
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            &[Color::BLACK, Color::WHITE, Color::BLACK],
+        )
+        .unwrap();
+        let positions: Vec<f64> = brush.stops.iter().map(|stop| stop.Pos).collect();
+        assert_eq!(positions, [0.0, 0.5, 1.0]);
+        assert_eq!(
+            LinearGradientBrush::new(0.0, 0.0, 1.0, 0.0, &[]),
+            Err(BrushError::NoStops)
+        );
+        assert!(matches!(
+            LinearGradientBrush::new(0.0, 0.0, 1.0, 0.0, &[(f64::NAN, Color::BLACK)]),
+            Err(BrushError::InvalidStopPosition(_))
+        ));
+        assert_eq!(
+            RadialGradientBrush::between(0.0, 0.0, -1.0, Color::BLACK, Color::WHITE),
+            Err(BrushError::InvalidRadius(-1.0))
+        );
+    }
+
+    #[test]
+    fn samples_images_by_repeat_mode() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let image = ImageBrush::new(2, 1, [red, blue].concat()).unwrap();
+        let (red, blue) = (Color::rgb(1.0, 0.0, 0.0), Color::rgb(0.0, 0.0, 1.0));
+        assert_eq!(image.color_at(-0.5, 0.5), blue);
+        assert_eq!(image.color_at(2.5, 7.5), red);
+
+        let image = image.with_repeat(ImageRepeat::Reflect);
+        assert_eq!(image.color_at(-0.5, 0.5), red);
+        assert_eq!(image.color_at(2.5, 0.5), blue);
+        assert_eq!(image.color_at(4.5, 0.5), red);
+
+        let image = image.with_repeat(ImageRepeat::None);
+        assert_eq!(image.color_at(1.5, 0.5), blue);
+        assert_eq!(image.color_at(2.5, 0.5), Color::TRANSPARENT);
+        assert_eq!(image.average_color(), Color::new(0.5, 0.0, 0.5, 1.0));
+
+        assert_eq!(
+            ImageBrush::new(2, 2, vec![0; 4]),
+            Err(BrushError::InvalidImageSize {
+                width: 2,
+                height: 2,
+                len: 4
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes_images_through_new() {
+        extern crate serde_json;
+
+        let image = ImageBrush::new(1, 1, vec![255, 0, 0, 255])
+            .unwrap()
+            .with_repeat(ImageRepeat::Reflect);
+        let json = serde_json::to_string(&image).unwrap();
+        assert_eq!(serde_json::from_str::<ImageBrush>(&json).unwrap(), image);
+
+        let empty = json.replace("\"width\":1", "\"width\":0");
+        assert!(serde_json::from_str::<ImageBrush>(&empty).is_err());
+        let short = json.replace("[255,0,0,255]", "[255,0]");
+        assert!(serde_json::from_str::<ImageBrush>(&short).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use draw::{
+    Brush, DisplayList, DrawCommand, DrawContext, PathData, Recorder, StrokeParams, TextLayout,
+    TextLayoutParams, Transform,
+};
+
+/// A surface supporting the drawing operations of a [`DrawContext`].
+///
+/// Write drawing code against this trait to render it not only in an `Area`, but also
+/// into a [`Recorder`] or onto a [`Rasterizer`](super::Rasterizer), e.g. to save thumbnails or
+/// compare against golden images without a window. A `DrawContext` is used as a canvas by
+/// reference:
+///
+/// ```no_run
+/// # use libui::controls::*;
+/// # use libui::draw::*;
+/// fn paint<C: Canvas>(canvas: &mut C, width: f64, height: f64) {
+///     let brush = Brush::Solid(Color { r: 0.2, g: 0.6, b: 0.8, a: 1.0 });
+///     canvas.fill(&PathData::rectangle(0.0, 0.0, width, height), &brush);
+/// }
+///
+/// struct Handler;
+///
+/// impl AreaHandler for Handler {
+///     fn draw(&mut self, _area: &Area, draw_params: &AreaDrawParams) {
+///         let mut canvas = &draw_params.context;
+///         paint(&mut canvas, draw_params.area_width, draw_params.area_height);
+///     }
+/// }
+///
+/// let mut thumbnail = Rasterizer::new(64, 48);
+/// paint(&mut thumbnail, 64.0, 48.0);
+/// thumbnail.save_png("thumbnail.png").unwrap();
+/// ```
+pub trait Canvas {
+    /// Fill the given path with the given brush.
+    fn fill(&mut self, path: &PathData, brush: &Brush);
+    /// Draw a stroke along the given path.
+    fn stroke(&mut self, path: &PathData, brush: &Brush, params: &StrokeParams);
+    /// Transform all following operations by the given transform.
+    fn transform(&mut self, transform: &Transform);
+    /// Restrict all following operations to the inside of the given path.
+    fn clip(&mut self, path: &PathData);
+    /// Push the current transform and clip onto the state stack.
+    fn save(&mut self);
+    /// Pop the transform and clip pushed by the last `save()`.
+    fn restore(&mut self);
+    /// Draw text with its top left corner at the given position.
+    fn draw_text(&mut self, layout: &TextLayoutParams, x: f64, y: f64);
+
+    /// Run `f` between a `save()` and its matching `restore()`, which also happens if `f`
+    /// panics.
+    fn saved<R, F: FnOnce(&mut Self) -> R>(&mut self, f: F) -> R
+    where
+        Self: Sized,
+    {
+        self.save();
+        let restore = RestoreGuard(self);
+        f(&mut *restore.0)
+    }
+}
+
+/// Restores a `Canvas` when dropped.
+struct RestoreGuard<'a, C: Canvas>(&'a mut C);
+
+impl<'a, C: Canvas> Drop for RestoreGuard<'a, C> {
+    fn drop(&mut self) {
+        self.0.restore();
+    }
+}
+
+// Calls are spelled out as `DrawContext::xxx()`, as `self.xxx()` would resolve to the trait.
+impl Canvas for &DrawContext {
+    fn fill(&mut self, path: &PathData, brush: &Brush) {
+        DrawContext::fill(self, &path.build(self), brush)
+    }
+
+    fn stroke(&mut self, path: &PathData, brush: &Brush, params: &StrokeParams) {
+        DrawContext::stroke(self, &path.build(self), brush, params)
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        DrawContext::transform(self, transform)
+    }
+
+    fn clip(&mut self, path: &PathData) {
+        DrawContext::clip(self, &path.build(self))
+    }
+
+    fn save(&mut self) {
+        DrawContext::save(self)
+    }
+
+    fn restore(&mut self) {
+        DrawContext::restore(self)
+    }
+
+    fn draw_text(&mut self, layout: &TextLayoutParams, x: f64, y: f64) {
+        DrawContext::draw_text(self, &TextLayout::new(layout), x, y)
+    }
+}
+
+impl Canvas for Recorder {
+    fn fill(&mut self, path: &PathData, brush: &Brush) {
+        Recorder::fill(self, path, brush)
+    }
+
+    fn stroke(&mut self, path: &PathData, brush: &Brush, params: &StrokeParams) {
+        Recorder::stroke(self, path, brush, params)
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        Recorder::transform(self, transform)
+    }
+
+    fn clip(&mut self, path: &PathData) {
+        Recorder::clip(self, path)
+    }
+
+    fn save(&mut self) {
+        Recorder::save(self)
+    }
+
+    fn restore(&mut self) {
+        Recorder::restore(self)
+    }
+
+    fn draw_text(&mut self, layout: &TextLayoutParams, x: f64, y: f64) {
+        Recorder::draw_text(self, layout, x, y)
+    }
+}
+
+impl DisplayList {
+    /// Draws the recorded commands onto any canvas.
+    ///
+    /// Transforms and clips of the display list do not affect later drawing on the canvas.
+    pub fn replay_to<C: Canvas + ?Sized>(&self, canvas: &mut C) {
+        canvas.save();
+        for command in self.commands() {
+            match *command {
+                DrawCommand::Fill {
+                    ref path,
+                    ref brush,
+                } => canvas.fill(path, brush),
+                DrawCommand::Stroke {
+                    ref path,
+                    ref brush,
+                    ref params,
+                } => canvas.stroke(path, brush, params),
+                DrawCommand::Transform(ref transform) => canvas.transform(transform),
+                DrawCommand::Clip(ref path) => canvas.clip(path),
+                DrawCommand::Save => canvas.save(),
+                DrawCommand::Restore => canvas.restore(),
+                DrawCommand::Text { ref layout, x, y } => canvas.draw_text(layout, x, y),
+            }
+        }
+        canvas.restore();
+    }
+}
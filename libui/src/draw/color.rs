@@ -0,0 +1,535 @@
+use draw::BrushGradientStop;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A color in the sRGB color space, with components in the range of [0, 1.0].
+///
+/// Colors are used by brushes, color buttons, text attributes and tables. They can be
+/// parsed from the usual CSS notations:
+///
+/// ```
+/// # use libui::draw::Color;
+/// let orange: Color = "#ff8000".parse().unwrap();
+/// assert_eq!(orange, Color::from_rgba8(255, 128, 0, 255));
+/// assert_eq!("hsl(30, 100%, 50%)".parse::<Color>().unwrap().to_rgba8(), [255, 128, 0, 255]);
+/// assert_eq!("rebeccapurple".parse::<Color>().unwrap().to_string(), "#663399");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Color {
+    /// Red component of the color
+    pub r: f64,
+    /// Green component of the color
+    pub g: f64,
+    /// Blue component of the color
+    pub b: f64,
+    /// Alpha (α) component of the color (that is, opacity).
+    pub a: f64,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+
+    pub const fn new(r: f64, g: f64, b: f64, a: f64) -> Color {
+        Color { r, g, b, a }
+    }
+
+    /// Creates an opaque color.
+    pub const fn rgb(r: f64, g: f64, b: f64) -> Color {
+        Color::new(r, g, b, 1.0)
+    }
+
+    /// Creates a color from components in the range of [0, 255].
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        let unit = |v: u8| v as f64 / 255.0;
+        Color::new(unit(r), unit(g), unit(b), unit(a))
+    }
+
+    /// Returns the components in the range of [0, 255], clamping components out of range.
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        let byte = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [byte(self.r), byte(self.g), byte(self.b), byte(self.a)]
+    }
+
+    /// Returns the same color with another opacity.
+    pub fn with_alpha(self, a: f64) -> Color {
+        Color { a, ..self }
+    }
+
+    /// Creates a color from hue in degrees, and saturation and lightness in the range of [0, 1.0].
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64, a: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_hue(hue, chroma, lightness - chroma / 2.0, a)
+    }
+
+    /// Returns hue in degrees in the range of [0, 360), saturation and lightness.
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Creates a color from hue in degrees, and saturation and value in the range of [0, 1.0].
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64, a: f64) -> Color {
+        let chroma = value * saturation;
+        Color::from_hue(hue, chroma, value - chroma, a)
+    }
+
+    /// Returns hue in degrees in the range of [0, 360), saturation and value.
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+
+    /// Creates a color from linear RGB components, as used for blending and lighting.
+    pub fn from_linear(r: f64, g: f64, b: f64, a: f64) -> Color {
+        let encode = |c: f64| {
+            if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Color::new(encode(r), encode(g), encode(b), a)
+    }
+
+    /// Returns the red, green and blue components in linear RGB.
+    pub fn to_linear(&self) -> (f64, f64, f64) {
+        let decode = |c: f64| {
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        (decode(self.r), decode(self.g), decode(self.b))
+    }
+
+    /// Interpolates between this color, at `t = 0`, and another, at `t = 1`.
+    pub fn lerp(&self, other: &Color, t: f64) -> Color {
+        let mix = |a: f64, b: f64| a + (b - a) * t;
+        Color::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        )
+    }
+
+    /// Returns the relative luminance as defined by WCAG, from 0 for black to 1 for white.
+    pub fn luminance(&self) -> f64 {
+        let (r, g, b) = self.to_linear();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// Returns the WCAG contrast ratio between two colors, from 1 to 21. Opacity is ignored.
+    ///
+    /// Text should have a contrast ratio of at least 4.5 to its background.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Returns a gradient stop with this color at the given position.
+    pub fn gradient_stop(&self, pos: f64) -> BrushGradientStop {
+        BrushGradientStop {
+            Pos: pos,
+            R: self.r,
+            G: self.g,
+            B: self.b,
+            A: self.a,
+        }
+    }
+
+    fn from_hue(hue: f64, chroma: f64, offset: f64, a: f64) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Color::new(r + offset, g + offset, b + offset, a)
+    }
+
+    /// Returns the hue in degrees, and the largest and smallest component.
+    fn hue(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (self.r, self.g, self.b);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        (hue, max, min)
+    }
+}
+
+impl From<BrushGradientStop> for Color {
+    fn from(stop: BrushGradientStop) -> Color {
+        Color::new(stop.R, stop.G, stop.B, stop.A)
+    }
+}
+
+/// Formats the color as `#rrggbb`, or `#rrggbbaa` if it is not fully opaque.
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b, a] = self.to_rgba8();
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)?;
+        if a != 255 {
+            write!(f, "{:02x}", a)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when a string is not a valid color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorParseError {
+    pub input: String,
+}
+
+impl Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid color: {:?}", self.input)
+    }
+}
+
+impl Error for ColorParseError {}
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()` and `hsla()`
+/// in their comma and space separated forms, and CSS color names.
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Color, ColorParseError> {
+        let input = s.trim().to_ascii_lowercase();
+        let color = if let Some(hex) = input.strip_prefix('#') {
+            parse_hex(hex)
+        } else if let Some(arguments) = function_arguments(&input, &["rgb", "rgba"]) {
+            parse_rgb(&arguments)
+        } else if let Some(arguments) = function_arguments(&input, &["hsl", "hsla"]) {
+            parse_hsl(&arguments)
+        } else if input == "transparent" {
+            Some(Color::TRANSPARENT)
+        } else {
+            NAMED_COLORS
+                .binary_search_by_key(&input.as_str(), |&(name, _)| name)
+                .ok()
+                .map(|i| {
+                    let rgb = NAMED_COLORS[i].1;
+                    Color::from_rgba8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255)
+                })
+        };
+        color.ok_or_else(|| ColorParseError {
+            input: s.to_string(),
+        })
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).unwrap() * 17;
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    match hex.len() {
+        3 => Some(Color::from_rgba8(digit(0), digit(1), digit(2), 255)),
+        4 => Some(Color::from_rgba8(digit(0), digit(1), digit(2), digit(3))),
+        6 => Some(Color::from_rgba8(pair(0), pair(2), pair(4), 255)),
+        8 => Some(Color::from_rgba8(pair(0), pair(2), pair(4), pair(6))),
+        _ => None,
+    }
+}
+
+/// Splits `name(a, b, c / d)` into its arguments, returning `None` for other functions.
+fn function_arguments(input: &str, names: &[&str]) -> Option<Vec<String>> {
+    let open = input.find('(')?;
+    if !names.contains(&input[..open].trim()) || !input.ends_with(')') {
+        return None;
+    }
+    let content = &input[open + 1..input.len() - 1];
+    Some(
+        content
+            .replace('/', " ")
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_string())
+            .collect(),
+    )
+}
+
+/// Parses a number, or a percentage of `scale`.
+fn parse_number(part: &str, scale: f64) -> Option<f64> {
+    match part.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok().map(|v| v / 100.0 * scale),
+        None => part.parse().ok(),
+    }
+}
+
+fn parse_alpha(arguments: &[String]) -> Option<f64> {
+    match arguments.get(3) {
+        Some(alpha) => parse_number(alpha, 1.0).map(|a| a.clamp(0.0, 1.0)),
+        None => Some(1.0),
+    }
+}
+
+fn parse_rgb(arguments: &[String]) -> Option<Color> {
+    if arguments.len() != 3 && arguments.len() != 4 {
+        return None;
+    }
+    let channel =
+        |i: usize| parse_number(&arguments[i], 255.0).map(|v| v.clamp(0.0, 255.0) / 255.0);
+    Some(Color::new(
+        channel(0)?,
+        channel(1)?,
+        channel(2)?,
+        parse_alpha(arguments)?,
+    ))
+}
+
+fn parse_hsl(arguments: &[String]) -> Option<Color> {
+    if arguments.len() != 3 && arguments.len() != 4 {
+        return None;
+    }
+    let hue = arguments[0].trim_end_matches("deg").parse::<f64>().ok()?;
+    // Saturation and lightness are percentages, with or without the sign.
+    let percentage = |i: usize| {
+        arguments[i]
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .ok()
+            .map(|v| (v / 100.0).clamp(0.0, 1.0))
+    };
+    Some(Color::from_hsl(
+        hue,
+        percentage(1)?,
+        percentage(2)?,
+        parse_alpha(arguments)?,
+    ))
+}
+
+/// The CSS named colors, sorted by name.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> [u8; 4] {
+        s.parse::<Color>().unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn parses_css_notations() {
+        assert_eq!(parse("#f80"), [255, 136, 0, 255]);
+        assert_eq!(parse("#FF880080"), [255, 136, 0, 128]);
+        assert_eq!(parse("rgb(255, 136, 0)"), [255, 136, 0, 255]);
+        assert_eq!(parse("rgba(100%, 0%, 0%, 0.5)"), [255, 0, 0, 128]);
+        assert_eq!(parse("rgb(0 0 255 / 25%)"), [0, 0, 255, 64]);
+        assert_eq!(parse("hsl(120deg, 100%, 25%)"), [0, 128, 0, 255]);
+        assert_eq!(parse(" Tomato "), [255, 99, 71, 255]);
+        assert_eq!(parse("transparent"), [0, 0, 0, 0]);
+        for invalid in &["#12345", "rgb(1, 2)", "hsl(a, 1%, 1%)", "notacolor", "#ggg"] {
+            assert!(invalid.parse::<Color>().is_err(), "{} parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn named_colors_are_sorted() {
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn converts_between_color_spaces() {
+        let color = Color::from_rgba8(51, 153, 204, 255);
+        let (h, s, l) = color.to_hsl();
+        assert!((h - 200.0).abs() < 1e-9 && (s - 0.6).abs() < 1e-9 && (l - 0.5).abs() < 1e-9);
+        assert_eq!(Color::from_hsl(h, s, l, 1.0).to_rgba8(), color.to_rgba8());
+
+        let (h, s, v) = color.to_hsv();
+        assert!((h - 200.0).abs() < 1e-9 && (s - 0.75).abs() < 1e-9 && (v - 0.8).abs() < 1e-9);
+        assert_eq!(Color::from_hsv(h, s, v, 1.0).to_rgba8(), color.to_rgba8());
+
+        let (r, g, b) = color.to_linear();
+        assert_eq!(
+            Color::from_linear(r, g, b, 1.0).to_rgba8(),
+            color.to_rgba8()
+        );
+    }
+
+    #[test]
+    fn measures_contrast() {
+        assert!((Color::BLACK.contrast_ratio(&Color::WHITE) - 21.0).abs() < 1e-9);
+        assert_eq!(Color::WHITE.contrast_ratio(&Color::WHITE), 1.0);
+        let grey = Color::BLACK.lerp(&Color::WHITE, 0.5);
+        assert_eq!(grey.to_string(), "#808080");
+    }
+}
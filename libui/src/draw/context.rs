@@ -1,4 +1,4 @@
-use draw::{Brush, Path, StrokeParams, Transform};
+use draw::{Brush, Path, StrokeParams, TextLayout, Transform};
 use libui_ffi::{self, uiDrawContext};
 
 /// Drawing context, used to draw custom content on the screen.
@@ -40,18 +40,62 @@ impl DrawContext {
         }
     }
 
+    /// Draw a laid out text with its top left corner at the given position.
+    pub fn draw_text(&self, layout: &TextLayout, x: f64, y: f64) {
+        unsafe { libui_ffi::uiDrawText(self.ui_draw_context, layout.ptr(), x, y) }
+    }
+
     /// Transform this DrawContext by the given Transform.
     pub fn transform(&self, txform: &Transform) {
         unsafe { libui_ffi::uiDrawTransform(self.ui_draw_context, txform.ptr()) }
     }
 
-    /// Open a modal allowing the user to save the contents of this DrawContext.
+    /// Restrict all further drawing to the inside of the given Path.
+    ///
+    /// The clip region is intersected with the current one, so clipping can only ever shrink it.
+    /// Use `save()` and `restore()`, or better `saved()`, to undo a clip. The Path must be ended.
+    pub fn clip(&self, path: &Path) {
+        unsafe { libui_ffi::uiDrawClip(self.ui_draw_context, path.ptr()) }
+    }
+
+    /// Push the current transform and clip region onto the state stack.
+    ///
+    /// Every save must be matched by a `restore()`. Prefer `saved()`, which cannot be left unmatched.
     pub fn save(&self) {
         unsafe { libui_ffi::uiDrawSave(self.ui_draw_context) }
     }
 
-    /// Open a modal allowing the user to load the contents of a DrawContext onto this one.
+    /// Pop the transform and clip region pushed by the last `save()`.
     pub fn restore(&self) {
         unsafe { libui_ffi::uiDrawRestore(self.ui_draw_context) }
     }
+
+    /// Run `f` with the current state saved, restoring it afterwards.
+    ///
+    /// Transforms and clips applied by `f` do not affect drawing after it returns.
+    /// The state is restored even if `f` panics.
+    ///
+    /// ```no_run
+    /// # use libui::draw::*;
+    /// # fn draw(ctx: &DrawContext, viewport: &Path, plot: &Path, brush: &Brush) {
+    /// ctx.saved(|ctx| {
+    ///     ctx.clip(viewport);
+    ///     ctx.fill(plot, brush);
+    /// });
+    /// # }
+    /// ```
+    pub fn saved<R, F: FnOnce(&DrawContext) -> R>(&self, f: F) -> R {
+        self.save();
+        let _restore = RestoreGuard(self);
+        f(self)
+    }
+}
+
+/// Restores a `DrawContext` when dropped.
+struct RestoreGuard<'a>(&'a DrawContext);
+
+impl<'a> Drop for RestoreGuard<'a> {
+    fn drop(&mut self) {
+        self.0.restore();
+    }
 }
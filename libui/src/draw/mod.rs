@@ -1,19 +1,39 @@
 //! Functions and types related to 2D vector graphics.
 
 mod brush;
+mod canvas;
+mod color;
 mod context;
 mod path;
+mod pathbuilder;
+mod pathdata;
+mod png;
+mod raster;
+mod recorder;
 mod strokeparams;
+mod svg;
+mod text;
 mod transform;
 
 pub use self::brush::*;
+pub use self::canvas::*;
+pub use self::color::*;
 pub use self::context::*;
 pub use self::path::*;
+pub use self::pathbuilder::*;
+pub use self::pathdata::*;
+pub use self::raster::*;
+pub use self::recorder::*;
 pub use self::strokeparams::*;
+pub use self::svg::*;
+pub use self::text::*;
 pub use self::transform::*;
 
 pub use libui_ffi::uiDrawDefaultMiterLimit as DEFAULT_MITER_LIMIT;
 
+/// A rectangle as `(x, y, width, height)`.
+pub type Rect = (f64, f64, f64, f64);
+
 // pub struct FontFamilies {
 //     ui_draw_font_families: *mut uiDrawFontFamilies,
 // }
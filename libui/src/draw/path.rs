@@ -13,7 +13,8 @@ impl Drop for Path {
 }
 
 /// Represents the fill mode used when drawing a path.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FillMode {
     /// Draw using the [non-zero winding number fill rule](https://en.wikipedia.org/wiki/Nonzero-rule).
     Winding,
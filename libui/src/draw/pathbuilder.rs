@@ -0,0 +1,404 @@
+use draw::pathdata::arc_span;
+use draw::{DrawContext, FillMode, Path, PathCommand, PathData};
+use std::f64::consts::PI;
+
+/// The distance of Bézier control points approximating a quarter circle of radius 1.
+const KAPPA: f64 = 0.552_284_749_830_793_4;
+
+/// Builds path data step by step, without needing a `DrawContext`.
+///
+/// Unlike [`Path`], a builder does not need to be ended, and drawing commands that need
+/// a current point start a new figure where there is none. Shapes are added as figures
+/// of their own. Finish with [`PathBuilder::finish()`] to get the [`PathData`], or
+/// create a `Path` at draw time with [`PathBuilder::build()`].
+///
+/// ```
+/// # use libui::draw::*;
+/// let badge = PathBuilder::new(FillMode::Winding)
+///     .rounded_rect(0.0, 0.0, 40.0, 20.0, 5.0)
+///     .star(50.0, 10.0, 10.0, 4.0, 5)
+///     .finish();
+/// assert!(badge.contains(20.0, 10.0));
+/// assert!(!badge.contains(1.0, 1.0));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathBuilder {
+    data: PathData,
+    current: Option<(f64, f64)>,
+    figure_start: (f64, f64),
+    in_figure: bool,
+}
+
+impl Default for PathBuilder {
+    fn default() -> Self {
+        PathBuilder::new(FillMode::Winding)
+    }
+}
+
+impl PathBuilder {
+    /// Creates an empty builder.
+    pub fn new(fill_mode: FillMode) -> PathBuilder {
+        PathBuilder {
+            data: PathData::new(fill_mode),
+            current: None,
+            figure_start: (0.0, 0.0),
+            in_figure: false,
+        }
+    }
+
+    /// Starts a new figure at the given point.
+    pub fn move_to(mut self, x: f64, y: f64) -> Self {
+        self.data.commands.push(PathCommand::NewFigure { x, y });
+        self.current = Some((x, y));
+        self.figure_start = (x, y);
+        self.in_figure = true;
+        self
+    }
+
+    /// Adds a straight line to the given point.
+    ///
+    /// Without a current point, this starts a new figure at the given point instead.
+    pub fn line_to(mut self, x: f64, y: f64) -> Self {
+        if self.current.is_none() {
+            return self.move_to(x, y);
+        }
+        self = self.ensure_figure((x, y));
+        self.data.commands.push(PathCommand::LineTo { x, y });
+        self.current = Some((x, y));
+        self
+    }
+
+    /// Adds a quadratic Bézier curve with the control point `(cx, cy)`.
+    pub fn quad_to(mut self, cx: f64, cy: f64, x: f64, y: f64) -> Self {
+        self = self.ensure_figure((cx, cy));
+        let (x0, y0) = self.current.unwrap();
+        self.cubic_to(
+            x0 + 2.0 / 3.0 * (cx - x0),
+            y0 + 2.0 / 3.0 * (cy - y0),
+            x + 2.0 / 3.0 * (cx - x),
+            y + 2.0 / 3.0 * (cy - y),
+            x,
+            y,
+        )
+    }
+
+    /// Adds a cubic Bézier curve with the control points `(c1x, c1y)` and `(c2x, c2y)`.
+    pub fn cubic_to(mut self, c1x: f64, c1y: f64, c2x: f64, c2y: f64, x: f64, y: f64) -> Self {
+        self = self.ensure_figure((c1x, c1y));
+        self.data.commands.push(PathCommand::BezierTo {
+            c1x,
+            c1y,
+            c2x,
+            c2y,
+            end_x: x,
+            end_y: y,
+        });
+        self.current = Some((x, y));
+        self
+    }
+
+    /// Adds a circular arc, connected to the current point by a straight line.
+    ///
+    /// The angles follow the conventions of [`Path::arc_to()`]. Without a current
+    /// point, the arc starts a new figure.
+    pub fn arc(
+        mut self,
+        x_center: f64,
+        y_center: f64,
+        radius: f64,
+        start_angle: f64,
+        sweep: f64,
+        negative: bool,
+    ) -> Self {
+        if self.in_figure {
+            self.data.commands.push(PathCommand::ArcTo {
+                x_center,
+                y_center,
+                radius,
+                start_angle,
+                sweep,
+                negative,
+            });
+        } else {
+            self.data.commands.push(PathCommand::NewFigureWithArc {
+                x_center,
+                y_center,
+                radius,
+                start_angle,
+                sweep,
+                negative,
+            });
+            self.figure_start = point_on_circle(x_center, y_center, radius, start_angle);
+            self.in_figure = true;
+        }
+        let end = start_angle + arc_span(start_angle, sweep, negative);
+        self.current = Some(point_on_circle(x_center, y_center, radius, end));
+        self
+    }
+
+    /// Closes the current figure with a straight line to its start.
+    pub fn close(mut self) -> Self {
+        if self.in_figure {
+            self.data.commands.push(PathCommand::CloseFigure);
+            self.in_figure = false;
+            self.current = Some(self.figure_start);
+        }
+        self
+    }
+
+    /// Adds a rectangle.
+    pub fn rect(mut self, x: f64, y: f64, width: f64, height: f64) -> Self {
+        self.data.commands.push(PathCommand::AddRectangle {
+            x,
+            y,
+            width,
+            height,
+        });
+        self.in_figure = false;
+        self.current = Some((x, y));
+        self.figure_start = (x, y);
+        self
+    }
+
+    /// Adds a rectangle with corners rounded by the given radius, limited to half the
+    /// width and height.
+    pub fn rounded_rect(self, x: f64, y: f64, width: f64, height: f64, radius: f64) -> Self {
+        let radius = radius.min(width.abs() / 2.0).min(height.abs() / 2.0);
+        if radius <= 0.0 {
+            return self.rect(x, y, width, height);
+        }
+        let (right, bottom) = (x + width, y + height);
+        self.end_figure()
+            .arc(x + radius, y + radius, radius, PI, PI / 2.0, false)
+            .arc(
+                right - radius,
+                y + radius,
+                radius,
+                -PI / 2.0,
+                PI / 2.0,
+                false,
+            )
+            .arc(
+                right - radius,
+                bottom - radius,
+                radius,
+                0.0,
+                PI / 2.0,
+                false,
+            )
+            .arc(
+                x + radius,
+                bottom - radius,
+                radius,
+                PI / 2.0,
+                PI / 2.0,
+                false,
+            )
+            .close()
+    }
+
+    /// Adds an ellipse with the given center and radii.
+    pub fn ellipse(self, x_center: f64, y_center: f64, x_radius: f64, y_radius: f64) -> Self {
+        let (kx, ky) = (x_radius * KAPPA, y_radius * KAPPA);
+        let (left, right) = (x_center - x_radius, x_center + x_radius);
+        let (top, bottom) = (y_center - y_radius, y_center + y_radius);
+        self.move_to(right, y_center)
+            .cubic_to(
+                right,
+                y_center + ky,
+                x_center + kx,
+                bottom,
+                x_center,
+                bottom,
+            )
+            .cubic_to(x_center - kx, bottom, left, y_center + ky, left, y_center)
+            .cubic_to(left, y_center - ky, x_center - kx, top, x_center, top)
+            .cubic_to(x_center + kx, top, right, y_center - ky, right, y_center)
+            .close()
+    }
+
+    /// Adds a circle with the given center and radius.
+    pub fn circle(self, x_center: f64, y_center: f64, radius: f64) -> Self {
+        self.end_figure()
+            .arc(x_center, y_center, radius, 0.0, 2.0 * PI, false)
+            .close()
+    }
+
+    /// Adds a closed figure through the given points. Does nothing without points.
+    pub fn polygon(self, points: &[(f64, f64)]) -> Self {
+        if points.is_empty() {
+            return self;
+        }
+        self.polyline(points).close()
+    }
+
+    /// Adds an open figure through the given points. Does nothing without points.
+    pub fn polyline(mut self, points: &[(f64, f64)]) -> Self {
+        if let Some((&(x, y), rest)) = points.split_first() {
+            self = self.move_to(x, y);
+            for &(x, y) in rest {
+                self = self.line_to(x, y);
+            }
+        }
+        self
+    }
+
+    /// Adds a star with the given number of points, pointing upwards.
+    ///
+    /// The tips lie on the outer radius, the corners between them on the inner radius.
+    pub fn star(
+        self,
+        x_center: f64,
+        y_center: f64,
+        outer_radius: f64,
+        inner_radius: f64,
+        points: usize,
+    ) -> Self {
+        let corners: Vec<(f64, f64)> = (0..points * 2)
+            .map(|i| {
+                let radius = if i % 2 == 0 {
+                    outer_radius
+                } else {
+                    inner_radius
+                };
+                let angle = -PI / 2.0 + PI * i as f64 / points as f64;
+                point_on_circle(x_center, y_center, radius, angle)
+            })
+            .collect();
+        self.polygon(&corners)
+    }
+
+    /// Returns the path data built so far.
+    pub fn data(&self) -> &PathData {
+        &self.data
+    }
+
+    /// Returns the finished path data.
+    pub fn finish(self) -> PathData {
+        self.data
+    }
+
+    /// Creates an ended `Path` for drawing.
+    pub fn build(&self, ctx: &DrawContext) -> Path {
+        self.data.build(ctx)
+    }
+
+    /// Starts a new figure at the current point, or at `fallback` if there is none.
+    fn ensure_figure(self, fallback: (f64, f64)) -> Self {
+        if self.in_figure {
+            return self;
+        }
+        let (x, y) = self.current.unwrap_or(fallback);
+        self.move_to(x, y)
+    }
+
+    /// Leaves the current figure open, so the next arc starts a new figure.
+    fn end_figure(mut self) -> Self {
+        self.in_figure = false;
+        self
+    }
+}
+
+impl From<PathBuilder> for PathData {
+    fn from(builder: PathBuilder) -> PathData {
+        builder.finish()
+    }
+}
+
+fn point_on_circle(x_center: f64, y_center: f64, radius: f64, angle: f64) -> (f64, f64) {
+    (
+        x_center + radius * angle.cos(),
+        y_center + radius * angle.sin(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_figures_where_needed() {
+        let data = PathBuilder::default()
+            .line_to(1.0, 1.0)
+            .line_to(2.0, 0.0)
+            .close()
+            .line_to(3.0, 3.0)
+            .finish();
+        assert_eq!(
+            data.commands,
+            [
+                PathCommand::NewFigure { x: 1.0, y: 1.0 },
+                PathCommand::LineTo { x: 2.0, y: 0.0 },
+                PathCommand::CloseFigure,
+                PathCommand::NewFigure { x: 1.0, y: 1.0 },
+                PathCommand::LineTo { x: 3.0, y: 3.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn measures_shapes() {
+        let (x, y, width, height) = PathBuilder::default()
+            .circle(10.0, 10.0, 5.0)
+            .finish()
+            .bounds()
+            .unwrap();
+        assert!((x - 5.0).abs() < 1e-3 && (y - 5.0).abs() < 1e-3);
+        assert!((width - 10.0).abs() < 2e-3 && (height - 10.0).abs() < 2e-3);
+
+        let ellipse = PathBuilder::default().ellipse(0.0, 0.0, 4.0, 2.0).finish();
+        assert!(ellipse.contains(3.9, 0.0));
+        assert!(!ellipse.contains(3.0, 1.9));
+        assert_eq!(PathBuilder::default().finish().bounds(), None);
+    }
+
+    #[test]
+    fn hit_tests_with_fill_mode() {
+        let ring = |fill_mode| {
+            PathBuilder::new(fill_mode)
+                .circle(0.0, 0.0, 10.0)
+                .circle(0.0, 0.0, 5.0)
+                .finish()
+        };
+        assert!(ring(FillMode::Winding).contains(0.0, 1.0));
+        assert!(!ring(FillMode::Alternate).contains(0.0, 1.0));
+        assert!(ring(FillMode::Alternate).contains(0.0, 7.0));
+    }
+
+    #[test]
+    fn flattens_rounded_rects() {
+        let polylines = PathBuilder::default()
+            .rounded_rect(0.0, 0.0, 20.0, 10.0, 3.0)
+            .finish()
+            .flatten(0.1);
+        assert_eq!(polylines.len(), 1);
+        assert!(polylines[0].closed);
+        assert!(!polylines[0].points.contains(&(0.0, 0.0)));
+    }
+
+    #[test]
+    fn flattens_with_zero_tolerance() {
+        let polylines = PathBuilder::default()
+            .circle(0.0, 0.0, 10.0)
+            .finish()
+            .flatten(0.0);
+        assert!(polylines[0].points.len() <= 257);
+    }
+
+    #[test]
+    fn spans_arcs_without_looping() {
+        let quarter = PI / 2.0;
+        assert!((arc_span(0.0, quarter, true) + 3.0 * quarter).abs() < 1e-9);
+        assert!((arc_span(0.0, -quarter, false) - 3.0 * quarter).abs() < 1e-9);
+        assert_eq!(arc_span(0.0, 4.0 * PI, false), 2.0 * PI);
+        assert_eq!(arc_span(1e17, 1.0, true), -(2.0 * PI - 1.0));
+        assert_eq!(arc_span(0.0, f64::INFINITY, false), 0.0);
+        assert_eq!(arc_span(f64::NAN, 1.0, true), 0.0);
+
+        let path = PathBuilder::default()
+            .arc(0.0, 0.0, 10.0, 1e17, f64::INFINITY, false)
+            .arc(0.0, 0.0, 10.0, 0.0, 1e20, true)
+            .finish();
+        assert!(path.bounds().is_some());
+    }
+}
@@ -0,0 +1,340 @@
+use draw::{DrawContext, FillMode, Path};
+use std::f64::consts::PI;
+
+/// The tolerance used when measuring paths.
+const BOUNDS_TOLERANCE: f64 = 0.001;
+
+/// The smallest tolerance used when flattening, so curves get a bounded number of lines.
+const MIN_TOLERANCE: f64 = 1e-6;
+
+/// The most lines a single arc or Bézier curve is flattened into.
+const MAX_SEGMENTS: f64 = 256.0;
+
+/// A figure of a path approximated by straight lines, see [`PathData::flatten()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<(f64, f64)>,
+    /// Whether the figure was closed, connecting the last point to the first.
+    pub closed: bool,
+}
+
+/// A single step of a path outline, mirroring the methods of [`Path`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PathCommand {
+    NewFigure {
+        x: f64,
+        y: f64,
+    },
+    NewFigureWithArc {
+        x_center: f64,
+        y_center: f64,
+        radius: f64,
+        start_angle: f64,
+        sweep: f64,
+        negative: bool,
+    },
+    LineTo {
+        x: f64,
+        y: f64,
+    },
+    ArcTo {
+        x_center: f64,
+        y_center: f64,
+        radius: f64,
+        start_angle: f64,
+        sweep: f64,
+        negative: bool,
+    },
+    BezierTo {
+        c1x: f64,
+        c1y: f64,
+        c2x: f64,
+        c2y: f64,
+        end_x: f64,
+        end_y: f64,
+    },
+    CloseFigure,
+    AddRectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+/// The outline of a path as plain data, independent of any `DrawContext`.
+///
+/// Unlike a [`Path`], which only lives within a draw callback, path data can be kept
+/// around, compared and sent to other threads. Use [`PathData::build()`] to turn it
+/// into a `Path` for drawing.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PathData {
+    pub fill_mode: FillMode,
+    pub commands: Vec<PathCommand>,
+}
+
+impl PathData {
+    /// Creates empty path data.
+    pub fn new(fill_mode: FillMode) -> PathData {
+        PathData {
+            fill_mode,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Creates path data consisting of a single rectangle.
+    pub fn rectangle(x: f64, y: f64, width: f64, height: f64) -> PathData {
+        let mut data = PathData::new(FillMode::Winding);
+        data.commands.push(PathCommand::AddRectangle {
+            x,
+            y,
+            width,
+            height,
+        });
+        data
+    }
+
+    /// Creates an ended `Path` with the outline described by this data.
+    pub fn build(&self, ctx: &DrawContext) -> Path {
+        let path = Path::new(ctx, self.fill_mode);
+        for command in &self.commands {
+            match *command {
+                PathCommand::NewFigure { x, y } => path.new_figure(ctx, x, y),
+                PathCommand::NewFigureWithArc {
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                } => path.new_figure_with_arc(
+                    ctx,
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                ),
+                PathCommand::LineTo { x, y } => path.line_to(ctx, x, y),
+                PathCommand::ArcTo {
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                } => path.arc_to(
+                    ctx,
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                ),
+                PathCommand::BezierTo {
+                    c1x,
+                    c1y,
+                    c2x,
+                    c2y,
+                    end_x,
+                    end_y,
+                } => path.bezier_to(ctx, c1x, c1y, c2x, c2y, end_x, end_y),
+                PathCommand::CloseFigure => path.close_figure(ctx),
+                PathCommand::AddRectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => path.add_rectangle(ctx, x, y, width, height),
+            }
+        }
+        path.end(ctx);
+        path
+    }
+
+    /// Approximates the outline with straight lines, one polyline per figure.
+    ///
+    /// `tolerance` is the maximum distance between a curve and the lines replacing it.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Polyline> {
+        let mut polylines: Vec<Polyline> = Vec::new();
+        let mut current: Vec<(f64, f64)> = Vec::new();
+        let finish =
+            |current: &mut Vec<(f64, f64)>, closed: bool, polylines: &mut Vec<Polyline>| {
+                if !current.is_empty() {
+                    polylines.push(Polyline {
+                        points: current.split_off(0),
+                        closed,
+                    });
+                }
+            };
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::NewFigure { x, y } => {
+                    finish(&mut current, false, &mut polylines);
+                    current.push((x, y));
+                }
+                PathCommand::NewFigureWithArc {
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                } => {
+                    finish(&mut current, false, &mut polylines);
+                    let arc = (x_center, y_center, radius, start_angle, sweep, negative);
+                    flatten_arc(&mut current, arc, tolerance);
+                }
+                PathCommand::LineTo { x, y } => current.push((x, y)),
+                PathCommand::ArcTo {
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                } => {
+                    let arc = (x_center, y_center, radius, start_angle, sweep, negative);
+                    flatten_arc(&mut current, arc, tolerance);
+                }
+                PathCommand::BezierTo {
+                    c1x,
+                    c1y,
+                    c2x,
+                    c2y,
+                    end_x,
+                    end_y,
+                } => {
+                    let start = current.last().cloned().unwrap_or((c1x, c1y));
+                    let controls = [start, (c1x, c1y), (c2x, c2y), (end_x, end_y)];
+                    flatten_bezier(&mut current, controls, tolerance);
+                }
+                PathCommand::CloseFigure => finish(&mut current, true, &mut polylines),
+                PathCommand::AddRectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    finish(&mut current, false, &mut polylines);
+                    polylines.push(Polyline {
+                        points: vec![
+                            (x, y),
+                            (x + width, y),
+                            (x + width, y + height),
+                            (x, y + height),
+                        ],
+                        closed: true,
+                    });
+                }
+            }
+        }
+        finish(&mut current, false, &mut polylines);
+        polylines
+    }
+
+    /// Returns the bounding box `(x, y, width, height)` of the outline, or `None` if
+    /// the path is empty.
+    ///
+    /// Curves are measured by their flattened outline, so the box is exact to within
+    /// a thousandth of a unit.
+    pub fn bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let mut points = self
+            .flatten(BOUNDS_TOLERANCE)
+            .into_iter()
+            .flat_map(|p| p.points);
+        let (x, y) = points.next()?;
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (x, y, x, y);
+        for (x, y) in points {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        Some((min_x, min_y, max_x - min_x, max_y - min_y))
+    }
+
+    /// Returns true if the point lies inside the area filled by this path, respecting
+    /// its fill mode. Use this for hit testing.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let mut winding = 0;
+        for polyline in self.flatten(BOUNDS_TOLERANCE) {
+            let points = &polyline.points;
+            // Every figure is filled as if it was closed.
+            for (i, &(x0, y0)) in points.iter().enumerate() {
+                let (x1, y1) = points[(i + 1) % points.len()];
+                if (y0 <= y) != (y1 <= y) {
+                    let crossing = x0 + (y - y0) * (x1 - x0) / (y1 - y0);
+                    if crossing > x {
+                        winding += if y1 > y0 { 1 } else { -1 };
+                    }
+                }
+            }
+        }
+        match self.fill_mode {
+            FillMode::Winding => winding != 0,
+            FillMode::Alternate => winding % 2 != 0,
+        }
+    }
+}
+
+/// Appends an arc, following the conventions of `Path::arc_to()`.
+pub(crate) fn flatten_arc(
+    points: &mut Vec<(f64, f64)>,
+    (x_center, y_center, radius, start, sweep, negative): (f64, f64, f64, f64, f64, bool),
+    tolerance: f64,
+) {
+    let span = arc_span(start, sweep, negative);
+    let tolerance = tolerance.max(MIN_TOLERANCE);
+    let step = if radius > tolerance {
+        2.0 * (1.0 - tolerance / radius).acos()
+    } else {
+        PI / 2.0
+    };
+    let segments = (span.abs() / step).ceil().clamp(1.0, MAX_SEGMENTS) as usize;
+    for i in 0..=segments {
+        let angle = start + span * i as f64 / segments as f64;
+        points.push((
+            x_center + radius * angle.cos(),
+            y_center + radius * angle.sin(),
+        ));
+    }
+}
+
+fn flatten_bezier(points: &mut Vec<(f64, f64)>, c: [(f64, f64); 4], tolerance: f64) {
+    let length: f64 = (0..3)
+        .map(|i| ((c[i + 1].0 - c[i].0).powi(2) + (c[i + 1].1 - c[i].1).powi(2)).sqrt())
+        .sum();
+    let tolerance = tolerance.max(MIN_TOLERANCE);
+    let segments = (length / tolerance).sqrt().ceil().clamp(1.0, MAX_SEGMENTS) as usize;
+    for i in 1..=segments {
+        let t = i as f64 / segments as f64;
+        let u = 1.0 - t;
+        let (a, b, cc, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        points.push((
+            a * c[0].0 + b * c[1].0 + cc * c[2].0 + d * c[3].0,
+            a * c[0].1 + b * c[1].1 + cc * c[2].1 + d * c[3].1,
+        ));
+    }
+}
+
+/// Returns the signed angle covered by an arc, following the conventions of `Path::arc_to()`:
+/// the arc ends at `start + sweep`, reached by turning in the positive direction unless
+/// `negative` is set, and never covers more than a full circle.
+pub(crate) fn arc_span(start: f64, sweep: f64, negative: bool) -> f64 {
+    if !start.is_finite() || !sweep.is_finite() {
+        return 0.0;
+    }
+    let span = match (negative, sweep) {
+        (false, sweep) if sweep >= 0.0 => sweep,
+        (false, sweep) => sweep.rem_euclid(2.0 * PI),
+        (true, sweep) if sweep <= 0.0 => sweep,
+        (true, sweep) => -(-sweep).rem_euclid(2.0 * PI),
+    };
+    span.clamp(-2.0 * PI, 2.0 * PI)
+}
@@ -0,0 +1,895 @@
+use draw::pathdata::flatten_arc;
+use draw::png;
+use draw::{
+    Brush, BrushGradientStop, Canvas, FillMode, PathData, StrokeParams, TextLayoutParams, Transform,
+};
+use libui_ffi;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+type Point = (f64, f64);
+
+/// Vertical samples taken per pixel row when computing coverage.
+const SUBSAMPLES: usize = 4;
+
+/// The maximum distance, in pixels, between a curve and its flattened outline.
+const TOLERANCE: f64 = 0.1;
+
+/// An in-memory canvas that draws with antialiasing into an RGBA image.
+///
+/// The rasterizer implements [`Canvas`] without any window or platform drawing code, so
+/// drawing code written against the trait can render thumbnails, export images or be
+/// compared against golden images in tests. Text is not supported and is skipped.
+///
+/// ```
+/// # use libui::draw::*;
+/// let mut image = Rasterizer::new(4, 4);
+/// image.clear(1.0, 1.0, 1.0, 1.0);
+/// image.fill(
+///     &PathData::rectangle(0.0, 0.0, 2.0, 4.0),
+///     &Brush::Solid(Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 }),
+/// );
+/// assert_eq!(image.pixel(0, 0), [255, 0, 0, 255]);
+/// assert_eq!(image.pixel(3, 0), [255, 255, 255, 255]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rasterizer {
+    width: u32,
+    height: u32,
+    /// Premultiplied RGBA, row by row.
+    pixels: Vec<[f32; 4]>,
+    state: State,
+    saved: Vec<State>,
+}
+
+#[derive(Clone, Debug)]
+struct State {
+    ctm: Transform,
+    /// The coverage of the clip for every pixel, or `None` if nothing is clipped.
+    clip: Option<Vec<f32>>,
+}
+
+/// The coverage of a shape within the bounding box of the affected pixels.
+struct Coverage {
+    x0: usize,
+    y0: usize,
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl Rasterizer {
+    /// Creates a fully transparent image of the given size in pixels.
+    pub fn new(width: u32, height: u32) -> Rasterizer {
+        Rasterizer {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+            state: State {
+                ctm: Transform::identity(),
+                clip: None,
+            },
+            saved: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Sets every pixel to the given color, ignoring the transform and clip.
+    pub fn clear(&mut self, r: f64, g: f64, b: f64, a: f64) {
+        let color = premultiply(r, g, b, a);
+        for pixel in &mut self.pixels {
+            *pixel = color;
+        }
+    }
+
+    /// Returns the color of a pixel as non-premultiplied RGBA.
+    ///
+    /// # Panics
+    /// Panics if the pixel lies outside of the image.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        assert!(
+            x < self.width && y < self.height,
+            "Pixel ({}, {}) is outside of the {}x{} image",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        let [r, g, b, a] = self.pixels[(y * self.width + x) as usize];
+        let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        if a <= 0.0 {
+            [0, 0, 0, 0]
+        } else {
+            [to_byte(r / a), to_byte(g / a), to_byte(b / a), to_byte(a)]
+        }
+    }
+
+    /// Returns the image as non-premultiplied RGBA bytes, row by row from the top.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.pixels.len() * 4);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                bytes.extend_from_slice(&self.pixel(x, y));
+            }
+        }
+        bytes
+    }
+
+    /// Encodes the image as PNG.
+    pub fn write_png<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        png::write_rgba(writer, self.width, self.height, &self.to_rgba8())
+    }
+
+    /// Saves the image as a PNG file.
+    pub fn save_png<P: AsRef<::std::path::Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_png(&mut writer)?;
+        writer.flush()
+    }
+
+    /// The scale from user space to pixels, used to choose how finely curves are flattened.
+    fn device_scale(&self) -> f64 {
+        self.state.ctm.determinant().abs().sqrt().max(1e-6)
+    }
+
+    /// Computes the coverage of polygons given in user space.
+    fn coverage(&self, polygons: &[Vec<Point>], fill_mode: FillMode) -> Option<Coverage> {
+        let ctm = self.state.ctm;
+        let mut edges = Vec::new();
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for polygon in polygons {
+            let points: Vec<Point> = polygon.iter().map(|&p| ctm.transform_point(p)).collect();
+            // Polygons with NaN or infinite points, e.g. from a degenerate transform, are dropped.
+            if points.iter().any(|&(x, y)| !x.is_finite() || !y.is_finite()) {
+                continue;
+            }
+            for (i, &(x0, y0)) in points.iter().enumerate() {
+                let (x1, y1) = points[(i + 1) % points.len()];
+                min_x = min_x.min(x0);
+                max_x = max_x.max(x0);
+                min_y = min_y.min(y0);
+                max_y = max_y.max(y0);
+                if y0 != y1 {
+                    edges.push((x0, y0, x1, y1));
+                }
+            }
+        }
+        if edges.is_empty() {
+            return None;
+        }
+
+        let clamp = |v: f64, max: u32| v.max(0.0).min(max as f64) as usize;
+        let (x0, x1) = (
+            clamp(min_x.floor(), self.width),
+            clamp(max_x.ceil(), self.width),
+        );
+        let (y0, y1) = (
+            clamp(min_y.floor(), self.height),
+            clamp(max_y.ceil(), self.height),
+        );
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        let mut coverage = Coverage {
+            x0,
+            y0,
+            width: x1 - x0,
+            height: y1 - y0,
+            values: vec![0.0; (x1 - x0) * (y1 - y0)],
+        };
+
+        let weight = 1.0 / SUBSAMPLES as f32;
+        let mut crossings = Vec::new();
+        for row in 0..coverage.height {
+            let values = &mut coverage.values[row * coverage.width..(row + 1) * coverage.width];
+            for sample in 0..SUBSAMPLES {
+                let y = (y0 + row) as f64 + (sample as f64 + 0.5) / SUBSAMPLES as f64;
+                crossings.clear();
+                for &(ex0, ey0, ex1, ey1) in &edges {
+                    if (ey0 <= y && y < ey1) || (ey1 <= y && y < ey0) {
+                        let x = ex0 + (y - ey0) * (ex1 - ex0) / (ey1 - ey0);
+                        crossings.push((x, if ey1 > ey0 { 1 } else { -1 }));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut winding = 0;
+                let mut span_start = 0.0;
+                for &(x, direction) in &crossings {
+                    let was_inside = is_inside(winding, fill_mode);
+                    winding += direction;
+                    let inside = is_inside(winding, fill_mode);
+                    if !was_inside && inside {
+                        span_start = x;
+                    } else if was_inside && !inside {
+                        let start = (span_start - x0 as f64).max(0.0);
+                        let end = (x - x0 as f64).min(coverage.width as f64);
+                        add_span(values, start, end, weight);
+                    }
+                }
+            }
+        }
+        Some(coverage)
+    }
+
+    fn composite(&mut self, coverage: &Coverage, brush: &Brush) {
+        let inverse = self.state.ctm.inverse();
+        for row in 0..coverage.height {
+            let y = coverage.y0 + row;
+            for column in 0..coverage.width {
+                let x = coverage.x0 + column;
+                let index = y * self.width as usize + x;
+                let mut alpha = coverage.values[row * coverage.width + column];
+                if let Some(ref clip) = self.state.clip {
+                    alpha *= clip[index];
+                }
+                if alpha <= 0.0 {
+                    continue;
+                }
+                let source = match inverse {
+                    Some(ref inverse) => paint(
+                        brush,
+                        inverse.transform_point((x as f64 + 0.5, y as f64 + 0.5)),
+                    ),
+                    None => continue,
+                };
+                let destination = &mut self.pixels[index];
+                let keep = 1.0 - source[3] * alpha.min(1.0);
+                for channel in 0..4 {
+                    destination[channel] =
+                        source[channel] * alpha.min(1.0) + destination[channel] * keep;
+                }
+            }
+        }
+    }
+
+    fn stroke_polygons(&self, path: &PathData, params: &StrokeParams) -> Vec<Vec<Point>> {
+        let half = params.thickness / 2.0;
+        let tolerance = TOLERANCE / self.device_scale();
+        let mut polygons = Vec::new();
+        if half <= 0.0 {
+            return polygons;
+        }
+
+        for polyline in path.flatten(TOLERANCE / self.device_scale()) {
+            let mut points = polyline.points;
+            points.dedup();
+            if polyline.closed && points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+
+            let pieces = if is_dashed(&params.dashes) {
+                let mut outline = points.clone();
+                if polyline.closed {
+                    outline.push(points[0]);
+                }
+                dash(&outline, &params.dashes, params.dash_phase)
+                    .into_iter()
+                    .map(|piece| (piece, false))
+                    .collect()
+            } else {
+                vec![(points, polyline.closed)]
+            };
+
+            for (points, closed) in pieces {
+                stroke_polyline(&mut polygons, &points, closed, half, params, tolerance);
+            }
+        }
+
+        for polygon in &mut polygons {
+            if signed_area(polygon) < 0.0 {
+                polygon.reverse();
+            }
+        }
+        polygons
+    }
+}
+
+impl Canvas for Rasterizer {
+    fn fill(&mut self, path: &PathData, brush: &Brush) {
+        let polygons: Vec<Vec<Point>> = path
+            .flatten(TOLERANCE / self.device_scale())
+            .into_iter()
+            .map(|polyline| polyline.points)
+            .collect();
+        if let Some(coverage) = self.coverage(&polygons, path.fill_mode) {
+            self.composite(&coverage, brush);
+        }
+    }
+
+    fn stroke(&mut self, path: &PathData, brush: &Brush, params: &StrokeParams) {
+        let polygons = self.stroke_polygons(path, params);
+        if let Some(coverage) = self.coverage(&polygons, FillMode::Winding) {
+            self.composite(&coverage, brush);
+        }
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        self.state.ctm = *transform * self.state.ctm;
+    }
+
+    fn clip(&mut self, path: &PathData) {
+        let polygons: Vec<Vec<Point>> = path
+            .flatten(TOLERANCE / self.device_scale())
+            .into_iter()
+            .map(|polyline| polyline.points)
+            .collect();
+        let coverage = self.coverage(&polygons, path.fill_mode);
+        let width = self.width as usize;
+        let mut clip = self
+            .state
+            .clip
+            .take()
+            .unwrap_or_else(|| vec![1.0; self.pixels.len()]);
+        for (index, value) in clip.iter_mut().enumerate() {
+            let (x, y) = (index % width, index / width);
+            *value *= match coverage {
+                Some(ref c)
+                    if x >= c.x0 && x < c.x0 + c.width && y >= c.y0 && y < c.y0 + c.height =>
+                {
+                    c.values[(y - c.y0) * c.width + x - c.x0].min(1.0)
+                }
+                _ => 0.0,
+            };
+        }
+        self.state.clip = Some(clip);
+    }
+
+    fn save(&mut self) {
+        self.saved.push(self.state.clone());
+    }
+
+    /// # Panics
+    /// Panics if there is no matching `save()`.
+    fn restore(&mut self) {
+        self.state = self.saved.pop().expect("restore() without matching save()");
+    }
+
+    /// Does nothing, as the rasterizer has no access to fonts.
+    fn draw_text(&mut self, _layout: &TextLayoutParams, _x: f64, _y: f64) {}
+}
+
+fn is_inside(winding: i32, fill_mode: FillMode) -> bool {
+    match fill_mode {
+        FillMode::Winding => winding != 0,
+        FillMode::Alternate => winding % 2 != 0,
+    }
+}
+
+/// Adds the horizontal coverage of the span `start..end` to a row of pixels.
+fn add_span(values: &mut [f32], start: f64, end: f64, weight: f32) {
+    if start >= end {
+        return;
+    }
+    let first = start.floor() as usize;
+    let last = (end.ceil() as usize).min(values.len());
+    for (pixel, value) in values.iter_mut().enumerate().take(last).skip(first) {
+        let overlap = end.min(pixel as f64 + 1.0) - start.max(pixel as f64);
+        *value += overlap as f32 * weight;
+    }
+}
+
+/// Returns whether a dash pattern dashes lines at all. Patterns with negative or
+/// non-finite lengths, or without any length, draw solid lines.
+fn is_dashed(dashes: &[f64]) -> bool {
+    let total: f64 = dashes.iter().sum();
+    dashes.iter().all(|&d| d >= 0.0) && total > 0.0 && total.is_finite()
+}
+
+/// Splits a polyline into its dashes.
+fn dash(points: &[Point], dashes: &[f64], phase: f64) -> Vec<Vec<Point>> {
+    let mut pattern = dashes.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(dashes);
+    }
+    let total: f64 = pattern.iter().sum();
+
+    let mut index = 0;
+    let mut remaining = pattern[0];
+    let mut offset = phase.rem_euclid(total);
+    while offset > 0.0 {
+        if offset < remaining {
+            remaining -= offset;
+            break;
+        }
+        offset -= remaining;
+        index = (index + 1) % pattern.len();
+        remaining = pattern[index];
+    }
+
+    let mut pieces = Vec::new();
+    let mut piece = Vec::new();
+    if index % 2 == 0 && !points.is_empty() {
+        piece.push(points[0]);
+    }
+    for pair in points.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let length = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+        let mut position = 0.0;
+        while length - position > remaining {
+            position += remaining;
+            let t = position / length;
+            let point = (
+                start.0 + (end.0 - start.0) * t,
+                start.1 + (end.1 - start.1) * t,
+            );
+            if index % 2 == 0 {
+                piece.push(point);
+                pieces.push(piece.split_off(0));
+            } else {
+                piece.push(point);
+            }
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= length - position;
+        if index % 2 == 0 {
+            piece.push(end);
+        }
+    }
+    if piece.len() > 1 {
+        pieces.push(piece);
+    }
+    pieces
+}
+
+/// Adds the polygons making up the stroke of a polyline, given in user space.
+fn stroke_polyline(
+    polygons: &mut Vec<Vec<Point>>,
+    points: &[Point],
+    closed: bool,
+    half: f64,
+    params: &StrokeParams,
+    tolerance: f64,
+) {
+    if points.len() < 2 {
+        if let (Some(&point), libui_ffi::uiDrawLineCapRound) = (points.first(), params.cap) {
+            polygons.push(circle(point, half, tolerance));
+        }
+        return;
+    }
+
+    let segment_count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let segment = |i: usize| (points[i], points[(i + 1) % points.len()]);
+    for i in 0..segment_count {
+        let (start, end) = segment(i);
+        let (nx, ny) = normal(start, end, half);
+        polygons.push(vec![
+            (start.0 + nx, start.1 + ny),
+            (end.0 + nx, end.1 + ny),
+            (end.0 - nx, end.1 - ny),
+            (start.0 - nx, start.1 - ny),
+        ]);
+    }
+
+    let joins = if closed {
+        0..points.len()
+    } else {
+        1..points.len() - 1
+    };
+    for i in joins {
+        let previous = segment((i + points.len() - 1) % points.len());
+        let next = segment(i);
+        join(polygons, previous, next, half, params, tolerance);
+    }
+
+    if !closed {
+        cap(polygons, points[1], points[0], half, params.cap, tolerance);
+        let n = points.len();
+        cap(
+            polygons,
+            points[n - 2],
+            points[n - 1],
+            half,
+            params.cap,
+            tolerance,
+        );
+    }
+}
+
+/// Adds the join between the segments `previous` and `next`, which meet at `next.0`.
+fn join(
+    polygons: &mut Vec<Vec<Point>>,
+    previous: (Point, Point),
+    next: (Point, Point),
+    half: f64,
+    params: &StrokeParams,
+    tolerance: f64,
+) {
+    let vertex = next.0;
+    let d1 = (previous.1 .0 - previous.0 .0, previous.1 .1 - previous.0 .1);
+    let d2 = (next.1 .0 - next.0 .0, next.1 .1 - next.0 .1);
+    let cross = d1.0 * d2.1 - d1.1 * d2.0;
+    if cross == 0.0 && d1.0 * d2.0 + d1.1 * d2.1 >= 0.0 {
+        return;
+    }
+
+    if params.join == libui_ffi::uiDrawLineJoinRound {
+        polygons.push(circle(vertex, half, tolerance));
+        return;
+    }
+
+    // The offsets towards the outside of the turn.
+    let side = if cross > 0.0 { -1.0 } else { 1.0 };
+    let n1 = normal(previous.0, previous.1, half);
+    let n2 = normal(next.0, next.1, half);
+    let o1 = (n1.0 * side, n1.1 * side);
+    let o2 = (n2.0 * side, n2.1 * side);
+    let outer1 = (vertex.0 + o1.0, vertex.1 + o1.1);
+    let outer2 = (vertex.0 + o2.0, vertex.1 + o2.1);
+
+    let dot = o1.0 * o2.0 + o1.1 * o2.1;
+    if params.join == libui_ffi::uiDrawLineJoinMiter && half * half + dot > 0.0 {
+        let scale = half * half / (half * half + dot);
+        let miter = ((o1.0 + o2.0) * scale, (o1.1 + o2.1) * scale);
+        if (miter.0 * miter.0 + miter.1 * miter.1).sqrt() / half <= params.miter_limit {
+            polygons.push(vec![
+                vertex,
+                outer1,
+                (vertex.0 + miter.0, vertex.1 + miter.1),
+                outer2,
+            ]);
+            return;
+        }
+    }
+    polygons.push(vec![vertex, outer1, outer2]);
+}
+
+/// Adds the cap at `end` of the segment from `start` to `end`.
+fn cap(
+    polygons: &mut Vec<Vec<Point>>,
+    start: Point,
+    end: Point,
+    half: f64,
+    style: u32,
+    tolerance: f64,
+) {
+    match style {
+        libui_ffi::uiDrawLineCapRound => polygons.push(circle(end, half, tolerance)),
+        libui_ffi::uiDrawLineCapSquare => {
+            let (nx, ny) = normal(start, end, half);
+            // The normal rotated back is the direction of the segment.
+            let (dx, dy) = (ny, -nx);
+            polygons.push(vec![
+                (end.0 + nx, end.1 + ny),
+                (end.0 + nx + dx, end.1 + ny + dy),
+                (end.0 - nx + dx, end.1 - ny + dy),
+                (end.0 - nx, end.1 - ny),
+            ]);
+        }
+        _ => {}
+    }
+}
+
+/// Returns the normal of a segment with the given length.
+fn normal(start: Point, end: Point, length: f64) -> Point {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let norm = (dx * dx + dy * dy).sqrt();
+    (-dy / norm * length, dx / norm * length)
+}
+
+fn circle(center: Point, radius: f64, tolerance: f64) -> Vec<Point> {
+    let mut points = Vec::new();
+    flatten_arc(
+        &mut points,
+        (center.0, center.1, radius, 0.0, 2.0 * PI, false),
+        tolerance,
+    );
+    points.pop();
+    points
+}
+
+fn signed_area(polygon: &[Point]) -> f64 {
+    let mut area = 0.0;
+    for (i, &(x0, y0)) in polygon.iter().enumerate() {
+        let (x1, y1) = polygon[(i + 1) % polygon.len()];
+        area += x0 * y1 - x1 * y0;
+    }
+    area / 2.0
+}
+
+fn premultiply(r: f64, g: f64, b: f64, a: f64) -> [f32; 4] {
+    [(r * a) as f32, (g * a) as f32, (b * a) as f32, a as f32]
+}
+
+/// Returns the premultiplied color of a brush at a point in user space.
+fn paint(brush: &Brush, (x, y): Point) -> [f32; 4] {
+    match *brush {
+        Brush::Solid(ref solid) => premultiply(solid.r, solid.g, solid.b, solid.a),
+        Brush::LinearGradient(ref linear) => {
+            let (dx, dy) = (linear.end_x - linear.start_x, linear.end_y - linear.start_y);
+            let length = dx * dx + dy * dy;
+            let t = if length > 0.0 {
+                ((x - linear.start_x) * dx + (y - linear.start_y) * dy) / length
+            } else {
+                0.0
+            };
+            gradient_color(&linear.stops, t)
+        }
+        Brush::RadialGradient(ref radial) => {
+            // Solve |p - c(t)| = r(t) for the circles interpolated between the start point
+            // and the outer circle, taking the largest t with a non-negative radius.
+            let (cx, cy) = (
+                radial.outer_circle_center_x - radial.start_x,
+                radial.outer_circle_center_y - radial.start_y,
+            );
+            let (px, py) = (x - radial.start_x, y - radial.start_y);
+            let radius = radial.outer_radius;
+            let a = cx * cx + cy * cy - radius * radius;
+            let b = px * cx + py * cy;
+            let c = px * px + py * py;
+            let t = if a.abs() < 1e-12 {
+                if b == 0.0 {
+                    return [0.0; 4];
+                }
+                c / (2.0 * b)
+            } else {
+                let discriminant = b * b - a * c;
+                if discriminant < 0.0 {
+                    return [0.0; 4];
+                }
+                let root = discriminant.sqrt();
+                let (t1, t2) = ((b + root) / a, (b - root) / a);
+                let t = t1.max(t2);
+                if t * radius >= 0.0 {
+                    t
+                } else {
+                    t1.min(t2)
+                }
+            };
+            if t * radius < 0.0 {
+                return [0.0; 4];
+            }
+            gradient_color(&radial.stops, t)
+        }
+        Brush::Image(ref image) => {
+            let color = image.color_at(x, y);
+            premultiply(color.r, color.g, color.b, color.a)
+        }
+    }
+}
+
+/// Interpolates between the stops of a gradient, padding beyond the first and last.
+fn gradient_color(stops: &[BrushGradientStop], t: f64) -> [f32; 4] {
+    let color = |stop: &BrushGradientStop| premultiply(stop.R, stop.G, stop.B, stop.A);
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return [0.0; 4],
+    };
+    if t <= first.Pos {
+        return color(first);
+    }
+    for pair in stops.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        if t <= to.Pos {
+            let span = to.Pos - from.Pos;
+            let f = if span > 0.0 {
+                ((t - from.Pos) / span) as f32
+            } else {
+                1.0
+            };
+            let (a, b) = (color(from), color(to));
+            return [
+                a[0] + (b[0] - a[0]) * f,
+                a[1] + (b[1] - a[1]) * f,
+                a[2] + (b[2] - a[2]) * f,
+                a[3] + (b[3] - a[3]) * f,
+            ];
+        }
+    }
+    color(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{Color, ImageBrush, LinearGradientBrush, PathCommand};
+
+    fn solid(r: f64, g: f64, b: f64) -> Brush {
+        Brush::Solid(Color::rgb(r, g, b))
+    }
+
+    fn stroke_params(thickness: f64) -> StrokeParams {
+        StrokeParams {
+            join: libui_ffi::uiDrawLineJoinMiter,
+            ..StrokeParams::new(thickness)
+        }
+    }
+
+    #[test]
+    fn fills_rectangles_with_antialiasing() {
+        let mut image = Rasterizer::new(8, 8);
+        image.fill(
+            &PathData::rectangle(2.0, 2.0, 3.5, 4.0),
+            &solid(0.0, 0.0, 1.0),
+        );
+        assert_eq!(image.pixel(1, 3), [0, 0, 0, 0]);
+        assert_eq!(image.pixel(2, 2), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(5, 3), [0, 0, 255, 128]);
+        assert_eq!(image.pixel(2, 6), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn clips_until_restored() {
+        let mut image = Rasterizer::new(8, 8);
+        image.saved(|image| {
+            image.clip(&PathData::rectangle(0.0, 0.0, 4.0, 8.0));
+            image.fill(
+                &PathData::rectangle(0.0, 0.0, 8.0, 4.0),
+                &solid(1.0, 0.0, 0.0),
+            );
+        });
+        image.fill(
+            &PathData::rectangle(0.0, 4.0, 8.0, 4.0),
+            &solid(0.0, 1.0, 0.0),
+        );
+        assert_eq!(image.pixel(1, 1), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(6, 1), [0, 0, 0, 0]);
+        assert_eq!(image.pixel(6, 6), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn restores_when_saved_drawing_panics() {
+        let mut image = Rasterizer::new(8, 8);
+        let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+            image.saved(|image| {
+                image.clip(&PathData::rectangle(0.0, 0.0, 4.0, 8.0));
+                panic!("drawing failed");
+            })
+        }));
+        assert!(result.is_err());
+        image.fill(
+            &PathData::rectangle(0.0, 0.0, 8.0, 8.0),
+            &solid(0.0, 1.0, 0.0),
+        );
+        assert_eq!(image.pixel(6, 6), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn fills_with_image_patterns() {
+        let pattern = ImageBrush::new(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        let mut image = Rasterizer::new(8, 2);
+        image.transform(&Transform::from_matrix([2.0, 0.0, 0.0, 2.0, 0.0, 0.0]));
+        image.fill(
+            &PathData::rectangle(0.0, 0.0, 4.0, 1.0),
+            &Brush::Image(pattern),
+        );
+        assert_eq!(image.pixel(1, 1), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(2, 0), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(4, 0), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn applies_transforms() {
+        let mut image = Rasterizer::new(8, 8);
+        image.transform(&Transform::from_matrix([2.0, 0.0, 0.0, 2.0, 0.0, 0.0]));
+        image.transform(&Transform::from_matrix([1.0, 0.0, 0.0, 1.0, 1.0, 0.0]));
+        image.fill(
+            &PathData::rectangle(0.0, 0.0, 1.0, 1.0),
+            &solid(1.0, 1.0, 1.0),
+        );
+        assert_eq!(image.pixel(1, 1), [0, 0, 0, 0]);
+        assert_eq!(image.pixel(2, 1), [255, 255, 255, 255]);
+        assert_eq!(image.pixel(3, 1), [255, 255, 255, 255]);
+        assert_eq!(image.pixel(4, 1), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn strokes_lines() {
+        let mut path = PathData::new(FillMode::Winding);
+        path.commands
+            .push(PathCommand::NewFigure { x: 1.0, y: 4.0 });
+        path.commands.push(PathCommand::LineTo { x: 7.0, y: 4.0 });
+        let mut image = Rasterizer::new(8, 8);
+        image.stroke(&path, &solid(0.0, 0.0, 0.0), &stroke_params(2.0));
+        assert_eq!(image.pixel(4, 3), [0, 0, 0, 255]);
+        assert_eq!(image.pixel(4, 4), [0, 0, 0, 255]);
+        assert_eq!(image.pixel(4, 5), [0, 0, 0, 0]);
+        assert_eq!(image.pixel(0, 4), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn strokes_invalid_dash_patterns_solid() {
+        let mut path = PathData::new(FillMode::Winding);
+        path.commands
+            .push(PathCommand::NewFigure { x: 0.0, y: 4.0 });
+        path.commands.push(PathCommand::LineTo { x: 8.0, y: 4.0 });
+        for dashes in &[vec![5.0, -5.0], vec![0.0, 0.0], vec![f64::NAN, 1.0]] {
+            let mut image = Rasterizer::new(8, 8);
+            let params = StrokeParams {
+                dashes: dashes.clone(),
+                ..stroke_params(2.0)
+            };
+            image.stroke(&path, &solid(0.0, 0.0, 0.0), &params);
+            assert_eq!(image.pixel(6, 4), [0, 0, 0, 255]);
+        }
+        let mut image = Rasterizer::new(8, 8);
+        let params = StrokeParams {
+            dashes: vec![4.0],
+            ..stroke_params(2.0)
+        };
+        image.stroke(&path, &solid(0.0, 0.0, 0.0), &params);
+        assert_eq!(image.pixel(2, 4), [0, 0, 0, 255]);
+        assert_eq!(image.pixel(6, 4), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn respects_fill_modes() {
+        let mut path = PathData::rectangle(0.0, 0.0, 8.0, 8.0);
+        path.commands.push(PathCommand::AddRectangle {
+            x: 2.0,
+            y: 2.0,
+            width: 4.0,
+            height: 4.0,
+        });
+        let mut image = Rasterizer::new(8, 8);
+        image.fill(&path, &solid(1.0, 0.0, 0.0));
+        assert_eq!(image.pixel(4, 4), [255, 0, 0, 255]);
+
+        path.fill_mode = FillMode::Alternate;
+        let mut image = Rasterizer::new(8, 8);
+        image.fill(&path, &solid(1.0, 0.0, 0.0));
+        assert_eq!(image.pixel(4, 4), [0, 0, 0, 0]);
+        assert_eq!(image.pixel(1, 4), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn interpolates_linear_gradients() {
+        let stop = |pos, r| BrushGradientStop {
+            Pos: pos,
+            R: r,
+            G: 0.0,
+            B: 0.0,
+            A: 1.0,
+        };
+        let brush = Brush::LinearGradient(LinearGradientBrush {
+            start_x: 0.0,
+            start_y: 0.0,
+            end_x: 10.0,
+            end_y: 0.0,
+            stops: vec![stop(0.0, 0.0), stop(1.0, 1.0)],
+        });
+        let mut image = Rasterizer::new(10, 1);
+        image.fill(&PathData::rectangle(0.0, 0.0, 10.0, 1.0), &brush);
+        assert_eq!(image.pixel(0, 0), [13, 0, 0, 255]);
+        assert_eq!(image.pixel(9, 0), [242, 0, 0, 255]);
+    }
+
+    #[test]
+    fn skips_non_finite_points() {
+        let mut image = Rasterizer::new(8, 8);
+        let mut path = PathData::rectangle(2.0, 2.0, 4.0, 4.0);
+        path.commands.extend(vec![
+            PathCommand::NewFigure { x: 0.0, y: 0.0 },
+            PathCommand::LineTo {
+                x: f64::NAN,
+                y: 8.0,
+            },
+            PathCommand::LineTo { x: 8.0, y: 8.0 },
+            PathCommand::CloseFigure,
+        ]);
+        image.fill(&path, &solid(0.0, 0.0, 1.0));
+        assert_eq!(image.pixel(3, 3), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn encodes_png() {
+        let mut png = Vec::new();
+        Rasterizer::new(3, 2).write_png(&mut png).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        assert_eq!(&png[12..16], b"IHDR");
+    }
+}
@@ -0,0 +1,240 @@
+use draw::{Brush, DrawContext, PathData, StrokeParams, TextLayoutParams, Transform};
+
+/// A drawing operation stored in a [`DisplayList`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DrawCommand {
+    Fill {
+        path: PathData,
+        brush: Brush,
+    },
+    Stroke {
+        path: PathData,
+        brush: Brush,
+        params: StrokeParams,
+    },
+    Transform(Transform),
+    Clip(PathData),
+    Save,
+    Restore,
+    Text {
+        layout: TextLayoutParams,
+        x: f64,
+        y: f64,
+    },
+}
+
+/// A recorded sequence of drawing operations, created by a [`Recorder`].
+///
+/// Display lists are plain data: they can be kept to redraw expensive scenes quickly,
+/// compared in tests or sent to other threads, and are serializable with the `serde` feature.
+/// Every save in a display list is matched by a restore.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    /// Returns the recorded commands in drawing order.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Draws the recorded commands onto a DrawContext.
+    ///
+    /// Transforms and clips of the display list do not affect later drawing on the context.
+    pub fn replay(&self, ctx: &DrawContext) {
+        let mut canvas = ctx;
+        self.replay_to(&mut canvas);
+    }
+}
+
+/// Records drawing operations into a [`DisplayList`] instead of drawing them.
+///
+/// A recorder offers the drawing operations of a [`DrawContext`], but works anywhere,
+/// not just inside a draw callback. Paths and texts are passed as plain data.
+///
+/// ```
+/// # use libui::draw::*;
+/// let mut recorder = Recorder::new();
+/// recorder.saved(|r| {
+///     r.clip(&PathData::rectangle(0.0, 0.0, 100.0, 100.0));
+///     r.fill(
+///         &PathData::rectangle(10.0, 10.0, 200.0, 50.0),
+///         &Brush::Solid(Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 }),
+///     );
+/// });
+/// let list = recorder.finish();
+/// assert_eq!(list.len(), 4);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Recorder {
+    list: DisplayList,
+    depth: usize,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    /// Records filling the given path with the given brush.
+    pub fn fill(&mut self, path: &PathData, brush: &Brush) {
+        self.push(DrawCommand::Fill {
+            path: path.clone(),
+            brush: brush.clone(),
+        });
+    }
+
+    /// Records a stroke along the given path.
+    pub fn stroke(&mut self, path: &PathData, brush: &Brush, params: &StrokeParams) {
+        self.push(DrawCommand::Stroke {
+            path: path.clone(),
+            brush: brush.clone(),
+            params: params.clone(),
+        });
+    }
+
+    /// Records transforming all following operations.
+    pub fn transform(&mut self, transform: &Transform) {
+        self.push(DrawCommand::Transform(*transform));
+    }
+
+    /// Records restricting all following operations to the inside of the given path.
+    pub fn clip(&mut self, path: &PathData) {
+        self.push(DrawCommand::Clip(path.clone()));
+    }
+
+    /// Records drawing text with its top left corner at the given position.
+    pub fn draw_text(&mut self, layout: &TextLayoutParams, x: f64, y: f64) {
+        self.push(DrawCommand::Text {
+            layout: layout.clone(),
+            x,
+            y,
+        });
+    }
+
+    /// Records saving the current transform and clip.
+    pub fn save(&mut self) {
+        self.depth += 1;
+        self.push(DrawCommand::Save);
+    }
+
+    /// Records restoring the transform and clip of the matching `save()`.
+    ///
+    /// # Panics
+    /// Panics if there is no matching `save()`.
+    pub fn restore(&mut self) {
+        assert!(self.depth > 0, "restore() without matching save()");
+        self.depth -= 1;
+        self.push(DrawCommand::Restore);
+    }
+
+    /// Records the operations of `f` between a save and its restore.
+    pub fn saved<R, F: FnOnce(&mut Recorder) -> R>(&mut self, f: F) -> R {
+        self.save();
+        let result = f(self);
+        self.restore();
+        result
+    }
+
+    /// Appends the commands of a display list, e.g. a cached part of a scene.
+    pub fn append(&mut self, list: &DisplayList) {
+        self.list.commands.extend_from_slice(&list.commands);
+    }
+
+    /// Returns the recorded display list, restoring any unmatched saves.
+    pub fn finish(mut self) -> DisplayList {
+        while self.depth > 0 {
+            self.restore();
+        }
+        self.list
+    }
+
+    fn push(&mut self, command: DrawCommand) {
+        self.list.commands.push(command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{Color, FillMode, PathCommand};
+
+    fn red() -> Brush {
+        Brush::Solid(Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        })
+    }
+
+    #[test]
+    fn records_commands_in_order() {
+        let mut path = PathData::new(FillMode::Alternate);
+        path.commands
+            .push(PathCommand::NewFigure { x: 0.0, y: 0.0 });
+        path.commands.push(PathCommand::LineTo { x: 5.0, y: 5.0 });
+
+        let mut recorder = Recorder::new();
+        recorder.transform(&Transform::from_matrix([2.0, 0.0, 0.0, 2.0, 1.0, 1.0]));
+        recorder.fill(&path, &red());
+        let list = recorder.finish();
+
+        assert_eq!(
+            list.commands(),
+            [
+                DrawCommand::Transform(Transform::from_matrix([2.0, 0.0, 0.0, 2.0, 1.0, 1.0])),
+                DrawCommand::Fill { path, brush: red() },
+            ]
+        );
+    }
+
+    #[test]
+    fn finish_balances_saves() {
+        let mut recorder = Recorder::new();
+        recorder.save();
+        recorder.save();
+        recorder.restore();
+        let list = recorder.finish();
+        assert_eq!(list.commands().last(), Some(&DrawCommand::Restore));
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "without matching save")]
+    fn unmatched_restore_panics() {
+        Recorder::new().restore();
+    }
+
+    #[test]
+    fn display_lists_can_be_sent() {
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&Recorder::new().finish());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn display_lists_roundtrip_through_serde() {
+        extern crate serde_json;
+
+        let mut recorder = Recorder::new();
+        recorder.saved(|r| {
+            r.clip(&PathData::rectangle(0.0, 0.0, 10.0, 10.0));
+            r.fill(&PathData::rectangle(1.0, 1.0, 2.0, 2.0), &red());
+        });
+        let list = recorder.finish();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(serde_json::from_str::<DisplayList>(&json).unwrap(), list);
+    }
+}
@@ -1,12 +1,13 @@
 use draw::DrawContext;
 use std::marker::PhantomData;
 use std::os::raw::c_double;
-use libui_ffi::uiDrawStrokeParams;
+use libui_ffi::{self, uiDrawStrokeParams};
 
 pub use libui_ffi::uiDrawLineCap as LineCap;
 pub use libui_ffi::uiDrawLineJoin as LineJoin;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StrokeParams {
     pub cap: LineCap,
     pub join: LineJoin,
@@ -23,6 +24,19 @@ pub struct StrokeParamsRef<'a> {
 }
 
 impl StrokeParams {
+    /// Creates parameters for undashed lines of a thickness, with flat caps and round
+    /// joins.
+    pub fn new(thickness: f64) -> StrokeParams {
+        StrokeParams {
+            cap: libui_ffi::uiDrawLineCapFlat,
+            join: libui_ffi::uiDrawLineJoinRound,
+            thickness,
+            miter_limit: libui_ffi::uiDrawDefaultMiterLimit,
+            dashes: Vec::new(),
+            dash_phase: 0.0,
+        }
+    }
+
     pub fn as_stroke_params_ref(&self, _ctx: &DrawContext) -> StrokeParamsRef {
         StrokeParamsRef {
             ui_draw_stroke_params: uiDrawStrokeParams {
@@ -0,0 +1,1008 @@
+use controls::{SlantStyle, StretchStyle};
+use draw::pathdata::arc_span;
+use draw::png;
+use draw::{
+    Brush, BrushGradientStop, Canvas, Color, DisplayList, FillMode, ImageBrush, ImageRepeat,
+    PathCommand, PathData, StrokeParams, TextAlign, TextAttribute, TextLayoutParams, Transform,
+    Underline,
+};
+use libui_ffi;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt::{self, Display, Write};
+
+/// A canvas that writes the drawing operations into an SVG document.
+///
+/// Paths, solid, gradient and image brushes, stroke styles, transforms and clips are written
+/// as their SVG equivalents. Text is written as `<text>` elements without wrapping.
+///
+/// ```
+/// # use libui::draw::*;
+/// let mut svg = SvgWriter::new(100.0, 50.0);
+/// svg.fill(
+///     &PathData::rectangle(10.0, 10.0, 80.0, 30.0),
+///     &Brush::Solid(Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 }),
+/// );
+/// let document = svg.finish();
+/// assert!(document.contains(r##"<path d="M10 10H90V40H10Z" fill="#ff0000"/>"##));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SvgWriter {
+    width: f64,
+    height: f64,
+    defs: String,
+    body: String,
+    next_id: usize,
+    state: SvgState,
+    saved: Vec<SvgState>,
+}
+
+#[derive(Clone, Debug)]
+struct SvgState {
+    ctm: Transform,
+    /// The id of the `<clipPath>` currently in effect.
+    clip: Option<usize>,
+}
+
+impl SvgWriter {
+    /// Creates an empty document of the given size.
+    pub fn new(width: f64, height: f64) -> SvgWriter {
+        SvgWriter {
+            width,
+            height,
+            defs: String::new(),
+            body: String::new(),
+            next_id: 0,
+            state: SvgState {
+                ctm: Transform::identity(),
+                clip: None,
+            },
+            saved: Vec::new(),
+        }
+    }
+
+    /// Returns the finished SVG document.
+    pub fn finish(self) -> String {
+        let mut document = String::new();
+        write!(
+            document,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width,
+            h = self.height
+        )
+        .unwrap();
+        document.push('\n');
+        if !self.defs.is_empty() {
+            document.push_str("<defs>\n");
+            document.push_str(&self.defs);
+            document.push_str("</defs>\n");
+        }
+        document.push_str(&self.body);
+        document.push_str("</svg>\n");
+        document
+    }
+
+    fn id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Adds an element, wrapped into a group carrying the clip.
+    ///
+    /// Clips are given in device coordinates, so they must not be affected by the
+    /// transform of the element itself.
+    fn push_element(&mut self, element: &str) {
+        match self.state.clip {
+            Some(clip) => writeln!(
+                self.body,
+                r#"<g clip-path="url(#clip{})">{}</g>"#,
+                clip, element
+            )
+            .unwrap(),
+            None => writeln!(self.body, "{}", element).unwrap(),
+        }
+    }
+
+    fn transform_attribute(&self) -> String {
+        let m = self.state.ctm.matrix();
+        if self.state.ctm == Transform::identity() {
+            String::new()
+        } else {
+            format!(
+                r#" transform="matrix({} {} {} {} {} {})""#,
+                m[0], m[1], m[2], m[3], m[4], m[5]
+            )
+        }
+    }
+
+    /// Returns the attributes painting with the given brush, e.g. `fill="#ff0000"`.
+    fn paint_attributes(&mut self, property: &str, brush: &Brush) -> String {
+        let (paint, opacity) = match *brush {
+            Brush::Solid(ref color) => (color.with_alpha(1.0).to_string(), color.a),
+            Brush::LinearGradient(ref linear) => {
+                let id = self.id();
+                write!(
+                    self.defs,
+                    r#"<linearGradient id="paint{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">"#,
+                    id, linear.start_x, linear.start_y, linear.end_x, linear.end_y
+                )
+                .unwrap();
+                write_stops(&mut self.defs, &linear.stops);
+                self.defs.push_str("</linearGradient>\n");
+                (format!("url(#paint{})", id), 1.0)
+            }
+            Brush::RadialGradient(ref radial) => {
+                let id = self.id();
+                write!(
+                    self.defs,
+                    r#"<radialGradient id="paint{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}" fx="{}" fy="{}">"#,
+                    id,
+                    radial.outer_circle_center_x,
+                    radial.outer_circle_center_y,
+                    radial.outer_radius,
+                    radial.start_x,
+                    radial.start_y
+                )
+                .unwrap();
+                write_stops(&mut self.defs, &radial.stops);
+                self.defs.push_str("</radialGradient>\n");
+                (format!("url(#paint{})", id), 1.0)
+            }
+            Brush::Image(ref image) => {
+                let id = self.id();
+                write_pattern(&mut self.defs, id, image);
+                (format!("url(#paint{})", id), 1.0)
+            }
+        };
+        let mut attributes = format!(r#" {}="{}""#, property, paint);
+        if opacity < 1.0 {
+            write!(attributes, r#" {}-opacity="{}""#, property, opacity).unwrap();
+        }
+        attributes
+    }
+}
+
+impl Canvas for SvgWriter {
+    fn fill(&mut self, path: &PathData, brush: &Brush) {
+        let mut element = format!(r#"<path d="{}""#, path.to_svg_path());
+        element.push_str(&self.paint_attributes("fill", brush));
+        if path.fill_mode == FillMode::Alternate {
+            element.push_str(r#" fill-rule="evenodd""#);
+        }
+        element.push_str(&self.transform_attribute());
+        element.push_str("/>");
+        self.push_element(&element);
+    }
+
+    fn stroke(&mut self, path: &PathData, brush: &Brush, params: &StrokeParams) {
+        let mut element = format!(r#"<path d="{}" fill="none""#, path.to_svg_path());
+        element.push_str(&self.paint_attributes("stroke", brush));
+        write!(element, r#" stroke-width="{}""#, params.thickness).unwrap();
+        match params.cap {
+            libui_ffi::uiDrawLineCapRound => element.push_str(r#" stroke-linecap="round""#),
+            libui_ffi::uiDrawLineCapSquare => element.push_str(r#" stroke-linecap="square""#),
+            _ => {}
+        }
+        match params.join {
+            libui_ffi::uiDrawLineJoinRound => element.push_str(r#" stroke-linejoin="round""#),
+            libui_ffi::uiDrawLineJoinBevel => element.push_str(r#" stroke-linejoin="bevel""#),
+            _ => write!(element, r#" stroke-miterlimit="{}""#, params.miter_limit).unwrap(),
+        }
+        if !params.dashes.is_empty() {
+            let dashes: Vec<String> = params.dashes.iter().map(|d| d.to_string()).collect();
+            write!(element, r#" stroke-dasharray="{}""#, dashes.join(" ")).unwrap();
+            if params.dash_phase != 0.0 {
+                write!(element, r#" stroke-dashoffset="{}""#, params.dash_phase).unwrap();
+            }
+        }
+        element.push_str(&self.transform_attribute());
+        element.push_str("/>");
+        self.push_element(&element);
+    }
+
+    fn transform(&mut self, transform: &Transform) {
+        self.state.ctm = *transform * self.state.ctm;
+    }
+
+    fn clip(&mut self, path: &PathData) {
+        let id = self.id();
+        write!(self.defs, r#"<clipPath id="clip{}""#, id).unwrap();
+        // Nested clips intersect with the clip in effect.
+        if let Some(outer) = self.state.clip {
+            write!(self.defs, r#" clip-path="url(#clip{})""#, outer).unwrap();
+        }
+        write!(self.defs, r#"><path d="{}""#, path.to_svg_path()).unwrap();
+        if path.fill_mode == FillMode::Alternate {
+            self.defs.push_str(r#" clip-rule="evenodd""#);
+        }
+        let transform = self.transform_attribute();
+        self.defs.push_str(&transform);
+        self.defs.push_str("/></clipPath>\n");
+        self.state.clip = Some(id);
+    }
+
+    fn save(&mut self) {
+        self.saved.push(self.state.clone());
+    }
+
+    /// # Panics
+    /// Panics if there is no matching `save()`.
+    fn restore(&mut self) {
+        self.state = self.saved.pop().expect("restore() without matching save()");
+    }
+
+    fn draw_text(&mut self, layout: &TextLayoutParams, x: f64, y: f64) {
+        let font = &layout.default_font;
+        let (anchor, x) = match layout.align {
+            TextAlign::Left => ("start", x),
+            TextAlign::Center if layout.width >= 0.0 => ("middle", x + layout.width / 2.0),
+            TextAlign::Right if layout.width >= 0.0 => ("end", x + layout.width),
+            _ => ("start", x),
+        };
+        let mut element = format!(
+            r#"<text x="{}" y="{}" dominant-baseline="text-before-edge" font-family="{}" font-size="{}""#,
+            x,
+            y,
+            escape(&font.family),
+            font.size
+        );
+        if font.weight != 400 {
+            write!(element, r#" font-weight="{}""#, font.weight).unwrap();
+        }
+        element.push_str(&slant_attribute(font.slant));
+        element.push_str(&stretch_attribute(font.stretch));
+        if anchor != "start" {
+            write!(element, r#" text-anchor="{}""#, anchor).unwrap();
+        }
+        element.push_str(&self.transform_attribute());
+        element.push('>');
+
+        // Split the text where attributes start or end, styling each piece with a <tspan>.
+        let string = &layout.string;
+        let text = string.text();
+        let mut bounds = vec![0, text.len()];
+        for (range, _) in string.attributes() {
+            bounds.push(range.start);
+            bounds.push(range.end);
+        }
+        bounds.sort_unstable();
+        bounds.dedup();
+        for piece in bounds.windows(2) {
+            let (start, end) = (piece[0], piece[1]);
+            let mut style = String::new();
+            for (range, attribute) in string.attributes() {
+                if range.start <= start && end <= range.end {
+                    style.push_str(&text_attribute(attribute));
+                }
+            }
+            let content = escape(&text[start..end]);
+            if style.is_empty() {
+                element.push_str(&content);
+            } else {
+                write!(element, "<tspan{}>{}</tspan>", style, content).unwrap();
+            }
+        }
+        element.push_str("</text>");
+        self.push_element(&element);
+    }
+}
+
+impl DisplayList {
+    /// Returns an SVG document of the given size showing the recorded drawing.
+    pub fn to_svg(&self, width: f64, height: f64) -> String {
+        let mut svg = SvgWriter::new(width, height);
+        self.replay_to(&mut svg);
+        svg.finish()
+    }
+}
+
+/// The reason SVG path data could not be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgPathError {
+    /// The byte offset in the path data at which parsing failed.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for SvgPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid SVG path data at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl Error for SvgPathError {}
+
+impl PathData {
+    /// Returns the outline in SVG path data syntax, as used in the `d` attribute of `<path>`.
+    pub fn to_svg_path(&self) -> String {
+        let mut d = String::new();
+        for command in &self.commands {
+            match *command {
+                PathCommand::NewFigure { x, y } => write!(d, "M{} {}", x, y).unwrap(),
+                PathCommand::NewFigureWithArc {
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                } => {
+                    let (x, y) = point_on_circle(x_center, y_center, radius, start_angle);
+                    write!(d, "M{} {}", x, y).unwrap();
+                    write_arc(
+                        &mut d,
+                        x_center,
+                        y_center,
+                        radius,
+                        start_angle,
+                        sweep,
+                        negative,
+                    );
+                }
+                PathCommand::LineTo { x, y } => write!(d, "L{} {}", x, y).unwrap(),
+                PathCommand::ArcTo {
+                    x_center,
+                    y_center,
+                    radius,
+                    start_angle,
+                    sweep,
+                    negative,
+                } => {
+                    let (x, y) = point_on_circle(x_center, y_center, radius, start_angle);
+                    write!(d, "L{} {}", x, y).unwrap();
+                    write_arc(
+                        &mut d,
+                        x_center,
+                        y_center,
+                        radius,
+                        start_angle,
+                        sweep,
+                        negative,
+                    );
+                }
+                PathCommand::BezierTo {
+                    c1x,
+                    c1y,
+                    c2x,
+                    c2y,
+                    end_x,
+                    end_y,
+                } => write!(d, "C{} {} {} {} {} {}", c1x, c1y, c2x, c2y, end_x, end_y).unwrap(),
+                PathCommand::CloseFigure => d.push('Z'),
+                PathCommand::AddRectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => write!(d, "M{} {}H{}V{}H{}Z", x, y, x + width, y + height, x).unwrap(),
+            }
+        }
+        d
+    }
+
+    /// Parses SVG path data, as found in the `d` attribute of `<path>`.
+    ///
+    /// All commands are supported in their absolute and relative forms. Quadratic curves
+    /// and elliptical arcs are converted to cubic Bézier curves. The path uses the
+    /// `Winding` fill mode, matching the SVG default `fill-rule` of `nonzero`.
+    /// Use [`PathData::build()`] to turn the result into a `Path` for drawing.
+    ///
+    /// ```
+    /// # use libui::draw::*;
+    /// let path = PathData::from_svg_path("M 10,10 h 20 v 20 z").unwrap();
+    /// assert_eq!(path.commands.len(), 4);
+    /// assert_eq!(path.commands[2], PathCommand::LineTo { x: 30.0, y: 30.0 });
+    /// ```
+    pub fn from_svg_path(d: &str) -> Result<PathData, SvgPathError> {
+        SvgPathParser::new(d).parse()
+    }
+}
+
+fn write_stops(out: &mut String, stops: &[BrushGradientStop]) {
+    for stop in stops {
+        write!(
+            out,
+            r#"<stop offset="{}" stop-color="{}""#,
+            stop.Pos,
+            Color::rgb(stop.R, stop.G, stop.B)
+        )
+        .unwrap();
+        if stop.A < 1.0 {
+            write!(out, r#" stop-opacity="{}""#, stop.A).unwrap();
+        }
+        out.push_str("/>");
+    }
+}
+
+/// The tile size of patterns which do not repeat, large enough to not repeat in practice.
+const UNREPEATED_TILE: f64 = 1e7;
+
+/// Writes a `<pattern>` with the image embedded as PNG.
+fn write_pattern(out: &mut String, id: usize, image: &ImageBrush) {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let (tile_width, tile_height) = match image.repeat() {
+        ImageRepeat::None => (UNREPEATED_TILE, UNREPEATED_TILE),
+        ImageRepeat::Repeat => (width, height),
+        ImageRepeat::Reflect => (2.0 * width, 2.0 * height),
+    };
+    let m = image.transform().matrix();
+    write!(
+        out,
+        r#"<pattern id="paint{}" patternUnits="userSpaceOnUse" width="{}" height="{}" patternTransform="matrix({} {} {} {} {} {})">"#,
+        id, tile_width, tile_height, m[0], m[1], m[2], m[3], m[4], m[5]
+    )
+    .unwrap();
+    let mut data = Vec::new();
+    png::write_rgba(&mut data, image.width(), image.height(), image.pixels()).unwrap();
+    let element = format!(
+        r#"<image width="{}" height="{}" preserveAspectRatio="none" href="data:image/png;base64,{}""#,
+        width,
+        height,
+        base64(&data)
+    );
+    out.push_str(&element);
+    out.push_str("/>");
+    if image.repeat() == ImageRepeat::Reflect {
+        for (sx, sy) in &[(-1.0, 1.0), (1.0, -1.0), (-1.0, -1.0)] {
+            write!(
+                out,
+                r#"{} transform="matrix({} 0 0 {} {} {})"/>"#,
+                element,
+                sx,
+                sy,
+                if *sx < 0.0 { tile_width } else { 0.0 },
+                if *sy < 0.0 { tile_height } else { 0.0 }
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("</pattern>\n");
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[n >> (18 - 6 * i) & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn slant_attribute(slant: SlantStyle) -> String {
+    match slant {
+        SlantStyle::Normal => String::new(),
+        SlantStyle::Oblique => r#" font-style="oblique""#.to_string(),
+        SlantStyle::Italic => r#" font-style="italic""#.to_string(),
+    }
+}
+
+fn stretch_attribute(stretch: StretchStyle) -> String {
+    let name = match stretch {
+        StretchStyle::UltraCondensed => "ultra-condensed",
+        StretchStyle::ExtraCondensed => "extra-condensed",
+        StretchStyle::Condensed => "condensed",
+        StretchStyle::SemiCondensed => "semi-condensed",
+        StretchStyle::Normal => return String::new(),
+        StretchStyle::SemiExpanded => "semi-expanded",
+        StretchStyle::Expanded => "expanded",
+        StretchStyle::ExtraExpanded => "extra-expanded",
+        StretchStyle::UltraExpanded => "ultra-expanded",
+    };
+    format!(r#" font-stretch="{}""#, name)
+}
+
+fn text_attribute(attribute: &TextAttribute) -> String {
+    match *attribute {
+        TextAttribute::Family(ref family) => format!(r#" font-family="{}""#, escape(family)),
+        TextAttribute::Size(size) => format!(r#" font-size="{}""#, size),
+        TextAttribute::Weight(weight) => format!(r#" font-weight="{}""#, weight),
+        TextAttribute::Slant(slant) => slant_attribute(slant),
+        TextAttribute::Stretch(stretch) => stretch_attribute(stretch),
+        TextAttribute::Color(c) if c.a < 1.0 => {
+            format!(r#" fill="{}" fill-opacity="{}""#, c.with_alpha(1.0), c.a)
+        }
+        TextAttribute::Color(c) => format!(r#" fill="{}""#, c),
+        // SVG text has no background.
+        TextAttribute::Background(_) => String::new(),
+        TextAttribute::Underline(Underline::None) => String::new(),
+        TextAttribute::Underline(_) => r#" text-decoration="underline""#.to_string(),
+    }
+}
+
+fn point_on_circle(x_center: f64, y_center: f64, radius: f64, angle: f64) -> (f64, f64) {
+    (
+        x_center + radius * angle.cos(),
+        y_center + radius * angle.sin(),
+    )
+}
+
+/// Writes a circular arc as SVG arc commands, split into parts of at most half a circle.
+fn write_arc(
+    d: &mut String,
+    x_center: f64,
+    y_center: f64,
+    radius: f64,
+    start: f64,
+    sweep: f64,
+    negative: bool,
+) {
+    let span = arc_span(start, sweep, negative);
+    let parts = (span.abs() / PI).ceil().max(1.0) as usize;
+    let sweep_flag = if span >= 0.0 { 1 } else { 0 };
+    for part in 1..=parts {
+        let angle = start + span * part as f64 / parts as f64;
+        let (x, y) = point_on_circle(x_center, y_center, radius, angle);
+        write!(d, "A{} {} 0 0 {} {} {}", radius, radius, sweep_flag, x, y).unwrap();
+    }
+}
+
+struct SvgPathParser<'a> {
+    data: &'a [u8],
+    position: usize,
+    path: PathData,
+    current: (f64, f64),
+    figure_start: (f64, f64),
+    /// Whether a figure is open, so drawing commands do not need to start a new one.
+    in_figure: bool,
+    /// The second control point of the previous curve, reflected by `S` and `T`.
+    last_cubic: Option<(f64, f64)>,
+    last_quadratic: Option<(f64, f64)>,
+}
+
+impl<'a> SvgPathParser<'a> {
+    fn new(d: &'a str) -> SvgPathParser<'a> {
+        SvgPathParser {
+            data: d.as_bytes(),
+            position: 0,
+            path: PathData::new(FillMode::Winding),
+            current: (0.0, 0.0),
+            figure_start: (0.0, 0.0),
+            in_figure: false,
+            last_cubic: None,
+            last_quadratic: None,
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, SvgPathError> {
+        Err(SvgPathError {
+            position: self.position,
+            message: message.to_string(),
+        })
+    }
+
+    fn parse(mut self) -> Result<PathData, SvgPathError> {
+        self.skip_separators();
+        let mut command = None;
+        while self.position < self.data.len() {
+            let byte = self.data[self.position];
+            if byte.is_ascii_alphabetic() {
+                self.position += 1;
+                command = Some(byte);
+            } else if command.is_none() {
+                return self.error("path data must start with a command");
+            }
+            let letter = command.unwrap();
+            let relative = letter.is_ascii_lowercase();
+            self.command(letter.to_ascii_uppercase(), relative)?;
+            // Coordinates following a move are implicit line commands.
+            command = match letter {
+                b'M' => Some(b'L'),
+                b'm' => Some(b'l'),
+                b'Z' | b'z' => None,
+                other => Some(other),
+            };
+            self.skip_separators();
+        }
+        Ok(self.path)
+    }
+
+    fn command(&mut self, command: u8, relative: bool) -> Result<(), SvgPathError> {
+        let origin = if relative { self.current } else { (0.0, 0.0) };
+        let (mut last_cubic, mut last_quadratic) = (None, None);
+        match command {
+            b'M' => {
+                let (x, y) = self.point(origin)?;
+                self.path.commands.push(PathCommand::NewFigure { x, y });
+                self.in_figure = true;
+                self.current = (x, y);
+                self.figure_start = (x, y);
+            }
+            b'L' => {
+                let (x, y) = self.point(origin)?;
+                self.line_to(x, y);
+            }
+            b'H' => {
+                let x = self.number()? + origin.0;
+                let y = self.current.1;
+                self.line_to(x, y);
+            }
+            b'V' => {
+                let x = self.current.0;
+                let y = self.number()? + origin.1;
+                self.line_to(x, y);
+            }
+            b'C' | b'S' => {
+                let c1 = if command == b'C' {
+                    self.point(origin)?
+                } else {
+                    reflect(self.last_cubic, self.current)
+                };
+                let c2 = self.point(origin)?;
+                let end = self.point(origin)?;
+                self.bezier_to(c1, c2, end);
+                last_cubic = Some(c2);
+            }
+            b'Q' | b'T' => {
+                let control = if command == b'Q' {
+                    self.point(origin)?
+                } else {
+                    reflect(self.last_quadratic, self.current)
+                };
+                let end = self.point(origin)?;
+                let start = self.current;
+                let c1 = (
+                    start.0 + 2.0 / 3.0 * (control.0 - start.0),
+                    start.1 + 2.0 / 3.0 * (control.1 - start.1),
+                );
+                let c2 = (
+                    end.0 + 2.0 / 3.0 * (control.0 - end.0),
+                    end.1 + 2.0 / 3.0 * (control.1 - end.1),
+                );
+                self.bezier_to(c1, c2, end);
+                last_quadratic = Some(control);
+            }
+            b'A' => {
+                let rx = self.number()?;
+                let ry = self.number()?;
+                let rotation = self.number()?;
+                let large_arc = self.flag()?;
+                let sweep = self.flag()?;
+                let end = self.point(origin)?;
+                self.arc_to(rx, ry, rotation.to_radians(), large_arc, sweep, end);
+            }
+            b'Z' => {
+                if self.in_figure {
+                    self.path.commands.push(PathCommand::CloseFigure);
+                    self.in_figure = false;
+                }
+                self.current = self.figure_start;
+            }
+            _ => {
+                self.position -= 1;
+                return self.error(&format!("unknown command '{}'", command as char));
+            }
+        }
+        self.last_cubic = last_cubic;
+        self.last_quadratic = last_quadratic;
+        Ok(())
+    }
+
+    /// Starts a new figure at the current point if the last one was closed.
+    fn ensure_figure(&mut self) {
+        if !self.in_figure {
+            let (x, y) = self.current;
+            self.path.commands.push(PathCommand::NewFigure { x, y });
+            self.figure_start = self.current;
+            self.in_figure = true;
+        }
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.ensure_figure();
+        self.path.commands.push(PathCommand::LineTo { x, y });
+        self.current = (x, y);
+    }
+
+    fn bezier_to(&mut self, c1: (f64, f64), c2: (f64, f64), end: (f64, f64)) {
+        self.ensure_figure();
+        self.path.commands.push(PathCommand::BezierTo {
+            c1x: c1.0,
+            c1y: c1.1,
+            c2x: c2.0,
+            c2y: c2.1,
+            end_x: end.0,
+            end_y: end.1,
+        });
+        self.current = end;
+    }
+
+    /// Appends an elliptical arc as Bézier curves, following the SVG implementation notes.
+    fn arc_to(
+        &mut self,
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        end: (f64, f64),
+    ) {
+        let start = self.current;
+        if start == end {
+            return;
+        }
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(end.0, end.1);
+            return;
+        }
+
+        let (sin, cos) = rotation.sin_cos();
+        let (dx, dy) = ((start.0 - end.0) / 2.0, (start.1 - end.1) / 2.0);
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+        let cx1 = coefficient * rx * y1 / ry;
+        let cy1 = -coefficient * ry * x1 / rx;
+        let center = (
+            cos * cx1 - sin * cy1 + (start.0 + end.0) / 2.0,
+            sin * cx1 + cos * cy1 + (start.1 + end.1) / 2.0,
+        );
+
+        let angle = |ux: f64, uy: f64| uy.atan2(ux);
+        let theta = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut delta = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry) - theta;
+        if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        } else if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        }
+
+        // Approximate each part of at most a quarter turn with one cubic curve.
+        let point = |t: f64| {
+            let (x, y) = (rx * t.cos(), ry * t.sin());
+            (center.0 + cos * x - sin * y, center.1 + sin * x + cos * y)
+        };
+        let derivative = |t: f64| {
+            let (x, y) = (-rx * t.sin(), ry * t.cos());
+            (cos * x - sin * y, sin * x + cos * y)
+        };
+        let parts = (delta.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+        let step = delta / parts as f64;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+        for part in 0..parts {
+            let (t0, t1) = (theta + step * part as f64, theta + step * (part + 1) as f64);
+            let (p0, d0, d1) = (point(t0), derivative(t0), derivative(t1));
+            let p1 = if part + 1 == parts { end } else { point(t1) };
+            self.bezier_to(
+                (p0.0 + k * d0.0, p0.1 + k * d0.1),
+                (p1.0 - k * d1.0, p1.1 - k * d1.1),
+                p1,
+            );
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.position < self.data.len()
+            && (self.data[self.position].is_ascii_whitespace() || self.data[self.position] == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn point(&mut self, origin: (f64, f64)) -> Result<(f64, f64), SvgPathError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok((origin.0 + x, origin.1 + y))
+    }
+
+    fn flag(&mut self) -> Result<bool, SvgPathError> {
+        self.skip_separators();
+        match self.data.get(self.position) {
+            Some(&b'0') => {
+                self.position += 1;
+                Ok(false)
+            }
+            Some(&b'1') => {
+                self.position += 1;
+                Ok(true)
+            }
+            _ => self.error("expected a flag"),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, SvgPathError> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |parser: &mut SvgPathParser, start: usize| {
+            while parser.position < parser.data.len()
+                && parser.data[parser.position].is_ascii_digit()
+            {
+                parser.position += 1;
+            }
+            parser.position > start
+        };
+
+        if let Some(&b'+') | Some(&b'-') = self.data.get(self.position) {
+            self.position += 1;
+        }
+        let integer_start = self.position;
+        let mut valid = digits(self, integer_start);
+        if self.data.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            let fraction_start = self.position;
+            valid |= digits(self, fraction_start);
+        }
+        if valid {
+            if let Some(&b'e') | Some(&b'E') = self.data.get(self.position) {
+                let mark = self.position;
+                self.position += 1;
+                if let Some(&b'+') | Some(&b'-') = self.data.get(self.position) {
+                    self.position += 1;
+                }
+                let exponent_start = self.position;
+                if !digits(self, exponent_start) {
+                    self.position = mark;
+                }
+            }
+        }
+
+        let text = ::std::str::from_utf8(&self.data[start..self.position]).unwrap();
+        match text.parse() {
+            Ok(number) if valid => Ok(number),
+            _ => {
+                self.position = start;
+                self.error("expected a number")
+            }
+        }
+    }
+}
+
+fn reflect(control: Option<(f64, f64)>, current: (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{Color, LinearGradientBrush, Recorder};
+
+    #[test]
+    fn parses_relative_and_implicit_commands() {
+        let path = PathData::from_svg_path("m1 2 3 4zl-1.5.5e1").unwrap();
+        assert_eq!(
+            path.commands,
+            [
+                PathCommand::NewFigure { x: 1.0, y: 2.0 },
+                PathCommand::LineTo { x: 4.0, y: 6.0 },
+                PathCommand::CloseFigure,
+                PathCommand::NewFigure { x: 1.0, y: 2.0 },
+                PathCommand::LineTo { x: -0.5, y: 7.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_curves_to_cubics() {
+        let path = PathData::from_svg_path("M0 0Q3 3 6 0A5 5 0 0 1 16 0").unwrap();
+        assert_eq!(
+            path.commands[1],
+            PathCommand::BezierTo {
+                c1x: 2.0,
+                c1y: 2.0,
+                c2x: 4.0,
+                c2y: 2.0,
+                end_x: 6.0,
+                end_y: 0.0,
+            }
+        );
+        // A half circle becomes two quarter circles, the first ending at its top.
+        assert_eq!(path.commands.len(), 4);
+        match path.commands[2] {
+            PathCommand::BezierTo { end_x, end_y, .. } => {
+                assert!((end_x - 11.0).abs() < 1e-9 && (end_y + 5.0).abs() < 1e-9);
+            }
+            ref other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let error = PathData::from_svg_path("M 1 2 L 3 x").unwrap_err();
+        assert_eq!(error.position, 10);
+        assert_eq!(PathData::from_svg_path("10 10").unwrap_err().position, 0);
+    }
+
+    #[test]
+    fn path_data_roundtrips() {
+        let d = "M1 2L3 4C5 6 7 8 9 10ZM0 0H5V5H0Z";
+        assert_eq!(
+            PathData::from_svg_path(d).unwrap().to_svg_path(),
+            "M1 2L3 4C5 6 7 8 9 10ZM0 0L5 0L5 5L0 5Z"
+        );
+    }
+
+    #[test]
+    fn writes_display_lists() {
+        let mut recorder = Recorder::new();
+        recorder.saved(|r| {
+            r.transform(&Transform::from_matrix([2.0, 0.0, 0.0, 2.0, 0.0, 0.0]));
+            r.clip(&PathData::rectangle(0.0, 0.0, 10.0, 10.0));
+            r.fill(
+                &PathData::rectangle(0.0, 0.0, 20.0, 20.0),
+                &Brush::LinearGradient(LinearGradientBrush {
+                    start_x: 0.0,
+                    start_y: 0.0,
+                    end_x: 20.0,
+                    end_y: 0.0,
+                    stops: vec![],
+                }),
+            );
+        });
+        recorder.fill(
+            &PathData::rectangle(0.0, 0.0, 1.0, 1.0),
+            &Brush::Solid(Color {
+                r: 0.0,
+                g: 0.0,
+                b: 1.0,
+                a: 0.5,
+            }),
+        );
+        let svg = recorder.finish().to_svg(40.0, 40.0);
+        assert!(svg.contains(r#"<clipPath id="clip1"><path d="M0 0H10V10H0Z" transform="matrix(2 0 0 2 0 0)"/></clipPath>"#));
+        assert!(svg.contains(r#"<g clip-path="url(#clip1)"><path d="M0 0H20V20H0Z" fill="url(#paint2)" transform="matrix(2 0 0 2 0 0)"/></g>"#));
+        assert!(svg.contains(r##"<path d="M0 0H1V1H0Z" fill="#0000ff" fill-opacity="0.5"/>"##));
+    }
+
+    #[test]
+    fn embeds_image_patterns() {
+        assert_eq!(base64(b"libui"), "bGlidWk=");
+        assert_eq!(base64(b"ui"), "dWk=");
+        let pattern = ImageBrush::new(1, 1, vec![0, 0, 0, 255])
+            .unwrap()
+            .with_repeat(ImageRepeat::Reflect);
+        let mut svg = SvgWriter::new(10.0, 10.0);
+        svg.fill(
+            &PathData::rectangle(0.0, 0.0, 10.0, 10.0),
+            &Brush::Image(pattern),
+        );
+        let document = svg.finish();
+        assert!(document.contains(r#"<pattern id="paint1" patternUnits="userSpaceOnUse" width="2" height="2" patternTransform="matrix(1 0 0 1 0 0)"><image width="1" height="1" preserveAspectRatio="none" href="data:image/png;base64,iVBORw0KGgo"#));
+        assert_eq!(document.matches("<image ").count(), 4);
+        assert!(document.contains(r#"fill="url(#paint1)""#));
+    }
+}
@@ -0,0 +1,257 @@
+use controls::{FontDescription, SlantStyle, StretchStyle};
+use draw::Color;
+use libui_ffi::{self, uiAttribute, uiDrawTextLayout, uiDrawTextLayoutParams, uiFontDescriptor};
+use std::ffi::CString;
+use std::ops::Range;
+use std::os::raw::c_uint;
+
+/// The underline styles text can be drawn with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Underline {
+    None,
+    Single,
+    Double,
+    /// The wavy underline used to mark spelling mistakes.
+    Suggestion,
+}
+
+/// A style applied to a range of an [`AttributedString`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextAttribute {
+    Family(String),
+    Size(f64),
+    /// The font weight, from 0 to 1000. 400 is regular, 700 bold.
+    Weight(u32),
+    Slant(SlantStyle),
+    Stretch(StretchStyle),
+    Color(Color),
+    Background(Color),
+    Underline(Underline),
+}
+
+impl TextAttribute {
+    unsafe fn to_ui_attribute(&self) -> *mut uiAttribute {
+        match *self {
+            TextAttribute::Family(ref family) => {
+                let family = CString::new(family.as_bytes().to_vec()).unwrap();
+                libui_ffi::uiNewFamilyAttribute(family.as_ptr())
+            }
+            TextAttribute::Size(size) => libui_ffi::uiNewSizeAttribute(size),
+            TextAttribute::Weight(weight) => libui_ffi::uiNewWeightAttribute(weight),
+            TextAttribute::Slant(slant) => libui_ffi::uiNewItalicAttribute(slant as c_uint),
+            TextAttribute::Stretch(stretch) => libui_ffi::uiNewStretchAttribute(stretch as c_uint),
+            TextAttribute::Color(c) => libui_ffi::uiNewColorAttribute(c.r, c.g, c.b, c.a),
+            TextAttribute::Background(c) => libui_ffi::uiNewBackgroundAttribute(c.r, c.g, c.b, c.a),
+            TextAttribute::Underline(underline) => {
+                libui_ffi::uiNewUnderlineAttribute(underline as c_uint)
+            }
+        }
+    }
+}
+
+/// Text with styles applied to byte ranges of it.
+///
+/// ```
+/// # use libui::draw::*;
+/// let mut text = AttributedString::new("Hello ");
+/// text.append_with("world", TextAttribute::Weight(700));
+/// assert_eq!(text.text(), "Hello world");
+/// assert_eq!(text.attributes()[0].0, 6..11);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttributedString {
+    text: String,
+    attributes: Vec<(Range<usize>, TextAttribute)>,
+}
+
+impl AttributedString {
+    /// Creates an attributed string without any attributes.
+    pub fn new(text: &str) -> AttributedString {
+        AttributedString {
+            text: text.to_string(),
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the attributes and the byte ranges they apply to, in the order they were set.
+    pub fn attributes(&self) -> &[(Range<usize>, TextAttribute)] {
+        &self.attributes
+    }
+
+    /// Appends text without attributes.
+    pub fn append(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    /// Appends text with an attribute applied to it.
+    pub fn append_with(&mut self, text: &str, attribute: TextAttribute) {
+        let start = self.text.len();
+        self.text.push_str(text);
+        let end = self.text.len();
+        self.set_attribute(start..end, attribute);
+    }
+
+    /// Applies an attribute to a byte range of the text.
+    ///
+    /// Later attributes of the same kind override earlier ones where they overlap.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or does not lie on character boundaries.
+    pub fn set_attribute(&mut self, range: Range<usize>, attribute: TextAttribute) {
+        assert!(
+            range.start <= range.end && self.text.get(range.clone()).is_some(),
+            "Attribute range {:?} is not a valid range of the text",
+            range
+        );
+        self.attributes.push((range, attribute));
+    }
+}
+
+/// The horizontal alignment of the lines of a text layout.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Everything needed to lay out a block of text.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextLayoutParams {
+    pub string: AttributedString,
+    /// The font used where no attribute says otherwise.
+    pub default_font: FontDescription,
+    /// The width at which lines are wrapped. Use `-1.0` to not wrap at all.
+    pub width: f64,
+    pub align: TextAlign,
+}
+
+impl TextLayoutParams {
+    /// Creates parameters for text in one color, on lines which are neither wrapped nor
+    /// aligned.
+    ///
+    /// ```
+    /// # use libui::controls::FontDescription;
+    /// # use libui::draw::*;
+    /// let font = FontDescription::default();
+    /// let title = TextLayoutParams::colored("Title", Color::BLACK, &font)
+    ///     .wrapped(200.0, TextAlign::Center)
+    ///     .bold(true);
+    /// assert_eq!(title.string.attributes().len(), 2);
+    /// ```
+    pub fn colored(text: &str, color: Color, font: &FontDescription) -> TextLayoutParams {
+        let mut string = AttributedString::new(text);
+        string.set_attribute(0..text.len(), TextAttribute::Color(color));
+        TextLayoutParams {
+            string,
+            default_font: font.clone(),
+            width: -1.0,
+            align: TextAlign::Left,
+        }
+    }
+
+    /// Wraps the lines at a width and aligns them within it.
+    pub fn wrapped(mut self, width: f64, align: TextAlign) -> Self {
+        self.width = width;
+        self.align = align;
+        self
+    }
+
+    /// Makes all text bold if `bold` is set.
+    pub fn bold(mut self, bold: bool) -> Self {
+        if bold {
+            let len = self.string.text().len();
+            self.string.set_attribute(0..len, TextAttribute::Weight(700));
+        }
+        self
+    }
+}
+
+/// A block of text laid out for drawing with [`DrawContext::draw_text()`].
+pub struct TextLayout {
+    ui_draw_text_layout: *mut uiDrawTextLayout,
+}
+
+impl Drop for TextLayout {
+    fn drop(&mut self) {
+        unsafe { libui_ffi::uiDrawFreeTextLayout(self.ui_draw_text_layout) }
+    }
+}
+
+impl TextLayout {
+    /// Lays out text. The parameters are copied and not needed afterwards.
+    pub fn new(params: &TextLayoutParams) -> TextLayout {
+        unsafe {
+            let text = CString::new(params.string.text.as_bytes().to_vec()).unwrap();
+            let string = libui_ffi::uiNewAttributedString(text.as_ptr());
+            for (range, attribute) in &params.string.attributes {
+                // The string takes ownership of the attribute.
+                libui_ffi::uiAttributedStringSetAttribute(
+                    string,
+                    attribute.to_ui_attribute(),
+                    range.start,
+                    range.end,
+                );
+            }
+
+            let font = &params.default_font;
+            let family = CString::new(font.family.as_bytes().to_vec()).unwrap();
+            let mut descriptor = uiFontDescriptor {
+                Family: family.as_ptr() as *mut _,
+                Size: font.size,
+                Weight: font.weight,
+                Italic: font.slant as c_uint,
+                Stretch: font.stretch as c_uint,
+            };
+            let mut ui_params = uiDrawTextLayoutParams {
+                String: string,
+                DefaultFont: &mut descriptor,
+                Width: params.width,
+                Align: params.align as c_uint,
+            };
+            // The layout copies everything it needs from the parameters.
+            let layout = libui_ffi::uiDrawNewTextLayout(&mut ui_params);
+            libui_ffi::uiFreeAttributedString(string);
+
+            TextLayout {
+                ui_draw_text_layout: layout,
+            }
+        }
+    }
+
+    /// Returns the width and height of the laid out text.
+    pub fn extents(&self) -> (f64, f64) {
+        let mut size = (0.0, 0.0);
+        unsafe {
+            libui_ffi::uiDrawTextLayoutExtents(self.ui_draw_text_layout, &mut size.0, &mut size.1)
+        }
+        size
+    }
+
+    /// Return the underlying pointer for this TextLayout.
+    pub fn ptr(&self) -> *mut uiDrawTextLayout {
+        self.ui_draw_text_layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "not a valid range")]
+    fn attribute_ranges_must_be_char_boundaries() {
+        let mut text = AttributedString::new("Grüße");
+        text.set_attribute(0..3, TextAttribute::Size(12.0));
+    }
+}
@@ -1,13 +1,53 @@
-use std::mem;
+use libui_ffi::uiDrawMatrix;
 use std::ops::Mul;
-use libui_ffi::{self, uiDrawMatrix};
 
 /// A transformation which can be applied to the contents of a DrawContext.
+///
+/// The math is done in Rust, so transforms can be used and tested anywhere, not only on the
+/// UI thread. Like libui's `uiDrawMatrix*` functions, translating, scaling and rotating
+/// apply before the operations a transform already contains, that is in its coordinate
+/// system, while skewing and composing apply after them:
+///
+/// ```
+/// # use libui::draw::Transform;
+/// let mut transform = Transform::identity();
+/// transform.scale(0.0, 0.0, 2.0, 2.0);
+/// transform.translate(10.0, 0.0);
+/// assert_eq!(transform.transform_point((1.0, 1.0)), (22.0, 2.0));
+/// ```
 #[derive(Copy, Clone, Debug)]
 pub struct Transform {
     ui_matrix: uiDrawMatrix,
 }
 
+/// A transform split into simple operations, applied in the order of the fields:
+/// scaling, then skewing along the x axis, rotation and finally translation.
+///
+/// Angles are given in radians.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TransformComponents {
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub skew: f64,
+    pub rotation: f64,
+    pub translate_x: f64,
+    pub translate_y: f64,
+}
+
+impl Default for TransformComponents {
+    fn default() -> Self {
+        TransformComponents {
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew: 0.0,
+            rotation: 0.0,
+            translate_x: 0.0,
+            translate_y: 0.0,
+        }
+    }
+}
+
 impl Transform {
     /// Create a Transform from an existing raw uiDrawMatrix.
     pub fn from_ui_matrix(ui_matrix: &uiDrawMatrix) -> Transform {
@@ -16,74 +56,206 @@ impl Transform {
         }
     }
 
+    /// Return the underlying uiDrawMatrix.
+    pub fn to_ui_matrix(&self) -> uiDrawMatrix {
+        self.ui_matrix
+    }
+
+    /// Create a Transform from the matrix elements `[m11, m12, m21, m22, m31, m32]`.
+    pub fn from_matrix(m: [f64; 6]) -> Transform {
+        Transform::from_ui_matrix(&uiDrawMatrix {
+            M11: m[0],
+            M12: m[1],
+            M21: m[2],
+            M22: m[3],
+            M31: m[4],
+            M32: m[5],
+        })
+    }
+
+    /// Return the matrix elements `[m11, m12, m21, m22, m31, m32]`.
+    ///
+    /// A point `(x, y)` is transformed to `(x * m11 + y * m21 + m31, x * m12 + y * m22 + m32)`.
+    pub fn matrix(&self) -> [f64; 6] {
+        let m = &self.ui_matrix;
+        [m.M11, m.M12, m.M21, m.M22, m.M31, m.M32]
+    }
+
     /// Create a new Transform that does nothing.
     pub fn identity() -> Transform {
-        unsafe {
-            let mut matrix = mem::MaybeUninit::uninit();
-            libui_ffi::uiDrawMatrixSetIdentity(matrix.as_mut_ptr());
-            Transform::from_ui_matrix(&matrix.assume_init())
+        Transform::from_matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    /// Create a Transform from its components, the inverse of [`Transform::decompose()`].
+    pub fn from_components(components: &TransformComponents) -> Transform {
+        let (sin, cos) = components.rotation.sin_cos();
+        let skew = components.skew.tan();
+        let (sx, sy) = (components.scale_x, components.scale_y);
+        Transform::from_matrix([
+            cos * sx,
+            sin * sx,
+            sy * (skew * cos - sin),
+            sy * (skew * sin + cos),
+            components.translate_x,
+            components.translate_y,
+        ])
+    }
+
+    /// Split this Transform into scaling, skewing, rotation and translation.
+    ///
+    /// The horizontal scale is never negative; a mirroring transform has a negative
+    /// vertical scale instead.
+    pub fn decompose(&self) -> TransformComponents {
+        let [a, b, c, d, e, f] = self.matrix();
+        let scale_x = (a * a + b * b).sqrt();
+        let (rotation, scale_y, shear) = if scale_x == 0.0 {
+            (0.0, d, if d == 0.0 { 0.0 } else { c / d })
+        } else {
+            let (sin, cos) = (b / scale_x, a / scale_x);
+            let scale_y = d * cos - c * sin;
+            let shear = if scale_y == 0.0 {
+                0.0
+            } else {
+                (c * cos + d * sin) / scale_y
+            };
+            (b.atan2(a), scale_y, shear)
+        };
+        TransformComponents {
+            scale_x,
+            scale_y,
+            skew: shear.atan(),
+            rotation,
+            translate_x: e,
+            translate_y: f,
         }
     }
 
     /// Modify this Transform to translate by the given amounts.
     pub fn translate(&mut self, x: f64, y: f64) {
-        unsafe { libui_ffi::uiDrawMatrixTranslate(&mut self.ui_matrix, x, y) }
+        self.prepend(Transform::from_matrix([1.0, 0.0, 0.0, 1.0, x, y]));
     }
 
     /// Modify this Transform to scale by the given amounts from the given center.
     pub fn scale(&mut self, x_center: f64, y_center: f64, x: f64, y: f64) {
-        unsafe { libui_ffi::uiDrawMatrixScale(&mut self.ui_matrix, x_center, y_center, x, y) }
+        self.prepend(Transform::from_matrix([
+            x,
+            0.0,
+            0.0,
+            y,
+            x_center - x * x_center,
+            y_center - y * y_center,
+        ]));
     }
 
     /// Modify this Transform to rotate around the given center by the given angle.
     pub fn rotate(&mut self, x: f64, y: f64, angle: f64) {
-        unsafe { libui_ffi::uiDrawMatrixRotate(&mut self.ui_matrix, x, y, angle) }
+        let (sin, cos) = angle.sin_cos();
+        self.prepend(Transform::from_matrix([
+            cos,
+            sin,
+            -sin,
+            cos,
+            x - x * cos + y * sin,
+            y - x * sin - y * cos,
+        ]));
     }
 
     /// Modify this Transform to skew from the given point by the given amount.
+    ///
+    /// The amounts are the angles, in radians, by which the y and x axes are tilted.
     pub fn skew(&mut self, x: f64, y: f64, xamount: f64, yamount: f64) {
-        unsafe { libui_ffi::uiDrawMatrixSkew(&mut self.ui_matrix, x, y, xamount, yamount) }
+        let (tan_x, tan_y) = (xamount.tan(), yamount.tan());
+        self.compose(&Transform::from_matrix([
+            1.0,
+            tan_y,
+            tan_x,
+            1.0,
+            -y * tan_x,
+            -x * tan_y,
+        ]));
     }
 
     /// Compose this Transform with another, creating a Transform which represents both operations.
+    ///
+    /// The other transform is applied after this one.
     pub fn compose(&mut self, src: &Transform) {
-        unsafe { libui_ffi::uiDrawMatrixMultiply(&mut self.ui_matrix, src.ptr()) }
+        let [a0, a1, a2, a3, a4, a5] = self.matrix();
+        let [b0, b1, b2, b3, b4, b5] = src.matrix();
+        *self = Transform::from_matrix([
+            a0 * b0 + a1 * b2,
+            a0 * b1 + a1 * b3,
+            a2 * b0 + a3 * b2,
+            a2 * b1 + a3 * b3,
+            a4 * b0 + a5 * b2 + b4,
+            a4 * b1 + a5 * b3 + b5,
+        ]);
+    }
+
+    /// Applies `operation` before this Transform.
+    fn prepend(&mut self, mut operation: Transform) {
+        operation.compose(self);
+        *self = operation;
+    }
+
+    /// Returns the factor by which this Transform scales areas, negative if it mirrors.
+    pub fn determinant(&self) -> f64 {
+        let m = &self.ui_matrix;
+        m.M11 * m.M22 - m.M12 * m.M21
     }
 
     /// Returns true if inverting this Transform is possible.
     pub fn invertible(&self) -> bool {
-        unsafe {
-            libui_ffi::uiDrawMatrixInvertible(
-                &self.ui_matrix as *const uiDrawMatrix as *mut uiDrawMatrix,
-            ) != 0
-        }
+        let determinant = self.determinant();
+        determinant != 0.0 && determinant.is_finite()
     }
 
     /// Attempts to invert the Transform, returning true if it succeeded and false if it failed.
     pub fn invert(&mut self) -> bool {
-        unsafe { libui_ffi::uiDrawMatrixInvert(&mut self.ui_matrix) != 0 }
+        match self.inverse() {
+            Some(inverse) => {
+                *self = inverse;
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn transform_point(&self, mut point: (f64, f64)) -> (f64, f64) {
-        unsafe {
-            libui_ffi::uiDrawMatrixTransformPoint(
-                &self.ui_matrix as *const uiDrawMatrix as *mut uiDrawMatrix,
-                &mut point.0,
-                &mut point.1,
-            );
-            point
+    /// Returns the inverse of this Transform, if there is one.
+    pub fn inverse(&self) -> Option<Transform> {
+        if !self.invertible() {
+            return None;
         }
+        let det = self.determinant();
+        let [a, b, c, d, e, f] = self.matrix();
+        Some(Transform::from_matrix([
+            d / det,
+            -b / det,
+            -c / det,
+            a / det,
+            (c * f - d * e) / det,
+            (b * e - a * f) / det,
+        ]))
     }
 
-    pub fn transform_size(&self, mut size: (f64, f64)) -> (f64, f64) {
-        unsafe {
-            libui_ffi::uiDrawMatrixTransformSize(
-                &self.ui_matrix as *const uiDrawMatrix as *mut uiDrawMatrix,
-                &mut size.0,
-                &mut size.1,
-            );
-            size
-        }
+    pub fn transform_point(&self, point: (f64, f64)) -> (f64, f64) {
+        let m = &self.ui_matrix;
+        let (x, y) = self.transform_size(point);
+        (x + m.M31, y + m.M32)
+    }
+
+    /// Transforms a distance, ignoring the translation.
+    pub fn transform_size(&self, size: (f64, f64)) -> (f64, f64) {
+        let m = &self.ui_matrix;
+        let (x, y) = size;
+        (x * m.M11 + y * m.M21, x * m.M12 + y * m.M22)
+    }
+
+    /// Returns true if all matrix elements differ by at most `epsilon`.
+    pub fn approx_eq(&self, other: &Transform, epsilon: f64) -> bool {
+        self.matrix()
+            .iter()
+            .zip(other.matrix().iter())
+            .all(|(a, b)| (a - b).abs() <= epsilon)
     }
 
     pub fn ptr(&self) -> *mut uiDrawMatrix {
@@ -91,6 +263,12 @@ impl Transform {
     }
 }
 
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
 impl Mul<Transform> for Transform {
     type Output = Transform;
 
@@ -99,3 +277,80 @@ impl Mul<Transform> for Transform {
         self
     }
 }
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Transform) -> bool {
+        self.matrix() == other.matrix()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Transform {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.matrix().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Transform {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Transform, D::Error> {
+        <[f64; 6]>::deserialize(deserializer).map(Transform::from_matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn operations_compose_like_libui() {
+        let mut transform = Transform::default();
+        transform.translate(1.0, 0.0);
+        transform.rotate(0.0, 0.0, FRAC_PI_2);
+        let (x, y) = transform.transform_point((1.0, 0.0));
+        assert!((x - 1.0).abs() < 1e-12 && (y - 1.0).abs() < 1e-12);
+        transform.skew(0.0, 0.0, 0.0, FRAC_PI_4);
+        let (x, y) = transform.transform_point((1.0, 0.0));
+        assert!((x - 1.0).abs() < 1e-12 && (y - 2.0).abs() < 1e-12);
+        assert_eq!(transform.transform_size((0.0, 0.0)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn inverts() {
+        let mut transform = Transform::from_matrix([2.0, 1.0, 0.5, 3.0, 4.0, -5.0]);
+        let inverse = transform.inverse().unwrap();
+        assert!((transform * inverse).approx_eq(&Transform::identity(), 1e-12));
+        assert!(!Transform::from_matrix([1.0, 2.0, 2.0, 4.0, 0.0, 0.0]).invert());
+        assert!(transform.invert());
+        assert_eq!(transform, inverse);
+    }
+
+    #[test]
+    fn decomposes_into_components() {
+        let components = TransformComponents {
+            scale_x: 2.0,
+            scale_y: -0.5,
+            skew: 0.3,
+            rotation: 1.2,
+            translate_x: 7.0,
+            translate_y: -3.0,
+        };
+        let transform = Transform::from_components(&components);
+        let decomposed = transform.decompose();
+        assert!((decomposed.scale_x - 2.0).abs() < 1e-12);
+        assert!((decomposed.scale_y + 0.5).abs() < 1e-12);
+        assert!((decomposed.skew - 0.3).abs() < 1e-12);
+        assert!((decomposed.rotation - 1.2).abs() < 1e-12);
+        assert!(Transform::from_components(&decomposed).approx_eq(&transform, 1e-12));
+
+        let mut scaled = Transform::identity();
+        scaled.scale(1.0, 1.0, 3.0, 3.0);
+        let decomposed = scaled.decompose();
+        assert_eq!(
+            (decomposed.translate_x, decomposed.translate_y),
+            (-2.0, -2.0)
+        );
+        assert_eq!((decomposed.rotation, decomposed.skew), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,751 @@
+use editor::segment;
+use editor::{Rope, TextStyle};
+use std::ops::Range;
+
+/// A selection of text, from the anchor where it started to the head where the caret
+/// is. An empty selection is just a caret.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Selection {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+impl Selection {
+    pub fn new(anchor: usize, head: usize) -> Selection {
+        Selection { anchor, head }
+    }
+
+    /// Creates an empty selection at a byte offset.
+    pub fn caret(at: usize) -> Selection {
+        Selection::new(at, at)
+    }
+
+    /// Returns the selected byte range, from the smaller offset to the larger one.
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+/// A caret movement, see [`TextBuffer::move_caret()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Movement {
+    /// To the previous grapheme cluster.
+    Left,
+    /// To the next grapheme cluster.
+    Right,
+    /// To the start of the previous word.
+    WordLeft,
+    /// To the end of the next word.
+    WordRight,
+    Up,
+    Down,
+    /// Up by the given number of lines.
+    PageUp(usize),
+    /// Down by the given number of lines.
+    PageDown(usize),
+    /// To the first non-whitespace character of the line, or to its start if the caret
+    /// is there already.
+    LineStart,
+    LineEnd,
+    DocumentStart,
+    DocumentEnd,
+}
+
+/// How [`TextBuffer::find()`] matches text.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SearchOptions {
+    pub match_case: bool,
+    /// Only match whole words.
+    pub whole_word: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Edit {
+    at: usize,
+    removed: String,
+    inserted: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// The edits of one undo step.
+#[derive(Clone, Debug)]
+struct Transaction {
+    edits: Vec<Edit>,
+    before: Selection,
+    after: Selection,
+    kind: EditKind,
+}
+
+/// Maps an offset through the replacement of `removed` bytes at `at` by `inserted`
+/// bytes. Offsets inside the removed text move to its start, or with `after` to the end
+/// of the inserted text, as do offsets at `at` itself.
+fn map_offset(offset: usize, at: usize, removed: usize, inserted: usize, after: bool) -> usize {
+    if offset < at || (offset == at && !after) {
+        offset
+    } else if offset >= at + removed && !(offset == at && removed == 0) {
+        offset - removed + inserted
+    } else if after {
+        at + inserted
+    } else {
+        at
+    }
+}
+
+/// The text of a [`CodeEditor`](super::CodeEditor), with its selection, undo history and
+/// styled ranges.
+///
+/// Edits through the buffer are recorded for [`TextBuffer::undo()`]. Typing and deleting
+/// character by character are merged into one undo step per word.
+///
+/// ```
+/// # use libui::editor::*;
+/// let mut buffer = TextBuffer::from("let x = 1;");
+/// buffer.move_caret(Movement::DocumentEnd, false);
+/// buffer.move_caret(Movement::WordLeft, true);
+/// assert_eq!(buffer.selected_text(), ";");
+/// buffer.insert("\nlet y = 2;");
+/// assert_eq!(buffer.line_count(), 2);
+/// buffer.undo();
+/// assert_eq!(buffer.text(), "let x = 1;");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TextBuffer {
+    rope: Rope,
+    selection: Selection,
+    /// The column kept while moving up and down across shorter lines.
+    goal_column: Option<usize>,
+    undo: Vec<Transaction>,
+    redo: Vec<Transaction>,
+    /// Whether the next typing or deletion may be merged into the last undo step.
+    merge: bool,
+    styles: Vec<(Range<usize>, TextStyle)>,
+    tab_width: usize,
+    revision: u64,
+}
+
+impl Default for TextBuffer {
+    fn default() -> TextBuffer {
+        TextBuffer {
+            rope: Rope::new(),
+            selection: Selection::default(),
+            goal_column: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            merge: false,
+            styles: Vec::new(),
+            tab_width: 4,
+            revision: 0,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for TextBuffer {
+    fn from(text: &'a str) -> TextBuffer {
+        TextBuffer {
+            rope: Rope::from(text),
+            ..TextBuffer::default()
+        }
+    }
+}
+
+impl TextBuffer {
+    /// Creates an empty buffer.
+    pub fn new() -> TextBuffer {
+        TextBuffer::default()
+    }
+
+    /// Replaces the whole text, placing the caret at the start and clearing the undo
+    /// history and styles.
+    pub fn set_text(&mut self, text: &str) {
+        self.rope = Rope::from(text);
+        self.selection = Selection::default();
+        self.goal_column = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.merge = false;
+        self.styles.clear();
+        self.revision += 1;
+    }
+
+    pub fn text(&self) -> String {
+        self.rope.to_string()
+    }
+
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    /// Returns the length in bytes.
+    pub fn len(&self) -> usize {
+        self.rope.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rope.is_empty()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.rope.line_count()
+    }
+
+    /// Returns a line without its line break.
+    pub fn line(&self, line: usize) -> String {
+        self.rope.line(line)
+    }
+
+    /// Returns the byte range of a line, without its line break.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        self.rope.line_range(line)
+    }
+
+    /// Returns the line and column of a byte offset, counting from 0. Tabs advance to
+    /// the next tab stop and wide characters take two columns.
+    pub fn position(&self, at: usize) -> (usize, usize) {
+        let line = self.rope.line_of(at);
+        let range = self.rope.line_range(line);
+        let at = at.min(range.end) - range.start;
+        (
+            line,
+            segment::column_of(&self.line(line), at, self.tab_width),
+        )
+    }
+
+    /// Returns the byte offset closest to a line and column.
+    pub fn offset(&self, line: usize, column: usize) -> usize {
+        self.offset_at(line, column as f64)
+    }
+
+    /// Returns the byte offset closest to a line and a fractional column, e.g. under the
+    /// mouse.
+    pub(crate) fn offset_at(&self, line: usize, column: f64) -> usize {
+        let line = line.min(self.line_count() - 1);
+        let start = self.rope.line_start(line);
+        start + segment::offset_at_column(&self.line(line), column, self.tab_width)
+    }
+
+    /// Returns the number of columns a tab advances to.
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width.max(1);
+    }
+
+    /// Returns a number which changes with every change of the text.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// Sets the selection, moving its ends to the nearest character boundaries within
+    /// the text.
+    pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = Selection::new(self.snap(selection.anchor), self.snap(selection.head));
+        self.goal_column = None;
+        self.merge = false;
+    }
+
+    fn snap(&self, at: usize) -> usize {
+        let mut at = at.min(self.len());
+        while !self.rope.is_char_boundary(at) {
+            at -= 1;
+        }
+        at
+    }
+
+    pub fn select_all(&mut self) {
+        let len = self.len();
+        self.set_selection(Selection::new(0, len));
+    }
+
+    pub fn selected_text(&self) -> String {
+        self.rope.slice(self.selection.range())
+    }
+
+    /// Applies an edit without recording it.
+    fn apply(&mut self, at: usize, removed: usize, inserted: &str) {
+        self.rope.remove(at..at + removed);
+        self.rope.insert(at, inserted);
+        let added = inserted.len();
+        self.styles.retain_mut(|&mut (ref mut range, _)| {
+            let start = map_offset(range.start, at, removed, added, true);
+            let end = map_offset(range.end, at, removed, added, false);
+            *range = start..end.max(start);
+            start < end
+        });
+        self.revision += 1;
+    }
+
+    /// Applies and records replacements of sorted, non-overlapping ranges as one undo
+    /// step, leaving the selection at `after` or mapping it through the edits.
+    fn transact(
+        &mut self,
+        replacements: Vec<(Range<usize>, String)>,
+        after: Option<Selection>,
+        kind: EditKind,
+    ) {
+        let before = self.selection;
+        let mut selection = before;
+        let mut edits = Vec::with_capacity(replacements.len());
+        // Applied from the end, so the offsets of earlier ranges stay valid.
+        for (range, text) in replacements.into_iter().rev() {
+            let removed = self.rope.slice(range.clone());
+            self.apply(range.start, range.len(), &text);
+            let map = |at| map_offset(at, range.start, range.len(), text.len(), true);
+            selection = Selection::new(map(selection.anchor), map(selection.head));
+            edits.push(Edit {
+                at: range.start,
+                removed,
+                inserted: text,
+            });
+        }
+        if edits.is_empty() {
+            return;
+        }
+        let after = after.unwrap_or(selection);
+        self.selection = after;
+        self.goal_column = None;
+        self.redo.clear();
+
+        let merged = self.merge
+            && edits.len() == 1
+            && match self.undo.last_mut() {
+                Some(last) if last.kind == kind && last.after == before => {
+                    last.edits.append(&mut edits);
+                    last.after = after;
+                    true
+                }
+                _ => false,
+            };
+        if !merged {
+            self.undo.push(Transaction {
+                edits,
+                before,
+                after,
+                kind,
+            });
+        }
+        self.merge = kind != EditKind::Other;
+    }
+
+    /// Replaces a byte range with text as one undo step, placing the caret after it.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or not on character boundaries.
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        let caret = Selection::caret(range.start + text.len());
+        self.transact(
+            vec![(range, text.to_string())],
+            Some(caret),
+            EditKind::Other,
+        );
+    }
+
+    /// Replaces sorted, non-overlapping byte ranges as one undo step.
+    ///
+    /// # Panics
+    /// Panics if the ranges overlap, are out of bounds or not on character boundaries.
+    pub fn replace_ranges(&mut self, replacements: Vec<(Range<usize>, String)>) {
+        assert!(
+            replacements
+                .windows(2)
+                .all(|pair| pair[0].0.end <= pair[1].0.start),
+            "Replaced ranges must be sorted and must not overlap"
+        );
+        self.transact(replacements, None, EditKind::Other);
+    }
+
+    /// Replaces the selection with text, as if it was typed.
+    pub fn insert(&mut self, text: &str) {
+        let range = self.selection.range();
+        let caret = Selection::caret(range.start + text.len());
+        let typing = range.is_empty() && !text.contains('\n');
+        let kind = if typing {
+            EditKind::Typing
+        } else {
+            EditKind::Other
+        };
+        self.transact(vec![(range, text.to_string())], Some(caret), kind);
+        // A new undo step starts with every word.
+        if text.chars().all(char::is_whitespace) {
+            self.merge = false;
+        }
+    }
+
+    /// Deletes the selection, or the text between the caret and where the movement would
+    /// take it. Backspace deletes with [`Movement::Left`], Delete with
+    /// [`Movement::Right`].
+    pub fn delete(&mut self, movement: Movement) {
+        let (range, kind) = if self.selection.is_empty() {
+            let head = self.selection.head;
+            let target = self.moved(head, movement);
+            (head.min(target)..head.max(target), EditKind::Deleting)
+        } else {
+            (self.selection.range(), EditKind::Other)
+        };
+        if !range.is_empty() {
+            let caret = Selection::caret(range.start);
+            self.transact(vec![(range, String::new())], Some(caret), kind);
+        }
+    }
+
+    /// Moves the caret, extending the selection from its anchor if `extend` is set.
+    ///
+    /// Without `extend`, moving left or right collapses a selection to its start or end.
+    pub fn move_caret(&mut self, movement: Movement, extend: bool) {
+        let selection = self.selection;
+        let goal = self.goal_column;
+        let head = match movement {
+            Movement::Left if !extend && !selection.is_empty() => selection.range().start,
+            Movement::Right if !extend && !selection.is_empty() => selection.range().end,
+            _ => self.moved(selection.head, movement),
+        };
+        self.selection = if extend {
+            Selection::new(selection.anchor, head)
+        } else {
+            Selection::caret(head)
+        };
+        self.merge = false;
+        self.goal_column = match movement {
+            Movement::Up | Movement::Down | Movement::PageUp(_) | Movement::PageDown(_) => {
+                Some(goal.unwrap_or_else(|| self.position(selection.head).1))
+            }
+            _ => None,
+        };
+    }
+
+    /// Returns where a movement takes the caret from a byte offset.
+    fn moved(&self, at: usize, movement: Movement) -> usize {
+        let line = self.rope.line_of(at);
+        let range = self.rope.line_range(line);
+        let text = self.line(line);
+        let local = at.min(range.end) - range.start;
+        let vertical = |lines: isize| {
+            let column = self
+                .goal_column
+                .unwrap_or_else(|| segment::column_of(&text, local, self.tab_width));
+            let target = line as isize + lines;
+            if target < 0 {
+                0
+            } else if target as usize >= self.line_count() {
+                self.len()
+            } else {
+                self.offset(target as usize, column)
+            }
+        };
+        match movement {
+            Movement::Left | Movement::WordLeft if local == 0 => {
+                if line == 0 {
+                    0
+                } else {
+                    self.rope.line_range(line - 1).end
+                }
+            }
+            Movement::Right | Movement::WordRight if at >= range.end => {
+                self.rope.line_start(line + 1)
+            }
+            Movement::Left => range.start + segment::prev_grapheme(&text, local),
+            Movement::Right => range.start + segment::next_grapheme(&text, local),
+            Movement::WordLeft => range.start + segment::prev_word(&text, local),
+            Movement::WordRight => range.start + segment::next_word(&text, local),
+            Movement::Up => vertical(-1),
+            Movement::Down => vertical(1),
+            Movement::PageUp(lines) => vertical(-(lines.max(1) as isize)),
+            Movement::PageDown(lines) => vertical(lines.max(1) as isize),
+            Movement::LineStart => {
+                let indent = text.len() - text.trim_start().len();
+                if local == indent {
+                    range.start
+                } else {
+                    range.start + indent
+                }
+            }
+            Movement::LineEnd => range.end,
+            Movement::DocumentStart => 0,
+            Movement::DocumentEnd => self.len(),
+        }
+    }
+
+    /// Returns the range of the word at a byte offset.
+    pub fn word_at(&self, at: usize) -> Range<usize> {
+        let line = self.rope.line_of(at);
+        let range = self.rope.line_range(line);
+        let word = segment::word_at(&self.line(line), at.min(range.end) - range.start);
+        range.start + word.start..range.start + word.end
+    }
+
+    /// Indents the lines touched by the selection by one level, using a tab or
+    /// `tab_width` spaces.
+    pub fn indent(&mut self, spaces: bool) {
+        let unit = if spaces {
+            " ".repeat(self.tab_width)
+        } else {
+            "\t".to_string()
+        };
+        let replacements = self
+            .selected_lines()
+            .filter(|&line| !self.line_range(line).is_empty())
+            .map(|line| {
+                let start = self.rope.line_start(line);
+                (start..start, unit.clone())
+            })
+            .collect();
+        self.replace_ranges(replacements);
+    }
+
+    /// Removes one level of indentation from the lines touched by the selection.
+    pub fn outdent(&mut self) {
+        let replacements = self
+            .selected_lines()
+            .filter_map(|line| {
+                let range = self.line_range(line);
+                let text = self.line(line);
+                let width = if text.starts_with('\t') {
+                    1
+                } else {
+                    text.bytes()
+                        .take(self.tab_width)
+                        .take_while(|&b| b == b' ')
+                        .count()
+                };
+                if width == 0 {
+                    None
+                } else {
+                    Some((range.start..range.start + width, String::new()))
+                }
+            })
+            .collect();
+        self.replace_ranges(replacements);
+    }
+
+    /// Returns the lines touched by the selection. A selection ending at the start of a
+    /// line does not touch it.
+    fn selected_lines(&self) -> Range<usize> {
+        let range = self.selection.range();
+        let first = self.rope.line_of(range.start);
+        let mut last = self.rope.line_of(range.end);
+        if last > first && self.rope.line_start(last) == range.end {
+            last -= 1;
+        }
+        first..last + 1
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Reverts the last undo step, restoring the selection before it. Returns whether
+    /// there was one.
+    pub fn undo(&mut self) -> bool {
+        let transaction = match self.undo.pop() {
+            Some(transaction) => transaction,
+            None => return false,
+        };
+        for edit in transaction.edits.iter().rev() {
+            self.apply(edit.at, edit.inserted.len(), &edit.removed);
+        }
+        self.selection = transaction.before;
+        self.goal_column = None;
+        self.merge = false;
+        self.redo.push(transaction);
+        true
+    }
+
+    /// Reapplies the last undone step. Returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let transaction = match self.redo.pop() {
+            Some(transaction) => transaction,
+            None => return false,
+        };
+        for edit in &transaction.edits {
+            self.apply(edit.at, edit.removed.len(), &edit.inserted);
+        }
+        self.selection = transaction.after;
+        self.goal_column = None;
+        self.merge = false;
+        self.undo.push(transaction);
+        true
+    }
+
+    /// Returns the first match of `query` starting at or after a byte offset.
+    pub fn find(&self, query: &str, from: usize, options: SearchOptions) -> Option<Range<usize>> {
+        let text = self.text();
+        let from = from.min(text.len());
+        text[from..]
+            .char_indices()
+            .map(|(i, _)| from + i)
+            .find_map(|at| match_at(&text, at, query, options))
+    }
+
+    /// Returns the last match of `query` ending at or before a byte offset.
+    pub fn find_before(
+        &self,
+        query: &str,
+        before: usize,
+        options: SearchOptions,
+    ) -> Option<Range<usize>> {
+        let text = self.text();
+        let before = before.min(text.len());
+        text[..before]
+            .char_indices()
+            .rev()
+            .filter_map(|(at, _)| match_at(&text, at, query, options))
+            .find(|range| range.end <= before)
+    }
+
+    /// Returns all non-overlapping matches of `query`.
+    pub fn find_all(&self, query: &str, options: SearchOptions) -> Vec<Range<usize>> {
+        let text = self.text();
+        let mut matches = Vec::new();
+        let mut at = 0;
+        while at < text.len() {
+            match match_at(&text, at, query, options) {
+                Some(range) => {
+                    at = range.end;
+                    matches.push(range);
+                }
+                None => at += text[at..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        matches
+    }
+
+    /// Replaces all matches of `query` as one undo step. Returns the number of
+    /// replacements.
+    pub fn replace_all(&mut self, query: &str, replacement: &str, options: SearchOptions) -> usize {
+        let replacements: Vec<_> = self
+            .find_all(query, options)
+            .into_iter()
+            .map(|range| (range, replacement.to_string()))
+            .collect();
+        let count = replacements.len();
+        self.replace_ranges(replacements);
+        count
+    }
+
+    /// Returns the styled ranges, in the order they were added.
+    pub fn styles(&self) -> &[(Range<usize>, TextStyle)] {
+        &self.styles
+    }
+
+    /// Styles a byte range, e.g. to mark an error. The range moves with edits around it
+    /// and is removed when its text is deleted.
+    pub fn add_style(&mut self, range: Range<usize>, style: TextStyle) {
+        if range.start < range.end && range.end <= self.len() {
+            self.styles.push((range, style));
+        }
+    }
+
+    pub fn clear_styles(&mut self) {
+        self.styles.clear();
+    }
+}
+
+/// Returns the range of a match of `query` starting at a byte offset.
+fn match_at(text: &str, at: usize, query: &str, options: SearchOptions) -> Option<Range<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+    let end = if options.match_case {
+        if !text[at..].starts_with(query) {
+            return None;
+        }
+        at + query.len()
+    } else {
+        let mut haystack = text[at..].char_indices();
+        for q in query.chars() {
+            match haystack.next() {
+                Some((_, c)) if c == q || c.to_lowercase().eq(q.to_lowercase()) => {}
+                _ => return None,
+            }
+        }
+        haystack.next().map_or(text.len(), |(i, _)| at + i)
+    };
+    if options.whole_word {
+        let before = text[..at].chars().last().is_some_and(segment::is_word_char);
+        let after = text[end..]
+            .chars()
+            .next()
+            .is_some_and(segment::is_word_char);
+        if before || after {
+            return None;
+        }
+    }
+    Some(at..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_moves_and_undoes() {
+        let mut buffer = TextBuffer::from("fn main() {\n\tlet x = 1;\n}");
+        buffer.set_selection(Selection::caret(buffer.line_range(1).end));
+        for c in " // one two".chars() {
+            buffer.insert(&c.to_string());
+        }
+        buffer.delete(Movement::Left);
+        buffer.delete(Movement::WordLeft);
+        assert_eq!(buffer.line(1), "\tlet x = 1; // one ");
+        // Undo steps: the deletions, "two", "one ", "// " and " ".
+        buffer.undo();
+        assert_eq!(buffer.line(1), "\tlet x = 1; // one two");
+        buffer.undo();
+        assert_eq!(buffer.line(1), "\tlet x = 1; // one ");
+        buffer.redo();
+        assert_eq!(buffer.line(1), "\tlet x = 1; // one two");
+
+        // Moving down keeps the column across the shorter line.
+        buffer.set_selection(Selection::caret(6));
+        buffer.move_caret(Movement::Down, false);
+        assert_eq!(buffer.position(buffer.selection().head), (1, 6));
+        buffer.move_caret(Movement::Down, false);
+        assert_eq!(buffer.selection().head, buffer.len());
+        buffer.move_caret(Movement::Up, false);
+        assert_eq!(buffer.position(buffer.selection().head), (1, 6));
+        buffer.move_caret(Movement::LineStart, true);
+        assert_eq!(buffer.selected_text(), "le");
+
+        buffer.add_style(16..21, TextStyle::default());
+        let options = SearchOptions {
+            match_case: false,
+            whole_word: true,
+        };
+        assert_eq!(buffer.find("X", 0, options), Some(17..18));
+        assert_eq!(buffer.find_before("main", 17, options), Some(3..7));
+        assert_eq!(buffer.replace_all("x", "value", options), 1);
+        assert_eq!(buffer.line(1), "\tlet value = 1; // one two");
+        assert_eq!(buffer.styles()[0].0, 16..25);
+        buffer.select_all();
+        buffer.indent(false);
+        assert_eq!(
+            buffer.text(),
+            "\tfn main() {\n\t\tlet value = 1; // one two\n\t}"
+        );
+        buffer.undo();
+        buffer.undo();
+        assert_eq!(buffer.line(1), "\tlet x = 1; // one two");
+        assert_eq!(buffer.styles()[0].0, 16..21);
+    }
+}
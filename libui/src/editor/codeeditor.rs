@@ -0,0 +1,1074 @@
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, FontDescription, Key, Modifiers,
+    MouseButton, MouseEvent, ScrollingArea,
+};
+use draw::{
+    AttributedString, Brush, Canvas, Color, PathData, Rect, TextAlign, TextAttribute, TextLayout,
+    TextLayoutParams, Underline,
+};
+use editor::segment;
+use editor::{Highlighter, Movement, SearchOptions, Selection, TextBuffer, TextStyle};
+use std::cell::{Ref, RefCell};
+use std::ops::Range;
+use std::rc::Rc;
+
+type ChangedHandler = Box<dyn FnMut(&CodeEditor)>;
+type KeyHandler = Box<dyn FnMut(&CodeEditor, &AreaKeyEvent) -> bool>;
+
+#[cfg(target_os = "windows")]
+const MONOSPACE: &str = "Consolas";
+#[cfg(target_os = "macos")]
+const MONOSPACE: &str = "Menlo";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const MONOSPACE: &str = "Monospace";
+
+/// The font and colors of a [`CodeEditor`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EditorStyle {
+    /// The font of the text, which has to be a monospace font.
+    pub font: FontDescription,
+    pub text_color: Color,
+    pub background: Color,
+    /// The background of the line with the caret.
+    pub current_line: Color,
+    pub selection: Color,
+    pub caret: Color,
+    /// The background of matches highlighted by [`CodeEditor::highlight_matches()`].
+    pub search_match: Color,
+    pub gutter_background: Color,
+    pub line_number_color: Color,
+    /// The space around the text in points.
+    pub padding: f64,
+}
+
+impl Default for EditorStyle {
+    fn default() -> EditorStyle {
+        EditorStyle {
+            font: FontDescription {
+                family: MONOSPACE.to_string(),
+                size: 12.0,
+                weight: 400,
+                ..FontDescription::default()
+            },
+            text_color: Color::rgb(0.1, 0.1, 0.1),
+            background: Color::WHITE,
+            current_line: Color::rgb(0.96, 0.96, 0.9),
+            selection: Color::rgb(0.7, 0.82, 1.0),
+            caret: Color::BLACK,
+            search_match: Color::rgb(1.0, 0.9, 0.4),
+            gutter_background: Color::rgb(0.94, 0.94, 0.94),
+            line_number_color: Color::rgb(0.55, 0.55, 0.55),
+            padding: 4.0,
+        }
+    }
+}
+
+/// What a mouse drag selects, set by the number of clicks starting it.
+#[derive(Copy, Clone, PartialEq)]
+enum Granularity {
+    Character,
+    Word,
+    Line,
+}
+
+struct EditorState {
+    buffer: TextBuffer,
+    style: EditorStyle,
+    highlighter: Option<Box<dyn Highlighter>>,
+    line_numbers: bool,
+    read_only: bool,
+    insert_spaces: bool,
+    /// The query and options of highlighted matches, and the matches for a revision.
+    search: Option<(String, SearchOptions)>,
+    matches: Option<(u64, Vec<Range<usize>>)>,
+    composition: Option<String>,
+    /// The width and height of a character cell, measured for the font.
+    cell: (f64, f64),
+    /// The range first selected by the drag in progress, and what it selects.
+    drag: Option<(Range<usize>, Granularity)>,
+    /// The number of columns of the widest line seen, which sets the content width.
+    widest: usize,
+    scrolling: Option<ScrollingArea>,
+    /// The revision of the buffer `on_changed` was last called for.
+    notified: u64,
+    on_changed: Option<ChangedHandler>,
+    on_key: Option<KeyHandler>,
+}
+
+impl EditorState {
+    fn new() -> EditorState {
+        let style = EditorStyle::default();
+        EditorState {
+            buffer: TextBuffer::new(),
+            cell: estimate_cell(&style.font),
+            style,
+            highlighter: None,
+            line_numbers: true,
+            read_only: false,
+            insert_spaces: true,
+            search: None,
+            matches: None,
+            composition: None,
+            drag: None,
+            widest: 0,
+            scrolling: None,
+            notified: 0,
+            on_changed: None,
+            on_key: None,
+        }
+    }
+
+    fn gutter_width(&self) -> f64 {
+        if self.line_numbers {
+            let digits = self.buffer.line_count().to_string().len().max(2);
+            (digits + 2) as f64 * self.cell.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the x coordinate where lines start.
+    fn text_x(&self) -> f64 {
+        self.gutter_width() + self.style.padding
+    }
+
+    fn content_size(&self) -> (u64, u64) {
+        let (cell_width, line_height) = self.cell;
+        let padding = self.style.padding * 2.0;
+        let width = self.text_x() + (self.widest + 2) as f64 * cell_width + padding;
+        let height = self.buffer.line_count() as f64 * line_height + padding;
+        (width.ceil() as u64, height.ceil() as u64)
+    }
+
+    /// Runs an edit and widens the content to the caret line. Returns whether the width
+    /// of all lines has to be measured again, as the edit changed other lines or
+    /// shrank the widest one.
+    fn apply_edit<R, F: FnOnce(&mut EditorState) -> R>(&mut self, f: F) -> (R, bool) {
+        let (lines, line) = (self.buffer.line_count(), self.caret_line());
+        let before = self.line_width(line);
+        let result = f(self);
+        if self.buffer.line_count() != lines || self.caret_line() != line {
+            return (result, true);
+        }
+        let width = self.line_width(line);
+        self.widest = self.widest.max(width);
+        (result, width < before && before == self.widest)
+    }
+
+    fn caret_line(&self) -> usize {
+        self.buffer.rope().line_of(self.buffer.selection().head)
+    }
+
+    /// Returns the number of columns of a line.
+    fn line_width(&self, line: usize) -> usize {
+        self.buffer.position(self.buffer.line_range(line).end).1
+    }
+
+    /// Returns the column of the caret, counting a composition as typed text.
+    fn caret_column(&self) -> (usize, usize) {
+        let head = self.buffer.selection().head;
+        let (line, column) = self.buffer.position(head);
+        match self.composition {
+            Some(ref composition) => {
+                let start = self.buffer.line_range(line).start;
+                let mut text = self.buffer.line(line);
+                let at = clamp_to_text(&text, head - start);
+                text.insert_str(at, composition);
+                let tab_width = self.buffer.tab_width();
+                (
+                    line,
+                    segment::column_of(&text, at + composition.len(), tab_width),
+                )
+            }
+            None => (line, column),
+        }
+    }
+
+    fn caret_rect(&self) -> Rect {
+        let (line, column) = self.caret_column();
+        let (cell_width, line_height) = self.cell;
+        (
+            self.text_x() + column as f64 * cell_width,
+            self.style.padding + line as f64 * line_height,
+            cell_width.min(2.0),
+            line_height,
+        )
+    }
+
+    /// Returns the byte offset under a point of the content.
+    fn offset_at(&self, x: f64, y: f64) -> usize {
+        let (cell_width, line_height) = self.cell;
+        let line = ((y - self.style.padding) / line_height).floor().max(0.0) as usize;
+        if line >= self.buffer.line_count() {
+            return self.buffer.len();
+        }
+        let column = ((x - self.text_x()) / cell_width).max(0.0);
+        self.buffer.offset_at(line, column)
+    }
+
+    /// Recomputes the highlighted matches if the text changed since they were found.
+    fn refresh_matches(&mut self) {
+        let revision = self.buffer.revision();
+        let stale = self.matches.as_ref().map(|m| m.0) != Some(revision);
+        if stale {
+            self.matches = self
+                .search
+                .as_ref()
+                .map(|(query, options)| (revision, self.buffer.find_all(query, *options)));
+        }
+    }
+
+    fn visible_lines(&self) -> usize {
+        let visible = self
+            .scrolling
+            .as_ref()
+            .and_then(ScrollingArea::visible_rect);
+        visible.map_or(20, |rect| (rect.3 / self.cell.1).floor().max(1.0) as usize)
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let style = &self.style;
+        let buffer = &self.buffer;
+        let (cell_width, line_height) = self.cell;
+        let text_x = self.text_x();
+        let gutter = self.gutter_width();
+        let fill = |canvas: &mut C, (x, y, w, h): Rect, color: Color| {
+            canvas.fill(&PathData::rectangle(x, y, w, h), &Brush::Solid(color))
+        };
+        fill(canvas, clip, style.background);
+
+        let first = ((clip.1 - style.padding) / line_height).floor().max(0.0) as usize;
+        let last = ((clip.1 + clip.3 - style.padding) / line_height)
+            .ceil()
+            .max(0.0) as usize;
+        let last = last.min(buffer.line_count());
+        let selection = buffer.selection().range();
+        let (caret_line, caret_column) = self.caret_column();
+        let matches = self.matches.as_ref().map_or(&[][..], |m| &m.1[..]);
+        let tab_width = buffer.tab_width();
+        let right = clip.0 + clip.2;
+
+        for line in first..last {
+            let y = style.padding + line as f64 * line_height;
+            let range = buffer.line_range(line);
+            let text = buffer.line(line);
+            let column = |at: usize| {
+                let at = at.clamp(range.start, range.end) - range.start;
+                segment::column_of(&text, at, tab_width) as f64
+            };
+            let span = |canvas: &mut C, start: f64, end: f64, color: Color| {
+                let x = text_x + start * cell_width;
+                fill(
+                    canvas,
+                    (x, y, (end - start) * cell_width, line_height),
+                    color,
+                );
+            };
+
+            if line == caret_line && selection.is_empty() {
+                fill(
+                    canvas,
+                    (gutter, y, right - gutter, line_height),
+                    style.current_line,
+                );
+            }
+            for m in matches
+                .iter()
+                .filter(|m| m.start <= range.end && m.end > range.start)
+            {
+                span(canvas, column(m.start), column(m.end), style.search_match);
+            }
+            if !selection.is_empty() && selection.start <= range.end && selection.end > range.start
+            {
+                // A selected line break shows as one more column.
+                let newline = if selection.end > range.end { 1.0 } else { 0.0 };
+                let end = column(selection.end) + newline;
+                span(canvas, column(selection.start), end, style.selection);
+            }
+
+            let string = self.line_string(line, &text, range.start);
+            let params = TextLayoutParams {
+                string,
+                default_font: style.font.clone(),
+                width: -1.0,
+                align: TextAlign::Left,
+            };
+            canvas.draw_text(&params, text_x, y);
+
+            if line == caret_line {
+                let x = text_x + caret_column as f64 * cell_width;
+                fill(
+                    canvas,
+                    (x, y, cell_width.min(2.0), line_height),
+                    style.caret,
+                );
+            }
+        }
+
+        if gutter > 0.0 && clip.0 < gutter {
+            fill(
+                canvas,
+                (0.0, clip.1, gutter, clip.3),
+                style.gutter_background,
+            );
+            for line in first..last {
+                let mut number = AttributedString::new(&(line + 1).to_string());
+                let len = number.text().len();
+                number.set_attribute(0..len, TextAttribute::Color(style.line_number_color));
+                let params = TextLayoutParams {
+                    string: number,
+                    default_font: style.font.clone(),
+                    width: gutter - cell_width,
+                    align: TextAlign::Right,
+                };
+                canvas.draw_text(&params, 0.0, style.padding + line as f64 * line_height);
+            }
+        }
+    }
+
+    /// Builds the styled display text of a line, with tabs expanded and the composition
+    /// inserted at the caret.
+    fn line_string(&self, line: usize, text: &str, start: usize) -> AttributedString {
+        let (mut display, mut offsets) = segment::expand_tabs(text, self.buffer.tab_width());
+        let head = self.buffer.selection().head;
+        let composition = match self.composition {
+            Some(ref composition) if self.buffer.rope().line_of(head) == line => {
+                let at = clamp_to_text(text, head - start);
+                let display_at = offsets[at];
+                display.insert_str(display_at, composition);
+                for offset in &mut offsets[at..] {
+                    *offset += composition.len();
+                }
+                Some(display_at..display_at + composition.len())
+            }
+            _ => None,
+        };
+
+        let mut string = AttributedString::new(&display);
+        let len = display.len();
+        string.set_attribute(0..len, TextAttribute::Color(self.style.text_color));
+        let mut styles: Vec<(Range<usize>, TextStyle)> = match self.highlighter {
+            Some(ref highlighter) => highlighter.highlight_line(text),
+            None => Vec::new(),
+        };
+        let end = start + text.len();
+        styles.extend(
+            self.buffer
+                .styles()
+                .iter()
+                .filter(|&(range, _)| range.start < end && range.end > start)
+                .map(|(range, style)| {
+                    let range = range.start.max(start) - start..range.end.min(end) - start;
+                    (range, *style)
+                }),
+        );
+        for (range, style) in styles {
+            if range.start >= range.end || range.end > text.len() {
+                continue;
+            }
+            let (display_start, display_end) = (offsets[range.start], offsets[range.end]);
+            for attribute in style.attributes() {
+                string.set_attribute(display_start..display_end, attribute);
+            }
+        }
+        if let Some(range) = composition {
+            string.set_attribute(range, TextAttribute::Underline(Underline::Single));
+        }
+        string
+    }
+}
+
+/// Clamps an offset into a line to its text and a character boundary, as the caret may
+/// sit between the `\r` and `\n` of a line break.
+fn clamp_to_text(text: &str, at: usize) -> usize {
+    let mut at = at.min(text.len());
+    while !text.is_char_boundary(at) {
+        at -= 1;
+    }
+    at
+}
+
+/// Estimates the cell size of a monospace font, for drawing without a window.
+pub(crate) fn estimate_cell(font: &FontDescription) -> (f64, f64) {
+    let size = if font.size > 0.0 { font.size } else { 12.0 };
+    (size * 0.6, size * 1.3)
+}
+
+/// Measures the cell size of a monospace font.
+pub(crate) fn measure_cell(font: &FontDescription) -> (f64, f64) {
+    const SAMPLE: &str = "0123456789";
+    let layout = TextLayout::new(&TextLayoutParams {
+        string: AttributedString::new(SAMPLE),
+        default_font: font.clone(),
+        width: -1.0,
+        align: TextAlign::Left,
+    });
+    let (width, height) = layout.extents();
+    if width > 0.0 && height > 0.0 {
+        (width / SAMPLE.len() as f64, height)
+    } else {
+        estimate_cell(font)
+    }
+}
+
+/// Returns the character typed by a key with Shift held, for a US keyboard layout.
+fn shifted(c: char) -> char {
+    const PAIRS: &[(char, char)] = &[
+        ('1', '!'),
+        ('2', '@'),
+        ('3', '#'),
+        ('4', '$'),
+        ('5', '%'),
+        ('6', '^'),
+        ('7', '&'),
+        ('8', '*'),
+        ('9', '('),
+        ('0', ')'),
+        ('-', '_'),
+        ('=', '+'),
+        ('[', '{'),
+        (']', '}'),
+        ('\\', '|'),
+        (';', ':'),
+        ('\'', '"'),
+        (',', '<'),
+        ('.', '>'),
+        ('/', '?'),
+        ('`', '~'),
+    ];
+    match PAIRS.iter().find(|&&(plain, _)| plain == c) {
+        Some(&(_, shifted)) => shifted,
+        None => c.to_ascii_uppercase(),
+    }
+}
+
+/// Returns the text typed by a key, if any.
+pub(crate) fn typed_text(key: Key, shift: bool) -> Option<char> {
+    let c = match key {
+        Key::Char(c) if shift => shifted(c),
+        Key::Char(c) => c,
+        Key::Space => ' ',
+        Key::Keypad(n) => (b'0' + n) as char,
+        Key::KeypadDot => '.',
+        Key::KeypadAdd => '+',
+        Key::KeypadSubtract => '-',
+        Key::KeypadMultiply => '*',
+        Key::KeypadDivide => '/',
+        _ => return None,
+    };
+    Some(c)
+}
+
+struct EditorHandler {
+    state: Rc<RefCell<EditorState>>,
+}
+
+impl EditorHandler {
+    fn editor(&self, area: &Area) -> CodeEditor {
+        CodeEditor {
+            area: area.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl AreaHandler for EditorHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let mut state = self.state.borrow_mut();
+        state.refresh_matches();
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        state.render(&mut &params.context, clip);
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        let editor = self.editor(area);
+        match *event {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                count,
+                ref state,
+            } => {
+                let extend = state.modifiers.contains(Modifiers::MODIFIER_SHIFT);
+                editor.select(|editor| {
+                    let at = editor.offset_at(state.x, state.y);
+                    let buffer = &mut editor.buffer;
+                    let anchor = buffer.selection().anchor;
+                    let (range, granularity) = match count {
+                        1 if extend => (anchor..anchor, Granularity::Character),
+                        1 => (at..at, Granularity::Character),
+                        2 => (buffer.word_at(at), Granularity::Word),
+                        _ => {
+                            let line = buffer.rope().line_of(at);
+                            let next = buffer.rope().line_start(line + 1);
+                            (buffer.line_range(line).start..next, Granularity::Line)
+                        }
+                    };
+                    let selection = if extend && count == 1 {
+                        Selection::new(anchor, at)
+                    } else {
+                        Selection::new(range.start, range.end)
+                    };
+                    buffer.set_selection(selection);
+                    editor.composition = None;
+                    editor.drag = Some((range, granularity));
+                });
+            }
+            MouseEvent::Move(ref state) => {
+                if self.state.borrow().drag.is_none() {
+                    return;
+                }
+                editor.select(|editor| {
+                    let at = editor.offset_at(state.x, state.y);
+                    let (ref origin, granularity) = *editor.drag.as_ref().unwrap();
+                    let buffer = &editor.buffer;
+                    let target = match granularity {
+                        Granularity::Character => at..at,
+                        Granularity::Word => buffer.word_at(at),
+                        Granularity::Line => {
+                            let line = buffer.rope().line_of(at);
+                            buffer.line_range(line).start..buffer.rope().line_start(line + 1)
+                        }
+                    };
+                    let selection = if target.start < origin.start {
+                        Selection::new(origin.end, target.start)
+                    } else {
+                        Selection::new(origin.start, target.end.max(origin.end))
+                    };
+                    editor.buffer.set_selection(selection);
+                });
+            }
+            MouseEvent::Release {
+                button: MouseButton::Left,
+                ..
+            }
+            | MouseEvent::DragBroken => self.state.borrow_mut().drag = None,
+            _ => {}
+        }
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        let editor = self.editor(area);
+        // The callback is taken out while it runs, so it can use the editor.
+        let callback = self.state.borrow_mut().on_key.take();
+        if let Some(mut callback) = callback {
+            let handled = callback(&editor, event);
+            let mut state = self.state.borrow_mut();
+            if state.on_key.is_none() {
+                state.on_key = Some(callback);
+            }
+            if handled {
+                return true;
+            }
+        }
+        if event.up {
+            return false;
+        }
+        editor.handle_key(event)
+    }
+}
+
+/// A text editor for source code and configuration files, drawn on a scrolling `Area`.
+///
+/// The editor shows line numbers, highlights the line with the caret and styles text
+/// with a [`Highlighter`] and the styled ranges of its [`TextBuffer`]. It supports
+/// moving the caret by grapheme cluster, word, line and page, selecting with Shift and
+/// the mouse (a double click selects a word, a triple click a line), undo and redo with
+/// Ctrl+Z, Ctrl+Shift+Z and Ctrl+Y, Tab and Shift+Tab to indent and outdent, and
+/// automatic indentation of new lines. On macOS, the Command key works like Ctrl.
+///
+/// Text is laid out on a grid of character cells, so the font has to be a monospace
+/// font; long lines are not wrapped.
+///
+/// libui areas receive keys, not text: typed characters are derived from the keys with
+/// a US keyboard layout, and input methods are not connected. Applications with another
+/// source of text input can feed it through [`CodeEditor::insert_text()`], show text
+/// being composed with [`CodeEditor::set_composition()`] and place a candidate window
+/// at [`CodeEditor::caret_rect()`].
+///
+/// ```no_run
+/// # use libui::editor::*;
+/// let editor = CodeEditor::new();
+/// editor.set_text("[server]\nport = 8080 # default\n");
+/// editor.set_highlighter(SyntaxHighlighter::new().with_line_comment("#"));
+/// editor.on_changed(|editor| println!("{} bytes", editor.buffer().len()));
+/// ```
+#[derive(Clone)]
+pub struct CodeEditor {
+    area: Area,
+    state: Rc<RefCell<EditorState>>,
+}
+
+impl CodeEditor {
+    /// Creates an empty editor.
+    pub fn new() -> CodeEditor {
+        let state = Rc::new(RefCell::new(EditorState::new()));
+        let (width, height) = {
+            let mut state = state.borrow_mut();
+            state.cell = measure_cell(&state.style.font);
+            state.content_size()
+        };
+        let handler = Box::new(EditorHandler {
+            state: state.clone(),
+        });
+        let scrolling = ScrollingArea::new(handler, width, height);
+        let area = scrolling.area().clone();
+        state.borrow_mut().scrolling = Some(scrolling);
+        CodeEditor { area, state }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    pub fn text(&self) -> String {
+        self.state.borrow().buffer.text()
+    }
+
+    /// Replaces the whole text, clearing the undo history and styled ranges.
+    pub fn set_text(&self, text: &str) {
+        self.update(|buffer| buffer.set_text(text));
+    }
+
+    /// Returns the buffer holding the text, selection and undo history.
+    pub fn buffer(&self) -> Ref<'_, TextBuffer> {
+        Ref::map(self.state.borrow(), |state| &state.buffer)
+    }
+
+    /// Modifies the buffer, then redraws the editor and notifies about changes.
+    pub fn update<R, F: FnOnce(&mut TextBuffer) -> R>(&self, f: F) -> R {
+        let (result, full) = {
+            let mut state = self.state.borrow_mut();
+            let revision = state.buffer.revision();
+            let result = f(&mut state.buffer);
+            (result, state.buffer.revision() != revision)
+        };
+        self.refresh(full, false);
+        result
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.state.borrow().buffer.selection()
+    }
+
+    /// Sets the selection and scrolls the caret into view.
+    pub fn set_selection(&self, selection: Selection) {
+        self.select(|state| state.buffer.set_selection(selection));
+    }
+
+    /// Replaces the selection with text, as if it was typed, ending any composition.
+    /// Does nothing in a read-only editor.
+    pub fn insert_text(&self, text: &str) {
+        self.edit(|state| {
+            state.composition = None;
+            state.buffer.insert(text);
+        });
+    }
+
+    /// Shows text being composed by an input method at the caret, underlined, without
+    /// changing the buffer. `None` ends the composition; commit the composed text with
+    /// [`CodeEditor::insert_text()`].
+    pub fn set_composition(&self, composition: Option<&str>) {
+        let composition = composition.filter(|c| !c.is_empty()).map(str::to_string);
+        self.select(|state| state.composition = composition);
+    }
+
+    /// Returns the rectangle of the caret in content coordinates, after any composition,
+    /// e.g. to place the candidate window of an input method.
+    pub fn caret_rect(&self) -> Rect {
+        self.state.borrow().caret_rect()
+    }
+
+    /// Reverts the last change. Returns whether there was one.
+    pub fn undo(&self) -> bool {
+        self.edit(|state| state.buffer.undo())
+    }
+
+    /// Reapplies the last undone change. Returns whether there was one.
+    pub fn redo(&self) -> bool {
+        self.edit(|state| state.buffer.redo())
+    }
+
+    /// Selects the next match of `query` after the selection, continuing from the start
+    /// at the end of the text. Returns whether there was a match.
+    pub fn find_next(&self, query: &str, options: SearchOptions) -> bool {
+        self.select(|state| {
+            let buffer = &mut state.buffer;
+            let from = buffer.selection().range().end;
+            let found = buffer
+                .find(query, from, options)
+                .or_else(|| buffer.find(query, 0, options));
+            if let Some(ref range) = found {
+                buffer.set_selection(Selection::new(range.start, range.end));
+            }
+            found.is_some()
+        })
+    }
+
+    /// Selects the previous match of `query` before the selection, continuing from the
+    /// end at the start of the text. Returns whether there was a match.
+    pub fn find_previous(&self, query: &str, options: SearchOptions) -> bool {
+        self.select(|state| {
+            let buffer = &mut state.buffer;
+            let before = buffer.selection().range().start;
+            let found = buffer
+                .find_before(query, before, options)
+                .or_else(|| buffer.find_before(query, buffer.len(), options));
+            if let Some(ref range) = found {
+                buffer.set_selection(Selection::new(range.start, range.end));
+            }
+            found.is_some()
+        })
+    }
+
+    /// Replaces the selection if it is a match of `query`, then selects the next match.
+    /// Returns whether there is a next match.
+    pub fn replace_next(&self, query: &str, replacement: &str, options: SearchOptions) -> bool {
+        self.edit(|state| {
+            let buffer = &mut state.buffer;
+            let range = buffer.selection().range();
+            if buffer.find(query, range.start, options) == Some(range.clone()) {
+                buffer.replace(range, replacement);
+            }
+        });
+        self.find_next(query, options)
+    }
+
+    /// Replaces all matches of `query` as one undo step. Returns the number of
+    /// replacements.
+    pub fn replace_all(&self, query: &str, replacement: &str, options: SearchOptions) -> usize {
+        self.edit(|state| state.buffer.replace_all(query, replacement, options))
+    }
+
+    /// Highlights all matches of `query`, updating them as the text changes. An empty
+    /// query removes the highlights.
+    pub fn highlight_matches(&self, query: &str, options: SearchOptions) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.search = if query.is_empty() {
+                None
+            } else {
+                Some((query.to_string(), options))
+            };
+            state.matches = None;
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Styles lines with a highlighter, e.g. for syntax highlighting.
+    pub fn set_highlighter<H: Highlighter + 'static>(&self, highlighter: H) {
+        self.state.borrow_mut().highlighter = Some(Box::new(highlighter));
+        self.area.queue_redraw_all();
+    }
+
+    pub fn clear_highlighter(&self) {
+        self.state.borrow_mut().highlighter = None;
+        self.area.queue_redraw_all();
+    }
+
+    pub fn style(&self) -> EditorStyle {
+        self.state.borrow().style.clone()
+    }
+
+    pub fn set_style(&self, style: EditorStyle) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.cell = measure_cell(&style.font);
+            state.style = style;
+        }
+        self.refresh(false, false);
+    }
+
+    pub fn set_line_numbers(&self, line_numbers: bool) {
+        self.state.borrow_mut().line_numbers = line_numbers;
+        self.refresh(false, false);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.state.borrow().read_only
+    }
+
+    /// Sets whether the text can only be selected, not edited, by the user.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.state.borrow_mut().read_only = read_only;
+    }
+
+    /// Sets the number of columns a tab advances to, and the number of spaces Tab
+    /// inserts.
+    pub fn set_tab_width(&self, tab_width: usize) {
+        self.state.borrow_mut().buffer.set_tab_width(tab_width);
+        self.refresh(true, false);
+    }
+
+    /// Sets whether Tab inserts spaces instead of a tab character.
+    pub fn set_insert_spaces(&self, insert_spaces: bool) {
+        self.state.borrow_mut().insert_spaces = insert_spaces;
+    }
+
+    /// Sets the callback run after the text was changed.
+    pub fn on_changed<F: FnMut(&CodeEditor) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_changed = Some(Box::new(callback));
+    }
+
+    /// Sets a callback which sees key events before the editor. Returning `true` marks
+    /// an event as handled, e.g. to run a console command on Enter instead of starting
+    /// a new line.
+    pub fn on_key<F: FnMut(&CodeEditor, &AreaKeyEvent) -> bool + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_key = Some(Box::new(callback));
+    }
+
+    /// Changes the selection or composition, then scrolls the caret into view.
+    fn select<R, F: FnOnce(&mut EditorState) -> R>(&self, f: F) -> R {
+        let result = f(&mut self.state.borrow_mut());
+        self.refresh(false, true);
+        result
+    }
+
+    /// Edits the text unless the editor is read-only, then scrolls the caret into view.
+    fn edit<R: Default, F: FnOnce(&mut EditorState) -> R>(&self, f: F) -> R {
+        if self.is_read_only() {
+            return R::default();
+        }
+        let (result, full) = self.state.borrow_mut().apply_edit(f);
+        self.refresh(full, true);
+        result
+    }
+
+    /// Updates the content size, scrolls to the caret, redraws and notifies about
+    /// changes of the text. With `full`, the width of all lines is measured again.
+    fn refresh(&self, full: bool, reveal: bool) {
+        let (scrolling, size, caret, changed) = {
+            let mut state = self.state.borrow_mut();
+            if full {
+                let widest = (0..state.buffer.line_count())
+                    .map(|line| state.line_width(line))
+                    .max()
+                    .unwrap_or(0);
+                state.widest = widest;
+            }
+            let revision = state.buffer.revision();
+            let changed = revision != state.notified;
+            state.notified = revision;
+            let caret = state.caret_rect();
+            (
+                state.scrolling.clone(),
+                state.content_size(),
+                caret,
+                changed,
+            )
+        };
+        if let Some(scrolling) = scrolling {
+            if scrolling.size() != size {
+                scrolling.set_size(size.0, size.1);
+            }
+            if reveal {
+                let (x, y, width, height) = caret;
+                let margin = self.state.borrow().cell.0 * 4.0;
+                scrolling.scroll_to(x - margin, y, width + margin * 2.0, height);
+            }
+        }
+        self.area.queue_redraw_all();
+
+        if changed {
+            // The callback is taken out while it runs, so it can use the editor.
+            let callback = self.state.borrow_mut().on_changed.take();
+            if let Some(mut callback) = callback {
+                callback(self);
+                let mut state = self.state.borrow_mut();
+                if state.on_changed.is_none() {
+                    state.on_changed = Some(callback);
+                }
+            }
+        }
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        let key = match Key::from_event(event) {
+            Some(key) => key,
+            None => return false,
+        };
+        let modifiers = event.modifiers;
+        let shift = modifiers.contains(Modifiers::MODIFIER_SHIFT);
+        let alt = modifiers.contains(Modifiers::MODIFIER_ALT);
+        let command = modifiers.intersects(Modifiers::MODIFIER_CTRL | Modifiers::MODIFIER_SUPER);
+        // Words are skipped with Ctrl, or with Option on macOS.
+        let word = command || alt;
+
+        let movement = match key {
+            Key::Left if word => Some(Movement::WordLeft),
+            Key::Left => Some(Movement::Left),
+            Key::Right if word => Some(Movement::WordRight),
+            Key::Right => Some(Movement::Right),
+            Key::Up => Some(Movement::Up),
+            Key::Down => Some(Movement::Down),
+            Key::Home if command => Some(Movement::DocumentStart),
+            Key::Home => Some(Movement::LineStart),
+            Key::End if command => Some(Movement::DocumentEnd),
+            Key::End => Some(Movement::LineEnd),
+            Key::PageUp => Some(Movement::PageUp(self.state.borrow().visible_lines())),
+            Key::PageDown => Some(Movement::PageDown(self.state.borrow().visible_lines())),
+            _ => None,
+        };
+        if let Some(movement) = movement {
+            self.select(|state| {
+                state.composition = None;
+                state.buffer.move_caret(movement, shift);
+            });
+            return true;
+        }
+
+        match key {
+            Key::Char('a') if command => self.select(|state| state.buffer.select_all()),
+            Key::Char('z') if command && shift => {
+                self.redo();
+            }
+            Key::Char('z') if command => {
+                self.undo();
+            }
+            Key::Char('y') if command => {
+                self.redo();
+            }
+            Key::Backspace => {
+                let movement = if word {
+                    Movement::WordLeft
+                } else {
+                    Movement::Left
+                };
+                self.edit(|state| state.buffer.delete(movement));
+            }
+            Key::Delete => {
+                let movement = if word {
+                    Movement::WordRight
+                } else {
+                    Movement::Right
+                };
+                self.edit(|state| state.buffer.delete(movement));
+            }
+            Key::Enter | Key::KeypadEnter if !command => self.edit(|state| {
+                // New lines keep the indentation of the line they are started from.
+                let buffer = &mut state.buffer;
+                let line = buffer.rope().line_of(buffer.selection().range().start);
+                let text = buffer.line(line);
+                let indent = &text[..text.len() - text.trim_start().len()];
+                buffer.insert(&format!("\n{}", indent));
+            }),
+            Key::Tab if !command && !alt => self.edit(|state| {
+                let buffer = &mut state.buffer;
+                let range = buffer.selection().range();
+                let multiline =
+                    buffer.rope().line_of(range.start) != buffer.rope().line_of(range.end);
+                if shift {
+                    buffer.outdent();
+                } else if multiline {
+                    buffer.indent(state.insert_spaces);
+                } else if state.insert_spaces {
+                    let column = buffer.position(range.start).1;
+                    let tab_width = buffer.tab_width();
+                    buffer.insert(&" ".repeat(tab_width - column % tab_width));
+                } else {
+                    buffer.insert("\t");
+                }
+            }),
+            key if !command && !alt => match typed_text(key, shift) {
+                Some(c) => self.insert_text(c.encode_utf8(&mut [0; 4])),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Default for CodeEditor {
+    fn default() -> Self {
+        CodeEditor::new()
+    }
+}
+
+impl From<CodeEditor> for Control {
+    fn from(editor: CodeEditor) -> Control {
+        editor.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{DrawCommand, Recorder};
+
+    #[test]
+    fn renders_styled_lines_and_composition() {
+        let mut state = EditorState::new();
+        state.buffer.set_text("let a = 1;\n\tb");
+        state.highlighter = Some(Box::new(|line: &str| {
+            line.find("let")
+                .map(|i| (i..i + 3, TextStyle::default().with_bold(true)))
+                .into_iter()
+                .collect::<Vec<_>>()
+        }));
+        state.buffer.set_selection(Selection::caret(13));
+        state.composition = Some("ü".to_string());
+
+        let (cell_width, line_height) = state.cell;
+        let (x, y) = (state.text_x(), state.style.padding);
+        assert_eq!(state.offset_at(x + 1.6 * cell_width, y), 2);
+        assert_eq!(
+            state.offset_at(x + 5.4 * cell_width, y + 1.5 * line_height),
+            13
+        );
+        assert_eq!(state.caret_column(), (1, 6));
+
+        let mut recorder = Recorder::new();
+        state.render(&mut recorder, (0.0, 0.0, 400.0, 100.0));
+        let texts: Vec<_> = recorder
+            .finish()
+            .commands()
+            .iter()
+            .filter_map(|command| match *command {
+                DrawCommand::Text { ref layout, .. } => Some(layout.string.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts[0].text(), "let a = 1;");
+        assert!(texts[0]
+            .attributes()
+            .contains(&(0..3, TextAttribute::Weight(700))));
+        assert_eq!(texts[1].text(), "    bü");
+        assert!(texts[1]
+            .attributes()
+            .contains(&(5..7, TextAttribute::Underline(Underline::Single))));
+        assert_eq!(texts[2].text(), "1");
+    }
+
+    #[test]
+    fn composes_at_the_end_of_crlf_lines() {
+        let mut state = EditorState::new();
+        state.buffer.set_text("ab\r\nc");
+        state.buffer.set_selection(Selection::caret(3));
+        state.composition = Some("ü".to_string());
+        assert_eq!(state.caret_column(), (0, 3));
+        assert_eq!(state.line_string(0, "ab", 0).text(), "abü");
+    }
+
+    #[test]
+    fn measures_the_width_after_edits() {
+        let mut state = EditorState::new();
+        state.buffer.set_text("abc\nabcdef");
+        state.widest = 6;
+        state.buffer.set_selection(Selection::caret(1));
+        assert_eq!(state.apply_edit(|s| s.buffer.insert("xyzuvw")), ((), false));
+        assert_eq!(state.widest, 9);
+        assert_eq!(state.apply_edit(|s| s.buffer.undo()), (true, true));
+
+        state.widest = 6;
+        state.buffer.set_selection(Selection::new(10, 5));
+        assert_eq!(state.apply_edit(|s| s.buffer.insert("")), ((), true));
+    }
+}
@@ -0,0 +1,1171 @@
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, FontDescription, Key, Modifiers,
+    MouseButton, MouseEvent, ScrollingArea,
+};
+use draw::{
+    Brush, Canvas, Color, PathBuilder, PathData, Rect, StrokeParams, TextAttribute,
+    TextLayoutParams,
+};
+use editor::codeeditor::{estimate_cell, measure_cell, typed_text};
+use editor::EditorStyle;
+use libui_ffi;
+use std::cell::{Ref, RefCell};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+
+type RangeHandler = Box<dyn FnMut(&HexView, Range<u64>)>;
+
+/// The bytes shown by a [`HexView`].
+///
+/// A view only reads the rows it draws, so sources can be far larger than memory, like
+/// disk images read through a [`FileSource`].
+pub trait HexSource {
+    /// Returns the number of bytes.
+    fn len(&self) -> u64;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads the bytes at an offset into a buffer, returning how many were read.
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> usize;
+
+    /// Overwrites bytes at an offset. Returns whether they were written; sources which
+    /// cannot be written keep the default, which writes nothing.
+    fn write(&mut self, _offset: u64, _data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl HexSource for Vec<u8> {
+    fn len(&self) -> u64 {
+        Vec::len(self) as u64
+    }
+
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> usize {
+        let start = offset.min(Vec::len(self) as u64) as usize;
+        let count = buffer.len().min(Vec::len(self) - start);
+        buffer[..count].copy_from_slice(&self[start..start + count]);
+        count
+    }
+
+    fn write(&mut self, offset: u64, data: &[u8]) -> bool {
+        let end = offset.checked_add(data.len() as u64);
+        match end {
+            Some(end) if end <= Vec::len(self) as u64 => {
+                self[offset as usize..end as usize].copy_from_slice(data);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A file shown by a [`HexView`], read and written in place as it is viewed and edited.
+#[derive(Debug)]
+pub struct FileSource {
+    file: RefCell<File>,
+    len: u64,
+    writable: bool,
+}
+
+impl FileSource {
+    /// Opens a file for viewing.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<FileSource> {
+        let file = File::open(path)?;
+        FileSource::with_file(file, false)
+    }
+
+    /// Opens a file for viewing and overwriting bytes.
+    pub fn open_writable<P: AsRef<Path>>(path: P) -> io::Result<FileSource> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        FileSource::with_file(file, true)
+    }
+
+    fn with_file(file: File, writable: bool) -> io::Result<FileSource> {
+        let len = file.metadata()?.len();
+        Ok(FileSource {
+            file: RefCell::new(file),
+            len,
+            writable,
+        })
+    }
+}
+
+impl HexSource for FileSource {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> usize {
+        let mut file = self.file.borrow_mut();
+        if file.seek(SeekFrom::Start(offset)).is_err() {
+            return 0;
+        }
+        let mut count = 0;
+        while count < buffer.len() {
+            match file.read(&mut buffer[count..]) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => count += n,
+            }
+        }
+        count
+    }
+
+    fn write(&mut self, offset: u64, data: &[u8]) -> bool {
+        let end = offset.checked_add(data.len() as u64);
+        if !self.writable || end.is_none_or(|end| end > self.len) {
+            return false;
+        }
+        let file = self.file.get_mut();
+        file.seek(SeekFrom::Start(offset)).is_ok() && file.write_all(data).is_ok()
+    }
+}
+
+/// The error returned by [`parse_offset()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffsetParseError {
+    pub input: String,
+}
+
+impl Display for OffsetParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid offset: {:?}", self.input)
+    }
+}
+
+impl Error for OffsetParseError {}
+
+/// Parses an offset typed by the user for [`HexView::go_to()`]: decimal, or hexadecimal
+/// with a `0x` or `$` prefix or an `h` suffix. Underscores separate digits.
+///
+/// ```
+/// # use libui::editor::parse_offset;
+/// assert_eq!(parse_offset("4096"), Ok(4096));
+/// assert_eq!(parse_offset("0x1000"), Ok(4096));
+/// assert_eq!(parse_offset("$FFFF_0000"), Ok(0xffff_0000));
+/// assert_eq!(parse_offset("1000h"), Ok(4096));
+/// assert!(parse_offset("0xg").is_err());
+/// ```
+pub fn parse_offset(input: &str) -> Result<u64, OffsetParseError> {
+    let text: String = input.trim().chars().filter(|&c| c != '_').collect();
+    let lower = text.to_ascii_lowercase();
+    let (digits, radix) = if let Some(hex) = lower.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(hex) = lower.strip_prefix('$') {
+        (hex, 16)
+    } else if let Some(hex) = lower.strip_suffix('h') {
+        (hex, 16)
+    } else {
+        (&lower[..], 10)
+    };
+    u64::from_str_radix(digits, radix).map_err(|_| OffsetParseError {
+        input: input.to_string(),
+    })
+}
+
+/// A highlighted range of bytes, e.g. a header field, with an optional tooltip shown
+/// when the mouse is over it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HexRegion {
+    pub range: Range<u64>,
+    pub color: Color,
+    pub tooltip: Option<String>,
+}
+
+impl HexRegion {
+    pub fn new(range: Range<u64>, color: Color) -> HexRegion {
+        HexRegion {
+            range,
+            color,
+            tooltip: None,
+        }
+    }
+
+    pub fn with_tooltip(mut self, tooltip: &str) -> Self {
+        self.tooltip = Some(tooltip.to_string());
+        self
+    }
+}
+
+/// The font and colors of a [`HexView`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HexStyle {
+    /// The font of all text, which has to be a monospace font.
+    pub font: FontDescription,
+    pub text_color: Color,
+    pub offset_color: Color,
+    /// The color of ASCII dots standing for other bytes.
+    pub dot_color: Color,
+    pub background: Color,
+    /// The selection in the pane with the cursor.
+    pub selection: Color,
+    /// The selection mirrored in the other pane.
+    pub inactive_selection: Color,
+    pub cursor: Color,
+    pub tooltip_background: Color,
+    pub tooltip_border: Color,
+    /// The space around the content in points.
+    pub padding: f64,
+}
+
+impl Default for HexStyle {
+    fn default() -> HexStyle {
+        HexStyle {
+            font: EditorStyle::default().font,
+            text_color: Color::rgb(0.1, 0.1, 0.1),
+            offset_color: Color::rgb(0.45, 0.45, 0.55),
+            dot_color: Color::rgb(0.65, 0.65, 0.65),
+            background: Color::WHITE,
+            selection: Color::rgb(0.7, 0.82, 1.0),
+            inactive_selection: Color::rgb(0.86, 0.9, 0.96),
+            cursor: Color::BLACK,
+            tooltip_background: Color::rgb(1.0, 1.0, 0.9),
+            tooltip_border: Color::rgb(0.5, 0.5, 0.5),
+            padding: 4.0,
+        }
+    }
+}
+
+/// The panes of a [`HexView`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HexPane {
+    Hex,
+    Ascii,
+}
+
+struct HexState {
+    source: Box<dyn HexSource>,
+    style: HexStyle,
+    bytes_per_row: usize,
+    /// The byte where the selection started and the byte with the cursor. Both are
+    /// selected.
+    anchor: u64,
+    cursor: u64,
+    pane: HexPane,
+    /// Whether the high nibble of the cursor byte was typed in the hex pane.
+    nibble: bool,
+    regions: Vec<HexRegion>,
+    /// The region under the mouse and the mouse position.
+    hover: Option<(usize, f64, f64)>,
+    dragging: bool,
+    read_only: bool,
+    cell: (f64, f64),
+    /// The scroll position last drawn and the row shown at the top of the view there,
+    /// used when the rows are taller than a scrolling area can be.
+    scroll_top: f64,
+    top_row: u64,
+    scrolling: Option<ScrollingArea>,
+    on_selection_changed: Option<RangeHandler>,
+    on_changed: Option<RangeHandler>,
+}
+
+impl HexState {
+    fn new(source: Box<dyn HexSource>) -> HexState {
+        let style = HexStyle::default();
+        HexState {
+            source,
+            cell: estimate_cell(&style.font),
+            style,
+            bytes_per_row: 16,
+            anchor: 0,
+            cursor: 0,
+            pane: HexPane::Hex,
+            nibble: false,
+            regions: Vec::new(),
+            hover: None,
+            dragging: false,
+            read_only: false,
+            scroll_top: 0.0,
+            top_row: 0,
+            scrolling: None,
+            on_selection_changed: None,
+            on_changed: None,
+        }
+    }
+
+    fn rows(&self) -> u64 {
+        let bytes_per_row = self.bytes_per_row as u64;
+        self.source.len().div_ceil(bytes_per_row).max(1)
+    }
+
+    /// Returns the number of hex digits of offsets.
+    fn offset_digits(&self) -> usize {
+        let last = self.source.len().saturating_sub(1);
+        (format!("{:x}", last).len()).max(8)
+    }
+
+    /// Returns the column of a byte of a row in the hex pane. Bytes are grouped by 8.
+    fn hex_column(&self, index: usize) -> usize {
+        self.offset_digits() + 2 + index * 3 + index / 8
+    }
+
+    fn ascii_column(&self, index: usize) -> usize {
+        let bytes = self.bytes_per_row;
+        self.offset_digits() + 2 + bytes * 3 + (bytes - 1) / 8 + 1 + index
+    }
+
+    fn x(&self, column: usize) -> f64 {
+        self.style.padding + column as f64 * self.cell.0
+    }
+
+    fn row_y(&self, row: u64) -> f64 {
+        self.style.padding + row as f64 * self.cell.1
+    }
+
+    /// Returns the height of all rows, which may exceed [`ScrollingArea::MAX_SIZE`].
+    fn rows_height(&self) -> f64 {
+        self.row_y(self.rows()) + self.style.padding
+    }
+
+    /// Returns whether the rows are too tall for a scrolling area, so its scroll range is
+    /// mapped onto them.
+    fn is_mapped(&self) -> bool {
+        self.rows_height() > ScrollingArea::MAX_SIZE as f64
+    }
+
+    fn content_size(&self) -> (u64, u64) {
+        let width = self.x(self.ascii_column(self.bytes_per_row)) + self.style.padding;
+        let height = self.rows_height().min(ScrollingArea::MAX_SIZE as f64);
+        (width.ceil() as u64, height.ceil() as u64)
+    }
+
+    /// Returns the last row which can be at the top of the view.
+    fn max_top_row(&self) -> u64 {
+        self.rows().saturating_sub(self.visible_rows())
+    }
+
+    /// Returns the scroll position showing the last rows when the scroll range is mapped.
+    fn max_scroll_top(&self) -> f64 {
+        (ScrollingArea::MAX_SIZE as f64 - self.view_height()).max(1.0)
+    }
+
+    /// Returns how far rows are drawn above their place in the content. With a mapped
+    /// scroll range, the row at the top of the view is drawn at the scroll position.
+    fn shift(&self) -> f64 {
+        if !self.is_mapped() {
+            return 0.0;
+        }
+        let top_row = self.top_row.min(self.max_top_row());
+        top_row as f64 * self.cell.1 - self.scroll_top
+    }
+
+    /// Follows the scroll position of a draw, picking the row at the top of the view in
+    /// proportion to it. Returns whether the rows moved, so all of them need a redraw.
+    fn scroll(&mut self, top: f64) -> bool {
+        if !self.is_mapped() || (top - self.scroll_top).abs() < 1.0 {
+            return false;
+        }
+        self.scroll_top = top;
+        let fraction = (top / self.max_scroll_top()).clamp(0.0, 1.0);
+        self.top_row = (fraction * self.max_top_row() as f64).round() as u64;
+        true
+    }
+
+    /// Returns the rectangle of the content to scroll into view to show a row. With a
+    /// mapped scroll range, the rows are moved just far enough to show it, and the
+    /// rectangle is the whole view at the matching scroll position.
+    fn reveal_rect(&mut self, row: u64) -> Rect {
+        if !self.is_mapped() {
+            return (0.0, self.row_y(row), 1.0, self.cell.1);
+        }
+        let visible = self.visible_rows();
+        let mut top_row = self.top_row.min(self.max_top_row());
+        if row < top_row {
+            top_row = row;
+        } else if row >= top_row + visible {
+            top_row = row + 1 - visible;
+        }
+        if top_row != self.top_row {
+            let fraction = top_row as f64 / self.max_top_row().max(1) as f64;
+            self.top_row = top_row;
+            self.scroll_top = (fraction * self.max_scroll_top()).round();
+        }
+        (0.0, self.scroll_top, 1.0, self.view_height())
+    }
+
+    /// Returns the selected byte range, which includes the cursor.
+    fn selection(&self) -> Range<u64> {
+        if self.source.is_empty() {
+            return 0..0;
+        }
+        self.anchor.min(self.cursor)..self.anchor.max(self.cursor) + 1
+    }
+
+    /// Returns the byte and pane under a point of the content, if any.
+    fn hit(&self, x: f64, y: f64) -> Option<(u64, HexPane)> {
+        if self.source.is_empty() {
+            return None;
+        }
+        let y = y + self.shift();
+        let row = ((y - self.style.padding) / self.cell.1).floor().max(0.0) as u64;
+        let row = row.min(self.rows() - 1);
+        let column = (x - self.style.padding) / self.cell.0;
+        let bytes = self.bytes_per_row;
+        let ascii = self.ascii_column(0) as f64 - 1.0;
+        let (index, pane) = if column >= ascii {
+            (
+                (column - ascii - 1.0).floor().max(0.0) as usize,
+                HexPane::Ascii,
+            )
+        } else {
+            let index = (0..bytes)
+                .take_while(|&i| column >= self.hex_column(i) as f64 - 0.5)
+                .last()
+                .unwrap_or(0);
+            (index, HexPane::Hex)
+        };
+        let offset = row * bytes as u64 + index.min(bytes - 1) as u64;
+        Some((offset.min(self.source.len() - 1), pane))
+    }
+
+    /// Moves the cursor, extending the selection from its anchor if `extend` is set.
+    fn move_to(&mut self, offset: i128, extend: bool) {
+        let last = self.source.len().saturating_sub(1) as i128;
+        self.cursor = offset.clamp(0, last.max(0)) as u64;
+        if !extend {
+            self.anchor = self.cursor;
+        }
+        self.nibble = false;
+    }
+
+    /// Writes bytes to the source. Returns whether they were written.
+    fn write(&mut self, offset: u64, data: &[u8]) -> bool {
+        !self.read_only && self.source.write(offset, data)
+    }
+
+    /// Types a hex digit at the cursor, overwriting one nibble. The cursor advances
+    /// after the low nibble.
+    fn type_hex(&mut self, digit: u8) -> Option<Range<u64>> {
+        let offset = self.cursor;
+        let mut byte = [0];
+        if self.source.read(offset, &mut byte) != 1 {
+            return None;
+        }
+        byte[0] = if self.nibble {
+            (byte[0] & 0xf0) | digit
+        } else {
+            (byte[0] & 0x0f) | digit << 4
+        };
+        if !self.write(offset, &byte) {
+            return None;
+        }
+        if self.nibble {
+            self.move_to(offset as i128 + 1, false);
+        } else {
+            self.anchor = offset;
+            self.nibble = true;
+        }
+        Some(offset..offset + 1)
+    }
+
+    /// Types a character in the ASCII pane, overwriting the byte at the cursor.
+    fn type_ascii(&mut self, c: char) -> Option<Range<u64>> {
+        if !c.is_ascii() {
+            return None;
+        }
+        let offset = self.cursor;
+        if offset >= self.source.len() || !self.write(offset, &[c as u8]) {
+            return None;
+        }
+        self.move_to(offset as i128 + 1, false);
+        Some(offset..offset + 1)
+    }
+
+    fn region_at(&self, offset: u64) -> Option<usize> {
+        self.regions
+            .iter()
+            .rposition(|region| region.range.contains(&offset))
+    }
+
+    fn view_height(&self) -> f64 {
+        let visible = self
+            .scrolling
+            .as_ref()
+            .and_then(ScrollingArea::visible_rect);
+        visible.map_or(16.0 * self.cell.1, |rect| rect.3)
+    }
+
+    fn visible_rows(&self) -> u64 {
+        (self.view_height() / self.cell.1).floor().max(1.0) as u64
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let style = &self.style;
+        let (cell_width, line_height) = self.cell;
+        let fill = |canvas: &mut C, (x, y, w, h): Rect, color: Color| {
+            canvas.fill(&PathData::rectangle(x, y, w, h), &Brush::Solid(color))
+        };
+        fill(canvas, clip, style.background);
+
+        let shift = self.shift();
+        let first = ((clip.1 + shift - style.padding) / line_height)
+            .floor()
+            .max(0.0) as u64;
+        let last = ((clip.1 + clip.3 + shift - style.padding) / line_height)
+            .ceil()
+            .max(0.0) as u64;
+        let last = last.min(self.rows());
+        if first >= last {
+            return;
+        }
+        let bytes = self.bytes_per_row;
+        let start = first * bytes as u64;
+        // Only the visible rows are read.
+        let mut data = vec![0; ((last - first) * bytes as u64) as usize];
+        let count = self.source.read(start, &mut data);
+        data.truncate(count);
+        let selection = self.selection();
+        let digits = self.offset_digits();
+
+        for row in first..last {
+            let y = self.row_y(row) - shift;
+            let row_start = row * bytes as u64;
+            let index = ((row - first) * bytes as u64) as usize;
+            let row_data = &data[index.min(data.len())..(index + bytes).min(data.len())];
+
+            // Backgrounds of the bytes of a range in this row, in both panes.
+            let span = |canvas: &mut C, range: &Range<u64>, hex: Color, ascii: Color| {
+                let from = range.start.max(row_start);
+                let to = range.end.min(row_start + row_data.len() as u64);
+                if from >= to {
+                    return;
+                }
+                let (a, b) = ((from - row_start) as usize, (to - row_start) as usize);
+                let hex_x = self.x(self.hex_column(a));
+                let hex_end = self.x(self.hex_column(b - 1) + 2);
+                fill(canvas, (hex_x, y, hex_end - hex_x, line_height), hex);
+                let ascii_x = self.x(self.ascii_column(a));
+                let ascii_width = (b - a) as f64 * cell_width;
+                fill(canvas, (ascii_x, y, ascii_width, line_height), ascii);
+            };
+            for region in &self.regions {
+                span(canvas, &region.range, region.color, region.color);
+            }
+            let (hex, ascii) = match self.pane {
+                HexPane::Hex => (style.selection, style.inactive_selection),
+                HexPane::Ascii => (style.inactive_selection, style.selection),
+            };
+            span(canvas, &selection, hex, ascii);
+
+            let offset = format!("{:01$X}", row_start, digits);
+            canvas.draw_text(
+                &TextLayoutParams::colored(&offset, style.offset_color, &style.font),
+                self.x(0),
+                y,
+            );
+
+            let mut hex_text = String::with_capacity(bytes * 3 + bytes / 8);
+            let mut ascii_text = String::with_capacity(bytes);
+            let mut dots = Vec::new();
+            for (i, &byte) in row_data.iter().enumerate() {
+                if i > 0 {
+                    hex_text.push_str(if i % 8 == 0 { "  " } else { " " });
+                }
+                hex_text.push_str(&format!("{:02X}", byte));
+                if (0x20..0x7f).contains(&byte) {
+                    ascii_text.push(byte as char);
+                } else {
+                    dots.push(i);
+                    ascii_text.push('.');
+                }
+            }
+            canvas.draw_text(
+                &TextLayoutParams::colored(&hex_text, style.text_color, &style.font),
+                self.x(self.hex_column(0)),
+                y,
+            );
+            let mut params = TextLayoutParams::colored(&ascii_text, style.text_color, &style.font);
+            for i in dots {
+                let color = TextAttribute::Color(style.dot_color);
+                params.string.set_attribute(i..i + 1, color);
+            }
+            canvas.draw_text(&params, self.x(self.ascii_column(0)), y);
+
+            if self.cursor >= row_start && self.cursor < row_start + bytes as u64 {
+                let index = (self.cursor - row_start) as usize;
+                let (column, width) = match self.pane {
+                    HexPane::Hex if self.nibble => (self.hex_column(index) + 1, 1),
+                    HexPane::Hex => (self.hex_column(index), 2),
+                    HexPane::Ascii => (self.ascii_column(index), 1),
+                };
+                let rect = PathData::rectangle(
+                    self.x(column) + 0.5,
+                    y + 0.5,
+                    width as f64 * cell_width - 1.0,
+                    line_height - 1.0,
+                );
+                let params = StrokeParams {
+                    join: libui_ffi::uiDrawLineJoinMiter,
+                    ..StrokeParams::new(1.0)
+                };
+                canvas.stroke(&rect, &Brush::Solid(style.cursor), &params);
+            }
+        }
+
+        if let Some((index, x, y)) = self.hover {
+            if let Some(ref tooltip) = self.regions.get(index).and_then(|r| r.tooltip.clone()) {
+                self.render_tooltip(canvas, clip, tooltip, x, y);
+            }
+        }
+    }
+
+    fn render_tooltip<C: Canvas>(&self, canvas: &mut C, clip: Rect, text: &str, x: f64, y: f64) {
+        let style = &self.style;
+        let (cell_width, line_height) = self.cell;
+        let columns = text.lines().map(|line| line.chars().count()).max();
+        let w = (columns.unwrap_or(0) + 2) as f64 * cell_width;
+        let h = (text.lines().count().max(1) as f64 + 0.5) * line_height;
+        let x = (x + cell_width)
+            .min(clip.0 + clip.2 - w - 1.0)
+            .max(clip.0 + 1.0);
+        let y = (y + line_height)
+            .min(clip.1 + clip.3 - h - 1.0)
+            .max(clip.1 + 1.0);
+        let frame = PathBuilder::default()
+            .rounded_rect(x, y, w, h, 3.0)
+            .finish();
+        canvas.fill(&frame, &Brush::Solid(style.tooltip_background));
+        let params = StrokeParams::new(1.0);
+        canvas.stroke(&frame, &Brush::Solid(style.tooltip_border), &params);
+        canvas.draw_text(
+            &TextLayoutParams::colored(text, style.text_color, &style.font),
+            x + cell_width,
+            y + line_height / 4.0,
+        );
+    }
+}
+
+struct HexHandler {
+    state: Rc<RefCell<HexState>>,
+}
+
+impl HexHandler {
+    fn view(&self, area: &Area) -> HexView {
+        HexView {
+            area: area.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl AreaHandler for HexHandler {
+    fn draw(&mut self, area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        let moved = {
+            let mut state = self.state.borrow_mut();
+            let visible = state
+                .scrolling
+                .as_ref()
+                .and_then(ScrollingArea::visible_rect);
+            state.scroll(visible.map_or(clip.1, |visible| visible.1))
+        };
+        // Only the exposed strip may be drawn after scrolling, but the rows moved too.
+        if moved {
+            area.queue_redraw_all();
+        }
+        self.state.borrow().render(&mut &params.context, clip);
+    }
+
+    fn mouse(&mut self, area: &Area, event: &MouseEvent) {
+        let view = self.view(area);
+        match *event {
+            MouseEvent::Press {
+                button: MouseButton::Left,
+                ref state,
+                ..
+            } => {
+                let extend = state.modifiers.contains(Modifiers::MODIFIER_SHIFT);
+                view.select_with(true, |hex| {
+                    if let Some((offset, pane)) = hex.hit(state.x, state.y) {
+                        hex.pane = pane;
+                        hex.move_to(offset as i128, extend);
+                        hex.dragging = true;
+                    }
+                });
+            }
+            MouseEvent::Move(ref state) => {
+                let (dragging, hover) = {
+                    let hex = self.state.borrow();
+                    let hover = hex
+                        .hit(state.x, state.y)
+                        .and_then(|(offset, _)| hex.region_at(offset))
+                        .map(|index| (index, state.x, state.y));
+                    (hex.dragging, hover)
+                };
+                if dragging {
+                    view.select_with(false, |hex| {
+                        if let Some((offset, _)) = hex.hit(state.x, state.y) {
+                            hex.move_to(offset as i128, true);
+                        }
+                    });
+                }
+                let mut hex = self.state.borrow_mut();
+                // The tooltip follows the mouse.
+                if hover.is_some() || hex.hover.is_some() {
+                    hex.hover = hover;
+                    area.queue_redraw_all();
+                }
+            }
+            MouseEvent::Release {
+                button: MouseButton::Left,
+                ..
+            }
+            | MouseEvent::DragBroken => self.state.borrow_mut().dragging = false,
+            MouseEvent::Leave if self.state.borrow_mut().hover.take().is_some() => {
+                area.queue_redraw_all()
+            }
+            _ => {}
+        }
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        if event.up {
+            return false;
+        }
+        self.view(area).handle_key(event)
+    }
+}
+
+/// A hex viewer and editor on a scrolling `Area`, showing offsets, bytes in hex and
+/// their ASCII characters.
+///
+/// Only the visible rows are read from the [`HexSource`], so the view copes with large
+/// buffers and files. Bytes are selected with the mouse or with the arrow keys and
+/// Shift; Tab switches between the hex and ASCII panes, and typing hex digits or
+/// characters overwrites the bytes at the cursor unless the view is read-only.
+/// [`HexRegion`]s highlight ranges of bytes and show tooltips on hover.
+///
+/// ```no_run
+/// # use libui::draw::Color;
+/// # use libui::editor::*;
+/// let firmware = vec![0u8; 64 * 1024];
+/// let view = HexView::new(firmware);
+/// view.add_region(
+///     HexRegion::new(0..16, Color::rgb(1.0, 0.9, 0.6)).with_tooltip("Header\nmagic, version"),
+/// );
+/// view.on_changed(|view, range| println!("patched {:?}: {:?}", range, view.read(range.clone())));
+/// view.go_to(parse_offset("0x200").unwrap());
+/// ```
+#[derive(Clone)]
+pub struct HexView {
+    area: Area,
+    state: Rc<RefCell<HexState>>,
+}
+
+impl HexView {
+    /// Creates a view showing the bytes of a source.
+    pub fn new<S: HexSource + 'static>(source: S) -> HexView {
+        let state = Rc::new(RefCell::new(HexState::new(Box::new(source))));
+        let (width, height) = {
+            let mut state = state.borrow_mut();
+            state.cell = measure_cell(&state.style.font);
+            state.content_size()
+        };
+        let handler = Box::new(HexHandler {
+            state: state.clone(),
+        });
+        let scrolling = ScrollingArea::new(handler, width, height);
+        let area = scrolling.area().clone();
+        state.borrow_mut().scrolling = Some(scrolling);
+        HexView { area, state }
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    /// Returns the source of the bytes.
+    pub fn source(&self) -> Ref<'_, dyn HexSource> {
+        Ref::map(self.state.borrow(), |state| &*state.source)
+    }
+
+    /// Shows the bytes of another source, moving the cursor to the start.
+    pub fn set_source<S: HexSource + 'static>(&self, source: S) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.source = Box::new(source);
+            state.hover = None;
+            state.move_to(0, false);
+        }
+        self.refresh(false);
+    }
+
+    /// Returns the number of bytes.
+    pub fn len(&self) -> u64 {
+        self.state.borrow().source.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads a range of bytes, which is cut short at the end of the source.
+    pub fn read(&self, range: Range<u64>) -> Vec<u8> {
+        let mut data = vec![0; range.end.saturating_sub(range.start) as usize];
+        let count = self.state.borrow().source.read(range.start, &mut data);
+        data.truncate(count);
+        data
+    }
+
+    /// Overwrites bytes, even in a read-only view. Returns whether the source wrote them.
+    pub fn write(&self, offset: u64, data: &[u8]) -> bool {
+        let written = self.state.borrow_mut().source.write(offset, data);
+        if written {
+            self.area.queue_redraw_all();
+        }
+        written
+    }
+
+    /// Returns the selected bytes, which include the byte with the cursor.
+    pub fn selection(&self) -> Range<u64> {
+        self.state.borrow().selection()
+    }
+
+    /// Selects a range of bytes, placing the cursor on its last byte, and scrolls it into
+    /// view.
+    pub fn set_selection(&self, range: Range<u64>) {
+        self.select_with(true, |state| {
+            state.move_to(range.start as i128, false);
+            let end = range
+                .start
+                .checked_add(1)
+                .map_or(range.end, |end| range.end.max(end));
+            state.move_to(end as i128 - 1, true);
+        });
+    }
+
+    /// Returns the offset of the byte with the cursor.
+    pub fn cursor(&self) -> u64 {
+        self.state.borrow().cursor
+    }
+
+    /// Moves the cursor to an offset and scrolls it into view.
+    pub fn go_to(&self, offset: u64) {
+        self.select_with(true, |state| state.move_to(offset as i128, false));
+    }
+
+    /// Returns the pane typed into.
+    pub fn pane(&self) -> HexPane {
+        self.state.borrow().pane
+    }
+
+    pub fn set_pane(&self, pane: HexPane) {
+        self.select_with(false, |state| {
+            state.pane = pane;
+            state.nibble = false;
+        });
+    }
+
+    pub fn bytes_per_row(&self) -> usize {
+        self.state.borrow().bytes_per_row
+    }
+
+    /// Sets the number of bytes in a row, 16 by default.
+    pub fn set_bytes_per_row(&self, bytes_per_row: usize) {
+        self.state.borrow_mut().bytes_per_row = bytes_per_row.max(1);
+        self.refresh(true);
+    }
+
+    pub fn regions(&self) -> Vec<HexRegion> {
+        self.state.borrow().regions.clone()
+    }
+
+    /// Highlights a region. Later regions are drawn over earlier ones.
+    pub fn add_region(&self, region: HexRegion) {
+        self.state.borrow_mut().regions.push(region);
+        self.area.queue_redraw_all();
+    }
+
+    pub fn clear_regions(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.regions.clear();
+            state.hover = None;
+        }
+        self.area.queue_redraw_all();
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.state.borrow().read_only
+    }
+
+    /// Sets whether typing leaves the bytes unchanged.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.state.borrow_mut().read_only = read_only;
+    }
+
+    pub fn style(&self) -> HexStyle {
+        self.state.borrow().style.clone()
+    }
+
+    pub fn set_style(&self, style: HexStyle) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.cell = measure_cell(&style.font);
+            state.style = style;
+        }
+        self.refresh(false);
+    }
+
+    /// Sets the callback run when the selection changed.
+    pub fn on_selection_changed<F: FnMut(&HexView, Range<u64>) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_selection_changed = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run after bytes were typed over, with their range.
+    pub fn on_changed<F: FnMut(&HexView, Range<u64>) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_changed = Some(Box::new(callback));
+    }
+
+    /// Changes the selection, then scrolls the cursor into view if `reveal` is set and
+    /// notifies about a changed selection.
+    fn select_with<R, F: FnOnce(&mut HexState) -> R>(&self, reveal: bool, f: F) -> R {
+        let (result, before, after) = {
+            let mut state = self.state.borrow_mut();
+            let before = state.selection();
+            let result = f(&mut state);
+            (result, before, state.selection())
+        };
+        if reveal {
+            self.reveal();
+        }
+        self.area.queue_redraw_all();
+        if before != after {
+            let callback = self.state.borrow_mut().on_selection_changed.take();
+            self.notify(callback, after, |state| &mut state.on_selection_changed);
+        }
+        result
+    }
+
+    /// Runs a callback taken out of its slot, then puts it back unless it was replaced.
+    fn notify<F>(&self, callback: Option<RangeHandler>, range: Range<u64>, slot: F)
+    where
+        F: Fn(&mut HexState) -> &mut Option<RangeHandler>,
+    {
+        if let Some(mut callback) = callback {
+            callback(self, range);
+            let mut state = self.state.borrow_mut();
+            let slot = slot(&mut state);
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+
+    /// Scrolls the row with the cursor into view.
+    fn reveal(&self) {
+        let (scrolling, rect) = {
+            let mut state = self.state.borrow_mut();
+            let row = state.cursor / state.bytes_per_row as u64;
+            let rect = state.reveal_rect(row);
+            (state.scrolling.clone(), rect)
+        };
+        if let Some(scrolling) = scrolling {
+            let visible = scrolling.visible_rect();
+            let x = visible.map_or(0.0, |v| v.0);
+            scrolling.scroll_to(x, rect.1, rect.2, rect.3);
+        }
+    }
+
+    /// Updates the content size after the layout changed.
+    fn refresh(&self, reveal: bool) {
+        let (scrolling, size) = {
+            let state = self.state.borrow();
+            (state.scrolling.clone(), state.content_size())
+        };
+        if let Some(scrolling) = scrolling {
+            if scrolling.size() != size {
+                scrolling.set_size(size.0, size.1);
+            }
+        }
+        if reveal {
+            self.reveal();
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        let key = match Key::from_event(event) {
+            Some(key) => key,
+            None => return false,
+        };
+        let modifiers = event.modifiers;
+        let shift = modifiers.contains(Modifiers::MODIFIER_SHIFT);
+        let command = modifiers.intersects(Modifiers::MODIFIER_CTRL | Modifiers::MODIFIER_SUPER);
+        let (cursor, bytes, page) = {
+            let state = self.state.borrow();
+            let bytes = state.bytes_per_row as i128;
+            (
+                state.cursor as i128,
+                bytes,
+                state.visible_rows() as i128 * bytes,
+            )
+        };
+        let row_start = cursor - cursor % bytes;
+        let target = match key {
+            Key::Left => Some(cursor - 1),
+            Key::Right => Some(cursor + 1),
+            Key::Up => Some(cursor - bytes),
+            Key::Down => Some(cursor + bytes),
+            Key::PageUp => Some(cursor - page),
+            Key::PageDown => Some(cursor + page),
+            Key::Home if command => Some(0),
+            Key::Home => Some(row_start),
+            Key::End if command => Some(i128::MAX),
+            Key::End => Some(row_start + bytes - 1),
+            _ => None,
+        };
+        if let Some(target) = target {
+            self.select_with(true, |state| state.move_to(target, shift));
+            return true;
+        }
+
+        match key {
+            Key::Tab => {
+                let pane = match self.pane() {
+                    HexPane::Hex => HexPane::Ascii,
+                    HexPane::Ascii => HexPane::Hex,
+                };
+                self.set_pane(pane);
+                true
+            }
+            Key::Char('a') if command => {
+                let len = self.len();
+                self.set_selection(0..len);
+                true
+            }
+            key if !command && !modifiers.contains(Modifiers::MODIFIER_ALT) => {
+                let c = match typed_text(key, shift) {
+                    Some(c) => c,
+                    None => return false,
+                };
+                let changed = self.select_with(true, |state| match state.pane {
+                    HexPane::Hex => c.to_digit(16).and_then(|d| state.type_hex(d as u8)),
+                    HexPane::Ascii => state.type_ascii(c),
+                });
+                if let Some(range) = changed {
+                    let callback = self.state.borrow_mut().on_changed.take();
+                    self.notify(callback, range, |state| &mut state.on_changed);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<HexView> for Control {
+    fn from(view: HexView) -> Control {
+        view.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_and_types_over_bytes() {
+        let mut state = HexState::new(Box::new((0..40u8).collect::<Vec<u8>>()));
+        assert_eq!(state.rows(), 3);
+        assert_eq!(state.hex_column(0), 10);
+        assert_eq!(state.hex_column(8), 35);
+        assert_eq!(state.ascii_column(0), 60);
+
+        let (cell_width, line_height) = state.cell;
+        let at = |column: f64, row: f64| (4.0 + column * cell_width, 4.0 + row * line_height);
+        let (x, y) = at(35.5, 1.5);
+        assert_eq!(state.hit(x, y), Some((24, HexPane::Hex)));
+        let (x, y) = at(62.2, 2.5);
+        assert_eq!(state.hit(x, y), Some((34, HexPane::Ascii)));
+        // Past the last byte.
+        let (x, y) = at(75.0, 2.5);
+        assert_eq!(state.hit(x, y), Some((39, HexPane::Ascii)));
+
+        state.move_to(3, false);
+        assert_eq!(state.type_hex(0xa), Some(3..4));
+        assert_eq!(state.type_hex(0xb), Some(3..4));
+        assert_eq!(state.cursor, 4);
+        state.pane = HexPane::Ascii;
+        assert_eq!(state.type_ascii('Z'), Some(4..5));
+        let mut bytes = [0; 3];
+        state.source.read(2, &mut bytes);
+        assert_eq!(bytes, [2, 0xab, b'Z']);
+        state.move_to(10, true);
+        assert_eq!(state.selection(), 5..11);
+        state.read_only = true;
+        assert_eq!(state.type_ascii('x'), None);
+    }
+
+    struct Zeros(u64);
+
+    impl HexSource for Zeros {
+        fn len(&self) -> u64 {
+            self.0
+        }
+
+        fn read(&self, offset: u64, buffer: &mut [u8]) -> usize {
+            let count = self.0.saturating_sub(offset).min(buffer.len() as u64) as usize;
+            buffer[..count].iter_mut().for_each(|byte| *byte = 0);
+            count
+        }
+    }
+
+    #[test]
+    fn maps_the_scroll_range_onto_rows_of_large_sources() {
+        let mut state = HexState::new(Box::new(Zeros(1 << 40)));
+        let (_, height) = state.content_size();
+        assert_eq!(height, ScrollingArea::MAX_SIZE);
+        let line_height = state.cell.1;
+        let middle = |state: &HexState| 4.0 + state.scroll_top + line_height / 2.0;
+
+        // Scrolling to the end shows the last rows.
+        let bottom = state.max_scroll_top();
+        assert!(state.scroll(bottom));
+        assert!(!state.scroll(bottom + 0.5));
+        let last_row = state.max_top_row();
+        assert_eq!(last_row, (1 << 36) - 16);
+        assert_eq!(state.hit(0.0, middle(&state)).unwrap().0, last_row * 16);
+
+        // Revealing a row in the middle moves the rows, not just the scroll position.
+        let row = 1 << 35;
+        let rect = state.reveal_rect(row);
+        assert_eq!(rect.1, state.scroll_top);
+        assert!(rect.1 < bottom / 2.0 + 1.0);
+        assert_eq!(state.hit(0.0, middle(&state)).unwrap().0, row * 16);
+        // Drawing at the new scroll position keeps them.
+        assert!(!state.scroll(rect.1));
+
+        // Small sources scroll as usual.
+        let mut state = HexState::new(Box::new(vec![0; 4096]));
+        assert_eq!(
+            state.reveal_rect(100),
+            (0.0, state.row_y(100), 1.0, line_height)
+        );
+        assert!(!state.scroll(100.0));
+        assert_eq!(state.shift(), 0.0);
+    }
+
+    #[test]
+    fn rejects_writes_past_the_end() {
+        let mut data = vec![0u8; 4];
+        assert!(!HexSource::write(&mut data, u64::MAX, &[1, 2]));
+        assert!(HexSource::write(&mut data, 2, &[1, 2]));
+        assert_eq!(data, [0, 0, 1, 2]);
+    }
+}
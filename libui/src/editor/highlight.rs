@@ -0,0 +1,217 @@
+use controls::SlantStyle;
+use draw::{Color, TextAttribute, Underline};
+use std::ops::Range;
+
+/// The style of a range of text in a [`CodeEditor`](super::CodeEditor).
+///
+/// Styles of overlapping ranges are combined, with later ones overriding what they set.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextStyle {
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: Option<Underline>,
+}
+
+impl TextStyle {
+    /// Creates a style setting the text color.
+    pub fn color(color: Color) -> TextStyle {
+        TextStyle {
+            color: Some(color),
+            ..TextStyle::default()
+        }
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    pub fn with_underline(mut self, underline: Underline) -> Self {
+        self.underline = Some(underline);
+        self
+    }
+
+    /// Returns the text attributes applying the style.
+    pub fn attributes(&self) -> Vec<TextAttribute> {
+        let mut attributes = Vec::new();
+        if let Some(color) = self.color {
+            attributes.push(TextAttribute::Color(color));
+        }
+        if let Some(background) = self.background {
+            attributes.push(TextAttribute::Background(background));
+        }
+        if self.bold {
+            attributes.push(TextAttribute::Weight(700));
+        }
+        if self.italic {
+            attributes.push(TextAttribute::Slant(SlantStyle::Italic));
+        }
+        if let Some(underline) = self.underline {
+            attributes.push(TextAttribute::Underline(underline));
+        }
+        attributes
+    }
+}
+
+/// Styles lines of text for display, e.g. for syntax highlighting.
+///
+/// Lines are highlighted on their own whenever they are drawn, so constructs spanning
+/// several lines, like block comments, are not recognized. Closures taking a line and
+/// returning styled byte ranges of it are highlighters:
+///
+/// ```
+/// # use libui::draw::Color;
+/// # use libui::editor::*;
+/// let todo = |line: &str| {
+///     line.match_indices("TODO")
+///         .map(|(i, m)| (i..i + m.len(), TextStyle::color(Color::rgb(0.8, 0.0, 0.0))))
+///         .collect::<Vec<_>>()
+/// };
+/// assert_eq!(todo.highlight_line("// TODO: fix")[0].0, 3..7);
+/// ```
+pub trait Highlighter {
+    /// Returns the styles of byte ranges of a line, which is passed without its line
+    /// break.
+    fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, TextStyle)>;
+}
+
+impl<F: Fn(&str) -> Vec<(Range<usize>, TextStyle)>> Highlighter for F {
+    fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, TextStyle)> {
+        self(line)
+    }
+}
+
+/// A highlighter for keywords, numbers, strings and line comments, enough for most
+/// configuration and scripting languages.
+///
+/// ```
+/// # use libui::editor::*;
+/// let lua = SyntaxHighlighter::new()
+///     .with_keywords(&["local", "function", "end", "return"])
+///     .with_line_comment("--");
+/// let styles = lua.highlight_line("local x = 42 -- the answer");
+/// let ranges: Vec<_> = styles.into_iter().map(|(range, _)| range).collect();
+/// assert_eq!(ranges, vec![0..5, 10..12, 13..26]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxHighlighter {
+    keywords: Vec<String>,
+    line_comment: Option<String>,
+    quotes: Vec<char>,
+    pub keyword_style: TextStyle,
+    pub number_style: TextStyle,
+    pub string_style: TextStyle,
+    pub comment_style: TextStyle,
+}
+
+impl Default for SyntaxHighlighter {
+    fn default() -> SyntaxHighlighter {
+        SyntaxHighlighter {
+            keywords: Vec::new(),
+            line_comment: None,
+            quotes: vec!['"', '\''],
+            keyword_style: TextStyle::color(Color::rgb(0.0, 0.2, 0.6)).with_bold(true),
+            number_style: TextStyle::color(Color::rgb(0.6, 0.3, 0.0)),
+            string_style: TextStyle::color(Color::rgb(0.1, 0.5, 0.1)),
+            comment_style: TextStyle::color(Color::rgb(0.45, 0.45, 0.45)).with_italic(true),
+        }
+    }
+}
+
+impl SyntaxHighlighter {
+    /// Creates a highlighter for numbers and strings in double or single quotes.
+    pub fn new() -> SyntaxHighlighter {
+        SyntaxHighlighter::default()
+    }
+
+    pub fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    /// Sets the prefix starting comments which last until the end of the line.
+    pub fn with_line_comment(mut self, prefix: &str) -> Self {
+        self.line_comment = Some(prefix.to_string());
+        self
+    }
+
+    /// Sets the characters delimiting strings. A backslash escapes the next character
+    /// within strings.
+    pub fn with_quotes(mut self, quotes: &[char]) -> Self {
+        self.quotes = quotes.to_vec();
+        self
+    }
+}
+
+impl Highlighter for SyntaxHighlighter {
+    fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, TextStyle)> {
+        let mut styles = Vec::new();
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if let Some(ref prefix) = self.line_comment {
+                if line[start..].starts_with(prefix.as_str()) {
+                    styles.push((start..line.len(), self.comment_style));
+                    break;
+                }
+            }
+            if self.quotes.contains(&c) {
+                let mut end = line.len();
+                while let Some((i, d)) = chars.next() {
+                    if d == '\\' {
+                        chars.next();
+                    } else if d == c {
+                        end = i + d.len_utf8();
+                        break;
+                    }
+                }
+                styles.push((start..end, self.string_style));
+            } else if c.is_alphanumeric() || c == '_' {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, d)) = chars.peek() {
+                    if !(d.is_alphanumeric() || d == '_' || (c.is_ascii_digit() && d == '.')) {
+                        break;
+                    }
+                    end = i + d.len_utf8();
+                    chars.next();
+                }
+                let word = &line[start..end];
+                if c.is_ascii_digit() {
+                    styles.push((start..end, self.number_style));
+                } else if self.keywords.iter().any(|k| k == word) {
+                    styles.push((start..end, self.keyword_style));
+                }
+            }
+        }
+        styles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_strings_and_escapes() {
+        let highlighter = SyntaxHighlighter::new()
+            .with_keywords(&["if"])
+            .with_line_comment("#");
+        let styles = highlighter.highlight_line(r##"if "a\"#b" # 0x1f"##);
+        assert_eq!(styles.len(), 3);
+        assert_eq!(styles[0], (0..2, highlighter.keyword_style));
+        assert_eq!(styles[1], (3..10, highlighter.string_style));
+        assert_eq!(styles[2], (11..17, highlighter.comment_style));
+    }
+}
@@ -0,0 +1,24 @@
+//! A code editor control and the text handling behind it.
+//!
+//! A [`CodeEditor`] edits a [`TextBuffer`], which keeps the text in a [`Rope`] together
+//! with the selection, the undo history and styled ranges. A [`Highlighter`] styles
+//! lines for display, e.g. the keywords, strings and comments found by a
+//! [`SyntaxHighlighter`].
+//!
+//! A [`HexView`] shows and edits binary data from a [`HexSource`], like a buffer or a
+//! file, as offsets, hex bytes and ASCII characters.
+
+mod buffer;
+mod codeeditor;
+mod hexview;
+mod highlight;
+mod rope;
+pub mod segment;
+
+pub use self::buffer::*;
+pub use self::codeeditor::*;
+pub use self::hexview::*;
+pub use self::highlight::*;
+pub use self::rope::*;
+
+pub(crate) use self::codeeditor::{estimate_cell, measure_cell, typed_text};
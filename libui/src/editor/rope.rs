@@ -0,0 +1,515 @@
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+/// The largest leaf, in bytes. Larger leaves are split when text is inserted.
+const MAX_LEAF: usize = 1024;
+
+#[derive(Clone, Debug)]
+enum Node {
+    Leaf(String),
+    Branch {
+        left: Box<Node>,
+        right: Box<Node>,
+        len: usize,
+        newlines: usize,
+        depth: usize,
+    },
+}
+
+impl Node {
+    fn branch(left: Node, right: Node) -> Node {
+        Node::Branch {
+            len: left.len() + right.len(),
+            newlines: left.newlines() + right.newlines(),
+            depth: left.depth().max(right.depth()) + 1,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    /// Builds a balanced tree from leaves.
+    fn balanced(mut leaves: Vec<String>) -> Node {
+        match leaves.len() {
+            0 => Node::Leaf(String::new()),
+            1 => Node::Leaf(leaves.pop().unwrap()),
+            n => {
+                let right = leaves.split_off(n / 2);
+                Node::branch(Node::balanced(leaves), Node::balanced(right))
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Node::Leaf(ref text) => text.len(),
+            Node::Branch { len, .. } => len,
+        }
+    }
+
+    fn newlines(&self) -> usize {
+        match *self {
+            Node::Leaf(ref text) => text.bytes().filter(|&b| b == b'\n').count(),
+            Node::Branch { newlines, .. } => newlines,
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match *self {
+            Node::Leaf(_) => 0,
+            Node::Branch { depth, .. } => depth,
+        }
+    }
+
+    /// Recomputes the cached values of a branch after its children changed, collapsing
+    /// it into one child if the other became empty.
+    fn update(&mut self) {
+        let collapsed = match *self {
+            Node::Leaf(_) => return,
+            Node::Branch {
+                ref mut left,
+                ref mut right,
+                ..
+            } => {
+                if left.len() == 0 {
+                    Some(mem::replace(&mut **right, Node::Leaf(String::new())))
+                } else if right.len() == 0 {
+                    Some(mem::replace(&mut **left, Node::Leaf(String::new())))
+                } else {
+                    None
+                }
+            }
+        };
+        match collapsed {
+            Some(node) => *self = node,
+            None => {
+                if let Node::Branch {
+                    ref left,
+                    ref right,
+                    ref mut len,
+                    ref mut newlines,
+                    ref mut depth,
+                } = *self
+                {
+                    *len = left.len() + right.len();
+                    *newlines = left.newlines() + right.newlines();
+                    *depth = left.depth().max(right.depth()) + 1;
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, at: usize, text: &str) {
+        match *self {
+            Node::Leaf(ref mut leaf) => {
+                leaf.insert_str(at, text);
+                if leaf.len() > MAX_LEAF {
+                    let leaves = split_leaves(&mem::take(leaf));
+                    *self = Node::balanced(leaves);
+                }
+                return;
+            }
+            Node::Branch {
+                ref mut left,
+                ref mut right,
+                ..
+            } => {
+                let left_len = left.len();
+                if at <= left_len {
+                    left.insert(at, text);
+                } else {
+                    right.insert(at - left_len, text);
+                }
+            }
+        }
+        self.update();
+    }
+
+    fn remove(&mut self, start: usize, end: usize) {
+        match *self {
+            Node::Leaf(ref mut leaf) => {
+                leaf.replace_range(start..end, "");
+                return;
+            }
+            Node::Branch {
+                ref mut left,
+                ref mut right,
+                ..
+            } => {
+                let left_len = left.len();
+                if start < left_len {
+                    left.remove(start, end.min(left_len));
+                }
+                if end > left_len {
+                    right.remove(start.max(left_len) - left_len, end - left_len);
+                }
+            }
+        }
+        self.update();
+    }
+
+    fn into_leaves(self, leaves: &mut Vec<String>) {
+        match self {
+            Node::Leaf(leaf) => {
+                // Small neighbours are merged, so leaves left over from removals vanish.
+                match leaves.last_mut() {
+                    Some(last) if last.len() + leaf.len() <= MAX_LEAF / 2 => last.push_str(&leaf),
+                    _ if leaf.is_empty() => {}
+                    _ => leaves.push(leaf),
+                }
+            }
+            Node::Branch { left, right, .. } => {
+                left.into_leaves(leaves);
+                right.into_leaves(leaves);
+            }
+        }
+    }
+
+    fn chunks<'a>(&'a self, chunks: &mut Vec<&'a str>) {
+        match *self {
+            Node::Leaf(ref leaf) => chunks.push(leaf),
+            Node::Branch {
+                ref left,
+                ref right,
+                ..
+            } => {
+                left.chunks(chunks);
+                right.chunks(chunks);
+            }
+        }
+    }
+
+    fn slice(&self, start: usize, end: usize, out: &mut String) {
+        match *self {
+            Node::Leaf(ref leaf) => out.push_str(&leaf[start..end]),
+            Node::Branch {
+                ref left,
+                ref right,
+                ..
+            } => {
+                let left_len = left.len();
+                if start < left_len {
+                    left.slice(start, end.min(left_len), out);
+                }
+                if end > left_len {
+                    right.slice(start.max(left_len) - left_len, end - left_len, out);
+                }
+            }
+        }
+    }
+
+    /// Counts the line breaks before a byte offset.
+    fn newlines_before(&self, at: usize) -> usize {
+        match *self {
+            Node::Leaf(ref leaf) => leaf.as_bytes()[..at]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count(),
+            Node::Branch {
+                ref left,
+                ref right,
+                ..
+            } => {
+                let left_len = left.len();
+                if at <= left_len {
+                    left.newlines_before(at)
+                } else {
+                    left.newlines() + right.newlines_before(at - left_len)
+                }
+            }
+        }
+    }
+
+    /// Returns the byte offset after the `n`th line break, counting from 1.
+    fn after_newline(&self, n: usize) -> usize {
+        match *self {
+            Node::Leaf(ref leaf) => leaf
+                .match_indices('\n')
+                .nth(n - 1)
+                .map_or(leaf.len(), |(i, _)| i + 1),
+            Node::Branch {
+                ref left,
+                ref right,
+                ..
+            } => {
+                let left_newlines = left.newlines();
+                if n <= left_newlines {
+                    left.after_newline(n)
+                } else {
+                    left.len() + right.after_newline(n - left_newlines)
+                }
+            }
+        }
+    }
+
+    fn is_char_boundary(&self, at: usize) -> bool {
+        match *self {
+            Node::Leaf(ref leaf) => leaf.is_char_boundary(at),
+            Node::Branch {
+                ref left,
+                ref right,
+                ..
+            } => {
+                let left_len = left.len();
+                if at <= left_len {
+                    left.is_char_boundary(at)
+                } else {
+                    right.is_char_boundary(at - left_len)
+                }
+            }
+        }
+    }
+}
+
+/// Splits text into leaves of at most `MAX_LEAF / 2` bytes, at character boundaries.
+fn split_leaves(text: &str) -> Vec<String> {
+    let mut leaves = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = (start + MAX_LEAF / 2).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        leaves.push(text[start..end].to_string());
+        start = end;
+    }
+    leaves
+}
+
+/// A string stored as a balanced tree of chunks, for editing large texts.
+///
+/// Inserting and removing text, and converting between byte offsets and line numbers,
+/// take logarithmic time in the length of the text. All offsets are byte offsets and
+/// must lie on character boundaries. Lines are separated by `'\n'`.
+///
+/// ```
+/// # use libui::editor::Rope;
+/// let mut rope = Rope::from("fn main() {\n}\n");
+/// rope.insert(12, "    println!(\"hi\");\n");
+/// assert_eq!(rope.line_count(), 4);
+/// assert_eq!(rope.line_start(2), 32);
+/// assert_eq!(rope.line_of(20), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rope {
+    root: Node,
+}
+
+impl Default for Rope {
+    fn default() -> Rope {
+        Rope {
+            root: Node::Leaf(String::new()),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Rope {
+    fn from(text: &'a str) -> Rope {
+        Rope {
+            root: Node::balanced(split_leaves(text)),
+        }
+    }
+}
+
+impl Rope {
+    /// Creates an empty rope.
+    pub fn new() -> Rope {
+        Rope::default()
+    }
+
+    /// Returns the length in bytes.
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of lines, which is one more than the number of line breaks.
+    pub fn line_count(&self) -> usize {
+        self.root.newlines() + 1
+    }
+
+    /// Inserts text at a byte offset.
+    ///
+    /// # Panics
+    /// Panics if the offset is out of bounds or not on a character boundary.
+    pub fn insert(&mut self, at: usize, text: &str) {
+        assert!(
+            self.is_char_boundary(at),
+            "Offset {} is not a character boundary of the rope",
+            at
+        );
+        if text.is_empty() {
+            return;
+        }
+        self.root.insert(at, text);
+        self.rebalance();
+    }
+
+    /// Removes a byte range.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or not on character boundaries.
+    pub fn remove(&mut self, range: Range<usize>) {
+        assert!(
+            range.start <= range.end
+                && self.is_char_boundary(range.start)
+                && self.is_char_boundary(range.end),
+            "Range {:?} is not a valid range of the rope",
+            range
+        );
+        if range.start == range.end {
+            return;
+        }
+        self.root.remove(range.start, range.end);
+        self.rebalance();
+    }
+
+    /// Returns a copy of a byte range.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or not on character boundaries.
+    pub fn slice(&self, range: Range<usize>) -> String {
+        assert!(
+            range.start <= range.end
+                && self.is_char_boundary(range.start)
+                && self.is_char_boundary(range.end),
+            "Range {:?} is not a valid range of the rope",
+            range
+        );
+        let mut out = String::with_capacity(range.end - range.start);
+        if range.start < range.end {
+            self.root.slice(range.start, range.end, &mut out);
+        }
+        out
+    }
+
+    /// Returns whether the byte offset lies on a character boundary, including the end.
+    pub fn is_char_boundary(&self, at: usize) -> bool {
+        at <= self.len() && self.root.is_char_boundary(at)
+    }
+
+    /// Returns the line containing a byte offset, counting from 0. Offsets past the end
+    /// belong to the last line.
+    pub fn line_of(&self, at: usize) -> usize {
+        self.root.newlines_before(at.min(self.len()))
+    }
+
+    /// Returns the byte offset where a line starts, or the length for lines past the
+    /// end.
+    pub fn line_start(&self, line: usize) -> usize {
+        if line == 0 {
+            0
+        } else if line >= self.line_count() {
+            self.len()
+        } else {
+            self.root.after_newline(line)
+        }
+    }
+
+    /// Returns the byte range of a line, without its line break.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.line_start(line);
+        let mut end = if line + 1 >= self.line_count() {
+            self.len()
+        } else {
+            self.line_start(line + 1) - 1
+        };
+        if end > start && self.is_char_boundary(end - 1) && self.slice(end - 1..end) == "\r" {
+            end -= 1;
+        }
+        start..end
+    }
+
+    /// Returns a line without its line break.
+    pub fn line(&self, line: usize) -> String {
+        self.slice(self.line_range(line))
+    }
+
+    /// Returns the chunks of text the rope is made of, in order.
+    pub fn chunks(&self) -> Vec<&str> {
+        let mut chunks = Vec::new();
+        self.root.chunks(&mut chunks);
+        chunks
+    }
+
+    /// Rebuilds the tree when edits at one place made it too deep.
+    fn rebalance(&mut self) {
+        let leaves = self.len() / (MAX_LEAF / 2) + 1;
+        let limit = 2 * (usize::BITS - leaves.leading_zeros()) as usize + 8;
+        if self.root.depth() > limit {
+            let root = mem::replace(&mut self.root, Node::Leaf(String::new()));
+            let mut leaves = Vec::new();
+            root.into_leaves(&mut leaves);
+            self.root = Node::balanced(leaves);
+        }
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len() == other.len() && self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for Rope {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_like_a_string() {
+        let mut rope = Rope::new();
+        let mut string = String::new();
+        // Typing at the end and in the middle splits leaves and rebalances the tree.
+        for i in 0..5000 {
+            let text = if i % 7 == 0 { "äö\n" } else { "line " };
+            let at = if i % 3 == 0 {
+                string.len()
+            } else {
+                let mut at = string.len() / 2;
+                while !string.is_char_boundary(at) {
+                    at -= 1;
+                }
+                at
+            };
+            rope.insert(at, text);
+            string.insert_str(at, text);
+        }
+        assert!(rope.root.depth() < 40);
+        let boundary = |string: &str, mut at: usize| {
+            while !string.is_char_boundary(at) {
+                at -= 1;
+            }
+            at
+        };
+        let (start, end) = (boundary(&string, 100), boundary(&string, 20_000));
+        rope.remove(start..end);
+        string.replace_range(start..end, "");
+        assert_eq!(rope.to_string(), string);
+        assert_eq!(rope.line_count(), string.split('\n').count());
+        let line = 40;
+        let start = string
+            .split('\n')
+            .take(line)
+            .map(|l| l.len() + 1)
+            .sum::<usize>();
+        assert_eq!(rope.line_start(line), start);
+        assert_eq!(rope.line_of(start), line);
+        assert_eq!(rope.line(line), string.split('\n').nth(line).unwrap());
+        let (start, end) = (boundary(&string, 10), boundary(&string, 30));
+        assert_eq!(rope.slice(start..end), &string[start..end]);
+    }
+}
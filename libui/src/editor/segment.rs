@@ -0,0 +1,307 @@
+//! Grapheme, word and column boundaries within a line of text.
+//!
+//! Grapheme clusters follow the common cases of Unicode text segmentation: combining
+//! marks, variation selectors, emoji modifiers and zero width joiner sequences stay with
+//! their base character, CR LF and pairs of regional indicators form one cluster.
+
+use std::ops::Range;
+
+/// Returns whether a character extends the grapheme cluster before it.
+fn is_extend(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036f
+        | 0x0483..=0x0489
+        | 0x0591..=0x05bd
+        | 0x0610..=0x061a
+        | 0x064b..=0x065f
+        | 0x0900..=0x0903
+        | 0x093a..=0x094f
+        | 0x0e31 | 0x0e34..=0x0e3a | 0x0e47..=0x0e4e
+        | 0x1ab0..=0x1aff
+        | 0x1dc0..=0x1dff
+        | 0x200c..=0x200d
+        | 0x20d0..=0x20ff
+        | 0x302a..=0x302f
+        | 0x3099..=0x309a
+        | 0xfe00..=0xfe0f
+        | 0xfe20..=0xfe2f
+        | 0x1f3fb..=0x1f3ff
+        | 0xe0020..=0xe007f
+        | 0xe0100..=0xe01ef)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// Returns whether a character takes two columns in a monospace font, like CJK
+/// ideographs and most emoji.
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd)
+}
+
+/// Returns the end of the grapheme cluster starting at a byte offset.
+pub fn next_grapheme(text: &str, at: usize) -> usize {
+    let mut chars = text[at..]
+        .char_indices()
+        .map(|(i, c)| (at + i, c))
+        .peekable();
+    let first = match chars.next() {
+        Some((_, c)) => c,
+        None => return text.len(),
+    };
+    let mut end = at + first.len_utf8();
+    match first {
+        '\r' => {
+            if let Some(&(i, '\n')) = chars.peek() {
+                return i + 1;
+            }
+            return end;
+        }
+        '\n' => return end,
+        c if is_regional_indicator(c) => {
+            if let Some(&(i, c)) = chars.peek() {
+                if is_regional_indicator(c) {
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+            }
+        }
+        _ => {}
+    }
+    let mut joined = false;
+    for (i, c) in chars {
+        if is_extend(c) || joined {
+            joined = c == '\u{200d}';
+            end = i + c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    end
+}
+
+/// Returns the start of the grapheme cluster ending at a byte offset.
+pub fn prev_grapheme(text: &str, at: usize) -> usize {
+    let mut start = 0;
+    let mut boundary = 0;
+    while boundary < at {
+        start = boundary;
+        boundary = next_grapheme(text, boundary);
+    }
+    start
+}
+
+/// Returns the grapheme boundaries of a text, including 0 and its length.
+pub fn grapheme_boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = vec![0];
+    let mut at = 0;
+    while at < text.len() {
+        at = next_grapheme(text, at);
+        boundaries.push(at);
+    }
+    boundaries
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punctuation,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' || is_extend(c) {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// Returns whether a character belongs to words, as opposed to whitespace and
+/// punctuation.
+pub fn is_word_char(c: char) -> bool {
+    class(c) == CharClass::Word
+}
+
+/// Returns the end of the word after a byte offset, skipping whitespace first. Runs of
+/// punctuation count as words.
+pub fn next_word(text: &str, at: usize) -> usize {
+    let mut chars = text[at..]
+        .char_indices()
+        .map(|(i, c)| (at + i, c))
+        .peekable();
+    while let Some(&(_, c)) = chars.peek() {
+        if class(c) != CharClass::Space {
+            break;
+        }
+        chars.next();
+    }
+    let word = match chars.peek() {
+        Some(&(_, c)) => class(c),
+        None => return text.len(),
+    };
+    for (i, c) in chars {
+        if class(c) != word {
+            return i;
+        }
+    }
+    text.len()
+}
+
+/// Returns the start of the word before a byte offset, skipping whitespace first.
+pub fn prev_word(text: &str, at: usize) -> usize {
+    let mut chars = text[..at].char_indices().rev().peekable();
+    while let Some(&(_, c)) = chars.peek() {
+        if class(c) != CharClass::Space {
+            break;
+        }
+        chars.next();
+    }
+    let word = match chars.peek() {
+        Some(&(_, c)) => class(c),
+        None => return 0,
+    };
+    let mut start = 0;
+    for (i, c) in chars {
+        if class(c) != word {
+            return start;
+        }
+        start = i;
+    }
+    start
+}
+
+/// Returns the range of the word, whitespace or punctuation run at a byte offset, as
+/// selected by a double click.
+pub fn word_at(text: &str, at: usize) -> Range<usize> {
+    let c = match text[at..]
+        .chars()
+        .next()
+        .or_else(|| text[..at].chars().last())
+    {
+        Some(c) => class(c),
+        None => return at..at,
+    };
+    let start = text[..at]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, d)| class(d) == c)
+        .last()
+        .map_or(at, |(i, _)| i);
+    let end = text[at..]
+        .char_indices()
+        .find(|&(_, d)| class(d) != c)
+        .map_or(text.len(), |(i, _)| at + i);
+    start..end
+}
+
+/// Returns the number of columns a grapheme cluster takes, given the column it starts
+/// at.
+fn cluster_width(cluster: &str, column: usize, tab_width: usize) -> usize {
+    match cluster.chars().next() {
+        Some('\t') => tab_width.max(1) - column % tab_width.max(1),
+        Some(c) if is_wide(c) => 2,
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+/// Returns the column of a byte offset, with tabs advancing to the next multiple of
+/// `tab_width` and wide characters taking two columns.
+pub fn column_of(text: &str, at: usize, tab_width: usize) -> usize {
+    let mut column = 0;
+    let mut start = 0;
+    while start < at {
+        let end = next_grapheme(text, start);
+        column += cluster_width(&text[start..end], column, tab_width);
+        start = end;
+    }
+    column
+}
+
+/// Returns the grapheme boundary closest to a column, rounding to the nearer side of a
+/// cluster spanning it.
+pub fn offset_at_column(text: &str, column: f64, tab_width: usize) -> usize {
+    let mut current = 0;
+    let mut start = 0;
+    while start < text.len() {
+        let end = next_grapheme(text, start);
+        let width = cluster_width(&text[start..end], current, tab_width);
+        if column < current as f64 + width as f64 / 2.0 {
+            return start;
+        }
+        current += width;
+        start = end;
+    }
+    text.len()
+}
+
+/// Expands tabs to spaces for display. Returns the expanded text and, for every byte
+/// offset of the original text up to its length, the offset in the expanded text.
+pub fn expand_tabs(text: &str, tab_width: usize) -> (String, Vec<usize>) {
+    let mut expanded = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut column = 0;
+    let mut start = 0;
+    while start < text.len() {
+        let end = next_grapheme(text, start);
+        let cluster = &text[start..end];
+        let width = cluster_width(cluster, column, tab_width);
+        offsets.extend((start..end).map(|_| expanded.len()));
+        if cluster == "\t" {
+            expanded.extend((0..width).map(|_| ' '));
+        } else {
+            expanded.push_str(cluster);
+        }
+        column += width;
+        start = end;
+    }
+    offsets.push(expanded.len());
+    (expanded, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_boundaries() {
+        // e + combining acute, a family emoji joined by ZWJs, a flag and CR LF.
+        let text = "e\u{301}x👨\u{200d}👩\u{200d}👧🇦🇹\r\n";
+        assert_eq!(grapheme_boundaries(text), vec![0, 3, 4, 22, 30, 32]);
+        assert_eq!(prev_grapheme(text, 22), 4);
+
+        let line = "  let foo_bar = bar.baz();";
+        assert_eq!(next_word(line, 0), 5);
+        assert_eq!(next_word(line, 5), 13);
+        assert_eq!(prev_word(line, 13), 6);
+        assert_eq!(prev_word(line, 19), 16);
+        assert_eq!(word_at(line, 8), 6..13);
+
+        assert_eq!(column_of("\ta\tb", 3, 4), 8);
+        assert_eq!(column_of("日本", 6, 4), 4);
+        assert_eq!(offset_at_column("\tab", 2.5, 4), 1);
+        assert_eq!(offset_at_column("\tab", 1.5, 4), 0);
+        assert_eq!(
+            expand_tabs("a\tb", 4),
+            ("a   b".to_string(), vec![0, 1, 4, 5])
+        );
+    }
+}
@@ -39,16 +39,23 @@ extern crate bitflags;
 
 extern crate libc;
 extern crate libui_ffi;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
+pub mod animation;
 mod builder;
 mod callback_helpers;
+pub mod charts;
 mod compile_tests;
 pub mod controls;
 pub mod draw;
+pub mod editor;
 mod error;
 mod ffi_tools;
 pub mod menus;
 pub mod str_tools;
+pub mod terminal;
 mod ui;
 
 pub use error::UIError;
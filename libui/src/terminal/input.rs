@@ -0,0 +1,149 @@
+use controls::{Key, Modifiers};
+use editor::typed_text;
+
+/// Returns the bytes an xterm sends to the program for a key, or `None` for keys
+/// without a sequence.
+///
+/// Ctrl with a letter sends its control character and Alt prefixes an escape. Cursor
+/// keys send application sequences like `ESC O A` when `app_cursor` is set, see
+/// [`Screen::app_cursor_keys()`](super::Screen::app_cursor_keys); with modifiers, they
+/// and the other special keys send the xterm form with the modifiers as a parameter,
+/// like `ESC [ 1 ; 5 A` for Ctrl+Up. Characters are those of a US keyboard layout.
+///
+/// ```
+/// # use libui::controls::{Key, Modifiers};
+/// # use libui::terminal::*;
+/// let none = Modifiers::empty();
+/// assert_eq!(key_bytes(Key::Char('c'), Modifiers::MODIFIER_CTRL, false), Some(vec![3]));
+/// assert_eq!(key_bytes(Key::Up, none, true), Some(b"\x1bOA".to_vec()));
+/// assert_eq!(key_bytes(Key::F(5), none, false), Some(b"\x1b[15~".to_vec()));
+/// ```
+pub fn key_bytes(key: Key, modifiers: Modifiers, app_cursor: bool) -> Option<Vec<u8>> {
+    let shift = modifiers.contains(Modifiers::MODIFIER_SHIFT);
+    let alt = modifiers.contains(Modifiers::MODIFIER_ALT);
+    let ctrl = modifiers.contains(Modifiers::MODIFIER_CTRL);
+    // The xterm modifier parameter: 1 plus 1 for Shift, 2 for Alt and 4 for Ctrl.
+    let parameter = 1 + shift as u8 + 2 * alt as u8 + 4 * ctrl as u8;
+
+    // Cursor and function keys ending in a letter, like `ESC [ A` or `ESC O P`.
+    let letter = |c: u8, ss3: bool| {
+        let mut bytes = Vec::new();
+        if parameter > 1 {
+            bytes.extend_from_slice(format!("\x1b[1;{}", parameter).as_bytes());
+        } else if ss3 {
+            bytes.extend_from_slice(b"\x1bO");
+        } else {
+            bytes.extend_from_slice(b"\x1b[");
+        }
+        bytes.push(c);
+        bytes
+    };
+    // Editing and function keys ending in a tilde, like `ESC [ 3 ~`.
+    let tilde = |n: u8| {
+        if parameter > 1 {
+            format!("\x1b[{};{}~", n, parameter).into_bytes()
+        } else {
+            format!("\x1b[{}~", n).into_bytes()
+        }
+    };
+
+    let bytes = match key {
+        Key::Up => letter(b'A', app_cursor),
+        Key::Down => letter(b'B', app_cursor),
+        Key::Right => letter(b'C', app_cursor),
+        Key::Left => letter(b'D', app_cursor),
+        Key::Home => letter(b'H', app_cursor),
+        Key::End => letter(b'F', app_cursor),
+        Key::F(n @ 1..=4) => letter(b'P' + n - 1, true),
+        Key::F(n @ 5..=12) => tilde([15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5]),
+        Key::F(_) => return None,
+        Key::Insert => tilde(2),
+        Key::Delete => tilde(3),
+        Key::PageUp => tilde(5),
+        Key::PageDown => tilde(6),
+        Key::Tab if shift => b"\x1b[Z".to_vec(),
+        key => {
+            let byte = match key {
+                Key::Enter | Key::KeypadEnter => b'\r',
+                Key::Tab => b'\t',
+                Key::Backspace if ctrl => 0x08,
+                Key::Backspace => 0x7f,
+                Key::Escape => 0x1b,
+                Key::Space if ctrl => 0,
+                Key::Char(c) if ctrl => match c {
+                    'a'..='z' => c as u8 - b'a' + 1,
+                    '@' | '2' => 0,
+                    '[' | '3' => 0x1b,
+                    '\\' | '4' => 0x1c,
+                    ']' | '5' => 0x1d,
+                    '6' => 0x1e,
+                    '/' | '-' | '7' => 0x1f,
+                    '8' => 0x7f,
+                    _ => return None,
+                },
+                key => {
+                    let c = typed_text(key, shift)?;
+                    let mut bytes = if alt { vec![0x1b] } else { Vec::new() };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                    return Some(bytes);
+                }
+            };
+            if alt {
+                vec![0x1b, byte]
+            } else {
+                vec![byte]
+            }
+        }
+    };
+    Some(bytes)
+}
+
+/// Returns the bytes sent for pasted text, bracketed by `ESC [ 200 ~` and `ESC [ 201 ~`
+/// if the program asked for it. Line breaks are sent as carriage returns, like typed
+/// Enter keys.
+pub fn paste_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    let text = text.replace("\r\n", "\r").replace('\n', "\r");
+    let mut bytes = Vec::with_capacity(text.len() + 12);
+    if bracketed {
+        bytes.extend_from_slice(b"\x1b[200~");
+        // An end marker in the text must not end the paste early.
+        bytes.extend_from_slice(text.replace("\x1b[201~", "").as_bytes());
+        bytes.extend_from_slice(b"\x1b[201~");
+    } else {
+        bytes.extend_from_slice(text.as_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_keys_like_xterm() {
+        let none = Modifiers::empty();
+        let shift = Modifiers::MODIFIER_SHIFT;
+        let alt = Modifiers::MODIFIER_ALT;
+        let ctrl = Modifiers::MODIFIER_CTRL;
+        let bytes = |key, modifiers, app_cursor| key_bytes(key, modifiers, app_cursor).unwrap();
+
+        assert_eq!(bytes(Key::Char('a'), shift, false), b"A");
+        assert_eq!(bytes(Key::Char('1'), shift, false), b"!");
+        assert_eq!(bytes(Key::Char('x'), alt, false), b"\x1bx");
+        assert_eq!(bytes(Key::Char('['), ctrl, false), b"\x1b");
+        assert_eq!(bytes(Key::Enter, none, false), b"\r");
+        assert_eq!(bytes(Key::Backspace, none, false), b"\x7f");
+        assert_eq!(bytes(Key::Backspace, alt, false), b"\x1b\x7f");
+        assert_eq!(bytes(Key::Tab, shift, false), b"\x1b[Z");
+        assert_eq!(bytes(Key::Left, none, false), b"\x1b[D");
+        assert_eq!(bytes(Key::Up, ctrl, true), b"\x1b[1;5A");
+        assert_eq!(bytes(Key::F(1), none, false), b"\x1bOP");
+        assert_eq!(bytes(Key::Delete, shift, false), b"\x1b[3;2~");
+        assert_eq!(bytes(Key::Keypad(7), none, false), b"7");
+        assert_eq!(key_bytes(Key::Char('.'), ctrl, false), None);
+
+        assert_eq!(paste_bytes("a\nb", false), b"a\rb");
+        assert_eq!(paste_bytes("x\x1b[201~", true), b"\x1b[200~x\x1b[201~");
+    }
+}
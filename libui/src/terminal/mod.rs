@@ -0,0 +1,17 @@
+//! A terminal emulator control and the parsing behind it.
+//!
+//! A [`Terminal`] shows the output of a program in a [`Screen`], a grid of character
+//! cells updated by the text and escape sequences a [`VtParser`] finds in the output.
+//! Key presses are sent back to the program as the bytes [`key_bytes()`] returns.
+//! Starting the program and connecting it to a pseudo terminal is left to the
+//! application.
+
+mod input;
+mod parser;
+mod screen;
+mod view;
+
+pub use self::input::*;
+pub use self::parser::*;
+pub use self::screen::*;
+pub use self::view::*;
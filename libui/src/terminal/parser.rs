@@ -0,0 +1,393 @@
+//! A parser splitting the output of a program into text and VT100/xterm control
+//! functions.
+
+use std::str;
+
+/// The most parameters kept for a control sequence; further ones are ignored.
+const MAX_PARAMS: usize = 32;
+/// The most intermediate bytes of an escape or control sequence; sequences with more are
+/// ignored.
+const MAX_INTERMEDIATES: usize = 2;
+/// The longest operating system command in bytes, as accepted by xterm; longer ones are
+/// ignored.
+const MAX_OSC: usize = 4096;
+
+/// A piece of terminal output, as found by a [`VtParser`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// A character to show.
+    Print(char),
+    /// A C0 control character, like a line feed or a backspace.
+    Execute(u8),
+    /// A control sequence introduced by `ESC [`, like `ESC [ 1 ; 31 m`.
+    Csi {
+        /// The parameters, with 0 for omitted ones. Sub-parameters separated by colons
+        /// are flattened into the list.
+        params: Vec<u16>,
+        /// A private marker like the `?` of `ESC [ ? 25 h`.
+        private: Option<u8>,
+        intermediates: Vec<u8>,
+        action: char,
+    },
+    /// An escape sequence, like `ESC 7` or `ESC ( 0`.
+    Esc {
+        intermediates: Vec<u8>,
+        action: char,
+    },
+    /// An operating system command like `ESC ] 0 ; title BEL`, split at semicolons.
+    Osc(Vec<String>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    /// An escape sequence with too many intermediates, ignored until its final byte.
+    EscapeIgnore,
+    Csi,
+    /// A control sequence with invalid characters, ignored until its final byte.
+    CsiIgnore,
+    Osc,
+    /// A device control or other string, ignored until its terminator.
+    IgnoreString,
+}
+
+/// A parser for the escape sequences of VT100 and xterm compatible terminals, following
+/// the state machine of the DEC ANSI parser.
+///
+/// The parser keeps its state between calls, so output can be fed in chunks as it is
+/// read, even when a chunk splits an escape sequence or a UTF-8 character. Invalid UTF-8
+/// is shown as U+FFFD.
+///
+/// ```
+/// # use libui::terminal::*;
+/// let mut parser = VtParser::new();
+/// let mut actions = Vec::new();
+/// parser.advance(b"a\x1b[1;3", |action| actions.push(action));
+/// parser.advance(b"1mb\r", |action| actions.push(action));
+/// assert_eq!(
+///     actions,
+///     vec![
+///         Action::Print('a'),
+///         Action::Csi {
+///             params: vec![1, 31],
+///             private: None,
+///             intermediates: vec![],
+///             action: 'm'
+///         },
+///         Action::Print('b'),
+///         Action::Execute(b'\r'),
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct VtParser {
+    state: State,
+    params: Vec<u16>,
+    /// Whether the last parameter has a digit or separator, telling `ESC [ m` with no
+    /// parameters apart from `ESC [ 0 m`.
+    param_started: bool,
+    private: Option<u8>,
+    intermediates: Vec<u8>,
+    osc: Vec<u8>,
+    /// The bytes of an incomplete UTF-8 character.
+    utf8: Vec<u8>,
+    /// Whether an escape ended a string, so a following backslash is part of the
+    /// terminator.
+    string_escape: bool,
+}
+
+impl Default for VtParser {
+    fn default() -> Self {
+        VtParser::new()
+    }
+}
+
+impl VtParser {
+    pub fn new() -> VtParser {
+        VtParser {
+            state: State::Ground,
+            params: Vec::new(),
+            param_started: false,
+            private: None,
+            intermediates: Vec::new(),
+            osc: Vec::new(),
+            utf8: Vec::new(),
+            string_escape: false,
+        }
+    }
+
+    /// Parses bytes of terminal output, calling `f` for every action found.
+    pub fn advance<F: FnMut(Action)>(&mut self, bytes: &[u8], mut f: F) {
+        for &byte in bytes {
+            self.byte(byte, &mut f);
+        }
+    }
+
+    fn byte<F: FnMut(Action)>(&mut self, byte: u8, f: &mut F) {
+        // Strings end with BEL or with ST, which is `ESC \`.
+        if self.state == State::Osc || self.state == State::IgnoreString {
+            let escape = self.string_escape;
+            self.string_escape = byte == 0x1b;
+            match byte {
+                0x07 => self.end_string(f),
+                b'\\' if escape => self.end_string(f),
+                0x1b => {}
+                _ if escape => {
+                    // An escape starting another sequence cancels the string.
+                    self.end_string(f);
+                    self.enter_escape();
+                    self.byte(byte, f);
+                }
+                _ if self.state == State::Osc && self.osc.len() < MAX_OSC => self.osc.push(byte),
+                _ if self.state == State::Osc => self.state = State::IgnoreString,
+                _ => {}
+            }
+            return;
+        }
+
+        match byte {
+            0x18 | 0x1a => {
+                self.utf8.clear();
+                self.state = State::Ground;
+                return;
+            }
+            0x1b => {
+                if !self.utf8.is_empty() {
+                    self.utf8.clear();
+                    f(Action::Print(char::REPLACEMENT_CHARACTER));
+                }
+                self.enter_escape();
+                return;
+            }
+            0x00..=0x1f => {
+                // C0 controls take effect even within escape sequences.
+                f(Action::Execute(byte));
+                return;
+            }
+            _ => {}
+        }
+
+        match self.state {
+            State::Ground => self.print(byte, f),
+            State::Escape | State::EscapeIntermediate => match byte {
+                0x20..=0x2f if self.intermediates.len() < MAX_INTERMEDIATES => {
+                    self.intermediates.push(byte);
+                    self.state = State::EscapeIntermediate;
+                }
+                0x20..=0x2f => self.state = State::EscapeIgnore,
+                b'[' if self.state == State::Escape => {
+                    self.state = State::Csi;
+                }
+                b']' if self.state == State::Escape => {
+                    self.osc.clear();
+                    self.state = State::Osc;
+                }
+                b'P' | b'X' | b'^' | b'_' if self.state == State::Escape => {
+                    self.state = State::IgnoreString;
+                }
+                0x30..=0x7e => {
+                    f(Action::Esc {
+                        intermediates: self.intermediates.clone(),
+                        action: byte as char,
+                    });
+                    self.state = State::Ground;
+                }
+                _ => {}
+            },
+            State::Csi => match byte {
+                b'0'..=b'9' => {
+                    if self.params.is_empty() {
+                        self.params.push(0);
+                    }
+                    let last = self.params.last_mut().unwrap();
+                    *last = last.saturating_mul(10).saturating_add((byte - b'0') as u16);
+                    self.param_started = true;
+                }
+                b';' | b':' => {
+                    if self.params.is_empty() {
+                        self.params.push(0);
+                    }
+                    if self.params.len() < MAX_PARAMS {
+                        self.params.push(0);
+                    }
+                    self.param_started = true;
+                }
+                b'<'..=b'?' if !self.param_started && self.private.is_none() => {
+                    self.private = Some(byte);
+                }
+                0x20..=0x2f if self.intermediates.len() < MAX_INTERMEDIATES => {
+                    self.intermediates.push(byte)
+                }
+                0x40..=0x7e => {
+                    f(Action::Csi {
+                        params: self.params.clone(),
+                        private: self.private,
+                        intermediates: self.intermediates.clone(),
+                        action: byte as char,
+                    });
+                    self.state = State::Ground;
+                }
+                _ => self.state = State::CsiIgnore,
+            },
+            State::EscapeIgnore => {
+                if let 0x30..=0x7e = byte {
+                    self.state = State::Ground;
+                }
+            }
+            State::CsiIgnore => {
+                if let 0x40..=0x7e = byte {
+                    self.state = State::Ground;
+                }
+            }
+            State::Osc | State::IgnoreString => unreachable!(),
+        }
+    }
+
+    fn enter_escape(&mut self) {
+        self.state = State::Escape;
+        self.params.clear();
+        self.param_started = false;
+        self.private = None;
+        self.intermediates.clear();
+    }
+
+    fn end_string<F: FnMut(Action)>(&mut self, f: &mut F) {
+        if self.state == State::Osc {
+            let text = String::from_utf8_lossy(&self.osc);
+            f(Action::Osc(text.split(';').map(str::to_string).collect()));
+        }
+        self.string_escape = false;
+        self.state = State::Ground;
+    }
+
+    /// Collects the bytes of UTF-8 characters.
+    fn print<F: FnMut(Action)>(&mut self, byte: u8, f: &mut F) {
+        if byte == 0x7f {
+            return;
+        }
+        if byte < 0x80 {
+            if !self.utf8.is_empty() {
+                self.utf8.clear();
+                f(Action::Print(char::REPLACEMENT_CHARACTER));
+            }
+            f(Action::Print(byte as char));
+            return;
+        }
+        let is_start = byte >= 0xc0;
+        if is_start && !self.utf8.is_empty() {
+            self.utf8.clear();
+            f(Action::Print(char::REPLACEMENT_CHARACTER));
+        }
+        if !is_start && self.utf8.is_empty() {
+            f(Action::Print(char::REPLACEMENT_CHARACTER));
+            return;
+        }
+        self.utf8.push(byte);
+        let len = match self.utf8[0] {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        if self.utf8.len() < len {
+            return;
+        }
+        let c = str::from_utf8(&self.utf8)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        self.utf8.clear();
+        f(Action::Print(c));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&[u8]]) -> Vec<Action> {
+        let mut parser = VtParser::new();
+        let mut actions = Vec::new();
+        for chunk in chunks {
+            parser.advance(chunk, |action| actions.push(action));
+        }
+        actions
+    }
+
+    #[test]
+    fn parses_split_sequences_and_strings() {
+        let actions = parse(&[
+            b"\xc3",
+            b"\xa4\x1b[?10",
+            b"49h\x1b]0;vim ~/a;b\x07\x1b(0x\x1b]2;t\x1b\\\x1bP1$r\x1b\\\xff",
+        ]);
+        assert_eq!(
+            actions,
+            vec![
+                Action::Print('ä'),
+                Action::Csi {
+                    params: vec![1049],
+                    private: Some(b'?'),
+                    intermediates: vec![],
+                    action: 'h'
+                },
+                Action::Osc(vec!["0".into(), "vim ~/a".into(), "b".into()]),
+                Action::Esc {
+                    intermediates: vec![b'('],
+                    action: '0'
+                },
+                Action::Print('x'),
+                Action::Osc(vec!["2".into(), "t".into()]),
+                Action::Print(char::REPLACEMENT_CHARACTER),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_utf8_split_across_chunks() {
+        let euro = "€".as_bytes();
+        let emoji = "😀".as_bytes();
+        let actions = parse(&[
+            &euro[..1],
+            &euro[1..2],
+            &euro[2..],
+            &emoji[..3],
+            &emoji[3..],
+        ]);
+        assert_eq!(actions, vec![Action::Print('€'), Action::Print('😀')]);
+        // A sequence cut short by another byte is replaced, and the byte still counts.
+        let actions = parse(&[&euro[..2], b"a", &emoji[..1], b"\x1b[m"]);
+        assert_eq!(
+            actions,
+            vec![
+                Action::Print(char::REPLACEMENT_CHARACTER),
+                Action::Print('a'),
+                Action::Print(char::REPLACEMENT_CHARACTER),
+                Action::Csi {
+                    params: vec![],
+                    private: None,
+                    intermediates: vec![],
+                    action: 'm'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_overlong_sequences() {
+        let mut title = b"\x1b]0;".to_vec();
+        title.resize(MAX_OSC + 10, b'x');
+        title.extend_from_slice(b"\x07a\x1b ()0b\x1b[ !\"pc");
+        assert_eq!(
+            parse(&[&title]),
+            vec![Action::Print('a'), Action::Print('b'), Action::Print('c')]
+        );
+        // A command of the longest length is kept.
+        let mut title = b"\x1b]0;".to_vec();
+        title.resize(MAX_OSC + 2, b'x');
+        title.push(0x07);
+        assert_eq!(parse(&[&title]).len(), 1);
+    }
+}
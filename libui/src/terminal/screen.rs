@@ -0,0 +1,870 @@
+use std::collections::VecDeque;
+use std::mem;
+use terminal::{Action, VtParser};
+
+/// The color of a cell, resolved against the palette of a [`Terminal`](super::Terminal).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TermColor {
+    /// The default foreground or background color.
+    Default,
+    /// One of the 256 xterm colors: 16 palette colors, a 6x6x6 color cube and 24 grays.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// The colors and attributes of a cell, as set by SGR sequences.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CellStyle {
+    pub foreground: TermColor,
+    pub background: TermColor,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// Swaps the foreground and background colors.
+    pub inverse: bool,
+    pub hidden: bool,
+    pub strikethrough: bool,
+}
+
+impl Default for CellStyle {
+    fn default() -> CellStyle {
+        CellStyle {
+            foreground: TermColor::Default,
+            background: TermColor::Default,
+            bold: false,
+            dim: false,
+            italic: false,
+            underline: false,
+            inverse: false,
+            hidden: false,
+            strikethrough: false,
+        }
+    }
+}
+
+/// A character on the screen with its style.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cell {
+    pub c: char,
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            c: ' ',
+            style: CellStyle::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Cursor {
+    row: usize,
+    col: usize,
+    style: CellStyle,
+    /// Whether the last column was written, so the next character goes to the next line.
+    pending_wrap: bool,
+    /// Whether the DEC special graphics set is selected, drawing lines with letters.
+    line_drawing: bool,
+}
+
+/// Returns the line drawing character a letter stands for in the DEC special graphics set.
+fn line_drawing(c: char) -> char {
+    match c {
+        '`' => '◆',
+        'a' => '▒',
+        'f' => '°',
+        'g' => '±',
+        'j' => '┘',
+        'k' => '┐',
+        'l' => '┌',
+        'm' => '└',
+        'n' => '┼',
+        'o' => '⎺',
+        'p' => '⎻',
+        'q' => '─',
+        'r' => '⎼',
+        's' => '⎽',
+        't' => '├',
+        'u' => '┤',
+        'v' => '┴',
+        'w' => '┬',
+        'x' => '│',
+        'y' => '≤',
+        'z' => '≥',
+        '{' => 'π',
+        '|' => '≠',
+        '}' => '£',
+        '~' => '·',
+        c => c,
+    }
+}
+
+/// The cell grid of a terminal, updated by the output of a program.
+///
+/// A screen parses VT100 and xterm control functions: cursor movement, erasing,
+/// inserting and deleting, scroll regions, SGR colors and attributes with 256 and RGB
+/// colors, and the alternate screen used by full screen programs. Lines scrolled off the
+/// top of the main screen go to the scrollback. Replies to queries like the cursor
+/// position are collected for the program, see [`Screen::take_responses()`].
+///
+/// Lines are addressed from the oldest scrollback line on; the visible rows follow the
+/// scrollback:
+///
+/// ```
+/// # use libui::terminal::*;
+/// let mut screen = Screen::new(10, 2);
+/// screen.feed(b"one\r\ntwo\r\nthree\x1b[1;31m!");
+/// assert_eq!(screen.scrollback_len(), 1);
+/// assert_eq!(screen.line_text(0), "one");
+/// assert_eq!(screen.line_text(2), "three!");
+/// assert!(screen.line(2)[5].style.bold);
+/// assert_eq!(screen.cursor(), (1, 6));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Screen {
+    cols: usize,
+    rows: usize,
+    lines: Vec<Vec<Cell>>,
+    /// The lines of the main screen while the alternate screen is shown.
+    other: Vec<Vec<Cell>>,
+    alternate: bool,
+    scrollback: VecDeque<Vec<Cell>>,
+    max_scrollback: usize,
+    cursor: Cursor,
+    saved: Cursor,
+    /// The cursor of the main screen while the alternate screen is shown.
+    main_cursor: Cursor,
+    /// The first and last row of the scroll region.
+    top: usize,
+    bottom: usize,
+    tabs: Vec<bool>,
+    autowrap: bool,
+    insert: bool,
+    cursor_visible: bool,
+    app_cursor_keys: bool,
+    bracketed_paste: bool,
+    title: String,
+    responses: Vec<u8>,
+    bell: bool,
+    parser: VtParser,
+}
+
+impl Screen {
+    /// Creates an empty screen of the given size, at least one cell.
+    pub fn new(cols: usize, rows: usize) -> Screen {
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        Screen {
+            cols,
+            rows,
+            lines: vec![vec![Cell::default(); cols]; rows],
+            other: vec![vec![Cell::default(); cols]; rows],
+            alternate: false,
+            scrollback: VecDeque::new(),
+            max_scrollback: 10_000,
+            cursor: Cursor::default(),
+            saved: Cursor::default(),
+            main_cursor: Cursor::default(),
+            top: 0,
+            bottom: rows - 1,
+            tabs: (0..cols).map(|col| col % 8 == 0).collect(),
+            autowrap: true,
+            insert: false,
+            cursor_visible: true,
+            app_cursor_keys: false,
+            bracketed_paste: false,
+            title: String::new(),
+            responses: Vec::new(),
+            bell: false,
+            parser: VtParser::new(),
+        }
+    }
+
+    /// Returns the number of columns and rows.
+    pub fn size(&self) -> (usize, usize) {
+        (self.cols, self.rows)
+    }
+
+    /// Changes the number of columns and rows. Rows removed above the cursor of the main
+    /// screen go to the scrollback, and come back from there when rows are added.
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        if (cols, rows) == (self.cols, self.rows) {
+            return;
+        }
+        let fit = |lines: &mut Vec<Vec<Cell>>,
+                   cursor: &mut Cursor,
+                   history: Option<&mut VecDeque<Vec<Cell>>>| {
+            let mut history = history;
+            while lines.len() > rows {
+                if cursor.row > 0 {
+                    let line = lines.remove(0);
+                    if let Some(ref mut history) = history {
+                        history.push_back(line);
+                    }
+                    cursor.row -= 1;
+                } else {
+                    lines.pop();
+                }
+            }
+            while lines.len() < rows {
+                match history.as_mut().and_then(|history| history.pop_back()) {
+                    Some(line) => {
+                        lines.insert(0, line);
+                        cursor.row += 1;
+                    }
+                    None => lines.push(Vec::new()),
+                }
+            }
+            for line in lines.iter_mut() {
+                line.resize(cols, Cell::default());
+            }
+            cursor.row = cursor.row.min(rows - 1);
+            cursor.col = cursor.col.min(cols - 1);
+            cursor.pending_wrap = false;
+        };
+        if self.alternate {
+            fit(&mut self.lines, &mut self.cursor, None);
+            fit(
+                &mut self.other,
+                &mut self.main_cursor,
+                Some(&mut self.scrollback),
+            );
+        } else {
+            fit(
+                &mut self.lines,
+                &mut self.cursor,
+                Some(&mut self.scrollback),
+            );
+            fit(&mut self.other, &mut Cursor::default(), None);
+        }
+        for line in &mut self.scrollback {
+            line.resize(cols, Cell::default());
+        }
+        self.saved.row = self.saved.row.min(rows - 1);
+        self.saved.col = self.saved.col.min(cols - 1);
+        self.saved.pending_wrap = false;
+        self.cols = cols;
+        self.rows = rows;
+        self.top = 0;
+        self.bottom = rows - 1;
+        self.tabs = (0..cols).map(|col| col % 8 == 0).collect();
+        self.trim_scrollback();
+    }
+
+    /// Parses output of the program and applies it.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut parser = mem::take(&mut self.parser);
+        parser.advance(bytes, |action| self.apply(action));
+        self.parser = parser;
+    }
+
+    /// Returns the number of scrollback lines.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Returns the number of scrollback lines and rows.
+    pub fn line_count(&self) -> usize {
+        self.scrollback.len() + self.rows
+    }
+
+    /// Returns a line, counting from the oldest scrollback line.
+    ///
+    /// # Panics
+    /// Panics if `index` is not less than [`Screen::line_count()`].
+    pub fn line(&self, index: usize) -> &[Cell] {
+        match index.checked_sub(self.scrollback.len()) {
+            Some(row) => &self.lines[row],
+            None => &self.scrollback[index],
+        }
+    }
+
+    /// Returns the text of a line without trailing spaces.
+    pub fn line_text(&self, index: usize) -> String {
+        let text: String = self.line(index).iter().map(|cell| cell.c).collect();
+        text.trim_end().to_string()
+    }
+
+    /// Returns the row and column of the cursor.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.cursor.row, self.cursor.col)
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Returns whether the alternate screen of full screen programs is shown.
+    pub fn is_alternate(&self) -> bool {
+        self.alternate
+    }
+
+    /// Returns whether the cursor keys send application sequences, like `ESC O A`.
+    pub fn app_cursor_keys(&self) -> bool {
+        self.app_cursor_keys
+    }
+
+    /// Returns whether pasted text is to be bracketed by `ESC [ 200 ~` and `ESC [ 201 ~`.
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Returns the title set by the program.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the replies to queries of the program, which are to be sent to it.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        mem::take(&mut self.responses)
+    }
+
+    /// Returns whether the bell rang since the last call.
+    pub fn take_bell(&mut self) -> bool {
+        mem::replace(&mut self.bell, false)
+    }
+
+    pub fn max_scrollback(&self) -> usize {
+        self.max_scrollback
+    }
+
+    /// Sets the number of scrollback lines kept, 10000 by default.
+    pub fn set_max_scrollback(&mut self, lines: usize) {
+        self.max_scrollback = lines;
+        self.trim_scrollback();
+    }
+
+    pub fn clear_scrollback(&mut self) {
+        self.scrollback.clear();
+    }
+
+    /// Resets the screen to its initial state, keeping its size and scrollback.
+    pub fn reset(&mut self) {
+        let scrollback = mem::take(&mut self.scrollback);
+        let max_scrollback = self.max_scrollback;
+        *self = Screen::new(self.cols, self.rows);
+        self.scrollback = scrollback;
+        self.max_scrollback = max_scrollback;
+    }
+
+    fn trim_scrollback(&mut self) {
+        while self.scrollback.len() > self.max_scrollback {
+            self.scrollback.pop_front();
+        }
+    }
+
+    /// Returns an erased cell, which keeps the current background color.
+    fn blank(&self) -> Cell {
+        Cell {
+            c: ' ',
+            style: CellStyle {
+                background: self.cursor.style.background,
+                ..CellStyle::default()
+            },
+        }
+    }
+
+    fn blank_line(&self) -> Vec<Cell> {
+        vec![self.blank(); self.cols]
+    }
+
+    fn apply(&mut self, action: Action) {
+        match action {
+            Action::Print(c) => self.print(c),
+            Action::Execute(byte) => self.execute(byte),
+            Action::Csi {
+                params,
+                private,
+                intermediates,
+                action,
+            } => self.csi(&params, private, &intermediates, action),
+            Action::Esc {
+                intermediates,
+                action,
+            } => self.esc(&intermediates, action),
+            Action::Osc(params) => {
+                if params.len() > 1 && (params[0] == "0" || params[0] == "2") {
+                    self.title = params[1..].join(";");
+                }
+            }
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        let c = if self.cursor.line_drawing {
+            line_drawing(c)
+        } else {
+            c
+        };
+        if self.cursor.pending_wrap && self.autowrap {
+            self.cursor.col = 0;
+            self.line_feed();
+        }
+        self.cursor.pending_wrap = false;
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        if self.insert {
+            let blank = self.blank();
+            let line = &mut self.lines[row];
+            line.insert(col, blank);
+            line.truncate(self.cols);
+        }
+        self.lines[row][col] = Cell {
+            c,
+            style: self.cursor.style,
+        };
+        if col + 1 < self.cols {
+            self.cursor.col += 1;
+        } else {
+            self.cursor.pending_wrap = true;
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            0x07 => self.bell = true,
+            0x08 => {
+                self.cursor.col = self.cursor.col.saturating_sub(1);
+                self.cursor.pending_wrap = false;
+            }
+            b'\t' => {
+                let next = (self.cursor.col + 1..self.cols).find(|&col| self.tabs[col]);
+                self.cursor.col = next.unwrap_or(self.cols - 1);
+                self.cursor.pending_wrap = false;
+            }
+            b'\n' | 0x0b | 0x0c => self.line_feed(),
+            b'\r' => {
+                self.cursor.col = 0;
+                self.cursor.pending_wrap = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the cursor down, scrolling at the bottom of the scroll region.
+    fn line_feed(&mut self) {
+        self.cursor.pending_wrap = false;
+        if self.cursor.row == self.bottom {
+            self.scroll_up(1);
+        } else if self.cursor.row + 1 < self.rows {
+            self.cursor.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        self.cursor.pending_wrap = false;
+        if self.cursor.row == self.top {
+            self.scroll_down(1);
+        } else {
+            self.cursor.row = self.cursor.row.saturating_sub(1);
+        }
+    }
+
+    /// Scrolls the scroll region up, moving lines off the top of the main screen into the
+    /// scrollback.
+    fn scroll_up(&mut self, count: usize) {
+        for _ in 0..count.min(self.bottom + 1 - self.top) {
+            let line = self.lines.remove(self.top);
+            if !self.alternate && self.top == 0 && self.max_scrollback > 0 {
+                self.scrollback.push_back(line);
+                self.trim_scrollback();
+            }
+            let blank = self.blank_line();
+            self.lines.insert(self.bottom, blank);
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count.min(self.bottom + 1 - self.top) {
+            self.lines.remove(self.bottom);
+            let blank = self.blank_line();
+            self.lines.insert(self.top, blank);
+        }
+    }
+
+    /// Erases the cells of a row in a range of columns.
+    fn erase(&mut self, row: usize, from: usize, to: usize) {
+        let blank = self.blank();
+        let to = to.min(self.cols);
+        for cell in &mut self.lines[row][from.min(to)..to] {
+            *cell = blank;
+        }
+    }
+
+    fn set_mode(&mut self, mode: u16, private: bool, on: bool) {
+        match (private, mode) {
+            (false, 4) => self.insert = on,
+            (true, 1) => self.app_cursor_keys = on,
+            (true, 7) => self.autowrap = on,
+            (true, 25) => self.cursor_visible = on,
+            (true, 47) | (true, 1047) | (true, 1049) => {
+                if on == self.alternate {
+                    return;
+                }
+                if on && mode == 1049 {
+                    self.saved = self.cursor;
+                }
+                mem::swap(&mut self.lines, &mut self.other);
+                self.alternate = on;
+                if on {
+                    self.main_cursor = self.cursor;
+                    self.lines = vec![self.blank_line(); self.rows];
+                } else if mode == 1049 {
+                    self.restore_cursor();
+                } else {
+                    self.cursor = self.main_cursor;
+                }
+                self.top = 0;
+                self.bottom = self.rows - 1;
+            }
+            (true, 2004) => self.bracketed_paste = on,
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, params: &[u16], private: Option<u8>, intermediates: &[u8], action: char) {
+        // Omitted parameters and 0 stand for the default, 1 for most functions.
+        let param = |i: usize, default: usize| match params.get(i) {
+            Some(&p) if p > 0 => p as usize,
+            _ => default,
+        };
+        let n = param(0, 1);
+        let (rows, cols) = (self.rows, self.cols);
+        if !intermediates.is_empty() {
+            return;
+        }
+        if private.is_some() && !"hl".contains(action) {
+            return;
+        }
+        self.cursor.pending_wrap = false;
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        match action {
+            'A' => {
+                let top = if row >= self.top { self.top } else { 0 };
+                self.cursor.row = row.saturating_sub(n).max(top);
+            }
+            'B' | 'e' => {
+                let bottom = if row <= self.bottom {
+                    self.bottom
+                } else {
+                    rows - 1
+                };
+                self.cursor.row = (row + n).min(bottom);
+            }
+            'C' | 'a' => self.cursor.col = (col + n).min(cols - 1),
+            'D' => self.cursor.col = col.saturating_sub(n),
+            'E' => {
+                self.cursor.row = (row + n).min(self.bottom.max(row));
+                self.cursor.col = 0;
+            }
+            'F' => {
+                self.cursor.row = row.saturating_sub(n).max(self.top.min(row));
+                self.cursor.col = 0;
+            }
+            'G' | '`' => self.cursor.col = (n - 1).min(cols - 1),
+            'H' | 'f' => {
+                self.cursor.row = (param(0, 1) - 1).min(rows - 1);
+                self.cursor.col = (param(1, 1) - 1).min(cols - 1);
+            }
+            'd' => self.cursor.row = (n - 1).min(rows - 1),
+            'J' => match params.first().cloned().unwrap_or(0) {
+                0 => {
+                    self.erase(row, col, cols);
+                    for row in row + 1..rows {
+                        self.erase(row, 0, cols);
+                    }
+                }
+                1 => {
+                    for row in 0..row {
+                        self.erase(row, 0, cols);
+                    }
+                    self.erase(row, 0, col + 1);
+                }
+                2 => {
+                    for row in 0..rows {
+                        self.erase(row, 0, cols);
+                    }
+                }
+                3 => self.scrollback.clear(),
+                _ => {}
+            },
+            'K' => match params.first().cloned().unwrap_or(0) {
+                0 => self.erase(row, col, cols),
+                1 => self.erase(row, 0, col + 1),
+                2 => self.erase(row, 0, cols),
+                _ => {}
+            },
+            'L' | 'M' if row >= self.top && row <= self.bottom => {
+                for _ in 0..n.min(self.bottom + 1 - row) {
+                    let blank = self.blank_line();
+                    if action == 'L' {
+                        self.lines.remove(self.bottom);
+                        self.lines.insert(row, blank);
+                    } else {
+                        self.lines.remove(row);
+                        self.lines.insert(self.bottom, blank);
+                    }
+                }
+                self.cursor.col = 0;
+            }
+            '@' => {
+                let blank = self.blank();
+                let line = &mut self.lines[row];
+                for _ in 0..n.min(cols - col) {
+                    line.insert(col, blank);
+                }
+                line.truncate(cols);
+            }
+            'P' => {
+                let blank = self.blank();
+                let line = &mut self.lines[row];
+                for _ in 0..n.min(cols - col) {
+                    line.remove(col);
+                    line.push(blank);
+                }
+            }
+            'X' => self.erase(row, col, col + n),
+            'S' => self.scroll_up(n),
+            'T' => self.scroll_down(n),
+            'm' => self.sgr(params),
+            'r' => {
+                let top = param(0, 1) - 1;
+                let bottom = param(1, rows).min(rows) - 1;
+                if top < bottom {
+                    self.top = top;
+                    self.bottom = bottom;
+                    self.cursor.row = 0;
+                    self.cursor.col = 0;
+                }
+            }
+            's' => self.saved = self.cursor,
+            'u' => self.restore_cursor(),
+            'h' | 'l' => {
+                for &mode in params {
+                    self.set_mode(mode, private == Some(b'?'), action == 'h');
+                }
+            }
+            'n' if n == 5 => self.responses.extend_from_slice(b"\x1b[0n"),
+            'n' if n == 6 => {
+                let report = format!("\x1b[{};{}R", row + 1, col + 1);
+                self.responses.extend_from_slice(report.as_bytes());
+            }
+            'c' if params.first().cloned().unwrap_or(0) == 0 => {
+                self.responses.extend_from_slice(b"\x1b[?6c")
+            }
+            'g' => match params.first().cloned().unwrap_or(0) {
+                0 => self.tabs[col] = false,
+                3 => self.tabs.iter_mut().for_each(|tab| *tab = false),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn restore_cursor(&mut self) {
+        self.cursor = self.saved;
+        self.cursor.row = self.cursor.row.min(self.rows - 1);
+        self.cursor.col = self.cursor.col.min(self.cols - 1);
+    }
+
+    fn esc(&mut self, intermediates: &[u8], action: char) {
+        match (intermediates, action) {
+            (b"", '7') => self.saved = self.cursor,
+            (b"", '8') => self.restore_cursor(),
+            (b"", 'D') => self.line_feed(),
+            (b"", 'E') => {
+                self.cursor.col = 0;
+                self.line_feed();
+            }
+            (b"", 'M') => self.reverse_index(),
+            (b"", 'H') => self.tabs[self.cursor.col] = true,
+            (b"", 'c') => self.reset(),
+            (b"(", '0') => self.cursor.line_drawing = true,
+            (b"(", _) => self.cursor.line_drawing = false,
+            _ => {}
+        }
+    }
+
+    fn sgr(&mut self, params: &[u16]) {
+        let style = &mut self.cursor.style;
+        if params.is_empty() {
+            *style = CellStyle::default();
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *style = CellStyle::default(),
+                1 => style.bold = true,
+                2 => style.dim = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                7 => style.inverse = true,
+                8 => style.hidden = true,
+                9 => style.strikethrough = true,
+                21 | 22 => {
+                    style.bold = false;
+                    style.dim = false;
+                }
+                23 => style.italic = false,
+                24 => style.underline = false,
+                27 => style.inverse = false,
+                28 => style.hidden = false,
+                29 => style.strikethrough = false,
+                p @ 30..=37 => style.foreground = TermColor::Indexed(p as u8 - 30),
+                39 => style.foreground = TermColor::Default,
+                p @ 40..=47 => style.background = TermColor::Indexed(p as u8 - 40),
+                49 => style.background = TermColor::Default,
+                p @ 90..=97 => style.foreground = TermColor::Indexed(p as u8 - 90 + 8),
+                p @ 100..=107 => style.background = TermColor::Indexed(p as u8 - 100 + 8),
+                p @ 38 | p @ 48 => {
+                    let color = match params.get(i + 1) {
+                        Some(&5) => {
+                            let index = params.get(i + 2).cloned().unwrap_or(0);
+                            i += 2;
+                            TermColor::Indexed(index.min(255) as u8)
+                        }
+                        Some(&2) => {
+                            let channel =
+                                |j: usize| params.get(i + j).cloned().unwrap_or(0).min(255) as u8;
+                            let color = TermColor::Rgb(channel(2), channel(3), channel(4));
+                            i += 4;
+                            color
+                        }
+                        _ => break,
+                    };
+                    if p == 38 {
+                        style.foreground = color;
+                    } else {
+                        style.background = color;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_the_grid_like_xterm() {
+        let mut screen = Screen::new(8, 3);
+        // Wrapping at the last column, erasing and inserting.
+        screen.feed(b"abcdefghij\x1b[1;3H\x1b[K\x1b[2;2H\x1b[2@X");
+        assert_eq!(screen.line_text(0), "ab");
+        assert_eq!(screen.line_text(1), "iX j");
+        // RGB and indexed colors, then a scroll region.
+        screen.feed(b"\x1b[38;2;1;2;3;48;5;200mZ\x1b[0m");
+        let cell = screen.line(1)[2];
+        assert_eq!(cell.c, 'Z');
+        assert_eq!(cell.style.foreground, TermColor::Rgb(1, 2, 3));
+        assert_eq!(cell.style.background, TermColor::Indexed(200));
+        screen.feed(b"\x1b[2;3r\x1b[3;1H\n\n");
+        assert_eq!(screen.line_text(0), "ab");
+        assert_eq!(screen.line_text(1), "");
+        assert_eq!(screen.scrollback_len(), 0);
+
+        // The alternate screen keeps the main screen and the cursor.
+        screen.feed(b"\x1b[r\x1b[3;4H\x1b[?1049h\x1b[Hvim\x1b[6n");
+        assert!(screen.is_alternate());
+        assert_eq!(screen.line_text(0), "vim");
+        assert_eq!(screen.take_responses(), b"\x1b[1;4R");
+        screen.feed(b"\x1b[?1049l");
+        assert_eq!(screen.line_text(0), "ab");
+        assert_eq!(screen.cursor(), (2, 3));
+
+        // Shrinking moves rows above the cursor to the scrollback.
+        screen.resize(4, 2);
+        assert_eq!(screen.scrollback_len(), 1);
+        assert_eq!(screen.line_text(0), "ab");
+        assert_eq!(screen.cursor(), (1, 3));
+        screen.resize(8, 3);
+        assert_eq!(screen.scrollback_len(), 0);
+    }
+
+    #[test]
+    fn resizes_keeping_lines_and_cursor() {
+        let mut screen = Screen::new(6, 4);
+        screen.feed(b"one\r\ntwo\r\nthree");
+        screen.resize(3, 2);
+        assert_eq!(screen.size(), (3, 2));
+        assert_eq!(screen.scrollback_len(), 2);
+        assert_eq!(screen.line_text(1), "two");
+        assert_eq!(screen.line_text(2), "thr");
+        assert_eq!(screen.cursor(), (0, 2));
+        // Growing brings the lines back from the scrollback.
+        screen.resize(3, 5);
+        assert_eq!(screen.scrollback_len(), 0);
+        assert_eq!(screen.line_text(0), "one");
+        assert_eq!(screen.cursor(), (2, 2));
+        screen.resize(0, 0);
+        assert_eq!(screen.size(), (1, 1));
+        screen.feed(b"x\x1b[K");
+    }
+
+    #[test]
+    fn restores_the_cursor_within_a_shrunk_screen() {
+        let mut screen = Screen::new(10, 6);
+        screen.feed(b"\x1b[6;9H\x1b[?1049h");
+        screen.resize(4, 3);
+        screen.feed(b"\x1b[?1049l");
+        assert!(!screen.is_alternate());
+        assert_eq!(screen.cursor(), (2, 3));
+        // Printing and erasing at the restored cursor stay within the grid.
+        screen.feed(b"ab\x1b[J\x1b[K");
+        screen.feed(b"\x1b[5;5H\x1b7");
+        screen.resize(2, 2);
+        screen.feed(b"\x1b8z");
+        assert_eq!(screen.cursor(), (1, 1));
+    }
+
+    #[test]
+    fn switches_to_the_alternate_screen_and_back() {
+        let mut screen = Screen::new(8, 3);
+        screen.feed(b"main\r\nline\x1b[?1049h");
+        assert!(screen.is_alternate());
+        assert_eq!(screen.line_text(0), "");
+        // The alternate screen has no scrollback.
+        screen.feed(b"\x1b[Ha\r\nb\r\nc\r\nd");
+        assert_eq!(screen.scrollback_len(), 0);
+        assert_eq!(screen.line_text(2), "d");
+        screen.feed(b"\x1b[?1049l");
+        assert_eq!(screen.line_text(0), "main");
+        assert_eq!(screen.line_text(1), "line");
+        assert_eq!(screen.cursor(), (1, 4));
+        // Mode 47 switches without saving the cursor, and entering twice does nothing.
+        screen.feed(b"\x1b[?47h\x1b[Hx\x1b[?47h");
+        assert_eq!(screen.line_text(0), "x");
+        screen.feed(b"\x1b[?47l");
+        assert_eq!(screen.line_text(0), "main");
+        assert_eq!(screen.cursor(), (1, 4));
+    }
+
+    #[test]
+    fn scrolls_only_within_the_scroll_region() {
+        let mut screen = Screen::new(4, 4);
+        screen.feed(b"a\r\nb\r\nc\r\nd");
+        // Line feeds at the bottom of rows 2 and 3 scroll just those rows.
+        screen.feed(b"\x1b[2;3r\x1b[3;1H\ne");
+        let text: Vec<String> = (0..4).map(|row| screen.line_text(row)).collect();
+        assert_eq!(text, ["a", "c", "e", "d"]);
+        assert_eq!(screen.scrollback_len(), 0);
+        // A reverse index at the top of the region scrolls it down.
+        screen.feed(b"\x1b[2;1H\x1bM");
+        let text: Vec<String> = (0..4).map(|row| screen.line_text(row)).collect();
+        assert_eq!(text, ["a", "", "c", "d"]);
+        // An invalid region is ignored, and resetting it scrolls the whole screen.
+        screen.feed(b"\x1b[3;2r\x1b[r\x1b[4;1H\n");
+        assert_eq!(screen.scrollback_len(), 1);
+        assert_eq!(screen.line_text(0), "a");
+    }
+}
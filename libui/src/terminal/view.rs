@@ -0,0 +1,637 @@
+use controls::{
+    Area, AreaDrawParams, AreaHandler, AreaKeyEvent, Control, FontDescription, Key, Modifiers,
+    ScrollingArea, SlantStyle,
+};
+use draw::{
+    AttributedString, Brush, Canvas, Color, PathData, Rect, TextAlign, TextAttribute,
+    TextLayoutParams, Underline,
+};
+use editor::{estimate_cell, measure_cell, EditorStyle};
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+use terminal::{key_bytes, paste_bytes, Cell, Screen, TermColor};
+
+type InputHandler = Box<dyn FnMut(&Terminal, &[u8])>;
+type ResizeHandler = Box<dyn FnMut(&Terminal, usize, usize)>;
+type TitleHandler = Box<dyn FnMut(&Terminal, &str)>;
+type BellHandler = Box<dyn FnMut(&Terminal)>;
+
+/// The font and colors of a [`Terminal`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TerminalStyle {
+    /// The font of the text, which has to be a monospace font.
+    pub font: FontDescription,
+    pub foreground: Color,
+    pub background: Color,
+    pub cursor: Color,
+    /// The colors 0 to 15: black, red, green, yellow, blue, magenta, cyan and white,
+    /// then their bright variants.
+    pub palette: [Color; 16],
+    /// Whether bold text in one of the first 8 colors uses the bright variant.
+    pub bold_is_bright: bool,
+    /// The space around the grid in points.
+    pub padding: f64,
+}
+
+impl Default for TerminalStyle {
+    fn default() -> TerminalStyle {
+        let rgb = |r, g, b| Color::from_rgba8(r, g, b, 255);
+        TerminalStyle {
+            font: EditorStyle::default().font,
+            foreground: rgb(0xd0, 0xd0, 0xd0),
+            background: rgb(0x1c, 0x1c, 0x1c),
+            cursor: rgb(0xa0, 0xa0, 0xa0),
+            palette: [
+                rgb(0x00, 0x00, 0x00),
+                rgb(0xcd, 0x31, 0x31),
+                rgb(0x0d, 0xbc, 0x79),
+                rgb(0xe5, 0xe5, 0x10),
+                rgb(0x24, 0x72, 0xc8),
+                rgb(0xbc, 0x3f, 0xbc),
+                rgb(0x11, 0xa8, 0xcd),
+                rgb(0xe5, 0xe5, 0xe5),
+                rgb(0x66, 0x66, 0x66),
+                rgb(0xf1, 0x4c, 0x4c),
+                rgb(0x23, 0xd1, 0x8b),
+                rgb(0xf5, 0xf5, 0x43),
+                rgb(0x3b, 0x8e, 0xea),
+                rgb(0xd6, 0x70, 0xd6),
+                rgb(0x29, 0xb8, 0xdb),
+                rgb(0xff, 0xff, 0xff),
+            ],
+            bold_is_bright: true,
+            padding: 4.0,
+        }
+    }
+}
+
+impl TerminalStyle {
+    /// Returns the color of a cell, or `default` for the default color.
+    pub fn color(&self, color: TermColor, default: Color) -> Color {
+        // The levels of the 6x6x6 color cube of xterm.
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        match color {
+            TermColor::Default => default,
+            TermColor::Indexed(i @ 0..=15) => self.palette[i as usize],
+            TermColor::Indexed(i @ 16..=231) => {
+                let i = (i - 16) as usize;
+                Color::from_rgba8(LEVELS[i / 36], LEVELS[i / 6 % 6], LEVELS[i % 6], 255)
+            }
+            TermColor::Indexed(i) => {
+                let gray = 8 + (i - 232) * 10;
+                Color::from_rgba8(gray, gray, gray, 255)
+            }
+            TermColor::Rgb(r, g, b) => Color::from_rgba8(r, g, b, 255),
+        }
+    }
+}
+
+/// How a run of cells is drawn; neighbouring cells with the same look share a text
+/// layout.
+#[derive(Copy, Clone, PartialEq)]
+struct Look {
+    foreground: Color,
+    background: Color,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    hidden: bool,
+}
+
+struct TerminalState {
+    screen: Screen,
+    style: TerminalStyle,
+    /// The width and height of a character cell, measured for the font.
+    cell: (f64, f64),
+    visible: Option<Rect>,
+    /// Whether the view shows the last row, and keeps doing so as output arrives.
+    follow: bool,
+    scrolling: Option<ScrollingArea>,
+    on_input: Option<InputHandler>,
+    on_resize: Option<ResizeHandler>,
+    on_title: Option<TitleHandler>,
+    on_bell: Option<BellHandler>,
+}
+
+impl TerminalState {
+    fn new(cols: usize, rows: usize) -> TerminalState {
+        let style = TerminalStyle::default();
+        TerminalState {
+            screen: Screen::new(cols, rows),
+            cell: estimate_cell(&style.font),
+            style,
+            visible: None,
+            follow: true,
+            scrolling: None,
+            on_input: None,
+            on_resize: None,
+            on_title: None,
+            on_bell: None,
+        }
+    }
+
+    fn content_size(&self) -> (u64, u64) {
+        let (cell_width, line_height) = self.cell;
+        let padding = self.style.padding * 2.0;
+        let (cols, _) = self.screen.size();
+        let width = cols as f64 * cell_width + padding;
+        let height = self.screen.line_count() as f64 * line_height + padding;
+        (width.ceil() as u64, height.ceil() as u64)
+    }
+
+    /// Returns the rectangle of the last row, which is kept in view while following.
+    fn last_row(&self) -> Rect {
+        let line_height = self.cell.1;
+        let y = self.style.padding + (self.screen.line_count() - 1) as f64 * line_height;
+        (0.0, y, 1.0, line_height + self.style.padding)
+    }
+
+    /// Returns the number of columns and rows fitting a view.
+    fn grid_size(&self, width: f64, height: f64) -> (usize, usize) {
+        let (cell_width, line_height) = self.cell;
+        let padding = self.style.padding * 2.0;
+        let cols = ((width - padding) / cell_width).floor().max(1.0);
+        let rows = ((height - padding) / line_height).floor().max(1.0);
+        (cols as usize, rows as usize)
+    }
+
+    fn look(&self, cell: &Cell, cursor: bool) -> Look {
+        let style = &self.style;
+        let cell_style = cell.style;
+        let foreground = match cell_style.foreground {
+            TermColor::Indexed(i) if i < 8 && cell_style.bold && style.bold_is_bright => {
+                TermColor::Indexed(i + 8)
+            }
+            color => color,
+        };
+        let mut foreground = style.color(foreground, style.foreground);
+        let mut background = style.color(cell_style.background, style.background);
+        if cell_style.inverse {
+            std::mem::swap(&mut foreground, &mut background);
+        }
+        if cell_style.dim {
+            foreground = foreground.lerp(&background, 0.4);
+        }
+        if cursor {
+            foreground = background;
+            background = style.cursor;
+        }
+        Look {
+            foreground,
+            background,
+            bold: cell_style.bold,
+            italic: cell_style.italic,
+            underline: cell_style.underline,
+            strikethrough: cell_style.strikethrough,
+            hidden: cell_style.hidden,
+        }
+    }
+
+    fn render<C: Canvas>(&self, canvas: &mut C, clip: Rect) {
+        let style = &self.style;
+        let screen = &self.screen;
+        let (cell_width, line_height) = self.cell;
+        let fill = |canvas: &mut C, (x, y, w, h): Rect, color: Color| {
+            canvas.fill(&PathData::rectangle(x, y, w, h), &Brush::Solid(color))
+        };
+        fill(canvas, clip, style.background);
+
+        let first = ((clip.1 - style.padding) / line_height).floor().max(0.0) as usize;
+        let last = ((clip.1 + clip.3 - style.padding) / line_height)
+            .ceil()
+            .max(0.0) as usize;
+        let last = last.min(screen.line_count());
+        let (cursor_row, cursor_col) = screen.cursor();
+        let cursor_line = screen.scrollback_len() + cursor_row;
+
+        for index in first..last {
+            let y = style.padding + index as f64 * line_height;
+            let line = screen.line(index);
+            let cursor =
+                Some(cursor_col).filter(|_| index == cursor_line && screen.cursor_visible());
+            let mut start = 0;
+            while start < line.len() {
+                let look = self.look(&line[start], cursor == Some(start));
+                let end = (start + 1..line.len())
+                    .find(|&col| self.look(&line[col], cursor == Some(col)) != look)
+                    .unwrap_or(line.len());
+                let x = style.padding + start as f64 * cell_width;
+                let width = (end - start) as f64 * cell_width;
+                if look.background != style.background {
+                    fill(canvas, (x, y, width, line_height), look.background);
+                }
+                let text: String = line[start..end].iter().map(|cell| cell.c).collect();
+                if !look.hidden && !text.trim().is_empty() {
+                    let mut string = AttributedString::new(&text);
+                    let range = 0..text.len();
+                    string.set_attribute(range.clone(), TextAttribute::Color(look.foreground));
+                    if look.bold {
+                        string.set_attribute(range.clone(), TextAttribute::Weight(700));
+                    }
+                    if look.italic {
+                        string
+                            .set_attribute(range.clone(), TextAttribute::Slant(SlantStyle::Italic));
+                    }
+                    if look.underline {
+                        string.set_attribute(range, TextAttribute::Underline(Underline::Single));
+                    }
+                    let params = TextLayoutParams {
+                        string,
+                        default_font: style.font.clone(),
+                        width: -1.0,
+                        align: TextAlign::Left,
+                    };
+                    canvas.draw_text(&params, x, y);
+                }
+                if look.strikethrough && !look.hidden {
+                    let thickness = (line_height / 14.0).max(1.0);
+                    fill(
+                        canvas,
+                        (x, y + line_height * 0.55, width, thickness),
+                        look.foreground,
+                    );
+                }
+                start = end;
+            }
+        }
+    }
+}
+
+struct TerminalHandler {
+    state: Rc<RefCell<TerminalState>>,
+}
+
+impl AreaHandler for TerminalHandler {
+    fn draw(&mut self, _area: &Area, params: &AreaDrawParams) {
+        let clip = (
+            params.clip_x,
+            params.clip_y,
+            params.clip_width,
+            params.clip_height,
+        );
+        self.state.borrow().render(&mut &params.context, clip);
+    }
+
+    fn key_event(&mut self, area: &Area, event: &AreaKeyEvent) -> bool {
+        if event.up {
+            return false;
+        }
+        let terminal = Terminal {
+            area: area.clone(),
+            state: self.state.clone(),
+        };
+        terminal.handle_key(event)
+    }
+}
+
+/// A terminal emulator showing the output of a program, drawn on a scrolling `Area`.
+///
+/// The terminal keeps a [`Screen`], which parses VT100 and xterm escape sequences into
+/// a grid of character cells with colors and attributes, and draws it with the text API.
+/// Lines scrolled off the top are kept as scrollback, which can be scrolled to with the
+/// scroll bar or Shift+PageUp and Shift+PageDown; new output scrolls back to the bottom.
+///
+/// The terminal does not start programs: the application connects it to a pseudo
+/// terminal or a pipe, passes the output of the program to [`Terminal::write()`] and
+/// sends the bytes given to [`Terminal::on_input()`] to the program. These are key
+/// presses, encoded like xterm does with [`key_bytes()`], pasted text and replies to
+/// queries of the program. The grid follows the size of the view, and
+/// [`Terminal::on_resize()`] reports new sizes for the pseudo terminal.
+///
+/// Every character takes one cell, so wide characters like CJK ideographs overlap their
+/// neighbours. As in the [`CodeEditor`](::editor::CodeEditor), characters are derived
+/// from keys with a US keyboard layout.
+///
+/// ```no_run
+/// # use libui::terminal::*;
+/// # use std::io::Write;
+/// # let mut pty = std::io::sink();
+/// let terminal = Terminal::new();
+/// terminal.on_input(move |_, bytes| {
+///     pty.write_all(bytes).unwrap();
+/// });
+/// terminal.on_resize(|_, cols, rows| println!("resize the pty to {}x{}", cols, rows));
+/// terminal.write(b"\x1b[1;32m$\x1b[0m ");
+/// ```
+#[derive(Clone)]
+pub struct Terminal {
+    area: Area,
+    state: Rc<RefCell<TerminalState>>,
+}
+
+impl Terminal {
+    /// Creates a terminal with 80 columns and 24 rows, until it is shown and takes the
+    /// size of its view.
+    pub fn new() -> Terminal {
+        let state = Rc::new(RefCell::new(TerminalState::new(80, 24)));
+        let (width, height) = {
+            let mut state = state.borrow_mut();
+            state.cell = measure_cell(&state.style.font);
+            state.content_size()
+        };
+        let handler = Box::new(TerminalHandler {
+            state: state.clone(),
+        });
+        let scrolling = ScrollingArea::new(handler, width, height);
+        let terminal = Terminal {
+            area: scrolling.area().clone(),
+            state: state.clone(),
+        };
+        // The area owns the callback, so it only holds on to the state weakly.
+        let (area, weak) = (terminal.area.clone(), Rc::downgrade(&state));
+        scrolling.on_scroll(move |_, visible| {
+            if let Some(state) = weak.upgrade() {
+                let area = area.clone();
+                Terminal { area, state }.track(visible);
+            }
+        });
+        state.borrow_mut().scrolling = Some(scrolling);
+        terminal
+    }
+
+    /// Returns the underlying area.
+    pub fn area(&self) -> &Area {
+        &self.area
+    }
+
+    /// Shows output of the program.
+    pub fn write(&self, bytes: &[u8]) {
+        let (responses, bell, title) = {
+            let mut state = self.state.borrow_mut();
+            let title = state.screen.title().to_string();
+            state.screen.feed(bytes);
+            let changed = state.screen.title() != title;
+            (
+                state.screen.take_responses(),
+                state.screen.take_bell(),
+                Some(state.screen.title().to_string()).filter(|_| changed),
+            )
+        };
+        self.refresh();
+        if !responses.is_empty() {
+            self.send(&responses);
+        }
+        if bell {
+            self.notify(|state| &mut state.on_bell, |callback| callback(self));
+        }
+        if let Some(title) = title {
+            self.notify(
+                |state| &mut state.on_title,
+                |callback| callback(self, &title),
+            );
+        }
+    }
+
+    /// Sends text to the program as if it was typed, bracketed if the program asked for
+    /// it.
+    pub fn paste(&self, text: &str) {
+        let bracketed = self.state.borrow().screen.bracketed_paste();
+        self.send(&paste_bytes(text, bracketed));
+    }
+
+    /// Returns the screen, with the cells, the cursor and the scrollback.
+    pub fn screen(&self) -> Ref<'_, Screen> {
+        Ref::map(self.state.borrow(), |state| &state.screen)
+    }
+
+    /// Returns the number of columns and rows.
+    pub fn size(&self) -> (usize, usize) {
+        self.state.borrow().screen.size()
+    }
+
+    /// Changes the number of columns and rows, until the view is resized.
+    pub fn resize(&self, cols: usize, rows: usize) {
+        self.state.borrow_mut().screen.resize(cols, rows);
+        self.refresh();
+    }
+
+    /// Returns the title set by the program.
+    pub fn title(&self) -> String {
+        self.state.borrow().screen.title().to_string()
+    }
+
+    pub fn clear_scrollback(&self) {
+        self.state.borrow_mut().screen.clear_scrollback();
+        self.refresh();
+    }
+
+    /// Sets the number of scrollback lines kept, 10000 by default.
+    pub fn set_max_scrollback(&self, lines: usize) {
+        self.state.borrow_mut().screen.set_max_scrollback(lines);
+        self.refresh();
+    }
+
+    pub fn style(&self) -> TerminalStyle {
+        self.state.borrow().style.clone()
+    }
+
+    pub fn set_style(&self, style: TerminalStyle) {
+        let visible = {
+            let mut state = self.state.borrow_mut();
+            state.cell = measure_cell(&style.font);
+            state.style = style;
+            state.visible
+        };
+        // The grid size depends on the cell size.
+        if let Some(visible) = visible {
+            self.fit(visible);
+        }
+        self.refresh();
+    }
+
+    /// Sets the callback receiving the bytes to send to the program.
+    pub fn on_input<F: FnMut(&Terminal, &[u8]) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_input = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run with the new number of columns and rows after the grid was
+    /// resized to fit the view.
+    pub fn on_resize<F: FnMut(&Terminal, usize, usize) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_resize = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run after the program changed the title.
+    pub fn on_title<F: FnMut(&Terminal, &str) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_title = Some(Box::new(callback));
+    }
+
+    /// Sets the callback run when the program rings the bell.
+    pub fn on_bell<F: FnMut(&Terminal) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_bell = Some(Box::new(callback));
+    }
+
+    /// Runs a callback, taken out while it runs so it can use the terminal.
+    fn notify<T, S, F>(&self, slot: S, f: F)
+    where
+        S: Fn(&mut TerminalState) -> &mut Option<T>,
+        F: FnOnce(&mut T),
+    {
+        let callback = slot(&mut self.state.borrow_mut()).take();
+        if let Some(mut callback) = callback {
+            f(&mut callback);
+            let mut state = self.state.borrow_mut();
+            let slot = slot(&mut state);
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+
+    fn send(&self, bytes: &[u8]) {
+        self.notify(
+            |state| &mut state.on_input,
+            |callback| callback(self, bytes),
+        );
+    }
+
+    /// Updates the content size, scrolls to the last row while following and redraws.
+    fn refresh(&self) {
+        let (scrolling, size, last_row) = {
+            let state = self.state.borrow();
+            let last_row = Some(state.last_row()).filter(|_| state.follow);
+            (state.scrolling.clone(), state.content_size(), last_row)
+        };
+        if let Some(scrolling) = scrolling {
+            if scrolling.size() != size {
+                scrolling.set_size(size.0, size.1);
+            }
+            if let Some((x, y, width, height)) = last_row {
+                scrolling.scroll_to(x, y, width, height);
+            }
+        }
+        self.area.queue_redraw_all();
+    }
+
+    /// Follows the visible rectangle, resizing the grid to the view.
+    fn track(&self, visible: Rect) {
+        let resized = {
+            let mut state = self.state.borrow_mut();
+            let resized = state.visible.map(|v| (v.2, v.3)) != Some((visible.2, visible.3));
+            let (_, last_y, _, last_height) = state.last_row();
+            state.follow = visible.1 + visible.3 >= last_y + last_height - state.cell.1 / 2.0;
+            state.visible = Some(visible);
+            resized
+        };
+        if resized {
+            self.fit(visible);
+            self.refresh();
+        }
+    }
+
+    fn fit(&self, visible: Rect) {
+        let size = {
+            let mut state = self.state.borrow_mut();
+            let size = state.grid_size(visible.2, visible.3);
+            if size == state.screen.size() {
+                return;
+            }
+            state.screen.resize(size.0, size.1);
+            size
+        };
+        self.notify(
+            |state| &mut state.on_resize,
+            |callback| callback(self, size.0, size.1),
+        );
+    }
+
+    /// Handles a key press, returning whether it was used.
+    fn handle_key(&self, event: &AreaKeyEvent) -> bool {
+        let key = match Key::from_event(event) {
+            Some(key) => key,
+            None => return false,
+        };
+        let modifiers = event.modifiers;
+        // Super combinations are shortcuts of the application, like copy on macOS.
+        if modifiers.contains(Modifiers::MODIFIER_SUPER) {
+            return false;
+        }
+        if modifiers == Modifiers::MODIFIER_SHIFT && (key == Key::PageUp || key == Key::PageDown) {
+            let (scrolling, visible) = {
+                let state = self.state.borrow();
+                (state.scrolling.clone(), state.visible)
+            };
+            if let (Some(scrolling), Some((x, y, width, height))) = (scrolling, visible) {
+                let y = if key == Key::PageUp {
+                    y - height
+                } else {
+                    y + height
+                };
+                scrolling.scroll_to(x, y.max(0.0), width, height);
+            }
+            return true;
+        }
+        let app_cursor = self.state.borrow().screen.app_cursor_keys();
+        match key_bytes(key, modifiers, app_cursor) {
+            Some(bytes) => {
+                self.state.borrow_mut().follow = true;
+                self.refresh();
+                self.send(&bytes);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Terminal::new()
+    }
+}
+
+impl From<Terminal> for Control {
+    fn from(terminal: Terminal) -> Control {
+        terminal.area.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{DrawCommand, Recorder};
+
+    #[test]
+    fn renders_runs_of_styled_cells() {
+        let mut state = TerminalState::new(10, 2);
+        state
+            .screen
+            .feed(b"ab\x1b[1;31mcd\x1b[0;44m \x1b[7mx\x1b[0m");
+        assert_eq!(state.grid_size(10.0 * state.cell.0 + 8.0, 50.0).0, 10);
+
+        let mut recorder = Recorder::new();
+        state.render(&mut recorder, (0.0, 0.0, 400.0, 100.0));
+        let commands = recorder.finish();
+        let texts: Vec<_> = commands
+            .commands()
+            .iter()
+            .filter_map(|command| match *command {
+                DrawCommand::Text { ref layout, .. } => Some(layout.string.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts.len(), 3);
+        assert_eq!(texts[0].text(), "ab");
+        assert_eq!(texts[1].text(), "cd");
+        let red = state.style.palette[9];
+        assert!(texts[1]
+            .attributes()
+            .contains(&(0..2, TextAttribute::Color(red))));
+        assert!(texts[1]
+            .attributes()
+            .contains(&(0..2, TextAttribute::Weight(700))));
+        // The inverse cell draws in the color of its blue background.
+        let blue = state.style.palette[4];
+        assert!(texts[2]
+            .attributes()
+            .contains(&(0..1, TextAttribute::Color(blue))));
+        // The blue cell, the inverse cell and the cursor have backgrounds.
+        let fills = commands
+            .commands()
+            .iter()
+            .filter(|command| matches!(**command, DrawCommand::Fill { .. }))
+            .count();
+        assert_eq!(fills, 4);
+    }
+}